@@ -17,14 +17,17 @@
 #      toolchain into your home if there is none — first run only),
 #   2. clones or updates the repo and builds `strip_stream` headless
 #      (no egui; a couple of minutes once, seconds afterwards),
-#   3. prints a pairing code and starts the loopback relay (cluster: jobs go
-#      through `srun`; --local: they run right here).
+#   3. prints a pairing code and starts the loopback relay, `strip_stream
+#      --serve` (cluster: jobs go through `srun`; --local: they run right here).
 #
 # Everything is per-user and temporary: Ctrl-C (or closing the ssh session)
 # stops the relay and any running job; only the build cache stays for next
 # time. Nothing listens on anything but 127.0.0.1.
 #
 # Options: --local  --partition=P  --time=HH:MM:SS  --cpus=N  --port=N
+#          --max-faces=N  --max-memory=SIZE  --time-limit=SECS  --max-strips=N
+#          (limits on every job, see `strip_stream --help`)
+#          --log=FILE     (append every finished job's summary to FILE)
 
 set -euo pipefail
 
@@ -33,6 +36,7 @@ BASE="${RHOMBIC_HOME:-$HOME/.cache/rhombic_strips}"
 PORT=8642
 MODE=slurm
 PARTITION="" TIMELIMIT="" CPUS=""
+LIMITS=()
 
 for arg in "$@"; do
   case "$arg" in
@@ -41,6 +45,8 @@ for arg in "$@"; do
     --time=*)        TIMELIMIT="${arg#*=}" ;;
     --cpus=*)        CPUS="${arg#*=}" ;;
     --port=*)        PORT="${arg#*=}" ;;
+    --max-faces=*|--max-memory=*|--time-limit=*|--max-strips=*|--log=*)
+                     LIMITS+=("${arg%%=*}" "${arg#*=}") ;;
     *) echo "serve.sh: unknown option '$arg'" >&2; exit 2 ;;
   esac
 done
@@ -48,7 +54,6 @@ done
 say()  { printf '\033[1;36m» %s\033[0m\n' "$*"; }
 warn() { printf '\033[1;33m» %s\033[0m\n' "$*"; }
 
-command -v python3 >/dev/null 2>&1 || { echo "python3 is required" >&2; exit 1; }
 command -v git     >/dev/null 2>&1 || { echo "git is required" >&2; exit 1; }
 
 # -- 1. toolchain --------------------------------------------------------------
@@ -77,22 +82,23 @@ else
   git clone --depth 1 "$REPO_URL" "$REPO"
 fi
 
-say "building strip_stream (headless — first build takes a few minutes)"
+say "building strip_stream and rhombic_strips (headless — first build takes a few minutes)"
 cargo build --manifest-path "$REPO/Cargo.toml" --release \
-  --bin strip_stream --no-default-features
+  --bin strip_stream --bin rhombic_strips --no-default-features
 BIN="$REPO/target/release/strip_stream"
 
 # -- 3. pairing token ------------------------------------------------------------
 
 TOKEN_FILE="$BASE/token"
 if [ ! -s "$TOKEN_FILE" ]; then
-  ( umask 077; python3 -c 'import secrets; print(secrets.token_hex(6))' > "$TOKEN_FILE" )
+  ( umask 077; od -An -N6 -tx1 /dev/urandom | tr -d ' \n' > "$TOKEN_FILE" )
 fi
 TOKEN="$(cat "$TOKEN_FILE")"
 
 # -- 4. mode ---------------------------------------------------------------------
 
 SRUN_ARGS=""
+SERVE_ARGS=(--serve --port "$PORT" ${LIMITS[@]+"${LIMITS[@]}"})
 if [ "$MODE" = slurm ]; then
   if command -v srun >/dev/null 2>&1; then
     [ -n "$PARTITION" ] && SRUN_ARGS="$SRUN_ARGS --partition=$PARTITION"
@@ -114,21 +120,15 @@ banner() {
 
 # Port already taken: most likely a helper from an earlier session. Print the
 # code again and just keep this ssh tunnel alive instead of failing.
-if python3 - "$PORT" <<'PY'
-import socket, sys
-s = socket.socket()
-try:
-    s.connect(("127.0.0.1", int(sys.argv[1]))); sys.exit(0)   # busy
-except OSError:
-    sys.exit(1)                                               # free
-PY
-then
+if (exec 3<>"/dev/tcp/127.0.0.1/$PORT") 2>/dev/null; then
   warn "a helper is already listening on port $PORT — reusing it"
   banner
   exec sleep infinity
 fi
 
+if [ "$MODE" = slurm ]; then
+  SERVE_ARGS+=("--srun=${SRUN_ARGS# }")
+fi
+
 banner
-RHOMBIC_TOKEN="$TOKEN" RHOMBIC_BIN="$BIN" RHOMBIC_MODE="$MODE" \
-RHOMBIC_SRUN_ARGS="$SRUN_ARGS" RHOMBIC_PORT="$PORT" \
-exec python3 "$REPO/cluster/relay.py"
+RHOMBIC_TOKEN="$TOKEN" exec "$BIN" "${SERVE_ARGS[@]}"
@@ -0,0 +1,260 @@
+//! Results tables for `rhombic_strips batch`.
+//!
+//! A batch runs `exists` or `count` over many instances — lattice files or
+//! generator parameter ranges such as `fence 5..17` — and records one
+//! [`Row`] per instance. Tables are CSV or JSON (chosen by the file
+//! extension) and are rewritten after every instance, so an interrupted
+//! batch resumes by skipping the instances the table already has.
+//!
+//! The process handling (one child per instance, killed at its time limit)
+//! lives in `src/main.rs`; this module is the part worth testing on its own.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// One instance's outcome.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Row {
+    /// File path, or the generator call (`"fence 7"`).
+    pub instance: String,
+    pub faces: usize,
+    pub dim: usize,
+    /// `"exists"` or `"count"`.
+    pub mode: String,
+    pub cyclic: bool,
+    /// `"yes"` / `"no"` (a strip exists), `"timeout"` or `"error"`.
+    pub result: String,
+    /// Number of strips (count mode, when finished).
+    pub count: Option<u64>,
+    /// Seconds.
+    pub wall_time: f64,
+    pub timeout: bool,
+}
+
+impl Row {
+    /// The key resuming goes by: an instance is done for one mode and
+    /// cyclicity, not for all of them.
+    pub fn key(&self) -> (String, String, bool) {
+        (self.instance.clone(), self.mode.clone(), self.cyclic)
+    }
+}
+
+const HEADER: [&str; 9] =
+    ["instance", "faces", "dim", "mode", "cyclic", "result", "count", "wall_time", "timeout"];
+
+/// Expand a generator call with ranges into one call per instance:
+/// every `a..b` token (inclusive) is expanded, several ranges give their
+/// product. `"young 2..3 3"` → `["young 2 3", "young 3 3"]`.
+pub fn expand_range(spec: &str) -> Result<Vec<String>, String> {
+    let mut out = vec![String::new()];
+    for token in spec.split_whitespace() {
+        let values: Vec<String> = match token.split_once("..") {
+            Some((a, b)) => {
+                let bad = || format!("bad range '{}'", token);
+                let (a, b): (usize, usize) =
+                    (a.parse().map_err(|_| bad())?, b.parse().map_err(|_| bad())?);
+                if a > b {
+                    return Err(bad());
+                }
+                (a..=b).map(|v| v.to_string()).collect()
+            }
+            None => vec![token.to_string()],
+        };
+        out = out
+            .iter()
+            .flat_map(|prefix| {
+                values.iter().map(move |v| {
+                    if prefix.is_empty() {
+                        v.clone()
+                    } else {
+                        format!("{} {}", prefix, v)
+                    }
+                })
+            })
+            .collect();
+    }
+    if out == [""] {
+        return Err("empty generator range".to_string());
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tables
+// ---------------------------------------------------------------------------
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "json")
+}
+
+/// Read a results table; a missing file is an empty table.
+pub fn read_table(path: &Path) -> Result<Vec<Row>, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("reading {} failed: {}", path.display(), e)),
+    };
+    if content.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    if is_json(path) {
+        serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+    } else {
+        from_csv(&content).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Write the whole table (through a temporary file, so an interrupted
+/// write never loses the rows already there).
+pub fn write_table(path: &Path, rows: &[Row]) -> Result<(), String> {
+    let content = if is_json(path) {
+        serde_json::to_string_pretty(rows).unwrap() + "\n"
+    } else {
+        to_csv(rows)
+    };
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("writing {} failed: {}", path.display(), e))
+}
+
+/// The instances of `rows`, for skipping them on resume. Timed-out rows are
+/// left out with `retry_timeouts`, so a longer time limit can redo them.
+pub fn done(rows: &[Row], retry_timeouts: bool) -> HashSet<(String, String, bool)> {
+    rows.iter().filter(|r| !(retry_timeouts && r.timeout)).map(Row::key).collect()
+}
+
+pub fn csv_header() -> String {
+    HEADER.join(",")
+}
+
+pub fn csv_line(r: &Row) -> String {
+    let count = r.count.map(|c| c.to_string()).unwrap_or_default();
+    let fields = [
+        csv_field(&r.instance),
+        r.faces.to_string(),
+        r.dim.to_string(),
+        csv_field(&r.mode),
+        r.cyclic.to_string(),
+        csv_field(&r.result),
+        count,
+        format!("{:.3}", r.wall_time),
+        r.timeout.to_string(),
+    ];
+    fields.join(",")
+}
+
+pub fn to_csv(rows: &[Row]) -> String {
+    let mut out = csv_header() + "\n";
+    for r in rows {
+        out += &csv_line(r);
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Split one CSV line, honouring double-quoted fields.
+fn split_csv(line: &str) -> Vec<String> {
+    let (mut fields, mut field, mut quoted) = (vec![], String::new(), false);
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn from_csv(content: &str) -> Result<Vec<Row>, String> {
+    let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().unwrap_or_default();
+    if split_csv(header) != HEADER {
+        return Err(format!("unexpected header '{}'", header));
+    }
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let f = split_csv(line);
+            let bad = || format!("line {}: malformed row", i + 2);
+            if f.len() != HEADER.len() {
+                return Err(bad());
+            }
+            Ok(Row {
+                instance: f[0].clone(),
+                faces: f[1].parse().map_err(|_| bad())?,
+                dim: f[2].parse().map_err(|_| bad())?,
+                mode: f[3].clone(),
+                cyclic: f[4].parse().map_err(|_| bad())?,
+                result: f[5].clone(),
+                count: if f[6].is_empty() { None } else { Some(f[6].parse().map_err(|_| bad())?) },
+                wall_time: f[7].parse().map_err(|_| bad())?,
+                timeout: f[8].parse().map_err(|_| bad())?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_expand_to_their_product() {
+        assert_eq!(expand_range("fence 5..7").unwrap(), ["fence 5", "fence 6", "fence 7"]);
+        assert_eq!(
+            expand_range("young 1..2 2..3").unwrap(),
+            ["young 1 2", "young 1 3", "young 2 2", "young 2 3"]
+        );
+        assert_eq!(expand_range("grid 12,3").unwrap(), ["grid 12,3"]);
+        assert!(expand_range("fence 7..5").is_err());
+        assert!(expand_range("").is_err());
+    }
+
+    #[test]
+    fn csv_roundtrip_with_quoting() {
+        let rows = vec![
+            Row {
+                instance: "grid 12,3".into(),
+                faces: 52,
+                dim: 4,
+                mode: "count".into(),
+                cyclic: false,
+                result: "yes".into(),
+                count: Some(1234),
+                wall_time: 0.25,
+                timeout: false,
+            },
+            Row {
+                instance: "lattices/\"odd\" name".into(),
+                faces: 8,
+                dim: 3,
+                mode: "exists".into(),
+                cyclic: true,
+                result: "timeout".into(),
+                count: None,
+                wall_time: 10.0,
+                timeout: true,
+            },
+        ];
+        assert_eq!(from_csv(&to_csv(&rows)).unwrap(), rows);
+        assert_eq!(done(&rows, true).len(), 1);
+        assert_eq!(done(&rows, false).len(), 2);
+    }
+}
@@ -5,13 +5,66 @@
 //! to stdout — the exact message shapes `app.js#onWorkerMessage` already
 //! understands, so the browser treats an SSH pipe and a Web Worker alike:
 //!
-//!   stdin  (first line): {"graph": <WireGraph>, "cyclic": bool,
-//!                         "mode": "exists"|"count"|"enumerate", "cap": 512}
+//!   stdin  (first line): {"graph": <Poset>, "cyclic": bool,
+//!                         "mode": "exists"|"count"|"enumerate", "cap": 512,
+//!                         "cache": true}
 //!   stdout (per line):   {"type":"note","message":...}
-//!                        {"type":"strips","strips":[...],"count":n}
-//!                        {"type":"progress","count":n}
-//!                        {"type":"done","count":n,"capped":bool}
-//!                        {"type":"error","message":...}
+//!                        {"type":"strips","strips":[...],"count":n, ...}
+//!                        {"type":"progress","count":n,"percent":p,
+//!                         "rate":strips/s,"eta":secs|null,"branches":[i,k]}
+//!                        {"type":"done","count":n,"capped":bool,
+//!                         "summary":{...}}
+//!                        {"type":"error","kind":...,"message":...}
+//!
+//! Every `done` carries the job's summary ([`rhombic_strips::summary`]): the
+//! lattice's fingerprint, mode, cyclic flag, shard, count, threads, seeds,
+//! hamiltonian paths tried, wall time, host and crate version. `--log FILE`
+//! also appends it to FILE, a line per job (the jobs of `--serve` too).
+//!
+//! Limits: the poset's faces (`--max-faces`) and the estimated size of its
+//! lattice (`--max-memory`, 4 GiB by default; the bridge matrix grows with
+//! the square of the faces) are checked before the lattice is built, the
+//! job's wall-clock time (`--time-limit`) and the strips an enumeration
+//! writes (`--max-strips`) while it runs. A job over one ends with an
+//! `error` whose `kind` names the limit; a job that cannot run at all (bad
+//! JSON, an unknown mode, no poset) with kind `invalid`.
+//!
+//! The Scripts panel's batch jobs run here too, in the scripts worker's
+//! shapes ([`rhombic_strips::scripts`]), natively and in parallel; both end
+//! with a `done` whose count is of the graphs or strips:
+//!
+//!   {"mode": "survey", "n": 6, "linear": true, "cyclic": true}
+//!                        -> {"type":"survey","results":[...],"phase":...,
+//!                            "level":k,"checked":i,"total":t,"done":bool}
+//!   {"mode": "bounds", "graph": <Poset>}
+//!                        -> {"type":"bounds","pairs":[...],"count":n,
+//!                            "distinct":d,"done":bool}
+//!
+//! Filters and aggregations: an `enumerate` job may carry a `filter`
+//! ([`rhombic_strips::filter`]; boundary chains, layer widths, faces that
+//! must be bridges or sit in gaps, linear strips that are also cyclic) and
+//! sends only the strips it keeps; its `done` adds how many were
+//! `searched`. With `aggregate` it sends no strips at all but, searching in
+//! parallel, one message of tables of the strips kept:
+//!
+//!   {"mode": "enumerate", "graph": <Poset>,
+//!    "filter": {"left": "012", "widths": [3, null], "bridges": ["01"],
+//!               "gaps": [...], "right": ..., "alsoCyclic": bool},
+//!    "aggregate": ["widths", "boundaries", "paths"]}
+//!                        -> {"type":"aggregate",
+//!                            "widths":[{"level","width","count"}...],
+//!                            "boundaries":[{"left","right","count"}...],
+//!                            "paths":[{"path","labels","count"}...]}
+//!
+//! Encoding: a job with `"encoding": "compact"` gets its strips (of
+//! `enumerate` and `exists`) as bytes instead ([`rhombic_strips::codec`]):
+//! layers delta-coded as varints, no edges, base64 in `data`, in batches of
+//! up to 256. The page recomputes the edges ([`rhombic_strips::web`]'s
+//! `StripDecoder`). Every compact message says so, and a `strip_stream`
+//! that predates the field (or `--coordinate`) answers in JSON, so the page
+//! asks for it on every job and decodes whatever comes back:
+//!
+//!   {"type":"strips","encoding":"compact","data":"...","count":n, ...}
 //!
 //! Native perks over the wasm build: `count` and `exists` run rayon-parallel
 //! over the Hamiltonian paths of level 0 (`rhombic::count_strips` semantics,
@@ -19,35 +72,172 @@
 //! stays sequential — it streams strips in a stable order — but on a big
 //! node, with a `cap` so a one-shot HTTP relay can't be flooded.
 //!
-//! Cancellation: the process dies with the pipe. When ssh (or the CGI relay
+//! Progress: the search is split into seeds weighed by their estimated size
+//! ([`rhombic_strips::progress`]); `percent` is the weight of the seeds
+//! finished, `eta` the time left at the pace so far (null until a seed is
+//! done). `count` and `exists` report once a second, `enumerate` with every
+//! batch of strips; in `exists` the count is of the paths tried.
+//!
+//! Results cache: `exists` and `count` answer from the on-disk cache of
+//! [`rhombic_strips::cache`] when the poset (up to isomorphism) is already
+//! known, and every finished search adds to it — so the same count is not
+//! recomputed on the cluster twice. `"cache": false` in the job skips it;
+//! `RHOMBIC_CACHE` picks the directory (or `off`).
+//!
+//! Cancellation: the process dies with the pipe. When ssh (or the HTTP relay
 //! behind it) goes away, writes fail and we exit; srun then tears down the
 //! allocation. A `{"cmd":"cancel"}` line on stdin also exits, for interactive
 //! bridges that keep stdin open.
+//!
+//! Sessions: `strip_stream --session` keeps reading jobs, one per line, so a
+//! bridge pays process start-up (and on the cluster the `srun` allocation)
+//! once. Every job carries an `"id"` (any JSON value), its jobs run side by
+//! side and every message they write carries that id; the last one is
+//! `done`, `error` or `{"type":"cancelled","id":...}`.
+//!
+//!   stdin  (per line):   {"id": 1, "graph": ..., "mode": ..., ...}
+//!                        {"cmd": "cancel", "id": 1}
+//!   stdout (per line):   the messages above, each with its job's "id"
+//!
+//! The lattices of recent posets are kept between jobs, so asking for the
+//! count after the existence of the same poset does not rebuild it. The
+//! session ends at EOF, once its running jobs have finished.
+//!
+//! `strip_stream --serve` is that HTTP relay ([`rhombic_strips::relay`]): a
+//! loopback server for the page's `/ping` and `/job` requests that runs each
+//! job as `strip_stream` in job mode, or under Slurm with `--srun`.
+//!
+//! `strip_stream --coordinate [HOST:]PORT` reads a `count` or `enumerate`
+//! job like the one-shot mode but searches it with `strip_stream --work`
+//! processes that connect over TCP ([`rhombic_strips::distributed`]). It
+//! leases them prefixes of the path search on demand and splits long ones,
+//! and writes the messages above; its `done` adds `workers`, `leases` and
+//! `splits`.
 
+use std::collections::{HashMap, VecDeque};
 use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
+use rhombic_strips::cache::{Cache, Known};
+use rhombic_strips::codec;
+use rhombic_strips::distributed::{self, Coordinator, Worker};
+use rhombic_strips::filter::{self, Filter, Tally};
 use rhombic_strips::lattice::{FaceId, Lattice};
 use rhombic_strips::plotting;
-use rhombic_strips::rhombic::{extensions, strips};
-use rhombic_strips::web::api::{wire_to_faces, WireGraph};
+use rhombic_strips::progress::{Progress, CHECK_EVERY};
+use rhombic_strips::relay::{self, Relay};
+use rhombic_strips::rhombic::{extensions, Strip};
+use rhombic_strips::scripts::{self, api::SurveyCore};
+use rhombic_strips::shard::Shard;
+use rhombic_strips::summary::Summary;
+use rhombic_strips::generators::Poset;
+
+const USAGE: &str = "\
+usage: strip_stream [--shard i/N]   run the job on the first line of stdin
+                                    (--shard: only part i of N, see merge)
+       strip_stream --session       run the jobs of every stdin line, by id
+       strip_stream --serve [--port N] [--srun[=ARGS]]
+                                    relay the web page's jobs over HTTP
+       strip_stream --coordinate [HOST:]PORT [--split-after SECS]
+                                    hand the job on stdin out to workers
+       strip_stream --work [HOST:]PORT [--threads N]
+                                    search for a coordinator until it is done
+
+  --port N        listen on 127.0.0.1:N (default 8642, 0 = any free port)
+  --srun[=ARGS]   run each job under `srun --unbuffered ARGS` (ARGS split
+                  at spaces, e.g. --srun=\"--partition=math --time=1:00:00\")
+
+  --split-after S split a worker's lease after S seconds (default 1) when
+                  other workers sit idle
+  --threads N     leases a worker searches side by side (default: all cores)
+
+Limits on every job, and its log (one-shot, --session, the jobs of --serve;
+the face and memory limits and the log also hold for --coordinate):
+  --max-faces N   refuse posets of more than N faces
+  --max-memory S  refuse posets whose lattice would take more than S bytes
+                  (suffix K, M, G or T; default 4G)
+  --time-limit S  stop a job after S seconds
+  --max-strips N  stop an enumeration after N strips, whatever its cap
+  --log FILE      append every finished job's summary to FILE (JSONL)
+A job over a limit ends with an error of that kind: {\"type\":\"error\",
+\"kind\":\"faces\"|\"memory\"|\"time\"|\"strips\",...}; a job that cannot run
+at all with one of kind \"invalid\".
+
+The pairing code the page must send is taken from $RHOMBIC_TOKEN; so is the
+one workers must show the coordinator, if set. A bare PORT is on 127.0.0.1
+(default 8643); forward it with ssh -R/-L to workers on other machines.";
 
 // -- wire messages (match worker.js) -----------------------------------------
 
 #[derive(Deserialize)]
 struct Job {
-    graph: WireGraph,
+    /// Session mode: tags every message of the job.
+    #[serde(default)]
+    id: Option<Value>,
+    /// Every mode but `survey`.
+    #[serde(default)]
+    graph: Option<Poset>,
     #[serde(default)]
     cyclic: bool,
     mode: String,
     /// Enumerate only: stop after this many strips (0 = unlimited).
     #[serde(default)]
     cap: usize,
+    /// Consult and fill the results cache.
+    #[serde(default = "yes")]
+    cache: bool,
+    /// Count or enumerate only part `i/N` of the search.
+    #[serde(default)]
+    shard: Option<String>,
+    /// Survey only: the graphs on up to `n` vertices.
+    #[serde(default)]
+    n: usize,
+    /// Survey only: check the tube posets for linear strips (`cyclic` for
+    /// cyclic ones).
+    #[serde(default = "yes")]
+    linear: bool,
+    /// Enumerate only: keep just these strips.
+    #[serde(default)]
+    filter: Filter,
+    /// Enumerate only: these aggregations of the strips kept, instead of
+    /// the strips.
+    #[serde(default)]
+    aggregate: Vec<String>,
+    /// How `strips` messages carry the strips.
+    #[serde(default)]
+    encoding: Encoding,
+}
+
+/// `"json"`: layers and edges spelled out; `"compact"`: layers only, in
+/// [`codec`]'s bytes, for the receiver to recompute the edges.
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Encoding {
+    #[default]
+    Json,
+    Compact,
+}
+
+impl Job {
+    fn graph(&self) -> Result<&Poset, String> {
+        self.graph.as_ref().ok_or_else(|| format!("a {} job needs a \"graph\"", self.mode))
+    }
+
+    /// The filter, for the summary, if it keeps less than every strip.
+    fn filtered(&self) -> Option<Filter> {
+        Some(self.filter.clone()).filter(|f| !f.is_empty())
+    }
+}
+
+fn yes() -> bool {
+    true
 }
 
 #[derive(Serialize)]
@@ -58,7 +248,28 @@ struct StripOut {
     cyclic_edges: Vec<(FaceId, FaceId)>,
 }
 
-fn emit(v: &serde_json::Value) {
+/// Make `v` a `strips` message of `strips`, in the job's encoding.
+fn put_strips(v: &mut Value, strips: Vec<Strip>, l: &Lattice, cyclic: bool, encoding: Encoding) {
+    v["type"] = json!("strips");
+    match encoding {
+        Encoding::Json => {
+            let strips: Vec<StripOut> = strips
+                .into_iter()
+                .map(|strip| {
+                    let (edges, cyclic_edges) = plotting::edges_strip(&strip, l, cyclic);
+                    StripOut { layers: strip, edges, cyclic_edges }
+                })
+                .collect();
+            v["strips"] = json!(strips);
+        }
+        Encoding::Compact => {
+            v["encoding"] = json!("compact");
+            v["data"] = json!(codec::encode_base64(&codec::encode_strips(&strips)));
+        }
+    }
+}
+
+fn emit(v: &Value) {
     let mut out = std::io::stdout().lock();
     if writeln!(out, "{}", v).and_then(|_| out.flush()).is_err() {
         // Reader hung up (browser cancelled, ssh died): stop computing.
@@ -66,35 +277,293 @@ fn emit(v: &serde_json::Value) {
     }
 }
 
-fn note(msg: &str) {
-    emit(&serde_json::json!({"type": "note", "message": msg}));
+/// A running job's side of the pipe: its messages, tagged with its id in a
+/// session, its cancel flag, its time limit, and since when it runs and
+/// where its summary is logged.
+#[derive(Clone)]
+struct Out {
+    id: Option<Value>,
+    cancel: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    started: Instant,
+    log: Option<Arc<Path>>,
+}
+
+impl Out {
+    fn new(id: Option<Value>, limits: &Limits, log: Option<Arc<Path>>) -> Self {
+        let started = Instant::now();
+        let deadline = limits.time.map(|t| started + t);
+        Out { id, cancel: Arc::new(AtomicBool::new(false)), deadline, started, log }
+    }
+
+    fn emit(&self, mut v: Value) {
+        if let Some(id) = &self.id {
+            v["id"] = id.clone();
+        }
+        emit(&v);
+    }
+
+    fn note(&self, msg: &str) {
+        self.emit(json!({"type": "note", "message": msg}));
+    }
+
+    /// End the job with `done` and its `summary`, which `--log` also appends
+    /// to the log.
+    fn done(&self, mut done: Value, summary: Summary) {
+        let summary = summary.finish(self.started);
+        if let Some(Err(e)) = self.log.as_ref().map(|path| summary.append(path)) {
+            self.note(&format!("summary not logged: {}", e));
+        }
+        done["summary"] = json!(summary);
+        self.emit(done);
+    }
+
+    /// Cancelled, or out of time.
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) || self.expired()
+    }
+
+    fn expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+/// Why a job failed: `kind` is the limit it ran into, or "invalid".
+struct Failure {
+    kind: &'static str,
+    message: String,
+}
+
+impl Failure {
+    fn message(&self) -> Value {
+        json!({"type": "error", "kind": self.kind, "message": self.message})
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure { kind: "invalid", message }
+    }
 }
 
-fn fail(msg: &str) -> ! {
-    emit(&serde_json::json!({"type": "error", "message": msg}));
+impl From<&str> for Failure {
+    fn from(message: &str) -> Self {
+        Failure::from(message.to_string())
+    }
+}
+
+fn fail(failure: impl Into<Failure>) -> ! {
+    emit(&failure.into().message());
     std::process::exit(1);
 }
 
+/// The default `--max-memory`: a lattice of about 16 000 faces.
+const MAX_MEMORY: u64 = 4 << 30;
+
+/// What one job may use.
+#[derive(Clone, Copy)]
+struct Limits {
+    faces: usize,
+    /// Bytes, by [`Lattice::estimate_bytes`].
+    memory: u64,
+    time: Option<Duration>,
+    strips: usize,
+}
+
+impl Limits {
+    /// Take the limit flags out of `args`; the limits and the flags as given,
+    /// for `--serve` to pass on to its jobs.
+    fn take(args: &mut Vec<String>) -> Result<(Limits, Vec<String>), String> {
+        let mut limits = Limits { faces: usize::MAX, memory: MAX_MEMORY, time: None, strips: usize::MAX };
+        let (mut taken, mut rest) = (vec![], vec![]);
+        let mut all = std::mem::take(args).into_iter();
+        while let Some(arg) = all.next() {
+            if !["--max-faces", "--max-memory", "--time-limit", "--max-strips"].contains(&arg.as_str()) {
+                rest.push(arg);
+                continue;
+            }
+            let value = all.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            let bad = || format!("{} {}: not a positive amount", arg, value);
+            let count = || value.parse().ok().filter(|&n: &usize| n > 0).ok_or_else(bad);
+            match arg.as_str() {
+                "--max-faces" => limits.faces = count()?,
+                "--max-memory" => limits.memory = parse_bytes(&value).ok_or_else(bad)?,
+                "--time-limit" => {
+                    let secs = value.parse::<f64>().ok().filter(|s| *s > 0.0);
+                    limits.time = Some(secs.and_then(|s| Duration::try_from_secs_f64(s).ok()).ok_or_else(bad)?);
+                }
+                _ => limits.strips = count()?,
+            }
+            taken.extend([arg, value]);
+        }
+        *args = rest;
+        Ok((limits, taken))
+    }
+
+    /// The lattice of `graph`, unless it has too many faces or would take
+    /// too much memory.
+    fn lattice(&self, graph: &Poset) -> Result<Lattice, Failure> {
+        let n = graph.labels.len();
+        if n > self.faces {
+            return Err(Failure {
+                kind: "faces",
+                message: format!("the poset has {} faces, over the limit of {} (--max-faces)", n, self.faces),
+            });
+        }
+        let faces = graph.to_faces()?;
+        let bytes = Lattice::estimate_bytes(&faces);
+        if bytes > self.memory {
+            return Err(Failure {
+                kind: "memory",
+                message: format!(
+                    "the lattice of these {} faces would take about {}, over the limit of {} (--max-memory)",
+                    n,
+                    bytes_text(bytes),
+                    bytes_text(self.memory)
+                ),
+            });
+        }
+        Ok(Lattice::from_faces(faces))
+    }
+}
+
+/// Take `--log FILE` out of `args`: the file every job appends its summary
+/// to, made absolute so that the jobs of `--serve` (under `srun` too) find it.
+fn take_log(args: &mut Vec<String>) -> Result<Option<Arc<Path>>, String> {
+    let Some(i) = args.iter().position(|a| a == "--log") else { return Ok(None) };
+    let path = args.get(i + 1).ok_or("--log needs a file")?;
+    let path = std::path::absolute(path).map_err(|e| format!("--log {}: {}", path, e))?;
+    args.drain(i..i + 2);
+    Ok(Some(path.into()))
+}
+
+/// "512M", "4G", "1.5T", "1000000" (bytes); K, M, G and T are powers of 1024.
+fn parse_bytes(s: &str) -> Option<u64> {
+    let s = s.trim().trim_end_matches(['B', 'b']).trim_end_matches('i');
+    let (number, unit) = match s.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&s[..i], c.to_ascii_uppercase()),
+        _ => (s, ' '),
+    };
+    let scale = match unit {
+        ' ' => 1u64,
+        'K' => 1 << 10,
+        'M' => 1 << 20,
+        'G' => 1 << 30,
+        'T' => 1 << 40,
+        _ => return None,
+    };
+    let x: f64 = number.trim().parse().ok().filter(|x: &f64| *x > 0.0 && x.is_finite())?;
+    Some((x * scale as f64) as u64)
+}
+
+/// "812 MiB", "12.3 GiB".
+fn bytes_text(bytes: u64) -> String {
+    let b = bytes as f64;
+    match bytes {
+        0..=1023 => format!("{} bytes", bytes),
+        1024..=1_048_575 => format!("{:.0} KiB", b / 1024.0),
+        1_048_576..=1_073_741_823 => format!("{:.0} MiB", b / 1_048_576.0),
+        _ => format!("{:.1} GiB", b / 1_073_741_824.0),
+    }
+}
+
+/// `--serve` and its options, passing `job_args` (the limits and `--log`) on
+/// to every job; the exit code on failure.
+fn serve(args: &[String], job_args: Vec<String>) -> i32 {
+    let mut serving = false;
+    let mut relay = Relay { port: relay::PORT, token: String::new(), srun: None, bin: PathBuf::new(), args: job_args };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--serve" => serving = true,
+            "--port" => match args.next().and_then(|p| p.parse().ok()) {
+                Some(p) => relay.port = p,
+                None => return usage("--port needs a port number"),
+            },
+            "--srun" => relay.srun = Some(vec![]),
+            "-h" | "--help" | "help" => {
+                println!("{}", USAGE);
+                return 0;
+            }
+            a => match a.strip_prefix("--srun=") {
+                Some(rest) => relay.srun = Some(rest.split_whitespace().map(str::to_string).collect()),
+                None => return usage(&format!("unknown argument '{}'", a)),
+            },
+        }
+    }
+    if !serving {
+        return usage("--port and --srun go with --serve");
+    }
+    relay.token = std::env::var("RHOMBIC_TOKEN").unwrap_or_default();
+    if relay.token.is_empty() {
+        return usage("RHOMBIC_TOKEN is not set (cluster/serve.sh sets it)");
+    }
+    relay.bin = match std::env::current_exe() {
+        Ok(bin) => bin,
+        Err(e) => {
+            eprintln!("strip_stream: cannot locate this binary: {}", e);
+            return 1;
+        }
+    };
+    match relay.serve() {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("strip_stream: {}", e);
+            1
+        }
+    }
+}
+
+fn usage(msg: &str) -> i32 {
+    eprintln!("strip_stream: {}\n\n{}", msg, USAGE);
+    2
+}
+
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let (limits, mut job_args) = Limits::take(&mut args).unwrap_or_else(|e| std::process::exit(usage(&e)));
+    let log = take_log(&mut args).unwrap_or_else(|e| std::process::exit(usage(&e)));
+    if let Some(path) = &log {
+        job_args.extend(["--log".to_string(), path.to_string_lossy().into_owned()]);
+    }
+    let shard = match args.as_slice() {
+        [] => None,
+        [a] if a == "--session" => return session(limits, log),
+        [a, spec] if a == "--shard" => Some(spec.clone()),
+        [a, ..] if a == "--coordinate" => return coordinate(&args, limits, log),
+        [a, ..] if a == "--work" && !job_args.is_empty() => {
+            std::process::exit(usage("the limits and --log go with jobs, not with --work"))
+        }
+        [a, ..] if a == "--work" => std::process::exit(work(&args)),
+        _ => std::process::exit(serve(&args, job_args)),
+    };
+
+    let mut job = read_job();
+    job.shard = shard.or(job.shard);
+    watch_stdin();
+
+    let out = Out::new(job.id.clone(), &limits, log);
+    if let Err(e) = run(&job, &|g| Ok(Arc::new(limits.lattice(g)?)), &limits, &out) {
+        fail(e);
+    }
+}
+
+/// The job on the first line of stdin.
+fn read_job() -> Job {
     let stdin = std::io::stdin();
     let mut first = String::new();
     if stdin.lock().read_line(&mut first).unwrap_or(0) == 0 {
         fail("no job on stdin");
     }
-    let job: Job = match serde_json::from_str(first.trim()) {
+    match serde_json::from_str(first.trim()) {
         Ok(j) => j,
-        Err(e) => fail(&format!("bad job JSON: {}", e)),
-    };
-
-    let faces = match wire_to_faces(&job.graph) {
-        Ok(f) => f,
-        Err(e) => fail(&e),
-    };
-    let lattice = Lattice::from_faces(faces);
-    let cyclic = job.cyclic;
+        Err(e) => fail(format!("bad job JSON: {}", e)),
+    }
+}
 
-    // stdin watcher: exit on {"cmd":"cancel"}; ignore everything else
-    // (including EOF — a one-shot relay closes stdin right after the job).
+/// stdin watcher: exit on {"cmd":"cancel"}; ignore everything else
+/// (including EOF — a one-shot relay closes stdin right after the job).
+fn watch_stdin() {
     std::thread::spawn(move || {
         for line in std::io::stdin().lock().lines() {
             let Ok(line) = line else { return };
@@ -103,164 +572,606 @@ fn main() {
             }
         }
     });
+}
+
+/// `--coordinate ADDR`: the job on stdin, searched by the `--work`ers that
+/// connect, with this process's usual messages on stdout.
+fn coordinate(args: &[String], limits: Limits, log: Option<Arc<Path>>) {
+    let mut coordinator = Coordinator {
+        addr: distributed::address(&distributed::PORT.to_string()),
+        token: std::env::var("RHOMBIC_TOKEN").unwrap_or_default(),
+        split_after: Duration::from_secs(1),
+    };
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--split-after" => match args.next().and_then(|s| s.parse::<f64>().ok()).filter(|s| *s >= 0.0) {
+                Some(s) => coordinator.split_after = Duration::from_secs_f64(s),
+                None => std::process::exit(usage("--split-after needs a number of seconds")),
+            },
+            a if !a.starts_with('-') => coordinator.addr = distributed::address(a),
+            a => std::process::exit(usage(&format!("unknown argument '{}'", a))),
+        }
+    }
+
+    let job = read_job();
+    if job.shard.is_some() {
+        fail("a coordinated search is whole: no shard");
+    }
+    if !job.filter.is_empty() || !job.aggregate.is_empty() {
+        fail("a coordinated search counts or lists every strip: no filter or aggregation");
+    }
+    let graph = job.graph().unwrap_or_else(|e| fail(e));
+    let lattice = limits.lattice(graph).unwrap_or_else(|e| fail(e));
+    watch_stdin();
+    let out = Out::new(job.id.clone(), &limits, log);
+    let cache = if job.cache { Cache::from_env() } else { None };
+    let cache = cache.as_ref().filter(|_| job.mode == "count");
+    let summary = Summary::new(&job.mode, Some(&lattice), job.cyclic);
+    if let Some(count) = recall(cache, &lattice, job.cyclic, &out, |k| k.count.is_some()).and_then(|k| k.count) {
+        out.done(json!({"type": "done", "count": count, "capped": false}), cached(summary, count));
+        return;
+    }
+    let emit = |v: Value| out.emit(v);
+    match coordinator.run(graph, &lattice, job.cyclic, &job.mode, job.cap, &emit) {
+        Ok(s) => {
+            if !s.capped {
+                remember(cache, &lattice, job.cyclic, Known { count: Some(s.count), ..Known::default() }, &out);
+            }
+            if s.capped {
+                out.note(&format!("stopped at the first {} strips (raise the cap to get more)", job.cap));
+            }
+            let done = json!({
+                "type": "done",
+                "count": s.count,
+                "capped": s.capped,
+                "workers": s.workers,
+                "leases": s.leases,
+                "splits": s.splits,
+            });
+            let summary = Summary { count: s.count, complete: !s.capped, workers: Some(s.workers), ..summary };
+            out.done(done, summary);
+        }
+        Err(e) => fail(e),
+    }
+}
+
+/// `--work ADDR [--threads N]`; the exit code.
+fn work(args: &[String]) -> i32 {
+    let mut worker = Worker {
+        addr: distributed::address(&distributed::PORT.to_string()),
+        token: std::env::var("RHOMBIC_TOKEN").unwrap_or_default(),
+        threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threads" => match args.next().and_then(|n| n.parse().ok()).filter(|&n| n > 0) {
+                Some(n) => worker.threads = n,
+                None => return usage("--threads needs a positive number"),
+            },
+            a if !a.starts_with('-') => worker.addr = distributed::address(a),
+            a => return usage(&format!("unknown argument '{}'", a)),
+        }
+    }
+    match worker.run(&|line| eprintln!("strip_stream: {}", line)) {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("strip_stream: {}", e);
+            1
+        }
+    }
+}
+
+/// Seeds an enumeration is split into, for its progress.
+const ENUMERATE_SEEDS: usize = 64;
+
+/// Lattices kept between the jobs of a session.
+const LATTICES: usize = 8;
+
+/// The lattices of the last [`LATTICES`] posets of a session, by their JSON.
+#[derive(Default)]
+struct Lattices(VecDeque<(String, Arc<Lattice>)>);
+
+impl Lattices {
+    fn get(cache: &Mutex<Lattices>, graph: &Poset, limits: &Limits, out: &Out) -> Result<Arc<Lattice>, Failure> {
+        let key = serde_json::to_string(graph).map_err(|e| e.to_string())?;
+        if let Some((_, l)) = cache.lock().unwrap().0.iter().find(|(k, _)| *k == key) {
+            out.note("same poset as an earlier job: reusing its lattice");
+            return Ok(l.clone());
+        }
+        let l = Arc::new(limits.lattice(graph)?);
+        let mut cache = cache.lock().unwrap();
+        if cache.0.len() == LATTICES {
+            cache.0.pop_front();
+        }
+        cache.0.push_back((key, l.clone()));
+        Ok(l)
+    }
+}
+
+/// `--session`: run the job on every stdin line on a thread of its own,
+/// cancel jobs by id, and wait for the running ones at EOF.
+fn session(limits: Limits, log: Option<Arc<Path>>) {
+    let running: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>> = Arc::default();
+    let lattices: Arc<Mutex<Lattices>> = Arc::default();
+    let mut threads = Vec::new();
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(e) => {
+                emit(&Failure::from(format!("bad job JSON: {}", e)).message());
+                continue;
+            }
+        };
+        let key = v["id"].to_string();
+        if v["cmd"] == "cancel" {
+            // a job that already finished has nothing left to cancel
+            if let Some(flag) = running.lock().unwrap().get(&key) {
+                flag.store(true, Ordering::Relaxed);
+            }
+            continue;
+        }
+        let tag = |msg: String| {
+            let mut error = Failure::from(msg).message();
+            if !v["id"].is_null() {
+                error["id"] = v["id"].clone();
+            }
+            error
+        };
+        if v["id"].is_null() {
+            emit(&tag("a session job needs an \"id\"".to_string()));
+            continue;
+        }
+        let job: Job = match serde_json::from_value(v.clone()) {
+            Ok(j) => j,
+            Err(e) => {
+                emit(&tag(format!("bad job JSON: {}", e)));
+                continue;
+            }
+        };
+        let out = Out::new(job.id.clone(), &limits, log.clone());
+        {
+            let mut running = running.lock().unwrap();
+            if running.contains_key(&key) {
+                emit(&tag(format!("job {} is already running", key)));
+                continue;
+            }
+            running.insert(key.clone(), out.cancel.clone());
+        }
 
+        let (running, lattices) = (running.clone(), lattices.clone());
+        threads.push(std::thread::spawn(move || {
+            let result = run(&job, &|g| Lattices::get(&lattices, g, &limits, &out), &limits, &out);
+            running.lock().unwrap().remove(&key);
+            match result {
+                Ok(true) => {}
+                Ok(false) => out.emit(json!({"type": "cancelled"})),
+                Err(e) => out.emit(e.message()),
+            }
+        }));
+        threads.retain(|t| !t.is_finished());
+    }
+    for t in threads {
+        let _ = t.join();
+    }
+}
+
+/// Run one job, with the lattice of its graph from `lattice`; false if it
+/// was cancelled (and wrote no `done`). Out of time it fails.
+fn run(
+    job: &Job,
+    lattice: &dyn Fn(&Poset) -> Result<Arc<Lattice>, Failure>,
+    limits: &Limits,
+    out: &Out,
+) -> Result<bool, Failure> {
+    let finished = run_mode(job, lattice, limits, out)?;
+    match limits.time {
+        Some(t) if !finished && out.expired() && !out.cancel.load(Ordering::Relaxed) => Err(Failure {
+            kind: "time",
+            message: format!("stopped at the time limit of {}s (--time-limit)", t.as_secs_f64()),
+        }),
+        _ => Ok(finished),
+    }
+}
+
+fn run_mode(
+    job: &Job,
+    lattice: &dyn Fn(&Poset) -> Result<Arc<Lattice>, Failure>,
+    limits: &Limits,
+    out: &Out,
+) -> Result<bool, Failure> {
     let threads = rayon::current_num_threads();
-    match job.mode.as_str() {
-        "count" => run_count(&lattice, cyclic, threads),
-        "exists" => run_exists(&lattice, cyclic, threads),
-        "enumerate" => run_enumerate(&lattice, cyclic, job.cap),
-        m => fail(&format!("unknown mode '{}'", m)),
+    if job.mode != "enumerate" && (!job.filter.is_empty() || !job.aggregate.is_empty()) {
+        return Err(format!("filters and aggregations go with enumerate, not {}", job.mode).into());
+    }
+    let shard = job.shard.as_deref().map(Shard::parse).transpose()?;
+    let whole = |m: &str| Err(format!("a shard counts or enumerates; {} searches whole", m).into());
+    if job.mode == "survey" {
+        return if shard.is_some() { whole("survey") } else { Ok(run_survey(job, threads, out)?) };
+    }
+    let lattice = &*lattice(job.graph()?)?;
+    // a shard's count is a part of one, not the lattice's
+    let cache = if job.cache && shard.is_none() { Cache::from_env() } else { None };
+    let cache = cache.as_ref();
+    Ok(match (job.mode.as_str(), shard) {
+        ("count", shard) => run_count(lattice, job.cyclic, threads, cache, shard, out),
+        ("exists", None) => run_exists(lattice, job.cyclic, job.encoding, threads, cache, out),
+        ("enumerate", shard) if !job.aggregate.is_empty() => run_aggregate(lattice, job, threads, cache, shard, out)?,
+        ("enumerate", shard) => run_enumerate(lattice, job, limits.strips, cache, shard, out)?,
+        ("bounds", None) => run_bounds(lattice, threads, out),
+        (m @ ("exists" | "bounds"), Some(_)) => return whole(m),
+        (m, _) => return Err(format!("unknown mode '{}'", m).into()),
+    })
+}
+
+/// A shard's `done` message gets its part for `rhombic_strips merge`.
+fn done_message(l: &Lattice, cyclic: bool, shard: Option<Shard>, mode: &str, count: usize, capped: bool) -> Value {
+    let mut v = json!({"type": "done", "count": count, "capped": capped});
+    if let Some(s) = shard {
+        v["shard"] = json!(s.part(l, cyclic, mode, count as u64, !capped));
+    }
+    v
+}
+
+/// The summary of a job the results cache answered with `count`.
+fn cached(summary: Summary, count: u64) -> Summary {
+    Summary { count, cached: true, ..summary }
+}
+
+/// The summary of a `mode` search of `l` on `threads` over the seeds of
+/// `progress`, to fill in with what it found.
+fn search_summary(mode: &str, l: &Lattice, cyclic: bool, shard: Option<Shard>, threads: usize, progress: &Progress) -> Summary {
+    Summary {
+        shard: shard.map(|s| format!("{}/{}", s.index, s.of)),
+        threads: Some(threads),
+        seeds: Some(progress.seeds()),
+        paths: Some(progress.paths()),
+        ..Summary::new(mode, Some(l), cyclic)
     }
 }
 
-/// Progress ticker: reports `counter` once a second until `done`.
+/// What the results cache knows about `l`, if that is `enough` to answer;
+/// announced with a note.
+fn recall(
+    cache: Option<&Cache>,
+    l: &Lattice,
+    cyclic: bool,
+    out: &Out,
+    enough: impl Fn(&Known) -> bool,
+) -> Option<Known> {
+    let known = cache?.get(l, cyclic).filter(enough)?;
+    out.note("known poset: answering from the results cache");
+    Some(known)
+}
+
+fn remember(cache: Option<&Cache>, l: &Lattice, cyclic: bool, known: Known, out: &Out) {
+    if let Some(Err(e)) = cache.map(|c| c.put(l, cyclic, None, known)) {
+        out.note(&format!("results cache not updated: {}", e));
+    }
+}
+
+/// Progress ticker: reports `progress` once a second until `done`.
 /// Doubles as a keep-alive so HTTP relays in the middle don't time out.
-fn spawn_ticker(counter: Arc<AtomicUsize>, done: Arc<AtomicBool>) -> std::thread::JoinHandle<()> {
+fn spawn_ticker(progress: Arc<Progress>, done: Arc<AtomicBool>, out: &Out) -> std::thread::JoinHandle<()> {
+    let out = out.clone();
+    let started = Instant::now();
     std::thread::spawn(move || {
+        // short naps, so a finished job is not kept waiting for the next tick
+        let mut next = Duration::from_secs(1);
         while !done.load(Ordering::Relaxed) {
-            std::thread::sleep(Duration::from_millis(1000));
-            if done.load(Ordering::Relaxed) {
-                break;
+            std::thread::sleep(Duration::from_millis(20));
+            if started.elapsed() >= next && !done.load(Ordering::Relaxed) {
+                next += Duration::from_secs(1);
+                out.emit(progress.report(started.elapsed()).message());
             }
-            emit(&serde_json::json!({
-                "type": "progress",
-                "count": counter.load(Ordering::Relaxed),
-            }));
         }
     })
 }
 
 /// Parallel count over independent subtrees of the hamiltonian-path DFS
 /// (`Lattice::ham_path_seeds`) — parallelising the path search itself, not
-/// just the extensions. Every found strip bumps a shared counter the ticker
-/// reports once a second.
-fn run_count(l: &Lattice, cyclic: bool, threads: usize) {
-    let seeds = l.ham_path_seeds(cyclic, threads * 16);
-    note(&format!(
+/// just the extensions. The ticker reports the strips found and the share
+/// of the seeds, by estimated size, searched. False if cancelled.
+fn run_count(
+    l: &Lattice,
+    cyclic: bool,
+    threads: usize,
+    cache: Option<&Cache>,
+    shard: Option<Shard>,
+    out: &Out,
+) -> bool {
+    if let Some(count) = recall(cache, l, cyclic, out, |k| k.count.is_some()).and_then(|k| k.count) {
+        let done = json!({"type": "done", "count": count, "capped": false});
+        out.done(done, cached(Summary::new("count", Some(l), cyclic), count));
+        return true;
+    }
+    let seeds = match shard {
+        Some(s) => s.seeds(l, cyclic),
+        None => l.ham_path_seeds(cyclic, threads * 16),
+    };
+    out.note(&format!(
         "counting on {} threads across {} search branches…",
         threads,
         seeds.len()
     ));
-    let counter = Arc::new(AtomicUsize::new(0));
+    let progress = Arc::new(Progress::new(&seeds));
     let done = Arc::new(AtomicBool::new(false));
-    let ticker = spawn_ticker(counter.clone(), done.clone());
+    let ticker = spawn_ticker(progress.clone(), done.clone(), out);
 
-    let max_dim = l.dim();
-    let total: usize = seeds
-        .into_par_iter()
-        .map(|paths| {
-            paths
-                .map(|path| {
-                    extensions(vec![path], l, max_dim, cyclic)
-                        .map(|_| {
-                            counter.fetch_add(1, Ordering::Relaxed);
-                            1usize
-                        })
-                        .sum::<usize>()
-                })
-                .sum::<usize>()
-        })
-        .sum();
+    let total = progress.count_strips(l, cyclic, seeds, &|| out.cancelled()) as usize;
 
     done.store(true, Ordering::Relaxed);
     let _ = ticker.join();
-    emit(&serde_json::json!({"type": "done", "count": total, "capped": false}));
+    if out.cancelled() {
+        return false;
+    }
+    remember(cache, l, cyclic, Known { count: Some(total as u64), ..Known::default() }, out);
+    let summary = Summary { count: total as u64, ..search_summary("count", l, cyclic, shard, threads, &progress) };
+    out.done(done_message(l, cyclic, shard, "count", total, false), summary);
+    true
 }
 
 /// Parallel existence over DFS seeds: first strip found by any thread, with
 /// its skeleton so the browser can display it (native `strip_exists` only
 /// returns a bool). A shared flag makes the other workers bail out at their
-/// next path instead of finishing their subtree.
-fn run_exists(l: &Lattice, cyclic: bool, threads: usize) {
+/// next path instead of finishing their subtree. Progress counts the paths
+/// tried. False if cancelled.
+fn run_exists(l: &Lattice, cyclic: bool, encoding: Encoding, threads: usize, cache: Option<&Cache>, out: &Out) -> bool {
+    // a cached "yes" needs its witness: the page shows the strip
+    if let Some(known) =
+        recall(cache, l, cyclic, out, |k| k.exists == Some(false) || k.witness.is_some())
+    {
+        let summary = Summary { cached: true, ..Summary::new("exists", Some(l), cyclic) };
+        emit_found(l, cyclic, encoding, known.witness, summary, out);
+        return true;
+    }
+
     let seeds = l.ham_path_seeds(cyclic, threads * 16);
-    note(&format!(
+    out.note(&format!(
         "searching on {} threads across {} search branches…",
         threads,
         seeds.len()
     ));
-    let tried = Arc::new(AtomicUsize::new(0));
+    let progress = Arc::new(Progress::new(&seeds));
     let done = Arc::new(AtomicBool::new(false));
-    let ticker = spawn_ticker(tried.clone(), done.clone());
+    let ticker = spawn_ticker(progress.clone(), done.clone(), out);
 
     let max_dim = l.dim();
     let flag = Arc::new(AtomicBool::new(false));
-    let found = seeds.into_par_iter().find_map_any(|paths| {
-        for path in paths {
-            if flag.load(Ordering::Relaxed) {
-                return None; // another worker already found one
-            }
-            tried.fetch_add(1, Ordering::Relaxed);
+    let found = seeds.into_par_iter().enumerate().find_map_any(|(i, paths)| {
+        // until another worker has found one
+        let stop = || flag.load(Ordering::Relaxed) || out.cancelled();
+        for path in paths.until(CHECK_EVERY, stop) {
+            progress.add(1);
+            progress.tried();
             if let Some(strip) = extensions(vec![path], l, max_dim, cyclic).next() {
                 flag.store(true, Ordering::Relaxed);
                 return Some(strip);
             }
         }
+        if !stop() {
+            progress.finish(i);
+        }
         None
     });
 
     done.store(true, Ordering::Relaxed);
     let _ = ticker.join();
+    if found.is_none() && out.cancelled() {
+        return false;
+    }
+    let known = Known { exists: Some(found.is_some()), count: None, witness: found.clone() };
+    remember(cache, l, cyclic, known, out);
+    emit_found(l, cyclic, encoding, found, search_summary("exists", l, cyclic, None, threads, &progress), out);
+    true
+}
 
+/// The strip found, if any, and `done`; `summary` gets the count.
+fn emit_found(l: &Lattice, cyclic: bool, encoding: Encoding, found: Option<Vec<Vec<FaceId>>>, summary: Summary, out: &Out) {
     let count = match found {
         Some(strip) => {
-            let (edges, cyclic_edges) = plotting::edges_strip(&strip, l, cyclic);
-            emit(&serde_json::json!({
-                "type": "strips",
-                "strips": [StripOut { layers: strip, edges, cyclic_edges }],
-                "count": 1,
-            }));
+            let mut v = json!({"count": 1});
+            put_strips(&mut v, vec![strip], l, cyclic, encoding);
+            out.emit(v);
             1
         }
         None => 0,
     };
-    emit(&serde_json::json!({"type": "done", "count": count, "capped": false}));
+    out.done(json!({"type": "done", "count": count, "capped": false}), Summary { count, ..summary });
 }
 
 /// Sequential streaming enumeration, batched like the wasm worker
 /// (a strips message at most every ~30 ms), capped for one-shot relays.
-fn run_enumerate(l: &Lattice, cyclic: bool, cap: usize) {
-    let cap = if cap == 0 { usize::MAX } else { cap };
-    let mut count = 0usize;
-    let mut batch: Vec<StripOut> = Vec::new();
-    let mut last_flush = std::time::Instant::now();
+/// Only the strips the job's filter keeps are sent, and counted; every
+/// message carries the progress. False if cancelled; fails at the `limit`
+/// (`--max-strips`) if that is below the cap.
+fn run_enumerate(
+    l: &Lattice,
+    job: &Job,
+    limit: usize,
+    cache: Option<&Cache>,
+    shard: Option<Shard>,
+    out: &Out,
+) -> Result<bool, Failure> {
+    let (cyclic, cap) = (job.cyclic, if job.cap == 0 { usize::MAX } else { job.cap });
+    let matcher = job.filter.matcher(l, cyclic)?;
+    let stop = cap.min(limit);
+    let (mut count, mut searched) = (0usize, 0u64);
+    let mut batch: Vec<Strip> = Vec::new();
+    // compact strips are cheap to send: bigger batches share more layers
+    let most = if job.encoding == Encoding::Compact { 256 } else { 8 };
+    let started = Instant::now();
+    let mut last_flush = Instant::now();
     let mut capped = false;
 
-    let flush = |batch: &mut Vec<StripOut>, count: usize| {
-        if batch.is_empty() {
-            emit(&serde_json::json!({"type": "progress", "count": count}));
-        } else {
-            emit(&serde_json::json!({
-                "type": "strips",
-                "strips": std::mem::take(batch),
-                "count": count,
-            }));
+    let seeds = match shard {
+        Some(s) => s.seeds(l, cyclic),
+        None => l.ham_path_seeds(cyclic, ENUMERATE_SEEDS),
+    };
+    let progress = Arc::new(Progress::new(&seeds));
+    let flush = |batch: &mut Vec<Strip>, count: usize| {
+        let mut v = progress.report(started.elapsed()).message();
+        v["count"] = json!(count);
+        if !batch.is_empty() {
+            put_strips(&mut v, std::mem::take(batch), l, cyclic, job.encoding);
         }
+        out.emit(v);
     };
 
-    for strip in strips(l, cyclic) {
-        count += 1;
-        let (edges, cyclic_edges) = plotting::edges_strip(&strip, l, cyclic);
-        batch.push(StripOut {
-            layers: strip,
-            edges,
-            cyclic_edges,
-        });
+    let stopped = out.clone();
+    for strip in progress.strips(l, cyclic, seeds, move || stopped.cancelled()) {
+        if out.cancelled() {
+            return Ok(false);
+        }
+        searched += 1;
+        if matcher.matches(&strip) {
+            count += 1;
+            batch.push(strip);
+        }
 
-        if last_flush.elapsed() >= Duration::from_millis(30) || batch.len() >= 8 {
+        // while the filter keeps nothing, progress alone once a second
+        let every = if batch.is_empty() { Duration::from_secs(1) } else { Duration::from_millis(30) };
+        if last_flush.elapsed() >= every || batch.len() >= most {
             flush(&mut batch, count);
-            last_flush = std::time::Instant::now();
+            last_flush = Instant::now();
         }
-        if count >= cap {
+        if count >= stop {
             capped = true;
             break;
         }
     }
+    // cancelled or out of time, the strips end early without a word
+    if !capped && out.cancelled() {
+        return Ok(false);
+    }
     flush(&mut batch, count);
+    if capped && limit < cap {
+        return Err(Failure {
+            kind: "strips",
+            message: format!("stopped at the limit of {} strips (--max-strips)", limit),
+        });
+    }
+    if !capped && job.filter.is_empty() {
+        remember(cache, l, cyclic, Known { count: Some(count as u64), ..Known::default() }, out);
+    }
     if capped {
-        note(&format!(
+        out.note(&format!(
             "stopped at the first {} strips (raise the cap to get more)",
             cap
         ));
     }
-    emit(&serde_json::json!({"type": "done", "count": count, "capped": capped}));
+    let summary = Summary {
+        count: count as u64,
+        complete: !capped,
+        filter: job.filtered(),
+        ..search_summary("enumerate", l, cyclic, shard, 1, &progress)
+    };
+    let mut done = done_message(l, cyclic, shard, "enumerate", count, capped);
+    if summary.filter.is_some() {
+        done["searched"] = json!(searched);
+    }
+    out.done(done, summary);
+    Ok(true)
+}
+
+/// The job's aggregations of the strips its filter keeps, in parallel over
+/// the seeds of the path search: one `aggregate` message with the tables,
+/// then `done` with the strips kept (and searched, if filtered). No strips
+/// are sent, so the cap does not apply. False if cancelled.
+fn run_aggregate(
+    l: &Lattice,
+    job: &Job,
+    threads: usize,
+    cache: Option<&Cache>,
+    shard: Option<Shard>,
+    out: &Out,
+) -> Result<bool, Failure> {
+    let cyclic = job.cyclic;
+    let matcher = job.filter.matcher(l, cyclic)?;
+    let empty = Tally::new(&job.aggregate)?;
+    let seeds = match shard {
+        Some(s) => s.seeds(l, cyclic),
+        None => l.ham_path_seeds(cyclic, threads * 16),
+    };
+    out.note(&format!(
+        "tallying {} on {} threads across {} search branches…",
+        job.aggregate.join(", "),
+        threads,
+        seeds.len()
+    ));
+    let progress = Arc::new(Progress::new(&seeds));
+    let done = Arc::new(AtomicBool::new(false));
+    let ticker = spawn_ticker(progress.clone(), done.clone(), out);
+
+    let tally = filter::tally_strips(l, cyclic, seeds, &matcher, &empty, &progress, &|| out.cancelled());
+
+    done.store(true, Ordering::Relaxed);
+    let _ = ticker.join();
+    let Some(tally) = tally else { return Ok(false) };
+    if job.filter.is_empty() {
+        remember(cache, l, cyclic, Known { count: Some(tally.count), ..Known::default() }, out);
+    }
+    let mut v = json!(tally.out(l));
+    v["type"] = json!("aggregate");
+    out.emit(v);
+
+    let summary = Summary {
+        count: tally.count,
+        filter: job.filtered(),
+        ..search_summary("enumerate", l, cyclic, shard, threads, &progress)
+    };
+    let mut done = done_message(l, cyclic, shard, "enumerate", tally.count as usize, false);
+    if summary.filter.is_some() {
+        done["searched"] = json!(tally.searched);
+    }
+    out.done(done, summary);
+    Ok(true)
+}
+
+/// How often the scripts report.
+const SCRIPT_EVERY_MS: f64 = 250.0;
+
+/// The graph survey of the Scripts panel, checking the graphs in parallel.
+fn run_survey(job: &Job, threads: usize, out: &Out) -> Result<bool, String> {
+    let core = SurveyCore::new(job.n, job.linear, job.cyclic)?;
+    out.note(&format!("surveying the connected graphs on up to {} vertices on {} threads…", job.n, threads));
+    let checked = AtomicUsize::new(0);
+    let emit = |step: scripts::api::SurveyStep| {
+        checked.store(step.checked, Ordering::Relaxed);
+        let mut v = json!(step);
+        v["type"] = json!("survey");
+        out.emit(v);
+    };
+    if !core.run(SCRIPT_EVERY_MS, &emit, &|| out.cancelled()) {
+        return Ok(false);
+    }
+    let count = checked.into_inner();
+    let summary = Summary { count: count as u64, threads: Some(threads), ..Summary::new("survey", None, job.cyclic) };
+    out.done(json!({"type": "done", "count": count, "capped": false}), summary);
+    Ok(true)
+}
+
+/// The strip boundary tally of the Scripts panel, enumerating in parallel.
+fn run_bounds(l: &Lattice, threads: usize, out: &Out) -> bool {
+    out.note(&format!("tallying strip boundaries on {} threads…", threads));
+    let count = AtomicUsize::new(0);
+    let emit = |step: scripts::BoundaryStep| {
+        count.store(step.count, Ordering::Relaxed);
+        let mut v = json!(step);
+        v["type"] = json!("bounds");
+        out.emit(v);
+    };
+    if !scripts::tally_boundaries(l, SCRIPT_EVERY_MS, &emit, &|| out.cancelled()) {
+        return false;
+    }
+    let count = count.into_inner();
+    let summary = Summary { count: count as u64, threads: Some(threads), ..Summary::new("bounds", Some(l), false) };
+    out.done(json!({"type": "done", "count": count, "capped": false}), summary);
+    true
 }
@@ -0,0 +1,188 @@
+//! Fixed-width multiword bitsets over vertex / element indices.
+//!
+//! The generators in [`crate::generators`] used to work on `u64` masks, which
+//! capped graphs at 64 vertices in principle and, with full `2^n` subset
+//! scans, at 10–20 in practice. [`BitSet`] keeps the mask arithmetic (union,
+//! intersection, subset tests are a few word operations, and the type is
+//! `Copy` and hashable) while allowing up to [`CAPACITY`] indices.
+//!
+//! Sets are ordered as the binary numbers they represent, so sorting by
+//! [`BitSet`] gives the same order as sorting the old `u64` masks.
+
+use std::cmp::Ordering;
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+const WORDS: usize = 4;
+
+/// Largest index + 1 a [`BitSet`] can hold.
+pub const CAPACITY: usize = 64 * WORDS;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct BitSet([u64; WORDS]);
+
+impl BitSet {
+    pub const EMPTY: BitSet = BitSet([0; WORDS]);
+
+    pub fn singleton(v: usize) -> Self {
+        let mut s = Self::EMPTY;
+        s.insert(v);
+        s
+    }
+
+    /// `{0, ..., n-1}`.
+    pub fn full(n: usize) -> Self {
+        assert!(n <= CAPACITY, "BitSet holds at most {} indices", CAPACITY);
+        let mut s = Self::EMPTY;
+        for (w, word) in s.0.iter_mut().enumerate() {
+            let lo = 64 * w;
+            if n >= lo + 64 {
+                *word = u64::MAX;
+            } else if n > lo {
+                *word = (1u64 << (n - lo)) - 1;
+            }
+        }
+        s
+    }
+
+    pub fn contains(self, v: usize) -> bool {
+        v < CAPACITY && (self.0[v / 64] >> (v % 64)) & 1 == 1
+    }
+
+    pub fn insert(&mut self, v: usize) {
+        self.0[v / 64] |= 1 << (v % 64);
+    }
+
+    pub fn remove(&mut self, v: usize) {
+        self.0[v / 64] &= !(1 << (v % 64));
+    }
+
+    pub fn with(mut self, v: usize) -> Self {
+        self.insert(v);
+        self
+    }
+
+    pub fn without(mut self, v: usize) -> Self {
+        self.remove(v);
+        self
+    }
+
+    /// `self \ other`.
+    pub fn and_not(self, other: Self) -> Self {
+        let mut s = self;
+        for (a, b) in s.0.iter_mut().zip(other.0) {
+            *a &= !b;
+        }
+        s
+    }
+
+    pub fn len(self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    pub fn is_subset(self, other: Self) -> bool {
+        self.and_not(other).is_empty()
+    }
+
+    pub fn is_disjoint(self, other: Self) -> bool {
+        (self & other).is_empty()
+    }
+
+    /// Smallest element.
+    pub fn first(self) -> Option<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .find(|(_, &w)| w != 0)
+            .map(|(i, w)| 64 * i + w.trailing_zeros() as usize)
+    }
+
+    /// Elements in increasing order.
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        self.0.into_iter().enumerate().flat_map(|(i, mut w)| {
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let b = w.trailing_zeros() as usize;
+                w &= w - 1;
+                Some(64 * i + b)
+            })
+        })
+    }
+}
+
+impl Ord for BitSet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for BitSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl BitOr for BitSet {
+    type Output = Self;
+    fn bitor(mut self, other: Self) -> Self {
+        self |= other;
+        self
+    }
+}
+
+impl BitOrAssign for BitSet {
+    fn bitor_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a |= b;
+        }
+    }
+}
+
+impl BitAnd for BitSet {
+    type Output = Self;
+    fn bitand(mut self, other: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a &= b;
+        }
+        self
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut s = Self::EMPTY;
+        for v in iter {
+            s.insert(v);
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_matches_u64_masks_and_spans_words() {
+        let masks: Vec<u64> = vec![5, 1, 12, 3, 8];
+        let mut sets: Vec<BitSet> = masks
+            .iter()
+            .map(|&m| (0..64).filter(|&v| (m >> v) & 1 == 1).collect())
+            .collect();
+        sets.sort();
+        let back: Vec<u64> =
+            sets.iter().map(|s| s.iter().fold(0, |m, v| m | 1 << v)).collect();
+        assert_eq!(back, [1, 3, 5, 8, 12]);
+
+        let high = BitSet::singleton(200);
+        assert!(high > BitSet::full(64));
+        assert_eq!(BitSet::full(130).len(), 130);
+        assert_eq!((BitSet::full(130) & high).first(), None);
+        assert_eq!(high.with(3).iter().collect::<Vec<_>>(), [3, 200]);
+    }
+}
@@ -0,0 +1,278 @@
+//! Persistent results cache, keyed by poset isomorphism class.
+//!
+//! Strip existence and strip counts do not depend on labels or face
+//! numbering, so a result computed once — by the CLI, a batch run or a
+//! `strip_stream` job on the cluster — is good for every copy of the same
+//! poset. The cache stores them on disk, one small JSON file per bucket:
+//!
+//! * the bucket is the numbering-independent key of [`crate::symmetry`];
+//! * within a bucket, entries are told apart by an exact isomorphism test,
+//!   so two posets share results only if they really are isomorphic. A
+//!   test that runs out of budget counts as "different" — a miss, never a
+//!   wrong answer.
+//!
+//! Files are replaced atomically (write, then rename). Concurrent writers
+//! can drop each other's updates; for a cache that only costs a recompute.
+//! The directory is `$RHOMBIC_CACHE`, or `~/.cache/rhombic_strips/results`;
+//! `RHOMBIC_CACHE=off` disables it.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lattice::{FaceId, Lattice};
+use crate::rhombic::Strip;
+use crate::symmetry::Shape;
+
+/// What is known about one poset, for one of linear / cyclic strips.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Known {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    /// One strip, so a cached "yes" can still be shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness: Option<Strip>,
+}
+
+impl Known {
+    /// Fold `other` in; a count settles existence and vice versa.
+    fn merge(&mut self, other: Known) {
+        self.exists = other.exists.or(self.exists);
+        self.count = other.count.or(self.count);
+        self.witness = other.witness.or(self.witness.take());
+        if let Some(c) = self.count {
+            self.exists = Some(c > 0);
+        }
+        if self.exists == Some(false) {
+            self.count = Some(0);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Entry {
+    /// What the poset was first stored as (file path, generator call).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// Level of every face.
+    levels: Vec<usize>,
+    /// Cover relations `(lower, upper)`.
+    covers: Vec<(FaceId, FaceId)>,
+    #[serde(default)]
+    linear: Known,
+    #[serde(default)]
+    cyclic: Known,
+}
+
+impl Entry {
+    fn known(&mut self, cyclic: bool) -> &mut Known {
+        if cyclic {
+            &mut self.cyclic
+        } else {
+            &mut self.linear
+        }
+    }
+}
+
+/// One line of [`Cache::export`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub key: String,
+    pub name: Option<String>,
+    pub faces: usize,
+    pub dim: usize,
+    pub cyclic: bool,
+    pub exists: Option<bool>,
+    pub count: Option<u64>,
+}
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn open(dir: impl Into<PathBuf>) -> Self {
+        Cache { dir: dir.into() }
+    }
+
+    /// The cache `$RHOMBIC_CACHE` points at, the default one, or `None`
+    /// for `RHOMBIC_CACHE=off` (or no home directory).
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("RHOMBIC_CACHE") {
+            Ok(v) if v == "off" => None,
+            Ok(v) if !v.is_empty() => Some(Self::open(v)),
+            _ => {
+                let base = std::env::var_os("XDG_CACHE_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cache")))?;
+                Some(Self::open(base.join("rhombic_strips").join("results")))
+            }
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// What is known about `l` (in `l`'s numbering), if anything.
+    pub fn get(&self, l: &Lattice, cyclic: bool) -> Option<Known> {
+        let shape = Shape::of(l);
+        let (key, colours) = shape.key();
+        for mut entry in self.read(&key) {
+            let stored = Shape { levels: entry.levels.clone(), covers: entry.covers.clone() };
+            if let Some(iso) = shape.isomorphism(&key, &colours, &stored) {
+                let mut known = entry.known(cyclic).clone();
+                if known.exists.is_none() && known.count.is_none() {
+                    return None;
+                }
+                // the witness is stored in the entry's numbering
+                let mut back = vec![0; iso.len()];
+                for (a, &b) in iso.iter().enumerate() {
+                    back[b] = a;
+                }
+                known.witness = known.witness.map(|s| relabel(&s, &back));
+                return Some(known);
+            }
+        }
+        None
+    }
+
+    /// Record what was learned about `l`, merging with what is stored.
+    pub fn put(&self, l: &Lattice, cyclic: bool, name: Option<&str>, known: Known) -> Result<(), String> {
+        let shape = Shape::of(l);
+        let (key, colours) = shape.key();
+        let mut entries = self.read(&key);
+        let mut found = false;
+        for entry in &mut entries {
+            let stored = Shape { levels: entry.levels.clone(), covers: entry.covers.clone() };
+            if let Some(iso) = shape.isomorphism(&key, &colours, &stored) {
+                let mut known = known.clone();
+                known.witness = known.witness.map(|s| relabel(&s, &iso));
+                entry.known(cyclic).merge(known);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            let mut entry = Entry {
+                name: name.map(str::to_string),
+                levels: shape.levels,
+                covers: shape.covers,
+                linear: Known::default(),
+                cyclic: Known::default(),
+            };
+            entry.known(cyclic).merge(known);
+            entries.push(entry);
+        }
+        self.write(&key, &entries)
+    }
+
+    /// Everything known, one row per poset and cyclicity with a result.
+    pub fn export(&self) -> Result<Vec<Summary>, String> {
+        let mut keys: Vec<String> = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(format!("reading {} failed: {}", self.dir.display(), e)),
+        };
+        keys.sort();
+        let mut rows = Vec::new();
+        for key in keys {
+            for entry in self.read(&key) {
+                for (cyclic, known) in [(false, &entry.linear), (true, &entry.cyclic)] {
+                    if known.exists.is_none() && known.count.is_none() {
+                        continue;
+                    }
+                    rows.push(Summary {
+                        key: key.clone(),
+                        name: entry.name.clone(),
+                        faces: entry.levels.len(),
+                        dim: entry.levels.iter().copied().max().unwrap_or(0),
+                        cyclic,
+                        exists: known.exists,
+                        count: known.count,
+                    });
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// A bucket; unreadable or corrupt files read as empty.
+    fn read(&self, key: &str) -> Vec<Entry> {
+        std::fs::read_to_string(self.path(key))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, key: &str, entries: &[Entry]) -> Result<(), String> {
+        let fail = |e: std::io::Error| format!("writing the results cache failed: {}", e);
+        std::fs::create_dir_all(&self.dir).map_err(fail)?;
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let tmp = self.dir.join(format!(".{}.{}.{}", key, std::process::id(), nanos));
+        std::fs::write(&tmp, serde_json::to_string(entries).unwrap()).map_err(fail)?;
+        std::fs::rename(&tmp, self.path(key)).map_err(fail)
+    }
+}
+
+fn relabel(strip: &Strip, map: &[FaceId]) -> Strip {
+    strip.iter().map(|layer| layer.iter().map(|&f| map[f]).collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{self, Poset};
+    use crate::rhombic;
+
+    /// `p` with its elements renumbered by `perm` (element `i` becomes `perm[i]`).
+    fn renumber(p: &Poset, perm: &[usize]) -> Poset {
+        let mut labels = vec![String::new(); p.len()];
+        for (i, l) in p.labels.iter().enumerate() {
+            labels[perm[i]] = l.clone();
+        }
+        let edges = p.edges.iter().map(|&(a, b)| (perm[a], perm[b])).collect();
+        Poset { labels, edges, ranks: None }
+    }
+
+    #[test]
+    fn isomorphic_copies_share_results_and_witnesses() {
+        let dir = std::env::temp_dir().join(format!("rhombic_cache_{}", std::process::id()));
+        let cache = Cache::open(&dir);
+        let p = generators::cube(3).unwrap();
+        let l = p.to_lattice().unwrap();
+        let strip = rhombic::strips(&l, false).next().unwrap();
+        let known = Known { exists: Some(true), count: None, witness: Some(strip) };
+        cache.put(&l, false, Some("cube 3"), known).unwrap();
+        cache.put(&l, false, None, Known { count: Some(672), ..Known::default() }).unwrap();
+
+        // a shuffled copy hits, and the witness comes back in its numbering
+        let n = p.len();
+        let perm: Vec<usize> = (0..n).map(|i| (i + 5) % n).collect();
+        let shuffled = renumber(&p, &perm).to_lattice().unwrap();
+        let known = cache.get(&shuffled, false).unwrap();
+        assert_eq!((known.exists, known.count), (Some(true), Some(672)));
+        rhombic::check_strip(&known.witness.unwrap(), &shuffled, false).unwrap();
+        assert!(cache.get(&shuffled, true).is_none());
+
+        // a different poset of the same size does not
+        let other = generators::simplex(3).unwrap().to_lattice().unwrap();
+        assert!(cache.get(&other, false).is_none());
+
+        let rows = cache.export().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].name.as_deref(), rows[0].count), (Some("cube 3"), Some(672)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
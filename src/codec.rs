@@ -0,0 +1,182 @@
+//! The compact encoding of a batch of strips, for `strip_stream`'s
+//! `strips` messages when a job asks for `"encoding": "compact"`.
+//!
+//! JSON spells out every face id of every layer and, with them, the strip's
+//! edges; over millions of strips that is most of what crosses the relay.
+//! Here a batch is bytes, sent as base64, and the edges are left to the
+//! receiver, who has the poset and recomputes them
+//! ([`crate::plotting::edges_strip`], in the browser through
+//! [`crate::web::StripDecoder`]). Each strip of the batch is, in varints
+//! (LEB128):
+//!
+//! ```text
+//! layers  kept  { length  delta... } for each layer after the first `kept`
+//! ```
+//!
+//! `kept` is how many leading layers are those of the strip before it in the
+//! batch (strips come in search order, so neighbours share their first
+//! layers); each face id is the zigzag-encoded difference to the one before
+//! it in its layer, the first to 0.
+
+use crate::rhombic::Strip;
+
+/// The batch as bytes.
+pub fn encode_strips(strips: &[Strip]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut previous: &[Vec<usize>] = &[];
+    for strip in strips {
+        let kept = strip.iter().zip(previous).take_while(|(a, b)| a == b).count();
+        put(&mut out, strip.len() as u64);
+        put(&mut out, kept as u64);
+        for layer in &strip[kept..] {
+            put(&mut out, layer.len() as u64);
+            let mut last = 0i64;
+            for &f in layer {
+                let delta = f as i64 - last;
+                put(&mut out, ((delta << 1) ^ (delta >> 63)) as u64);
+                last = f as i64;
+            }
+        }
+        previous = strip;
+    }
+    out
+}
+
+/// The batch [`encode_strips`] made of it.
+pub fn decode_strips(bytes: &[u8]) -> Result<Vec<Strip>, String> {
+    let mut input = Reader { bytes, at: 0 };
+    let mut strips: Vec<Strip> = Vec::new();
+    while input.at < bytes.len() {
+        let (layers, kept) = (input.len()?, input.len()?);
+        let previous = strips.last().map_or(&[][..], |s| &s[..]);
+        if kept > layers || kept > previous.len() {
+            return Err(format!("strip {} keeps {} layers of {}", strips.len(), kept, previous.len()));
+        }
+        let mut strip = previous[..kept].to_vec();
+        for _ in kept..layers {
+            let len = input.len()?;
+            let mut layer = Vec::with_capacity(len);
+            let mut last = 0i64;
+            for _ in 0..len {
+                let zigzag = input.varint()?;
+                let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                last = last.checked_add(delta).ok_or("a face id out of range")?;
+                layer.push(usize::try_from(last).map_err(|_| format!("negative face id {}", last))?);
+            }
+            strip.push(layer);
+        }
+        strips.push(strip);
+    }
+    Ok(strips)
+}
+
+fn put(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = *self.bytes.get(self.at).ok_or("the strips end mid-number")?;
+            self.at += 1;
+            v |= u64::from(b & 0x7f) << shift;
+            if b < 0x80 {
+                return Ok(v);
+            }
+        }
+        Err("a number of more than 64 bits".to_string())
+    }
+
+    /// A count, no bigger than the bytes left could hold (so a corrupt one
+    /// does not allocate).
+    fn len(&mut self) -> Result<usize, String> {
+        let v = self.varint()?;
+        usize::try_from(v)
+            .ok()
+            .filter(|&n| n <= self.bytes.len() - self.at)
+            .ok_or_else(|| format!("a count of {} with {} bytes left", v, self.bytes.len() - self.at))
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64, padded, for bytes inside JSON.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            out.push(if i <= chunk.len() { BASE64[(n >> (18 - 6 * i) & 63) as usize] as char } else { '=' });
+        }
+    }
+    out
+}
+
+/// The bytes of [`encode_base64`]'s text.
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        if chunk.len() == 1 {
+            return Err("base64 of the wrong length".to_string());
+        }
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let digit = BASE64.iter().position(|&d| d == c).ok_or_else(|| format!("'{}' is not base64", c as char))?;
+            n |= (digit as u32) << (18 - 6 * i);
+        }
+        out.extend(n.to_be_bytes()[1..chunk.len()].iter());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, rhombic};
+
+    #[test]
+    fn strips_round_trip_compactly() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let strips: Vec<Strip> = rhombic::strips(&l, false).take(100).collect();
+        let bytes = encode_strips(&strips);
+        assert_eq!(decode_strips(&bytes).unwrap(), strips);
+        assert!(bytes.len() < 27 * strips.len(), "{} bytes", bytes.len());
+        assert_eq!(decode_strips(&decode_base64(&encode_base64(&bytes)).unwrap()).unwrap(), strips);
+        assert_eq!(decode_strips(&[]).unwrap(), Vec::<Strip>::new());
+
+        for text in ["", "QQ==", "QUI=", "QUJD", "QUJDRA=="] {
+            assert_eq!(encode_base64(&decode_base64(text).unwrap()), text);
+        }
+        assert_eq!(encode_base64(b"ABCD"), "QUJDRA==");
+    }
+
+    #[test]
+    fn corrupt_strips_are_refused() {
+        let l = generators::boolean(3).unwrap().to_lattice().unwrap();
+        let strips: Vec<Strip> = rhombic::strips(&l, false).collect();
+        let bytes = encode_strips(&strips);
+        assert!(decode_strips(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_strips(&[1, 1]).is_err()); // keeps a layer of no strip
+        assert!(decode_strips(&[1, 0, 1, 1]).is_err()); // face -1
+        assert!(decode_strips(&[0xff; 11]).is_err());
+        // two steps of i64::MAX up
+        let mut huge = vec![1, 0, 2];
+        for _ in 0..2 {
+            put(&mut huge, u64::MAX - 1);
+        }
+        assert!(decode_strips(&huge).is_err());
+        assert!(decode_base64("QUJ!").is_err());
+        assert!(decode_base64("Q").is_err());
+    }
+}
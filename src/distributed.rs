@@ -0,0 +1,561 @@
+//! Work-stealing search over TCP: `strip_stream --coordinate` hands out
+//! pieces of one count or enumeration to `strip_stream --work` processes,
+//! on this machine or any other that can reach its port.
+//!
+//! A piece is a prefix of the hamiltonian path search of level 0 (see
+//! [`BridgeGraph::ham_path_prefixes`]); its strips are those whose first
+//! layer starts with it. The coordinator starts from a few hundred prefixes
+//! and leases one to every idle worker thread. The subtrees below them
+//! differ wildly in size, so once the queue is empty while threads idle, the
+//! oldest leases are split: their worker stops, reports what it has counted
+//! so far and hands back the rest of its search as deeper prefixes
+//! ([`HamiltonianIter::release`]), which go to the front of the queue.
+//! Nothing is searched twice, and the prefixes of a worker that goes away
+//! are simply leased again.
+//!
+//! Protocol, one JSON object per line in each direction:
+//!
+//! ```text
+//! worker -> {"type":"hello","token":...,"threads":n,"host":...}
+//!        <- {"type":"job","graph":<Poset>,"cyclic":b,"mode":"count"|"enumerate",
+//!            "lattice":<fingerprint>}     or {"type":"refused","message":...}
+//! worker -> {"type":"want"}                 one per idle thread
+//!        <- {"type":"lease","id":k,"prefix":[...]}
+//!        <- {"type":"split","id":k}          stop lease k, hand back the rest
+//! worker -> {"type":"strips","id":k,"strips":[...]}   enumerate only
+//! worker -> {"type":"result","id":k,"count":n,"rest":[[...],...]}
+//!        <- {"type":"finish"}
+//! worker -> {"type":"error","message":...}
+//! ```
+//!
+//! Workers check that the lattice they built from the poset has the
+//! coordinator's [`Lattice::fingerprint`]: prefixes are face ids, and mean
+//! nothing in a differently numbered lattice. The port is plain TCP with
+//! no encryption; bind it to loopback (the default) and forward it with
+//! `ssh -R`/`-L` to reach other machines, or set `$RHOMBIC_TOKEN` on both
+//! sides when binding a cluster's internal network.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::generators::Poset;
+use crate::lattice::{BridgeGraph, FaceId, Lattice, Step};
+use crate::plotting;
+use crate::progress::{self, Report};
+use crate::rhombic::{extensions, Strip};
+
+/// The port `--coordinate` and `--work` use when given none.
+pub const PORT: u16 = 8643;
+
+/// Prefixes to start with: plenty for a few machines' threads.
+const PREFIXES: usize = 256;
+/// DFS steps between a worker thread's looks at its split flag.
+const BUDGET: usize = 100_000;
+/// Strips per `strips` message.
+const BATCH: usize = 64;
+/// How long a worker keeps trying to reach a coordinator not yet listening.
+const PATIENCE: Duration = Duration::from_secs(30);
+
+/// `PORT` or `HOST:PORT`; a bare port is on loopback.
+pub fn address(spec: &str) -> String {
+    if spec.parse::<u16>().is_ok() {
+        format!("127.0.0.1:{}", spec)
+    } else {
+        spec.to_string()
+    }
+}
+
+/// What the search finds: `"count"` or `"enumerate"`.
+fn check_mode(mode: &str) -> Result<(), String> {
+    match mode {
+        "count" | "enumerate" => Ok(()),
+        m => Err(format!("the coordinator counts or enumerates, not '{}'", m)),
+    }
+}
+
+fn send(out: &mut TcpStream, v: &Value) -> std::io::Result<()> {
+    writeln!(out, "{}", v)?;
+    out.flush()
+}
+
+/// The totals of a finished search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub count: u64,
+    pub capped: bool,
+    /// Workers that joined.
+    pub workers: usize,
+    /// Prefixes leased, counting those handed back by splits.
+    pub leases: u64,
+    pub splits: u64,
+}
+
+pub struct Coordinator {
+    /// `HOST:PORT` to listen on; port 0 picks a free one (announced).
+    pub addr: String,
+    /// Asked of every worker; empty lets anyone who reaches the port in.
+    pub token: String,
+    /// A lease this old is split when threads sit idle.
+    pub split_after: Duration,
+}
+
+struct Peer {
+    out: TcpStream,
+    name: String,
+    /// Threads waiting for a lease.
+    wants: usize,
+}
+
+/// A prefix to search, with its estimated size.
+type Piece = (Vec<FaceId>, f64);
+
+struct Lease {
+    worker: usize,
+    piece: Piece,
+    since: Instant,
+    split: bool,
+    /// Enumerate: strips received, written out once the lease is done (a
+    /// lease that is lost is leased again, and its strips found again).
+    strips: Vec<Strip>,
+}
+
+enum Event {
+    Joined(usize, TcpStream, String),
+    Message(usize, Value),
+    Left(usize),
+}
+
+impl Coordinator {
+    /// Search `l` (built from `graph`) with whatever workers connect,
+    /// writing `strip_stream`'s messages — notes, `progress` once a second,
+    /// `strips` for `enumerate` (at most `cap`, 0 = all) — through `emit`.
+    /// The first note names the address.
+    pub fn run(
+        &self,
+        graph: &Poset,
+        l: &Lattice,
+        cyclic: bool,
+        mode: &str,
+        cap: usize,
+        emit: &dyn Fn(Value),
+    ) -> Result<Summary, String> {
+        check_mode(mode)?;
+        let enumerate = mode == "enumerate";
+        let cap = if cap == 0 { u64::MAX } else { cap as u64 };
+        let listener =
+            TcpListener::bind(&self.addr).map_err(|e| format!("cannot listen on {}: {}", self.addr, e))?;
+        let local = listener.local_addr().map_err(|e| e.to_string())?;
+        let job = json!({
+            "type": "job",
+            "graph": graph,
+            "cyclic": cyclic,
+            "mode": mode,
+            "lattice": l.fingerprint(),
+        });
+
+        let bridges = l.bridge_graph(0);
+        let weigh = |prefix: Vec<FaceId>| -> Result<Piece, String> {
+            let size = bridges.ham_paths_from(cyclic, prefix.clone())?.estimate_size(progress::PROBES);
+            Ok((prefix, size))
+        };
+        let mut queue: VecDeque<Piece> =
+            bridges.ham_path_prefixes(cyclic, PREFIXES).into_iter().map(weigh).collect::<Result<_, _>>()?;
+        let total: f64 = queue.iter().map(|(_, size)| size).sum();
+        let mut done = 0.0;
+        let started = Instant::now();
+        emit(json!({
+            "type": "note",
+            "message": format!("{} prefixes to search; waiting for workers on {}", queue.len(), local),
+        }));
+
+        let (tx, rx) = mpsc::channel();
+        {
+            let (job, token) = (job.to_string(), self.token.clone());
+            std::thread::spawn(move || accept(listener, tx, job, token));
+        }
+
+        let mut peers: HashMap<usize, Peer> = HashMap::new();
+        let mut leases: HashMap<u64, Lease> = HashMap::new();
+        let mut summary = Summary { count: 0, capped: false, workers: 0, leases: 0, splits: 0 };
+        let mut next_id = 0u64;
+        let mut last_progress = Instant::now();
+
+        while !summary.capped && (!queue.is_empty() || !leases.is_empty()) {
+            let event = match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Err("stopped accepting workers".to_string()),
+            };
+            let mut lost = Vec::new();
+            match event {
+                Some(Event::Joined(w, out, name)) => {
+                    emit(json!({"type": "note", "message": format!("worker {} joined: {}", w, name)}));
+                    summary.workers += 1;
+                    peers.insert(w, Peer { out, name, wants: 0 });
+                }
+                Some(Event::Left(w)) => lost.push(w),
+                Some(Event::Message(w, m)) => match m["type"].as_str() {
+                    Some("want") => {
+                        if let Some(p) = peers.get_mut(&w) {
+                            p.wants += 1;
+                        }
+                    }
+                    Some("strips") => {
+                        let lease = m["id"].as_u64().and_then(|id| leases.get_mut(&id)).filter(|l| l.worker == w);
+                        let strips = serde_json::from_value::<Vec<Strip>>(m["strips"].clone());
+                        match (lease, strips) {
+                            (Some(lease), Ok(strips)) => lease.strips.extend(strips),
+                            _ => return Err(format!("worker {}: bad strips message", w)),
+                        }
+                    }
+                    Some("result") => {
+                        let id = m["id"].as_u64().unwrap_or(u64::MAX);
+                        let lease = match leases.remove(&id) {
+                            Some(lease) if lease.worker == w => lease,
+                            _ => return Err(format!("worker {}: result for a lease it does not hold", w)),
+                        };
+                        let count = m["count"].as_u64().ok_or(format!("worker {}: result without a count", w))?;
+                        let rest = serde_json::from_value::<Vec<Vec<FaceId>>>(m["rest"].clone())
+                            .map_err(|e| format!("worker {}: bad rest of a split: {}", w, e))?;
+                        if enumerate {
+                            if lease.strips.len() as u64 != count {
+                                return Err(format!("worker {}: {} strips sent, {} counted", w, lease.strips.len(), count));
+                            }
+                            let take = (cap - summary.count).min(count) as usize;
+                            emit_strips(lease.strips.into_iter().take(take), l, cyclic, &mut summary.count, emit);
+                            summary.capped = summary.count >= cap;
+                        } else {
+                            summary.count += count;
+                        }
+                        // what the split hands back is still to do
+                        let rest = rest.into_iter().map(&weigh).collect::<Result<Vec<_>, _>>()?;
+                        done += (lease.piece.1 - rest.iter().map(|(_, size)| size).sum::<f64>()).max(0.0);
+                        for piece in rest.into_iter().rev() {
+                            queue.push_front(piece);
+                        }
+                    }
+                    Some("error") => {
+                        return Err(format!("worker {}: {}", w, m["message"].as_str().unwrap_or("unknown error")))
+                    }
+                    _ => return Err(format!("worker {}: unexpected message {}", w, m)),
+                },
+                None => {}
+            }
+
+            // lease the queue to waiting threads, the most idle worker first
+            while !queue.is_empty() {
+                let Some((&w, peer)) = peers.iter_mut().filter(|(_, p)| p.wants > 0).max_by_key(|(_, p)| p.wants)
+                else {
+                    break;
+                };
+                let piece = queue.pop_front().expect("queue non-empty");
+                next_id += 1;
+                peer.wants -= 1;
+                let sent = send(&mut peer.out, &json!({"type": "lease", "id": next_id, "prefix": piece.0}));
+                leases.insert(next_id, Lease { worker: w, piece, since: Instant::now(), split: false, strips: vec![] });
+                summary.leases += 1;
+                if sent.is_err() {
+                    lost.push(w);
+                    break;
+                }
+            }
+
+            // idle threads and nothing queued: split the oldest leases
+            let idle: usize = peers.values().map(|p| p.wants).sum();
+            if idle > 0 && queue.is_empty() {
+                let mut old: Vec<(&u64, &mut Lease)> =
+                    leases.iter_mut().filter(|(_, l)| !l.split && l.since.elapsed() >= self.split_after).collect();
+                old.sort_by_key(|(_, l)| l.since);
+                for (id, lease) in old.into_iter().take(idle) {
+                    lease.split = true;
+                    summary.splits += 1;
+                    if let Some(peer) = peers.get_mut(&lease.worker) {
+                        if send(&mut peer.out, &json!({"type": "split", "id": id})).is_err() {
+                            lost.push(lease.worker);
+                        }
+                    }
+                }
+            }
+
+            for w in lost {
+                let Some(peer) = peers.remove(&w) else { continue };
+                let ids: Vec<u64> = leases.iter().filter(|(_, l)| l.worker == w).map(|(&id, _)| id).collect();
+                for id in &ids {
+                    queue.push_front(leases.remove(id).expect("lease of the worker").piece);
+                }
+                let message = match ids.len() {
+                    0 => format!("worker {} left: {}", w, peer.name),
+                    n => format!("worker {} lost: {}; its {} prefixes go back in the queue", w, peer.name, n),
+                };
+                emit(json!({"type": "note", "message": message}));
+            }
+
+            if last_progress.elapsed() >= Duration::from_secs(1) {
+                last_progress = Instant::now();
+                let fraction = if total > 0.0 { done / total } else { 0.0 };
+                let mut report = Report::new(summary.count, fraction, started.elapsed()).message();
+                report["workers"] = json!(peers.len());
+                report["leases"] = json!(leases.len());
+                report["queued"] = json!(queue.len());
+                emit(report);
+            }
+        }
+
+        for peer in peers.values_mut() {
+            let _ = send(&mut peer.out, &json!({"type": "finish"}));
+        }
+        Ok(summary)
+    }
+}
+
+/// Write an enumeration's strips in `strip_stream`'s batches of eight.
+fn emit_strips(strips: impl Iterator<Item = Strip>, l: &Lattice, cyclic: bool, count: &mut u64, emit: &dyn Fn(Value)) {
+    let mut batch = Vec::new();
+    let flush = |batch: &mut Vec<Value>, count: u64| {
+        if !batch.is_empty() {
+            emit(json!({"type": "strips", "strips": std::mem::take(batch), "count": count}));
+        }
+    };
+    for strip in strips {
+        *count += 1;
+        let (edges, cyclic_edges) = plotting::edges_strip(&strip, l, cyclic);
+        batch.push(json!({"layers": strip, "edges": edges, "cyclicEdges": cyclic_edges}));
+        if batch.len() == 8 {
+            flush(&mut batch, *count);
+        }
+    }
+    flush(&mut batch, *count);
+}
+
+/// Greet every connection on a thread of its own: check its hello, send the
+/// job, and pass its messages on as events.
+fn accept(listener: TcpListener, tx: Sender<Event>, job: String, token: String) {
+    for (w, stream) in listener.incoming().enumerate() {
+        let Ok(stream) = stream else { continue };
+        let _ = stream.set_nodelay(true); // a lease is a few bytes each way
+        let (tx, job, token) = (tx.clone(), job.clone(), token.clone());
+        std::thread::spawn(move || {
+            let w = w + 1;
+            let Ok(mut out) = stream.try_clone() else { return };
+            let from = stream.peer_addr().map_or("?".to_string(), |a| a.to_string());
+            let mut lines = BufReader::new(stream).lines();
+            let hello: Value = lines
+                .next()
+                .and_then(|l| l.ok())
+                .and_then(|l| serde_json::from_str(&l).ok())
+                .unwrap_or_default();
+            if hello["type"] != "hello" || hello["token"].as_str().unwrap_or("") != token {
+                let _ = send(&mut out, &json!({"type": "refused", "message": "bad hello or token"}));
+                return;
+            }
+            if writeln!(out, "{}", job).and_then(|_| out.flush()).is_err() {
+                return;
+            }
+            let name = format!(
+                "{} ({}, {} threads)",
+                hello["host"].as_str().unwrap_or("?"),
+                from,
+                hello["threads"].as_u64().unwrap_or(0)
+            );
+            if tx.send(Event::Joined(w, out, name)).is_err() {
+                return;
+            }
+            for line in lines {
+                let Ok(line) = line else { break };
+                match serde_json::from_str(&line) {
+                    Ok(m) => {
+                        if tx.send(Event::Message(w, m)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+            let _ = tx.send(Event::Left(w));
+        });
+    }
+}
+
+pub struct Worker {
+    /// The coordinator's `HOST:PORT`.
+    pub addr: String,
+    pub token: String,
+    /// Leases searched side by side.
+    pub threads: usize,
+}
+
+/// What a worker thread is told to search.
+struct Task {
+    id: u64,
+    prefix: Vec<FaceId>,
+    split: Arc<AtomicBool>,
+}
+
+/// A worker's shared state: the search, and its line to the coordinator.
+struct Search {
+    lattice: Lattice,
+    graph: BridgeGraph,
+    cyclic: bool,
+    enumerate: bool,
+    out: Mutex<TcpStream>,
+    /// Split flags of the running leases.
+    splits: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+    stop: AtomicBool,
+}
+
+impl Search {
+    /// False once the coordinator is gone.
+    fn send(&self, v: &Value) -> bool {
+        send(&mut self.out.lock().unwrap_or_else(|e| e.into_inner()), v).is_ok()
+    }
+
+    /// One thread: ask for a lease, search it, report, until told to stop.
+    fn serve(&self, tasks: &Mutex<Receiver<Task>>) -> Result<(), String> {
+        let max_dim = self.lattice.dim();
+        loop {
+            if !self.send(&json!({"type": "want"})) {
+                return Ok(());
+            }
+            let Ok(task) = tasks.lock().unwrap_or_else(|e| e.into_inner()).recv() else { return Ok(()) };
+            let mut paths = self.graph.ham_paths_from(self.cyclic, task.prefix)?;
+            let mut count = 0u64;
+            let mut batch: Vec<Strip> = Vec::new();
+            let mut rest = Vec::new();
+            loop {
+                if self.stop.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                if task.split.load(Ordering::Relaxed) {
+                    rest = paths.release();
+                    break;
+                }
+                match paths.next_within(BUDGET) {
+                    Step::Path(path) => {
+                        for strip in extensions(vec![path], &self.lattice, max_dim, self.cyclic) {
+                            count += 1;
+                            if self.enumerate {
+                                batch.push(strip);
+                                if batch.len() == BATCH {
+                                    self.send(&json!({"type": "strips", "id": task.id, "strips": batch}));
+                                    batch.clear();
+                                }
+                            }
+                        }
+                    }
+                    Step::Done => break,
+                    Step::Paused => {}
+                }
+            }
+            if !batch.is_empty() {
+                self.send(&json!({"type": "strips", "id": task.id, "strips": batch}));
+            }
+            self.splits.lock().unwrap_or_else(|e| e.into_inner()).remove(&task.id);
+            self.send(&json!({"type": "result", "id": task.id, "count": count, "rest": rest}));
+        }
+    }
+}
+
+impl Worker {
+    /// Join the coordinator and search its leases until it says finish (or
+    /// goes away); `log` gets a line for every stage. The number of leases
+    /// searched.
+    pub fn run(&self, log: &dyn Fn(&str)) -> Result<u64, String> {
+        let addr = address(&self.addr);
+        let started = Instant::now();
+        let stream = loop {
+            match TcpStream::connect(&addr) {
+                Ok(s) => break s,
+                Err(_) if started.elapsed() < PATIENCE => std::thread::sleep(Duration::from_millis(250)),
+                Err(e) => return Err(format!("cannot reach the coordinator at {}: {}", addr, e)),
+            }
+        };
+        let _ = stream.set_nodelay(true);
+        let mut out = stream.try_clone().map_err(|e| e.to_string())?;
+        let mut lines = BufReader::new(stream).lines();
+        let threads = self.threads.max(1);
+        let hello = json!({"type": "hello", "token": self.token, "threads": threads, "host": crate::relay::host()});
+        send(&mut out, &hello).map_err(|e| format!("{}: {}", addr, e))?;
+
+        let job: Value = match lines.next() {
+            Some(Ok(line)) => serde_json::from_str(&line).map_err(|e| format!("bad job from {}: {}", addr, e))?,
+            _ => return Err(format!("{} closed the connection", addr)),
+        };
+        if job["type"] != "job" {
+            return Err(format!("{}: {}", addr, job["message"].as_str().unwrap_or("not a coordinator")));
+        }
+        let mode = job["mode"].as_str().unwrap_or("");
+        check_mode(mode)?;
+        let graph: Poset = serde_json::from_value(job["graph"].clone()).map_err(|e| e.to_string())?;
+        let lattice = Lattice::from_faces(graph.to_faces()?);
+        if job["lattice"].as_str() != Some(lattice.fingerprint().as_str()) {
+            let message = "the lattice built here differs from the coordinator's (another version?)";
+            let _ = send(&mut out, &json!({"type": "error", "message": message}));
+            return Err(message.to_string());
+        }
+        log(&format!("joined {}: {} of {} faces, {} threads", addr, mode, lattice.num_faces(), threads));
+
+        let search = Arc::new(Search {
+            graph: lattice.bridge_graph(0),
+            lattice,
+            cyclic: job["cyclic"].as_bool().unwrap_or(false),
+            enumerate: mode == "enumerate",
+            out: Mutex::new(out),
+            splits: Mutex::default(),
+            stop: AtomicBool::new(false),
+        });
+        let (tasks, rx) = mpsc::channel::<Task>();
+        let rx = Arc::new(Mutex::new(rx));
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (search, rx) = (search.clone(), rx.clone());
+                std::thread::spawn(move || {
+                    let result = search.serve(&rx);
+                    if let Err(e) = &result {
+                        search.send(&json!({"type": "error", "message": e}));
+                    }
+                    result
+                })
+            })
+            .collect();
+
+        let mut leased = 0;
+        for line in lines {
+            let Ok(line) = line else { break };
+            let m: Value = serde_json::from_str(&line).map_err(|e| format!("bad message from {}: {}", addr, e))?;
+            match m["type"].as_str() {
+                Some("lease") => {
+                    let id = m["id"].as_u64().ok_or("lease without an id")?;
+                    let prefix = serde_json::from_value(m["prefix"].clone()).map_err(|e| e.to_string())?;
+                    let split = Arc::new(AtomicBool::new(false));
+                    search.splits.lock().unwrap_or_else(|e| e.into_inner()).insert(id, split.clone());
+                    leased += 1;
+                    let _ = tasks.send(Task { id, prefix, split });
+                }
+                Some("split") => {
+                    // a lease that just finished has nothing left to split
+                    let id = m["id"].as_u64().unwrap_or(u64::MAX);
+                    if let Some(flag) = search.splits.lock().unwrap_or_else(|e| e.into_inner()).get(&id) {
+                        flag.store(true, Ordering::Relaxed);
+                    }
+                }
+                Some("finish") => break,
+                _ => return Err(format!("unexpected message from {}: {}", addr, m)),
+            }
+        }
+        search.stop.store(true, Ordering::Relaxed);
+        drop(tasks);
+        let _ = search.out.lock().unwrap_or_else(|e| e.into_inner()).shutdown(std::net::Shutdown::Both);
+        for handle in handles {
+            handle.join().map_err(|_| "a worker thread panicked".to_string())??;
+        }
+        log(&format!("finished: {} leases searched", leased));
+        Ok(leased)
+    }
+}
@@ -0,0 +1,131 @@
+//! Graphviz DOT export: the bridge graph of a level, and strips with one
+//! cluster per layer, for `dot`, `neato` and friends.
+//!
+//! Nodes are named `n<face id>` and labelled with the face label; the
+//! colours are those of the SVG and PNG renderers (vermillion for the
+//! hamiltonian path and the closing edges).
+
+use std::collections::HashSet;
+
+use crate::lattice::{FaceId, Lattice};
+use crate::plotting::edges_strip;
+use crate::rhombic::Layer;
+
+const INK: &str = "#23262e";
+const VERMILLION: &str = "#e66100";
+
+/// `s` as a DOT string.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn header(kind: &str, name: &str) -> String {
+    format!(
+        "{} {} {{\n  node [shape=box, fontname=\"Hack,monospace\", fontsize=11, color={}];\n  edge [color={}];\n",
+        kind,
+        quote(name),
+        quote(INK),
+        quote(INK)
+    )
+}
+
+/// The bridge graph of level `d`: its faces, and an edge between two faces
+/// wherever they have a bridge (named in the edge's tooltip). `path`, a
+/// hamiltonian path of the level such as a strip's layer, is drawn in
+/// vermillion, closed into a cycle with `cyclic`.
+pub fn level_dot(l: &Lattice, d: usize, path: Option<&[FaceId]>, cyclic: bool) -> String {
+    let graph = l.bridge_graph(d);
+    let path = path.unwrap_or(&[]);
+    let on_path = |a: FaceId, b: FaceId| {
+        let step = path.windows(2).any(|w| (w[0], w[1]) == (a, b) || (w[1], w[0]) == (a, b));
+        let closing = cyclic && path.len() > 2 && {
+            let (first, last) = (path[0], path[path.len() - 1]);
+            (first, last) == (a, b) || (last, first) == (a, b)
+        };
+        (step, closing)
+    };
+
+    let mut dot = header("graph", &format!("level {}", d));
+    for &f in graph.vertices() {
+        let mut attrs = format!("label={}", quote(l.face(f).label()));
+        if let Some(i) = path.iter().position(|&p| p == f) {
+            attrs += &format!(", xlabel=\"{}\"", i);
+        }
+        dot += &format!("  n{} [{}];\n", f, attrs);
+    }
+    for (u, v) in graph.edges() {
+        let bridge = l.bridge(u, v).map_or("", |b| l.face(b).label());
+        let mut attrs = format!("tooltip={}", quote(&format!("bridge {}", bridge)));
+        match on_path(u, v) {
+            (true, _) => attrs += &format!(", color={}, penwidth=2.5", quote(VERMILLION)),
+            (false, true) => attrs += &format!(", color={}, penwidth=2.5, style=dashed", quote(VERMILLION)),
+            _ => {}
+        }
+        dot += &format!("  n{} -- n{} [{}];\n", u, v, attrs);
+    }
+    dot += "}\n";
+    dot
+}
+
+/// A strip bottom up, one cluster per layer: each layer's hamiltonian path
+/// in vermillion inside its cluster, the edges of [`edges_strip`] between
+/// the clusters and, for cyclic strips, the closing edges dashed.
+pub fn strip_dot(layers: &[Layer], l: &Lattice, cyclic: bool) -> String {
+    let (edges, closing) = edges_strip(layers, l, cyclic);
+    let mut dot = header("digraph", if cyclic { "cyclic strip" } else { "strip" });
+    dot += "  rankdir=BT;\n  newrank=true;\n  edge [dir=none];\n";
+    for (k, layer) in layers.iter().enumerate() {
+        dot += &format!("  subgraph cluster_{} {{\n    label=\"level {}\";\n    color=\"#c8ccd4\";\n", k, k);
+        for &f in layer {
+            dot += &format!("    n{} [label={}];\n", f, quote(l.face(f).label()));
+        }
+        let ids: Vec<String> = layer.iter().map(|f| format!("n{}", f)).collect();
+        dot += &format!("    {{ rank=same; {}; }}\n", ids.join("; "));
+        if layer.len() > 1 {
+            dot += &format!("    {} [color={}, penwidth=2];\n", ids.join(" -> "), quote(VERMILLION));
+        }
+        dot += "  }\n";
+    }
+    let mut seen = HashSet::new();
+    for (a, b) in edges {
+        // a single face below covers every face above twice over
+        if seen.insert((a, b)) {
+            dot += &format!("  n{} -> n{};\n", a, b);
+        }
+    }
+    for (a, b) in closing {
+        dot += &format!("  n{} -> n{} [style=dashed, color={}, constraint=false];\n", a, b, quote(VERMILLION));
+    }
+    dot += "}\n";
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::rhombic;
+
+    #[test]
+    fn level_graphs_and_strips_as_dot() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let strip = rhombic::strips(&l, true).next().unwrap();
+
+        let dot = level_dot(&l, 0, Some(&strip[0]), true);
+        let graph = l.bridge_graph(0);
+        assert_eq!(dot.matches(" -- ").count(), graph.num_edges());
+        assert_eq!(dot.matches("[label=").count(), graph.num_vertices());
+        // the layer's path, closed into a cycle
+        assert_eq!(dot.matches("penwidth=2.5").count(), strip[0].len());
+        assert_eq!(dot.matches("style=dashed").count(), 1);
+
+        let dot = strip_dot(&strip, &l, true);
+        assert!(dot.starts_with("digraph \"cyclic strip\" {") && dot.ends_with("}\n"));
+        assert_eq!(dot.matches("subgraph cluster_").count(), strip.len());
+        assert_eq!(dot.matches("[label=").count(), l.num_faces());
+        let (edges, closing) = edges_strip(&strip, &l, true);
+        assert_eq!(dot.matches("constraint=false").count(), closing.len());
+        assert!(edges.iter().all(|(a, b)| dot.contains(&format!("  n{} -> n{};\n", a, b))));
+        assert_eq!(quote("a\"b\\"), "\"a\\\"b\\\\\"");
+    }
+}
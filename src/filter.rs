@@ -0,0 +1,397 @@
+//! Filters and aggregations over the strips of an enumeration, so that a
+//! search sends back only the strips wanted, or only figures about them.
+//!
+//! A [`Filter`] keeps strips by their boundary chains (written as the
+//! Scripts panel's boundary tally writes them), by their layer widths, by
+//! which faces are bridges and by whether a linear strip is also a cyclic
+//! one. Every layer holds each face of its level exactly once, so its length
+//! is the same in every strip; what varies is how many of its faces are
+//! bridges ([`layer_bridges`]) and how many sit in gaps. A layer's *width*
+//! here is its number of bridges, the strands carried on from the layer
+//! below; level 0 has none, so widths start at level 1.
+//!
+//! A [`Tally`] aggregates what is kept: the histogram of layer widths, the
+//! boundary pairs and the strips per hamiltonian path of level 0.
+//! `strip_stream` and the CLI's `enumerate` evaluate both natively
+//! ([`tally_strips`] in parallel), so only the results cross the wire.
+//!
+//! The text form of a filter (the CLI's `--filter`) is a comma-separated
+//! list, lists inside separated by `/`:
+//!
+//! ```text
+//! left=012,right=210     the boundary chains
+//! widths=3/-/1           widths of levels 1, 2, 3 (- for any)
+//! bridges=01/12          faces, by label, that must be bridges
+//! gaps=02                faces that must sit in gaps
+//! cyclic | not-cyclic    linear strips that are (not) also cyclic
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lattice::{FaceId, Lattice};
+use crate::rhombic::{check_strip, layer_bridges, Layer, Strip};
+use crate::scripts::{boundary_pair, pair_rows, PairOut};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{lattice::HamiltonianIter, progress::{Progress, CHECK_EVERY}, rhombic::extensions};
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+/// Which strips to keep; every condition given must hold.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct Filter {
+    /// The left boundary chain, as [`scripts::api::chain_to_perm`](crate::scripts::api::chain_to_perm) writes it.
+    pub left: Option<String>,
+    pub right: Option<String>,
+    /// The width of every layer from level 1 up, `None` for any; a shorter
+    /// list leaves the levels above free.
+    pub widths: Vec<Option<usize>>,
+    /// Faces, by label, that must be bridges.
+    pub bridges: Vec<String>,
+    /// Faces, by label, that must sit in gaps.
+    pub gaps: Vec<String>,
+    /// Linear strips that are (or are not) cyclic strips too.
+    pub also_cyclic: Option<bool>,
+}
+
+impl Filter {
+    /// Parse the text form (see the module docs); `""` keeps every strip.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut f = Filter::default();
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = match item.split_once('=') {
+                Some((k, v)) => (k.trim(), Some(v.trim())),
+                None => (item, None),
+            };
+            let list = |v: Option<&str>| -> Result<Vec<String>, String> {
+                let v = v.filter(|v| !v.is_empty()).ok_or(format!("filter: {} needs a value", key))?;
+                Ok(v.split('/').map(|s| s.trim().to_string()).collect())
+            };
+            match (key, value) {
+                ("left", Some(v)) if !v.is_empty() => f.left = Some(v.to_string()),
+                ("right", Some(v)) if !v.is_empty() => f.right = Some(v.to_string()),
+                ("widths", v) => {
+                    f.widths = list(v)?
+                        .iter()
+                        .map(|w| match w.as_str() {
+                            "-" | "*" => Ok(None),
+                            w => w.parse().map(Some).map_err(|_| format!("filter: bad width '{}'", w)),
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                ("bridges", v) => f.bridges = list(v)?,
+                ("gaps", v) => f.gaps = list(v)?,
+                ("cyclic", None) => f.also_cyclic = Some(true),
+                ("not-cyclic", None) => f.also_cyclic = Some(false),
+                _ => return Err(format!("filter: unknown condition '{}'", item)),
+            }
+        }
+        Ok(f)
+    }
+
+    /// Keeps every strip.
+    pub fn is_empty(&self) -> bool {
+        *self == Filter::default()
+    }
+
+    /// The filter for the strips of `l` (cyclic ones if `cyclic`), with its
+    /// faces looked up; fails on a label `l` does not have.
+    pub fn matcher<'a>(&self, l: &'a Lattice, cyclic: bool) -> Result<Matcher<'a>, String> {
+        if cyclic && self.also_cyclic.is_some() {
+            return Err("filter: (not-)cyclic is for linear strips".to_string());
+        }
+        if self.widths.len() > l.dim() {
+            return Err(format!(
+                "filter: {} widths given, but the strips have {} levels above 0",
+                self.widths.len(),
+                l.dim()
+            ));
+        }
+        let faces = |labels: &[String]| -> Result<Vec<FaceId>, String> {
+            labels
+                .iter()
+                .map(|label| {
+                    let f = (0..l.num_faces())
+                        .find(|&f| l.face(f).label() == label)
+                        .ok_or_else(|| format!("filter: no face is labelled '{}'", label))?;
+                    match l.face(f).dim() {
+                        0 => Err(format!("filter: '{}' is of level 0, which has no bridges", label)),
+                        _ => Ok(f),
+                    }
+                })
+                .collect()
+        };
+        Ok(Matcher { l, cyclic, bridges: faces(&self.bridges)?, gaps: faces(&self.gaps)?, filter: self.clone() })
+    }
+}
+
+/// A [`Filter`] for the strips of one lattice.
+pub struct Matcher<'a> {
+    l: &'a Lattice,
+    cyclic: bool,
+    filter: Filter,
+    bridges: Vec<FaceId>,
+    gaps: Vec<FaceId>,
+}
+
+impl Matcher<'_> {
+    /// Does `strip` pass?
+    pub fn matches(&self, strip: &Strip) -> bool {
+        let f = &self.filter;
+        if f.left.is_some() || f.right.is_some() {
+            let (left, right) = boundary_pair(self.l, strip);
+            if f.left.as_ref().is_some_and(|l| *l != left) || f.right.as_ref().is_some_and(|r| *r != right) {
+                return false;
+            }
+        }
+        if !f.widths.is_empty() || !self.bridges.is_empty() || !self.gaps.is_empty() {
+            let bridges = layer_bridges(strip, self.l, self.cyclic);
+            let widths_ok = f.widths.iter().zip(&bridges).all(|(w, b)| w.is_none_or(|w| w == b.len()));
+            let bridge = |face: FaceId| bridges[self.l.face(face).dim() - 1].contains(&face);
+            if !widths_ok || !self.bridges.iter().all(|&b| bridge(b)) || self.gaps.iter().any(|&g| bridge(g)) {
+                return false;
+            }
+        }
+        f.also_cyclic.is_none_or(|also| check_strip(strip, self.l, true).is_ok() == also)
+    }
+}
+
+/// Aggregations of the strips kept, each only if asked for.
+#[derive(Clone, Debug, Default)]
+pub struct Tally {
+    /// `(level, width)` to strips.
+    widths: Option<BTreeMap<(usize, usize), u64>>,
+    boundaries: Option<HashMap<(String, String), usize>>,
+    /// In the order of the search.
+    paths: Option<Vec<(Layer, u64)>>,
+    /// Strips kept.
+    pub count: u64,
+    /// Strips looked at.
+    pub searched: u64,
+}
+
+/// The aggregations [`Tally::new`] knows.
+pub const AGGREGATIONS: [&str; 3] = ["widths", "boundaries", "paths"];
+
+impl Tally {
+    /// An empty tally of the `kinds` (of [`AGGREGATIONS`]).
+    pub fn new(kinds: &[String]) -> Result<Self, String> {
+        let mut t = Tally::default();
+        for kind in kinds {
+            match kind.as_str() {
+                "widths" => t.widths = Some(BTreeMap::new()),
+                "boundaries" => t.boundaries = Some(HashMap::new()),
+                "paths" => t.paths = Some(vec![]),
+                k => return Err(format!("unknown aggregation '{}' (expected {})", k, AGGREGATIONS.join(", "))),
+            }
+        }
+        Ok(t)
+    }
+
+    /// Count in `strip`, one of `l`'s.
+    pub fn add(&mut self, l: &Lattice, cyclic: bool, strip: &Strip) {
+        self.count += 1;
+        if let Some(widths) = &mut self.widths {
+            for (level, bridges) in layer_bridges(strip, l, cyclic).iter().enumerate() {
+                *widths.entry((level + 1, bridges.len())).or_insert(0) += 1;
+            }
+        }
+        if let Some(boundaries) = &mut self.boundaries {
+            *boundaries.entry(boundary_pair(l, strip)).or_insert(0) += 1;
+        }
+        if let Some(paths) = &mut self.paths {
+            match paths.last_mut() {
+                Some((path, n)) if *path == strip[0] => *n += 1,
+                _ => paths.push((strip[0].clone(), 1)),
+            }
+        }
+    }
+
+    /// Add `other`, a tally of the same kinds of strips found later.
+    pub fn merge(&mut self, other: Tally) {
+        self.count += other.count;
+        self.searched += other.searched;
+        if let (Some(widths), Some(more)) = (&mut self.widths, other.widths) {
+            for (key, n) in more {
+                *widths.entry(key).or_insert(0) += n;
+            }
+        }
+        if let (Some(boundaries), Some(more)) = (&mut self.boundaries, other.boundaries) {
+            for (key, n) in more {
+                *boundaries.entry(key).or_insert(0) += n;
+            }
+        }
+        if let (Some(paths), Some(more)) = (&mut self.paths, other.paths) {
+            for (path, n) in more {
+                match paths.last_mut() {
+                    Some((last, k)) if *last == path => *k += n,
+                    _ => paths.push((path, n)),
+                }
+            }
+        }
+    }
+
+    /// The tables, for the wire and the CLI.
+    pub fn out(&self, l: &Lattice) -> TallyOut {
+        TallyOut {
+            widths: self.widths.as_ref().map(|widths| {
+                widths.iter().map(|(&(level, width), &count)| WidthRow { level, width, count }).collect()
+            }),
+            boundaries: self.boundaries.as_ref().map(pair_rows),
+            paths: self.paths.as_ref().map(|paths| {
+                paths
+                    .iter()
+                    .map(|(path, count)| PathRow {
+                        labels: path.iter().map(|&f| l.face(f).label().to_string()).collect(),
+                        path: path.clone(),
+                        count: *count,
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// A [`Tally`] as tables: `strip_stream`'s `aggregate` message.
+#[derive(Serialize)]
+pub struct TallyOut {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub widths: Option<Vec<WidthRow>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boundaries: Option<Vec<PairOut>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<PathRow>>,
+}
+
+/// Strips whose layer at `level` has `width` bridges.
+#[derive(Serialize)]
+pub struct WidthRow {
+    pub level: usize,
+    pub width: usize,
+    pub count: u64,
+}
+
+/// Strips kept over one hamiltonian path (cycle) of level 0; paths with
+/// none are left out.
+#[derive(Serialize)]
+pub struct PathRow {
+    pub path: Layer,
+    pub labels: Vec<String>,
+    pub count: u64,
+}
+
+/// Filter and tally the strips below `seeds` in parallel, the seeds' parts
+/// merged in the order of the sequential search; `tally` is the empty tally
+/// to start every seed with. `progress` (weighed over the same seeds) counts
+/// the strips kept. None if `cancelled` stopped it; it is asked at every
+/// path and strip, and every [`CHECK_EVERY`] steps of the path search.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn tally_strips(
+    l: &Lattice,
+    cyclic: bool,
+    seeds: Vec<HamiltonianIter>,
+    matcher: &Matcher,
+    tally: &Tally,
+    progress: &Progress,
+    cancelled: &(dyn Fn() -> bool + Sync),
+) -> Option<Tally> {
+    let max_dim = l.dim();
+    let parts: Vec<Option<Tally>> = seeds
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, paths)| {
+            let mut part = tally.clone();
+            for path in paths.until(CHECK_EVERY, cancelled) {
+                progress.tried();
+                for strip in extensions(vec![path], l, max_dim, cyclic) {
+                    if cancelled() {
+                        return None;
+                    }
+                    part.searched += 1;
+                    if matcher.matches(&strip) {
+                        part.add(l, cyclic, &strip);
+                        progress.add(1);
+                    }
+                }
+            }
+            if cancelled() {
+                return None;
+            }
+            progress.finish(i);
+            Some(part)
+        })
+        .collect();
+    let mut whole = tally.clone();
+    for part in parts {
+        whole.merge(part?);
+    }
+    Some(whole)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::rhombic;
+
+    #[test]
+    fn filters_split_the_strips() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let strips: Vec<Strip> = rhombic::strips(&l, false).collect();
+        let kept = |spec: &str| {
+            let matcher = Filter::parse(spec).unwrap().matcher(&l, false).unwrap();
+            strips.iter().filter(|s| matcher.matches(s)).count()
+        };
+        assert_eq!(kept(""), strips.len());
+        assert_eq!(kept("cyclic") + kept("not-cyclic"), strips.len());
+
+        // every face of level 1 is a bridge or in a gap
+        let face = l.level(1)[0];
+        let label = l.face(face).label();
+        assert_eq!(kept(&format!("bridges={}", label)) + kept(&format!("gaps={}", label)), strips.len());
+
+        // the widths of level 1 add up to all the strips, as do the boundaries
+        let all = Filter::default().matcher(&l, false).unwrap();
+        let mut tally = Tally::new(&["widths".to_string(), "boundaries".to_string(), "paths".to_string()]).unwrap();
+        for strip in &strips {
+            tally.add(&l, false, strip);
+        }
+        let out = tally.out(&l);
+        let widths = out.widths.unwrap();
+        for row in widths.iter().filter(|r| r.level == 1) {
+            assert_eq!(kept(&format!("widths={}", row.width)) as u64, row.count);
+        }
+        assert_eq!(widths.iter().filter(|r| r.level == 1).map(|r| r.count).sum::<u64>(), strips.len() as u64);
+        let pair = &out.boundaries.unwrap()[0];
+        assert_eq!(kept(&format!("left={},right={}", pair.left, pair.right)), pair.count);
+        let per_path: Vec<u64> = out.paths.unwrap().iter().map(|p| p.count).collect();
+        let expected: Vec<u64> =
+            rhombic::strips_per_path(&l, false).into_iter().map(|(_, n)| n as u64).filter(|&n| n > 0).collect();
+        assert_eq!(per_path, expected);
+
+        // in parallel, seed by seed, the same
+        let seeds = l.ham_path_seeds(false, 16);
+        let progress = Progress::new(&seeds);
+        let empty = Tally::new(&["paths".to_string()]).unwrap();
+        let whole = tally_strips(&l, false, seeds, &all, &empty, &progress, &|| false).unwrap();
+        assert_eq!((whole.count, whole.searched), (strips.len() as u64, strips.len() as u64));
+        assert_eq!(whole.out(&l).paths.unwrap().iter().map(|p| p.count).collect::<Vec<_>>(), per_path);
+    }
+
+    #[test]
+    fn bad_filters_say_why() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        assert!(Filter::parse("widths=2/x").unwrap_err().contains("bad width"));
+        assert!(Filter::parse("sideways").unwrap_err().contains("unknown condition"));
+        assert!(Filter::parse("bridges=nope").unwrap().matcher(&l, false).err().unwrap().contains("no face"));
+        let level0 = l.face(l.level(0)[0]).label().to_string();
+        assert!(Filter::parse(&format!("gaps={}", level0)).unwrap().matcher(&l, false).is_err());
+        assert!(Filter::parse("cyclic").unwrap().matcher(&l, true).is_err());
+        assert!(Filter::parse("widths=1/1/1/1").unwrap().matcher(&l, false).is_err());
+        assert!(Tally::new(&["colours".to_string()]).is_err());
+        let json: Filter = serde_json::from_str(r#"{"widths":[3,null],"alsoCyclic":true}"#).unwrap();
+        assert_eq!(json, Filter::parse("widths=3/-,cyclic").unwrap());
+    }
+}
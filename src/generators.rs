@@ -1,46 +1,1508 @@
-use rhombic_strips::web::api::*;
-use serde_json::Value;
-
-fn parse(s: &str) -> Value { serde_json::from_str(s).unwrap() }
-fn counts(v: &Value) -> (usize, usize) {
-    (v["labels"].as_array().unwrap().len(), v["edges"].as_array().unwrap().len())
-}
-
-#[test]
-fn generator_invariants() {
-    let g = parse(&gen_grid("211").unwrap());
-    assert_eq!(counts(&g), (12, 20));
-    assert_eq!(g["ranks"].as_array().unwrap().iter().map(|r| r.as_u64().unwrap()).max(), Some(4));
-
-    assert_eq!(counts(&parse(&gen_cube(2).unwrap())), (9, 12));
-    assert_eq!(counts(&parse(&gen_cube(3).unwrap())).0, 27);
-    assert_eq!(counts(&parse(&gen_simplex(2).unwrap())).0, 7);
-    assert_eq!(counts(&parse(&gen_simplex(3).unwrap())).0, 15);
-
-    // J(antichain_3) = B_3
-    let anti = r#"{"labels":["0","1","2"],"edges":[]}"#;
-    assert_eq!(counts(&parse(&gen_distributive(anti).unwrap())), (8, 12));
-
-    // tubes / tubings
-    let path3 = gen_graph("path", 3).unwrap();
-    let tp = parse(&gen_tube_poset(&path3).unwrap());
-    assert_eq!(counts(&tp), (6, 6));
-    let pent = parse(&gen_graph_associahedron(&path3).unwrap());
-    assert_eq!(counts(&pent).0, 11);
-    let hex = parse(&gen_graph_associahedron(&gen_graph("complete", 3).unwrap()).unwrap());
-    assert_eq!(counts(&hex).0, 13);
-    let assoc3 = parse(&gen_graph_associahedron(&gen_graph("path", 4).unwrap()).unwrap());
-    assert_eq!(counts(&assoc3).0, 45);
-    let perm3 = parse(&gen_graph_associahedron(&gen_graph("complete", 4).unwrap()).unwrap());
-    assert_eq!(counts(&perm3).0, 75);
-    assert_eq!(perm3["ranks"].as_array().unwrap().iter().map(|r| r.as_u64().unwrap()).max(), Some(3));
-
-    // file round-trip
-    let file = to_lattice_file(&gen_cube(2).unwrap()).unwrap();
-    let back = parse(&from_lattice_file(&file).unwrap());
-    assert_eq!(counts(&back), (9, 12));
-
-    // cycle rejection
-    let cyc = r#"{"labels":["a","b"],"edges":[[0,1],[1,0]]}"#;
-    assert!(poset_ranks(cyc).is_err());
+//! Poset and lattice generators, shared by every front end.
+//!
+//! Each generator returns a [`Poset`]: labelled elements plus cover
+//! relations (or, for graphs, edges). The GUI turns it into a
+//! `PosetGraph`, the browser receives it as JSON through
+//! [`crate::web::api`], and the CLI writes it with
+//! [`Poset::to_lattice_file`], so a generator added here is available
+//! everywhere at once. Tests live in `tests/generators.rs`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::bitset::{self, BitSet};
+use crate::lattice::{Face, FaceId, Lattice};
+
+// -- the common poset type -------------------------------------------------
+
+/// A finite poset given by its cover relations, or a simple graph given by
+/// its edges. This is also the JSON wire format of the browser front end.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct Poset {
+    pub labels: Vec<String>,
+    /// Poset mode: cover relations `(lower, upper)`; graph mode: edges.
+    pub edges: Vec<(usize, usize)>,
+    /// Longest-path ranks (poset outputs only); minima have rank 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranks: Option<Vec<usize>>,
+}
+
+impl Poset {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("bad graph JSON: {}", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Poset serializes")
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Longest-path rank of every node (minimal elements have rank 0).
+    /// Errors if the relation is cyclic.
+    pub fn ranks(&self) -> Result<Vec<usize>, String> {
+        let n = self.labels.len();
+        let mut succ: Vec<Vec<usize>> = vec![vec![]; n];
+        let mut indeg = vec![0usize; n];
+        for &(a, b) in &self.edges {
+            if a >= n || b >= n {
+                return Err(format!("edge ({}, {}) out of range", a, b));
+            }
+            succ[a].push(b);
+            indeg[b] += 1;
+        }
+
+        let mut rank = vec![0usize; n];
+        let mut queue: Vec<usize> = (0..n).filter(|&i| indeg[i] == 0).collect();
+        let mut processed = 0;
+        while let Some(i) = queue.pop() {
+            processed += 1;
+            for &j in &succ[i] {
+                rank[j] = rank[j].max(rank[i] + 1);
+                indeg[j] -= 1;
+                if indeg[j] == 0 {
+                    queue.push(j);
+                }
+            }
+        }
+        if processed != n {
+            return Err("Relation contains a cycle — not a poset.".to_string());
+        }
+        Ok(rank)
+    }
+
+    /// Fill in [`Poset::ranks`], checking acyclicity.
+    pub fn with_ranks(mut self) -> Result<Self, String> {
+        self.ranks = Some(self.ranks()?);
+        Ok(self)
+    }
+
+    /// Faces for `Lattice::from_faces`, in node order (FaceId == node index).
+    pub fn to_faces(&self) -> Result<Vec<Face>, String> {
+        let rank = self.ranks()?;
+        let n = self.labels.len();
+        let mut upsets: Vec<Vec<FaceId>> = vec![vec![]; n];
+        let mut downsets: Vec<Vec<FaceId>> = vec![vec![]; n];
+        for &(a, b) in &self.edges {
+            upsets[a].push(b);
+            downsets[b].push(a);
+        }
+        Ok(self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                Face::new(
+                    label.clone(),
+                    rank[i],
+                    std::mem::take(&mut upsets[i]),
+                    std::mem::take(&mut downsets[i]),
+                )
+            })
+            .collect())
+    }
+
+    pub fn to_lattice(&self) -> Result<Lattice, String> {
+        Ok(Lattice::from_faces(self.to_faces()?))
+    }
+
+    /// Write in the lattice file format `dim: label: {upset}, {downset}`.
+    pub fn to_lattice_file(&self) -> Result<String, String> {
+        let faces = self.to_faces()?;
+        let fmt_set =
+            |s: &[FaceId]| s.iter().map(usize::to_string).collect::<Vec<_>>().join(", ");
+        let mut out = String::new();
+        for face in &faces {
+            let label = face.label().replace(": ", "-"); // ": " is the field separator
+            out.push_str(&format!(
+                "{}: {}: {{{}}}, {{{}}}\n",
+                face.dim(),
+                label,
+                fmt_set(face.upset()),
+                fmt_set(face.downset())
+            ));
+        }
+        Ok(out)
+    }
+
+    /// The poset of a lattice (ranks left unset).
+    pub fn from_lattice(l: &Lattice) -> Self {
+        let mut g = Poset::default();
+        for (_, face) in l.faces() {
+            g.labels.push(face.label().to_string());
+        }
+        for (id, face) in l.faces() {
+            for &d in face.downset() {
+                g.edges.push((d, id));
+            }
+        }
+        g
+    }
+
+    pub fn from_lattice_file(content: &str) -> Result<Self, String> {
+        Self::from_lattice(&Lattice::from_str_content(content)?).with_ranks()
+    }
+
+    /// Add cover relations inferred from all-digit labels: same length,
+    /// digit sums differing by one, and exactly one differing position.
+    /// Returns the number of relations added.
+    pub fn infer_digit_relations(&mut self) -> usize {
+        let digit_sum = |s: &str| -> Option<i64> {
+            s.chars().map(|c| c.to_digit(10).map(|d| d as i64)).sum::<Option<i64>>()
+        };
+        let mut added = 0usize;
+        let n = self.labels.len();
+        for i in 0..n {
+            for j in 0..n {
+                if i == j || self.labels[i].len() != self.labels[j].len() {
+                    continue;
+                }
+                let (Some(sa), Some(sb)) =
+                    (digit_sum(&self.labels[i]), digit_sum(&self.labels[j]))
+                else {
+                    continue;
+                };
+                if sb != sa + 1 {
+                    continue;
+                }
+                let diff = self.labels[i]
+                    .chars()
+                    .zip(self.labels[j].chars())
+                    .filter(|(x, y)| x != y)
+                    .count();
+                if diff == 1 && !self.edges.contains(&(i, j)) {
+                    self.edges.push((i, j));
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
+
+// -- limits ----------------------------------------------------------------
+
+/// Size caps of the generators. Natively they only guard against runaway
+/// output; in the browser the defaults stay small so a click cannot
+/// freeze the tab, and the page can raise them with [`set_limits`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    /// Vertices of a graph for its tube poset.
+    pub tube_poset_vertices: usize,
+    /// Vertices of a graph (or ground set of a building set) for its
+    /// associahedron / nestohedron.
+    pub associahedron_vertices: usize,
+    /// Elements of P for J(P).
+    pub distributive_elements: usize,
+    /// Elements (tubes, tubings, order ideals) one generator may output.
+    pub max_faces: usize,
+}
+
+impl Default for Limits {
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        Limits {
+            tube_poset_vertices: 10,
+            associahedron_vertices: 12,
+            distributive_elements: 20,
+            max_faces: 20_000,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        Limits {
+            tube_poset_vertices: bitset::CAPACITY,
+            associahedron_vertices: bitset::CAPACITY,
+            distributive_elements: bitset::CAPACITY,
+            max_faces: 2_000_000,
+        }
+    }
+}
+
+static LIMITS: RwLock<Option<Limits>> = RwLock::new(None);
+
+/// The current limits.
+pub fn limits() -> Limits {
+    LIMITS.read().unwrap_or_else(|e| e.into_inner()).unwrap_or_default()
+}
+
+/// Replace the limits for all later generator calls. Vertex limits above
+/// the bitset capacity are clamped when used.
+pub fn set_limits(new: Limits) {
+    *LIMITS.write().unwrap_or_else(|e| e.into_inner()) = Some(new);
+}
+
+// -- poset generators -----------------------------------------------------
+
+/// Product of chains. `"211"` gives C3 x C2 x C2 (per-digit); inputs with
+/// separators like `"12,3"` allow multi-digit chain lengths.
+pub fn grid(spec: &str) -> Result<Poset, String> {
+    let dims: Vec<u32> = if spec.chars().all(|c| c.is_ascii_digit()) {
+        spec.chars().filter_map(|c| c.to_digit(10)).collect()
+    } else {
+        spec.split(|c: char| !c.is_ascii_digit())
+            .filter(|t| !t.is_empty())
+            .filter_map(|t| t.parse().ok())
+            .collect()
+    };
+    if dims.is_empty() {
+        return Err("Enter chain lengths, e.g. 211 or 12,3.".to_string());
+    }
+    let size: u64 = dims.iter().map(|&d| d as u64 + 1).product();
+    if size > 5_000 {
+        return Err(format!("Grid too large ({} elements).", size));
+    }
+
+    let mut points: Vec<Vec<u32>> = vec![vec![]];
+    for &d in &dims {
+        points = points
+            .into_iter()
+            .flat_map(|p| {
+                (0..=d).map(move |i| {
+                    let mut q = p.clone();
+                    q.push(i);
+                    q
+                })
+            })
+            .collect();
+    }
+
+    let sep = if dims.iter().all(|&d| d <= 9) { "" } else { "," };
+    let mut g = Poset::default();
+    for p in &points {
+        g.labels
+            .push(p.iter().map(u32::to_string).collect::<Vec<_>>().join(sep));
+    }
+    for (i, p) in points.iter().enumerate() {
+        for (j, q) in points.iter().enumerate() {
+            let mut up_by_one = None;
+            let mut ok = true;
+            for k in 0..p.len() {
+                if p[k] != q[k] {
+                    if up_by_one.is_none() && q[k] == p[k] + 1 {
+                        up_by_one = Some(k);
+                    } else {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+            if ok && up_by_one.is_some() {
+                g.edges.push((i, j));
+            }
+        }
+    }
+    g.with_ranks()
+}
+
+/// Face lattice of the d-cube (without the empty face): all words over
+/// {0, 1, *}, covers replace one fixed coordinate by *.
+pub fn cube(d: usize) -> Result<Poset, String> {
+    if !(1..=5).contains(&d) {
+        return Err("Cube dimension must be between 1 and 5.".to_string());
+    }
+    let mut faces: Vec<String> = (0..3usize.pow(d as u32))
+        .map(|mut k| {
+            (0..d)
+                .map(|_| {
+                    let c = ['0', '1', '*'][k % 3];
+                    k /= 3;
+                    c
+                })
+                .collect()
+        })
+        .collect();
+    faces.sort_by_key(|f: &String| (f.matches('*').count(), f.clone()));
+    let idx: HashMap<String, usize> =
+        faces.iter().enumerate().map(|(i, f)| (f.clone(), i)).collect();
+
+    let mut g = Poset { labels: faces.clone(), ..Default::default() };
+    for (i, f) in faces.iter().enumerate() {
+        for (k, c) in f.chars().enumerate() {
+            if c != '*' {
+                let mut upper: Vec<char> = f.chars().collect();
+                upper[k] = '*';
+                g.edges.push((i, idx[&upper.into_iter().collect::<String>()]));
+            }
+        }
+    }
+    g.with_ranks()
+}
+
+/// Face lattice of the d-simplex (without the empty face): nonempty
+/// subsets of {0, ..., d}, covers add one element.
+pub fn simplex(d: usize) -> Result<Poset, String> {
+    if !(1..=6).contains(&d) {
+        return Err("Simplex dimension must be between 1 and 6.".to_string());
+    }
+    let n = d + 1;
+    let mut masks: Vec<u64> = (1..(1u64 << n)).collect();
+    masks.sort_by_key(|m| (m.count_ones(), *m));
+    let idx: HashMap<u64, usize> =
+        masks.iter().enumerate().map(|(i, &m)| (m, i)).collect();
+
+    let label = |m: u64| -> String {
+        (0..n).filter(|&v| (m >> v) & 1 == 1).map(|v| v.to_string()).collect()
+    };
+    let mut g = Poset::default();
+    for &m in &masks {
+        g.labels.push(label(m));
+    }
+    for (i, &m) in masks.iter().enumerate() {
+        for v in 0..n {
+            if (m >> v) & 1 == 0 {
+                g.edges.push((i, idx[&(m | (1 << v))]));
+            }
+        }
+    }
+    g.with_ranks()
+}
+
+/// The distributive lattice J(P) of order ideals of the given poset.
+/// Ideals are enumerated along a linear extension (include an element
+/// only once its lower covers are in), so the work is proportional to
+/// the output rather than to 2^|P|.
+pub fn distributive(g: &Poset) -> Result<Poset, String> {
+    let rank = g.ranks()?; // must be acyclic
+    let n = g.labels.len();
+    let lim = limits();
+    let max_n = lim.distributive_elements.min(bitset::CAPACITY);
+    if n > max_n {
+        return Err(format!("Poset too large ({} > {} elements) for J(P).", n, max_n));
+    }
+    let mut down = vec![BitSet::EMPTY; n];
+    for &(a, b) in &g.edges {
+        down[b].insert(a);
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| rank[i]);
+
+    fn rec(
+        k: usize,
+        ideal: BitSet,
+        order: &[usize],
+        down: &[BitSet],
+        cap: usize,
+        out: &mut Vec<BitSet>,
+    ) -> Result<(), String> {
+        let Some(&x) = order.get(k) else {
+            if out.len() == cap {
+                return Err(format!("J(P) has more than {} elements — aborting.", cap));
+            }
+            out.push(ideal);
+            return Ok(());
+        };
+        rec(k + 1, ideal, order, down, cap, out)?;
+        if down[x].is_subset(ideal) {
+            rec(k + 1, ideal.with(x), order, down, cap, out)?;
+        }
+        Ok(())
+    }
+    let mut ideals = Vec::new();
+    rec(0, BitSet::EMPTY, &order, &down, lim.max_faces, &mut ideals)?;
+    ideals.sort_unstable();
+    let idx: HashMap<BitSet, usize> =
+        ideals.iter().enumerate().map(|(i, &m)| (m, i)).collect();
+
+    let labels = ideals
+        .iter()
+        .map(|&ideal| {
+            let mut labels: Vec<&str> = ideal.iter().map(|i| g.labels[i].as_str()).collect();
+            labels.sort_unstable();
+            if labels.is_empty() {
+                "0".to_string()
+            } else {
+                format!("{{{}}}", labels.join(","))
+            }
+        })
+        .collect();
+    let mut covers = Vec::new();
+    for (j, &ideal) in ideals.iter().enumerate() {
+        for x in ideal.iter() {
+            if let Some(&i) = idx.get(&ideal.without(x)) {
+                covers.push((i, j));
+            }
+        }
+    }
+    poset_from_covers(labels, covers)
+}
+
+// -- lattice families (Rust ports of the scripts in lattices/) -------------
+//
+// Each generator follows its script's element order, labels and covers,
+// so `to_lattice_file` of the result reproduces the checked-in files.
+
+/// Wire poset from labels and cover pairs. Covers are sorted, so upsets
+/// and downsets come out in ascending index order like the scripts'.
+fn poset_from_covers(
+    labels: Vec<String>,
+    mut covers: Vec<(usize, usize)>,
+) -> Result<Poset, String> {
+    covers.sort_unstable();
+    covers.dedup();
+    Poset { labels, edges: covers, ranks: None }.with_ranks()
+}
+
+/// J(F_n): order ideals of the fence on n elements (`distributed.py`).
+/// Even elements `b1, b2, ...` are minimal, odd elements `a1, a2, ...`
+/// cover their neighbours; an ideal is labelled by its sorted element
+/// labels, the bottom by `empty`.
+pub fn fence(n: usize) -> Result<Poset, String> {
+    if !(1..=20).contains(&n) {
+        return Err("Fence size must be between 1 and 20.".to_string());
+    }
+    let mut down_mask = vec![0u64; n];
+    for i in (1..n).step_by(2) {
+        down_mask[i] |= 1 << (i - 1);
+        if i + 1 < n {
+            down_mask[i] |= 1 << (i + 1);
+        }
+    }
+    let elements = |m: u64| (0..n).filter(move |&i| (m >> i) & 1 == 1);
+    let mut ideals: Vec<u64> = (0u64..(1 << n))
+        .filter(|&m| elements(m).all(|i| m & down_mask[i] == down_mask[i]))
+        .collect();
+    ideals.sort_by_cached_key(|&m| (m.count_ones(), elements(m).collect::<Vec<_>>()));
+    let idx: HashMap<u64, usize> =
+        ideals.iter().enumerate().map(|(i, &m)| (m, i)).collect();
+
+    let element_label = |i: usize| {
+        if i.is_multiple_of(2) {
+            format!("b{}", i / 2 + 1)
+        } else {
+            format!("a{}", (i - 1) / 2 + 1)
+        }
+    };
+    let labels = ideals
+        .iter()
+        .map(|&m| {
+            let mut ls: Vec<String> = elements(m).map(element_label).collect();
+            ls.sort();
+            if ls.is_empty() { "empty".to_string() } else { ls.concat() }
+        })
+        .collect();
+    let mut covers = Vec::new();
+    for (j, &m) in ideals.iter().enumerate() {
+        for i in elements(m) {
+            if let Some(&k) = idx.get(&(m & !(1 << i))) {
+                covers.push((k, j));
+            }
+        }
+    }
+    poset_from_covers(labels, covers)
+}
+
+/// The Boolean lattice B_n as in the `boolean_<n>` files: subsets of
+/// {0, ..., n-1} in binary order, labelled by bit strings with element k
+/// at position k; covers add one element.
+pub fn boolean(n: usize) -> Result<Poset, String> {
+    if !(1..=12).contains(&n) {
+        return Err("Boolean lattice rank must be between 1 and 12.".to_string());
+    }
+    let labels = (0..1usize << n)
+        .map(|m| (0..n).map(|k| if (m >> k) & 1 == 1 { '1' } else { '0' }).collect())
+        .collect();
+    let mut covers = Vec::new();
+    for m in 0..1usize << n {
+        for k in (0..n).filter(|&k| (m >> k) & 1 == 0) {
+            covers.push((m, m | 1 << k));
+        }
+    }
+    poset_from_covers(labels, covers)
+}
+
+/// Partitions of n as non-increasing part lists, in lexicographic order
+/// (`[1, 1, 1], [2, 1], [3]`) or its reverse.
+fn partitions(n: usize, descending: bool) -> Vec<Vec<usize>> {
+    fn rec(
+        max: usize,
+        rest: usize,
+        descending: bool,
+        prefix: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if rest == 0 {
+            out.push(prefix.clone());
+            return;
+        }
+        let parts = 1..=max.min(rest);
+        let parts: Vec<usize> =
+            if descending { parts.rev().collect() } else { parts.collect() };
+        for x in parts {
+            prefix.push(x);
+            rec(x, rest - x, descending, prefix, out);
+            prefix.pop();
+        }
+    }
+    let mut out = Vec::new();
+    rec(n, n, descending, &mut Vec::new(), &mut out);
+    out
+}
+
+fn partition_label(p: &[usize]) -> String {
+    p.iter().map(usize::to_string).collect()
+}
+
+/// Young's lattice cut to the partitions of 2, ..., n-1
+/// (`int_partitions.py`), ranked by size - 2; covers add one box.
+pub fn int_partitions(n: usize) -> Result<Poset, String> {
+    if !(3..=25).contains(&n) {
+        return Err("Partition size must be between 3 and 25.".to_string());
+    }
+    let parts: Vec<Vec<usize>> = (2..n).flat_map(|k| partitions(k, false)).collect();
+    let idx: HashMap<&[usize], usize> =
+        parts.iter().enumerate().map(|(i, p)| (p.as_slice(), i)).collect();
+    let mut covers = Vec::new();
+    for (i, p) in parts.iter().enumerate() {
+        for k in 0..=p.len() {
+            let mut q = p.clone();
+            if k == p.len() {
+                q.push(1);
+            } else if k == 0 || p[k - 1] > p[k] {
+                q[k] += 1;
+            } else {
+                continue;
+            }
+            if let Some(&j) = idx.get(q.as_slice()) {
+                covers.push((i, j));
+            }
+        }
+    }
+    poset_from_covers(parts.iter().map(|p| partition_label(p)).collect(), covers)
+}
+
+/// The refinement order on the partitions of n (`ref_int_partitions.py`):
+/// reverse lexicographic order starting at `[n]`, ranked by the number
+/// of parts - 1; covers split one part in two.
+pub fn int_partitions_ref(n: usize) -> Result<Poset, String> {
+    if !(1..=30).contains(&n) {
+        return Err("Partition size must be between 1 and 30.".to_string());
+    }
+    let parts = partitions(n, true);
+    let idx: HashMap<&[usize], usize> =
+        parts.iter().enumerate().map(|(i, p)| (p.as_slice(), i)).collect();
+    let mut covers = Vec::new();
+    for (i, p) in parts.iter().enumerate() {
+        for k in 0..p.len() {
+            for a in 1..=p[k] / 2 {
+                let mut q = p.clone();
+                q[k] = p[k] - a;
+                q.push(a);
+                q.sort_unstable_by(|x, y| y.cmp(x));
+                covers.push((i, idx[q.as_slice()]));
+            }
+        }
+    }
+    poset_from_covers(parts.iter().map(|p| partition_label(p)).collect(), covers)
+}
+
+/// The product of claws C_a × C_b (`prod_of_claws.py`): pairs (u, v) with
+/// u in 0..=a, v in 0..=b, where 0 lies below everything in a claw.
+pub fn prod_of_claws(a: usize, b: usize) -> Result<Poset, String> {
+    if a == 0 || b == 0 {
+        return Err("Claw sizes must be at least 1.".to_string());
+    }
+    if (a + 1) * (b + 1) > 5_000 {
+        return Err(format!("Product too large ({} elements).", (a + 1) * (b + 1)));
+    }
+    let sep = if a <= 9 && b <= 9 { "" } else { "," };
+    let index = |u: usize, v: usize| u * (b + 1) + v;
+    let mut labels = Vec::new();
+    let mut covers = Vec::new();
+    for u in 0..=a {
+        for v in 0..=b {
+            labels.push(format!("{}{}{}", u, sep, v));
+            if u == 0 {
+                covers.extend((1..=a).map(|w| (index(0, v), index(w, v))));
+            }
+            if v == 0 {
+                covers.extend((1..=b).map(|w| (index(u, 0), index(u, w))));
+            }
+        }
+    }
+    poset_from_covers(labels, covers)
+}
+
+/// Nondecreasing k-tuples over 0..m, lexicographically (Python's
+/// `combinations_with_replacement(range(m), k)`).
+fn multisets(m: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut out: Vec<Vec<usize>> = vec![vec![]];
+    for _ in 0..k {
+        out = out
+            .into_iter()
+            .flat_map(|t| {
+                let lo = t.last().copied().unwrap_or(0);
+                (lo..m).map(move |x| {
+                    let mut t = t.clone();
+                    t.push(x);
+                    t
+                })
+            })
+            .collect();
+    }
+    out
+}
+
+/// Edge lists of all rooted trees on 1, ..., n vertices (root 0), in the
+/// order and numbering of `gen_trees.py`: one subtree combination per
+/// partition of n-1, subtrees numbered consecutively after the root.
+fn rooted_tree_edges(n: usize) -> Vec<Vec<Vec<(usize, usize)>>> {
+    let mut trees: Vec<Vec<Vec<(usize, usize)>>> = vec![vec![], vec![vec![]]];
+    for size in 2..=n {
+        let mut level = Vec::new();
+        for partition in partitions(size - 1, true) {
+            // (part size, multiplicity); parts are non-increasing
+            let mut groups: Vec<(usize, usize)> = Vec::new();
+            for &p in &partition {
+                match groups.last_mut() {
+                    Some((q, c)) if *q == p => *c += 1,
+                    _ => groups.push((p, 1)),
+                }
+            }
+            let mut combos: Vec<Vec<&Vec<(usize, usize)>>> = vec![vec![]];
+            for &(p, c) in &groups {
+                let subtrees = &trees[p];
+                combos = combos
+                    .into_iter()
+                    .flat_map(|combo| {
+                        multisets(subtrees.len(), c).into_iter().map(move |sel| {
+                            let mut combo = combo.clone();
+                            combo.extend(sel.iter().map(|&s| &subtrees[s]));
+                            combo
+                        })
+                    })
+                    .collect();
+            }
+            for combo in combos {
+                let mut edges = Vec::new();
+                let mut next = 1;
+                for sub in combo {
+                    edges.push((0, next));
+                    edges.extend(sub.iter().map(|&(u, v)| (u + next, v + next)));
+                    next += sub.len() + 1;
+                }
+                edges.sort_unstable();
+                level.push(edges);
+            }
+        }
+        trees.push(level);
+    }
+    trees
+}
+
+/// All rooted trees on n vertices up to isomorphism (`gen_trees.py`), as
+/// graphs with the root at vertex 0.
+pub fn rooted_trees(n: usize) -> Result<Vec<Poset>, String> {
+    if !(1..=12).contains(&n) {
+        return Err("Tree size must be between 1 and 12.".to_string());
+    }
+    Ok(rooted_tree_edges(n)
+        .swap_remove(n)
+        .into_iter()
+        .map(|edges| Poset {
+            labels: (0..n).map(|v| v.to_string()).collect(),
+            edges,
+            ranks: None,
+        })
+        .collect())
+}
+
+// -- classic lattices ------------------------------------------------------
+
+#[derive(Clone)]
+enum BinTree {
+    Leaf,
+    Node(Box<BinTree>, Box<BinTree>),
+}
+
+impl BinTree {
+    /// All binary trees with n internal nodes, left comb first.
+    fn all(n: usize) -> Vec<BinTree> {
+        if n == 0 {
+            return vec![BinTree::Leaf];
+        }
+        let mut out = Vec::new();
+        for k in (0..n).rev() {
+            for l in BinTree::all(k) {
+                for r in BinTree::all(n - 1 - k) {
+                    out.push(BinTree::Node(Box::new(l.clone()), Box::new(r)));
+                }
+            }
+        }
+        out
+    }
+
+    /// Trees one right rotation `(xy)z -> x(yz)` above this one.
+    fn rotations(&self) -> Vec<BinTree> {
+        let BinTree::Node(l, r) = self else { return vec![] };
+        let mut out = Vec::new();
+        if let BinTree::Node(x, y) = l.as_ref() {
+            out.push(BinTree::Node(
+                x.clone(),
+                Box::new(BinTree::Node(y.clone(), r.clone())),
+            ));
+        }
+        for l2 in l.rotations() {
+            out.push(BinTree::Node(Box::new(l2), r.clone()));
+        }
+        for r2 in r.rotations() {
+            out.push(BinTree::Node(l.clone(), Box::new(r2)));
+        }
+        out
+    }
+
+    /// Bracketing of the leaves a, b, c, ..., e.g. `(ab)c`.
+    fn bracketing(&self) -> String {
+        fn rec(t: &BinTree, next: &mut u8, out: &mut String) {
+            match t {
+                BinTree::Leaf => {
+                    out.push(*next as char);
+                    *next += 1;
+                }
+                BinTree::Node(l, r) => {
+                    out.push('(');
+                    rec(l, next, out);
+                    rec(r, next, out);
+                    out.push(')');
+                }
+            }
+        }
+        let mut out = String::new();
+        rec(self, &mut b'a', &mut out);
+        if out.len() > 1 {
+            out = out[1..out.len() - 1].to_string(); // drop the outer pair
+        }
+        out
+    }
+}
+
+/// The Tamari lattice T_n: bracketings of n+1 letters, covers are right
+/// rotations `(xy)z < x(yz)`. Catalan(n) elements.
+pub fn tamari(n: usize) -> Result<Poset, String> {
+    if !(1..=8).contains(&n) {
+        return Err("Tamari size must be between 1 and 8.".to_string());
+    }
+    let trees = BinTree::all(n);
+    let labels: Vec<String> = trees.iter().map(BinTree::bracketing).collect();
+    let idx: HashMap<&str, usize> =
+        labels.iter().enumerate().map(|(i, l)| (l.as_str(), i)).collect();
+    let mut covers = Vec::new();
+    for (i, t) in trees.iter().enumerate() {
+        for up in t.rotations() {
+            covers.push((i, idx[up.bracketing().as_str()]));
+        }
+    }
+    poset_from_covers(labels, covers)
+}
+
+/// The weak (Bruhat) order on S_n: permutations in one-line notation,
+/// covers swap an adjacent ascent. Sorted by inversions, then lexically.
+pub fn weak_order(n: usize) -> Result<Poset, String> {
+    if !(1..=7).contains(&n) {
+        return Err("Weak order size must be between 1 and 7.".to_string());
+    }
+    let inversions = |p: &[usize]| -> usize {
+        (0..n).map(|i| (i + 1..n).filter(|&j| p[i] > p[j]).count()).sum()
+    };
+    let mut perms: Vec<Vec<usize>> = (1..=n).permutations(n).collect();
+    perms.sort_by_cached_key(|p| (inversions(p), p.clone()));
+    let idx: HashMap<&[usize], usize> =
+        perms.iter().enumerate().map(|(i, p)| (p.as_slice(), i)).collect();
+    let mut covers = Vec::new();
+    for (i, p) in perms.iter().enumerate() {
+        for k in (0..n - 1).filter(|&k| p[k] < p[k + 1]) {
+            let mut q = p.clone();
+            q.swap(k, k + 1);
+            covers.push((i, idx[q.as_slice()]));
+        }
+    }
+    poset_from_covers(perms.iter().map(|p| partition_label(p)).collect(), covers)
+}
+
+/// Set partitions of {1..n} as restricted growth strings, by number of
+/// blocks (descending), then lexically.
+fn restricted_growth_strings(n: usize) -> Vec<Vec<usize>> {
+    let mut out: Vec<Vec<usize>> = vec![vec![]];
+    for _ in 0..n {
+        out = out
+            .into_iter()
+            .flat_map(|rgs| {
+                let blocks = rgs.iter().max().map_or(0, |&m| m + 1);
+                (0..=blocks).map(move |b| {
+                    let mut rgs = rgs.clone();
+                    rgs.push(b);
+                    rgs
+                })
+            })
+            .collect();
+    }
+    let blocks = |rgs: &Vec<usize>| rgs.iter().max().map_or(0, |&m| m + 1);
+    out.sort_by_key(|rgs| std::cmp::Reverse(blocks(rgs)));
+    out
+}
+
+/// Canonical restricted growth string of a block assignment.
+fn normalize_rgs(blocks: &[usize]) -> Vec<usize> {
+    let mut relabel: HashMap<usize, usize> = HashMap::new();
+    blocks
+        .iter()
+        .map(|b| {
+            let next = relabel.len();
+            *relabel.entry(*b).or_insert(next)
+        })
+        .collect()
+}
+
+/// Block notation, e.g. `12|3`.
+fn set_partition_label(rgs: &[usize]) -> String {
+    let blocks = rgs.iter().max().map_or(0, |&m| m + 1);
+    (0..blocks)
+        .map(|b| {
+            rgs.iter()
+                .enumerate()
+                .filter(|&(_, &x)| x == b)
+                .map(|(i, _)| (i + 1).to_string())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Two blocks of a set partition cross if a < b < c < d with a, c in one
+/// and b, d in the other.
+fn is_noncrossing(rgs: &[usize]) -> bool {
+    let n = rgs.len();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    if rgs[a] == rgs[c] && rgs[b] == rgs[d] && rgs[a] != rgs[b] {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Set partitions ordered by refinement (finest at the bottom), optionally
+/// restricted to the noncrossing ones. Covers merge two blocks.
+fn partition_lattice(n: usize, noncrossing: bool) -> Result<Poset, String> {
+    let parts: Vec<Vec<usize>> = restricted_growth_strings(n)
+        .into_iter()
+        .filter(|p| !noncrossing || is_noncrossing(p))
+        .collect();
+    let idx: HashMap<&[usize], usize> =
+        parts.iter().enumerate().map(|(i, p)| (p.as_slice(), i)).collect();
+    let mut covers = Vec::new();
+    for (i, p) in parts.iter().enumerate() {
+        let blocks = p.iter().max().map_or(0, |&m| m + 1);
+        for x in 0..blocks {
+            for y in x + 1..blocks {
+                let merged: Vec<usize> =
+                    p.iter().map(|&b| if b == y { x } else { b }).collect();
+                if let Some(&j) = idx.get(normalize_rgs(&merged).as_slice()) {
+                    covers.push((i, j));
+                }
+            }
+        }
+    }
+    poset_from_covers(parts.iter().map(|p| set_partition_label(p)).collect(), covers)
+}
+
+/// The partition lattice Π_n of {1..n} under refinement (Bell(n)
+/// elements), labelled in block notation like `12|3`.
+pub fn set_partitions(n: usize) -> Result<Poset, String> {
+    if !(1..=8).contains(&n) {
+        return Err("Set partition size must be between 1 and 8.".to_string());
+    }
+    partition_lattice(n, false)
+}
+
+/// The noncrossing partition lattice NC(n) (Catalan(n) elements).
+pub fn noncrossing(n: usize) -> Result<Poset, String> {
+    if !(1..=9).contains(&n) {
+        return Err("Noncrossing partition size must be between 1 and 9.".to_string());
+    }
+    partition_lattice(n, true)
+}
+
+/// Young's lattice interval L(m, n): partitions with at most m parts,
+/// each at most n (Ferrers diagrams in an m × n box); covers add a box.
+pub fn young_interval(m: usize, n: usize) -> Result<Poset, String> {
+    if !(1..=50).contains(&m) || !(1..=50).contains(&n) {
+        return Err("Box sides must be between 1 and 50.".to_string());
+    }
+    // |L(m, n)| = binomial(m + n, m), built up factor by factor
+    let size = (1..=m.min(n) as u128).fold(1u128, |acc, k| acc * (m.max(n) as u128 + k) / k);
+    if size > 5_000 {
+        return Err(format!("L({}, {}) too large ({} elements).", m, n, size));
+    }
+    fn in_box(max: usize, slots: usize, prefix: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        out.push(prefix.clone());
+        if slots == 0 {
+            return;
+        }
+        for x in 1..=max {
+            prefix.push(x);
+            in_box(x, slots - 1, prefix, out);
+            prefix.pop();
+        }
+    }
+    let mut parts = Vec::new();
+    in_box(n, m, &mut Vec::new(), &mut parts);
+    parts.sort_by_cached_key(|p| (p.iter().sum::<usize>(), p.clone()));
+    let idx: HashMap<&[usize], usize> =
+        parts.iter().enumerate().map(|(i, p)| (p.as_slice(), i)).collect();
+    let mut covers = Vec::new();
+    for (i, p) in parts.iter().enumerate() {
+        for k in 0..=p.len() {
+            let mut q = p.clone();
+            if k == p.len() {
+                q.push(1);
+            } else if k == 0 || p[k - 1] > p[k] {
+                q[k] += 1;
+            } else {
+                continue;
+            }
+            if let Some(&j) = idx.get(q.as_slice()) {
+                covers.push((i, j));
+            }
+        }
+    }
+    let sep = if n <= 9 { "" } else { "," };
+    let labels = parts
+        .iter()
+        .map(|p| {
+            if p.is_empty() {
+                "empty".to_string()
+            } else {
+                p.iter().map(usize::to_string).collect::<Vec<_>>().join(sep)
+            }
+        })
+        .collect();
+    poset_from_covers(labels, covers)
+}
+
+/// The lattice of subspaces of F_2^n. A subspace is labelled by its
+/// reduced row echelon basis, e.g. `<100,011>`; the zero space is `0`.
+pub fn subspaces(n: usize) -> Result<Poset, String> {
+    if !(1..=6).contains(&n) {
+        return Err("Subspace lattice dimension must be between 1 and 6.".to_string());
+    }
+    // a subspace is the set of its vectors, as a mask over 0..2^n
+    let span_with = |space: u64, v: usize| -> u64 {
+        let mut out = space;
+        for u in (0..1usize << n).filter(|&u| (space >> u) & 1 == 1) {
+            out |= 1u64 << (u ^ v);
+        }
+        out
+    };
+    let mut spaces: Vec<u64> = vec![1];
+    let mut covers_masks: Vec<(u64, u64)> = Vec::new();
+    let mut seen: HashSet<u64> = HashSet::from([1]);
+    let mut k = 0;
+    while k < spaces.len() {
+        let s = spaces[k];
+        for v in (1..1usize << n).filter(|&v| (s >> v) & 1 == 0) {
+            let t = span_with(s, v);
+            if seen.insert(t) {
+                spaces.push(t);
+            }
+            covers_masks.push((s, t));
+        }
+        k += 1;
+    }
+    spaces.sort_by_key(|&s| (s.count_ones(), s));
+    let idx: HashMap<u64, usize> = spaces.iter().enumerate().map(|(i, &s)| (s, i)).collect();
+
+    let label = |s: u64| -> String {
+        // xor basis by leading bit, then fully reduced
+        let mut basis: Vec<usize> = Vec::new();
+        for mut v in (1..1usize << n).filter(|&v| (s >> v) & 1 == 1) {
+            for &b in &basis {
+                v = v.min(v ^ b);
+            }
+            if v != 0 {
+                basis.push(v);
+                basis.sort_unstable_by(|a, b| b.cmp(a));
+            }
+        }
+        for i in 0..basis.len() {
+            for j in 0..basis.len() {
+                let lead = 1 << (usize::BITS - 1 - basis[j].leading_zeros());
+                if i != j && basis[i] & lead != 0 {
+                    basis[i] ^= basis[j];
+                }
+            }
+        }
+        if basis.is_empty() {
+            return "0".to_string();
+        }
+        let bits = |v: usize| -> String {
+            (0..n).rev().map(|k| if (v >> k) & 1 == 1 { '1' } else { '0' }).collect()
+        };
+        format!("<{}>", basis.iter().map(|&v| bits(v)).collect::<Vec<_>>().join(","))
+    };
+    let labels = spaces.iter().map(|&s| label(s)).collect();
+    let covers = covers_masks.iter().map(|(s, t)| (idx[s], idx[t])).collect();
+    poset_from_covers(labels, covers)
+}
+
+// -- graph generators (graph mode) -----------------------------------------
+
+/// `kind`: "path" | "cycle" | "complete" | "star", on n vertices.
+pub fn graph(kind: &str, n: usize) -> Result<Poset, String> {
+    if n == 0 || n > bitset::CAPACITY {
+        return Err(format!("n must be between 1 and {}.", bitset::CAPACITY));
+    }
+    let mut g = Poset::default();
+    for i in 0..n {
+        g.labels.push(i.to_string());
+    }
+    match kind {
+        "path" => {
+            for i in 0..n.saturating_sub(1) {
+                g.edges.push((i, i + 1));
+            }
+        }
+        "cycle" => {
+            if n > 2 {
+                for i in 0..n {
+                    g.edges.push((i, (i + 1) % n));
+                }
+            } else if n == 2 {
+                g.edges.push((0, 1));
+            }
+        }
+        "complete" => {
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    g.edges.push((i, j));
+                }
+            }
+        }
+        "star" => {
+            for i in 1..n {
+                g.edges.push((0, i));
+            }
+        }
+        _ => return Err(format!("unknown graph kind '{}'", kind)),
+    }
+    Ok(g)
+}
+
+// -- graph associahedra: tubes and tubings (Carr–Devadoss) ------------------
+
+fn adjacency_sets(g: &Poset) -> Vec<BitSet> {
+    let mut adj = vec![BitSet::EMPTY; g.labels.len()];
+    for &(a, b) in &g.edges {
+        if a != b && a < adj.len() && b < adj.len() {
+            adj[a].insert(b);
+            adj[b].insert(a);
+        }
+    }
+    adj
+}
+
+fn set_connected(set: BitSet, adj: &[BitSet]) -> bool {
+    let Some(start) = set.first() else { return false };
+    let mut seen = BitSet::singleton(start);
+    let mut frontier = seen;
+    while !frontier.is_empty() {
+        let mut grow = BitSet::EMPTY;
+        for v in frontier.iter() {
+            grow |= adj[v];
+        }
+        frontier = (grow & set).and_not(seen);
+        seen |= frontier;
+    }
+    seen == set
+}
+
+fn neighbors(set: BitSet, adj: &[BitSet]) -> BitSet {
+    let mut nb = BitSet::EMPTY;
+    for v in set.iter() {
+        nb |= adj[v];
+    }
+    nb.and_not(set)
+}
+
+/// All connected vertex subsets, sorted, without scanning all 2^n sets:
+/// each set is grown from its smallest vertex, branching on the frontier
+/// vertices in order and forbidding the ones already branched on, so
+/// every connected set is reached exactly once.
+fn connected_subsets(adj: &[BitSet], cap: usize) -> Result<Vec<BitSet>, String> {
+    fn grow(
+        set: BitSet,
+        frontier: BitSet,
+        mut forbidden: BitSet,
+        adj: &[BitSet],
+        cap: usize,
+        out: &mut Vec<BitSet>,
+    ) -> Result<(), String> {
+        out.push(set);
+        if out.len() > cap {
+            return Err(format!("More than {} tubes — aborting.", cap));
+        }
+        for w in frontier.iter() {
+            forbidden.insert(w);
+            let next = set.with(w);
+            let next_frontier = (frontier | adj[w]).and_not(next).and_not(forbidden);
+            grow(next, next_frontier, forbidden, adj, cap, out)?;
+        }
+        Ok(())
+    }
+
+    let mut out = Vec::new();
+    for v in 0..adj.len() {
+        let forbidden = BitSet::full(v + 1);
+        grow(BitSet::singleton(v), adj[v].and_not(forbidden), forbidden, adj, cap, &mut out)?;
+    }
+    out.sort_unstable();
+    Ok(out)
+}
+
+/// When two disjoint tubes may lie in one nested set.
+enum Separation<'a> {
+    /// Graph tubes: not adjacent. Pairwise compatibility then suffices.
+    Graph(&'a [BitSet]),
+    /// General building set: no union of two or more pairwise disjoint
+    /// members lies in the building set.
+    Building(&'a HashSet<BitSet>),
+}
+
+/// Visit every nested set (tubing) of `tubes` once, in lexicographic
+/// order of tube indices, without materialising a tubes × tubes
+/// compatibility table: each level only keeps the later tubes that are
+/// compatible with everything chosen so far.
+fn for_each_tubing(
+    tubes: &[BitSet],
+    separation: &Separation,
+    visit: &mut dyn FnMut(&[usize]) -> Result<(), String>,
+) -> Result<(), String> {
+    let nbrs: Vec<BitSet> = match separation {
+        Separation::Graph(adj) => tubes.iter().map(|&t| neighbors(t, adj)).collect(),
+        Separation::Building(_) => Vec::new(),
+    };
+    let compatible = |i: usize, j: usize| -> bool {
+        let (a, b) = (tubes[i], tubes[j]);
+        if !a.is_disjoint(b) {
+            return a.is_subset(b) || b.is_subset(a);
+        }
+        match separation {
+            Separation::Graph(_) => nbrs[i].is_disjoint(b),
+            Separation::Building(building) => !building.contains(&(a | b)),
+        }
+    };
+
+    // Does `union` (already two or more disjoint members) extend, by
+    // further disjoint members of `pool`, to a union in the building set?
+    fn disjoint_union_hits(union: BitSet, pool: &[BitSet], building: &HashSet<BitSet>) -> bool {
+        if building.contains(&union) {
+            return true;
+        }
+        pool.iter().enumerate().any(|(k, &t)| {
+            t.is_disjoint(union) && disjoint_union_hits(union | t, &pool[k + 1..], building)
+        })
+    }
+
+    fn rec(
+        current: &mut Vec<usize>,
+        cands: &[usize],
+        tubes: &[BitSet],
+        separation: &Separation,
+        compatible: &dyn Fn(usize, usize) -> bool,
+        visit: &mut dyn FnMut(&[usize]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        visit(current)?;
+        for (k, &j) in cands.iter().enumerate() {
+            if let Separation::Building(building) = separation {
+                // pairs were checked when filtering; look for larger unions
+                let t = tubes[j];
+                let disjoint: Vec<BitSet> =
+                    current.iter().map(|&i| tubes[i]).filter(|u| u.is_disjoint(t)).collect();
+                if disjoint.iter().enumerate().any(|(k, &u)| {
+                    disjoint_union_hits(t | u, &disjoint[k + 1..], building)
+                }) {
+                    continue;
+                }
+            }
+            let next: Vec<usize> =
+                cands[k + 1..].iter().copied().filter(|&c| compatible(j, c)).collect();
+            current.push(j);
+            rec(current, &next, tubes, separation, compatible, visit)?;
+            current.pop();
+        }
+        Ok(())
+    }
+
+    let all: Vec<usize> = (0..tubes.len()).collect();
+    rec(&mut Vec::new(), &all, tubes, separation, &compatible, visit)
+}
+
+fn tube_label(set: BitSet, vertex_labels: &[&str]) -> String {
+    let single = vertex_labels.iter().all(|l| l.chars().count() == 1);
+    let parts: Vec<&str> = set.iter().map(|v| vertex_labels[v]).collect();
+    parts.join(if single { "" } else { "," })
+}
+
+fn checked_graph(g: &Poset, max_n: usize) -> Result<(usize, Vec<BitSet>, BitSet), String> {
+    let n = g.labels.len();
+    if n < 2 {
+        return Err("Draw a graph with at least 2 vertices first.".to_string());
+    }
+    if n > max_n.min(bitset::CAPACITY) {
+        return Err(format!("Graph too large ({} > {} vertices).", n, max_n));
+    }
+    let adj = adjacency_sets(g);
+    let full = BitSet::full(n);
+    if !set_connected(full, &adj) {
+        return Err("Graph must be connected.".to_string());
+    }
+    Ok((n, adj, full))
+}
+
+/// The poset of tubes of the drawn graph under inclusion.
+pub fn tube_poset(g: &Poset) -> Result<Poset, String> {
+    let lim = limits();
+    let (_, adj, _) = checked_graph(g, lim.tube_poset_vertices)?;
+    let mut subs = connected_subsets(&adj, lim.max_faces)?;
+    subs.sort_by_key(|s| (s.len(), *s));
+    let idx: HashMap<BitSet, usize> = subs.iter().enumerate().map(|(i, &s)| (s, i)).collect();
+
+    let vertex_labels: Vec<&str> = g.labels.iter().map(String::as_str).collect();
+    let labels = subs.iter().map(|&s| tube_label(s, &vertex_labels)).collect();
+    let mut covers = Vec::new();
+    for (j, &b) in subs.iter().enumerate() {
+        for v in b.iter() {
+            if let Some(&i) = idx.get(&b.without(v)) {
+                covers.push((i, j));
+            }
+        }
+    }
+    poset_from_covers(labels, covers)
+}
+
+/// The face lattice of a nestohedron: nested sets of `tubes` (the
+/// building set minus its maximal members) under reverse inclusion,
+/// labelled like tubings.
+fn nested_set_lattice(
+    vertex_labels: &[&str],
+    tubes: &[BitSet],
+    separation: Separation,
+) -> Result<Poset, String> {
+    let cap = limits().max_faces;
+    let mut tubings: Vec<Vec<usize>> = Vec::new();
+    for_each_tubing(tubes, &separation, &mut |t| {
+        if tubings.len() == cap {
+            return Err(format!("More than {} tubings — aborting.", cap));
+        }
+        tubings.push(t.to_vec());
+        Ok(())
+    })?;
+
+    let tubing_label = |t: &[usize]| -> String {
+        if t.is_empty() {
+            return "*".to_string();
+        }
+        let mut sorted = t.to_vec();
+        sorted.sort_by_key(|&i| (tubes[i].len(), tubes[i]));
+        sorted
+            .iter()
+            .map(|&i| tube_label(tubes[i], vertex_labels))
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+
+    let mut out = Poset::default();
+    let index: HashMap<&[usize], usize> =
+        tubings.iter().enumerate().map(|(i, t)| (t.as_slice(), i)).collect();
+    for t in &tubings {
+        out.labels.push(tubing_label(t));
+    }
+    // face(T) is covered by face(T \ {t}): removing a tube goes one dim up
+    for (i, t) in tubings.iter().enumerate() {
+        for k in 0..t.len() {
+            let mut sup = t.clone();
+            sup.remove(k);
+            out.edges.push((i, index[sup.as_slice()]));
+        }
+    }
+    out.with_ranks()
+}
+
+/// The face lattice of the graph associahedron of the drawn graph.
+/// Path -> associahedron, complete -> permutahedron, cycle -> cyclohedron,
+/// star -> stellahedron.
+pub fn graph_associahedron(g: &Poset) -> Result<Poset, String> {
+    let lim = limits();
+    let (_, adj, full) = checked_graph(g, lim.associahedron_vertices)?;
+    let mut tubes = connected_subsets(&adj, lim.max_faces)?;
+    tubes.retain(|&t| t != full);
+    let vertex_labels: Vec<&str> = g.labels.iter().map(String::as_str).collect();
+    nested_set_lattice(&vertex_labels, &tubes, Separation::Graph(&adj))
+}
+
+// -- nestohedra: arbitrary building sets (Postnikov, Feichtner–Sturmfels) ---
+
+/// A list of subsets: JSON (`[[1,2],[2,3]]`) or text like `gen_grid`'s
+/// spec — sets separated by spaces or `;`, elements as single digits
+/// (`12 23`) or comma-separated (`1,2;2,10`). Returns the ground set (the
+/// elements that occur, sorted) and the subsets over it.
+fn parse_set_list(spec: &str) -> Result<(Vec<usize>, Vec<BitSet>), String> {
+    let spec = spec.trim();
+    let sets: Vec<Vec<usize>> = if spec.starts_with('[') {
+        serde_json::from_str(spec).map_err(|e| format!("bad set list: {}", e))?
+    } else {
+        spec.split(|c: char| c.is_whitespace() || c == ';')
+            .filter(|t| !t.is_empty())
+            .map(|t| -> Result<Vec<usize>, String> {
+                let bad = || format!("bad set '{}'", t);
+                if t.contains(',') {
+                    t.split(',').map(|x| x.trim().parse().map_err(|_| bad())).collect()
+                } else {
+                    t.chars().map(|c| c.to_digit(10).map(|d| d as usize).ok_or_else(bad)).collect()
+                }
+            })
+            .collect::<Result<_, _>>()?
+    };
+    let mut ground: Vec<usize> = sets.iter().flatten().copied().collect();
+    ground.sort_unstable();
+    ground.dedup();
+    if ground.is_empty() {
+        return Err("Enter at least one nonempty set.".to_string());
+    }
+    let max_n = limits().associahedron_vertices.min(bitset::CAPACITY);
+    if ground.len() > max_n {
+        return Err(format!("Ground set too large ({} > {} elements).", ground.len(), max_n));
+    }
+    let pos: HashMap<usize, usize> = ground.iter().enumerate().map(|(i, &x)| (x, i)).collect();
+    let sets = sets
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.iter().map(|x| pos[x]).collect())
+        .collect();
+    Ok((ground, sets))
+}
+
+/// The building set generated by `sets`: singletons added, then closed
+/// under unions of intersecting members. Sorted.
+fn building_closure(n: usize, sets: &[BitSet]) -> Result<Vec<BitSet>, String> {
+    let cap = limits().max_faces;
+    let mut members: HashSet<BitSet> = sets.iter().copied().collect();
+    members.extend((0..n).map(BitSet::singleton));
+    let mut queue: Vec<BitSet> = members.iter().copied().collect();
+    while let Some(a) = queue.pop() {
+        let grown: Vec<BitSet> = members
+            .iter()
+            .filter(|&&b| !a.is_disjoint(b) && !a.is_subset(b) && !b.is_subset(a))
+            .map(|&b| a | b)
+            .collect();
+        for u in grown {
+            if members.insert(u) {
+                queue.push(u);
+            }
+        }
+        if members.len() > cap {
+            return Err(format!("Building set has more than {} members — aborting.", cap));
+        }
+    }
+    let mut out: Vec<BitSet> = members.into_iter().collect();
+    out.sort_unstable();
+    Ok(out)
+}
+
+/// Nestohedron face lattice of a (sorted) building set.
+fn building_set_lattice(vertex_labels: &[&str], building: &[BitSet]) -> Result<Poset, String> {
+    // the maximal members are pairwise disjoint; take them largest first
+    let mut by_size = building.to_vec();
+    by_size.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let mut maximal: Vec<BitSet> = Vec::new();
+    for b in by_size {
+        if maximal.iter().all(|&m| !b.is_subset(m)) {
+            maximal.push(b);
+        }
+    }
+    let tubes: Vec<BitSet> =
+        building.iter().copied().filter(|b| !maximal.contains(b)).collect();
+    let set: HashSet<BitSet> = building.iter().copied().collect();
+    nested_set_lattice(vertex_labels, &tubes, Separation::Building(&set))
+}
+
+/// Face lattice of the nestohedron of a building set given as a list of
+/// subsets (see `parse_set_list`). Singletons may be omitted; otherwise
+/// the list must already be closed under unions of intersecting sets.
+/// All intervals give the associahedron, all subsets the permutahedron.
+pub fn nestohedron(spec: &str) -> Result<Poset, String> {
+    let (ground, sets) = parse_set_list(spec)?;
+    let building = building_closure(ground.len(), &sets)?;
+    let given: HashSet<BitSet> = sets.iter().copied().collect();
+    let labels: Vec<String> = ground.iter().map(usize::to_string).collect();
+    let vertex_labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    if let Some(&u) = building.iter().find(|&&b| b.len() > 1 && !given.contains(&b)) {
+        return Err(format!(
+            "Not a building set: {{{}}} is a union of intersecting sets but missing \
+             (use the hypergraph closure to add it).",
+            tube_label(u, &vertex_labels)
+        ));
+    }
+    building_set_lattice(&vertex_labels, &building)
+}
+
+/// Face lattice of the hypergraphic nestohedron: the building set is
+/// generated by the hyperedges (for a graph's edges this is the graph
+/// associahedron). A single hyperedge on all vertices gives the simplex.
+pub fn hypergraph_nestohedron(spec: &str) -> Result<Poset, String> {
+    let (ground, sets) = parse_set_list(spec)?;
+    let building = building_closure(ground.len(), &sets)?;
+    let labels: Vec<String> = ground.iter().map(usize::to_string).collect();
+    let vertex_labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    building_set_lattice(&vertex_labels, &building)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tubes_of_a_path() {
+        let adj = adjacency_sets(&graph("path", 3).unwrap());
+        // connected subsets: {0},{1},{2},{01},{12},{012} — not {02}
+        let tubes = connected_subsets(&adj, usize::MAX).unwrap();
+        assert_eq!(tubes.len(), 6);
+        assert!(!tubes.contains(&[0, 2].into_iter().collect()));
+        assert!(!set_connected([0, 2].into_iter().collect(), &adj));
+    }
+
+    #[test]
+    fn tube_compatibility() {
+        let adj = adjacency_sets(&graph("path", 3).unwrap()); // 0 - 1 - 2
+        let set = |vs: &[usize]| vs.iter().copied().collect::<BitSet>();
+        let tubes = [set(&[0]), set(&[1]), set(&[2]), set(&[0, 1]), set(&[1, 2])];
+        let mut pairs = Vec::new();
+        for_each_tubing(&tubes, &Separation::Graph(&adj), &mut |t| {
+            if let [a, b] = t {
+                pairs.push((*a, *b));
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert!(pairs.contains(&(0, 3))); // nested
+        assert!(pairs.contains(&(0, 2))); // disjoint, non-adjacent
+        assert!(!pairs.contains(&(0, 1))); // disjoint but adjacent
+        assert!(!pairs.contains(&(3, 4))); // properly overlapping
+    }
 }
@@ -4,10 +4,11 @@
 //! * [`PosetGraph`] — the editable model (nodes + relations). In poset mode
 //!   the relations are cover relations `(lower, upper)`; in graph mode they
 //!   are undirected edges of a plain graph.
-//! * Generators — products of chains, digit-label relation inference, the
-//!   distributive lattice J(P), face lattices of cubes and simplices, and
-//!   graph associahedra: the poset of tubes under inclusion and the full
-//!   face lattice of tubings (Carr–Devadoss nested set complex).
+//! * Generators — every example, family and conversion (grids, J(P),
+//!   face lattices, graph associahedra and nestohedra, the `lattices/`
+//!   families and classic lattices) comes from [`crate::generators`];
+//!   [`PosetGraph`] only converts to and from its [`Poset`] and lays the
+//!   result out.
 //! * A background worker thread streaming strips over a bounded channel, so
 //!   the UI never blocks and "next strip" advances a lazy enumeration.
 //! * The egui app: pan/zoom canvas, node dragging & renaming, click-click
@@ -17,13 +18,18 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use eframe::egui::{self, Align2, Color32, FontId, Pos2, Rect, Sense, Stroke, Vec2};
 
-use crate::lattice::{Face, FaceId, Lattice};
+use crate::dot;
+use crate::lattice::{BridgeGraph, Face, FaceId, Lattice};
+use crate::layout;
 use crate::plotting;
-use crate::rhombic::{self, Strip};
+use crate::progress::{Progress, Report};
+use crate::rhombic::Strip;
+use crate::generators::{self, Poset};
+use crate::tikz::{self, Picture, Side, TikzOptions};
 
 // ===========================================================================
 // Model: an editable poset / graph diagram
@@ -84,107 +90,90 @@ impl PosetGraph {
         }
     }
 
-    /// Longest-path rank of every node (minimal elements have rank 0).
-    /// Errors if the relation is cyclic.
-    fn ranks(&self) -> Result<HashMap<NodeId, usize>, String> {
+    /// The shared [`Poset`] of the diagram, in node order.
+    fn to_poset(&self) -> Poset {
         let idx: HashMap<NodeId, usize> =
             self.nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
-        let n = self.nodes.len();
-        let mut succ: Vec<Vec<usize>> = vec![vec![]; n];
-        let mut indeg = vec![0usize; n];
-        for &(a, b) in &self.edges {
-            let (Some(&ia), Some(&ib)) = (idx.get(&a), idx.get(&b)) else { continue };
-            succ[ia].push(ib);
-            indeg[ib] += 1;
+        Poset {
+            labels: self.nodes.iter().map(|n| n.label.clone()).collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter_map(|(a, b)| Some((*idx.get(a)?, *idx.get(b)?)))
+                .collect(),
+            ranks: None,
         }
+    }
 
-        let mut rank = vec![0usize; n];
-        let mut queue: Vec<usize> = (0..n).filter(|&i| indeg[i] == 0).collect();
-        let mut processed = 0;
-        while let Some(i) = queue.pop() {
-            processed += 1;
-            for &j in &succ[i] {
-                rank[j] = rank[j].max(rank[i] + 1);
-                indeg[j] -= 1;
-                if indeg[j] == 0 {
-                    queue.push(j);
-                }
-            }
+    /// Rebuild the diagram from a [`Poset`] (node order kept).
+    fn from_poset(p: &Poset) -> Self {
+        let mut g = PosetGraph::default();
+        for label in &p.labels {
+            g.add_node(label.clone(), Pos2::ZERO);
         }
-        if processed != n {
-            return Err("Relation contains a cycle — not a poset.".to_string());
+        for &(a, b) in &p.edges {
+            g.edges.push((g.nodes[a].id, g.nodes[b].id));
         }
-        Ok(self.nodes.iter().enumerate().map(|(i, node)| (node.id, rank[i])).collect())
+        g
+    }
+
+    /// Longest-path rank of every node (minimal elements have rank 0).
+    /// Errors if the relation is cyclic.
+    #[cfg(test)]
+    fn ranks(&self) -> Result<HashMap<NodeId, usize>, String> {
+        let rank = self.to_poset().ranks()?;
+        Ok(self.nodes.iter().zip(rank).map(|(node, r)| (node.id, r)).collect())
     }
 
     /// Convert to faces for `Lattice::from_faces`. Returns the faces and the
     /// mapping `FaceId -> NodeId` (faces are in node order).
     fn to_faces(&self) -> Result<(Vec<Face>, Vec<NodeId>), String> {
-        let ranks = self.ranks()?;
-        let idx: HashMap<NodeId, usize> =
-            self.nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
-
-        let mut upsets: Vec<Vec<FaceId>> = vec![vec![]; self.nodes.len()];
-        let mut downsets: Vec<Vec<FaceId>> = vec![vec![]; self.nodes.len()];
-        for &(a, b) in &self.edges {
-            let (Some(&ia), Some(&ib)) = (idx.get(&a), idx.get(&b)) else { continue };
-            upsets[ia].push(ib);
-            downsets[ib].push(ia);
-        }
-
-        let faces = self
-            .nodes
-            .iter()
-            .enumerate()
-            .map(|(i, node)| {
-                Face::new(
-                    node.label.clone(),
-                    ranks[&node.id],
-                    std::mem::take(&mut upsets[i]),
-                    std::mem::take(&mut downsets[i]),
-                )
-            })
-            .collect();
-        let id_map = self.nodes.iter().map(|n| n.id).collect();
-        Ok((faces, id_map))
+        let faces = self.to_poset().to_faces()?;
+        Ok((faces, self.nodes.iter().map(|n| n.id).collect()))
     }
 
     /// Rebuild the diagram from a lattice (used for file loading).
     fn from_lattice(l: &Lattice) -> Self {
-        let mut g = PosetGraph::default();
-        for (_, face) in l.faces() {
-            g.add_node(face.label().to_string(), Pos2::ZERO);
-        }
-        for (id, face) in l.faces() {
-            for &d in face.downset() {
-                g.edges.push((g.nodes[d].id, g.nodes[id].id));
-            }
-        }
+        let mut g = Self::from_poset(&Poset::from_lattice(l));
         g.layout_by_rank();
         g
     }
 
+    /// A graph from [`generators::graph`], laid out for the graph editor.
+    fn graph(kind: &str, n: usize) -> Result<Self, String> {
+        let mut g = Self::from_poset(&generators::graph(kind, n)?);
+        if kind == "path" {
+            for (i, node) in g.nodes.iter_mut().enumerate() {
+                node.pos = egui::pos2(i as f32 * 80.0 - (n as f32 - 1.0) * 40.0, 0.0);
+            }
+        } else {
+            g.layout_circle();
+        }
+        if let (Some(center), "star") = (g.nodes.first_mut(), kind) {
+            center.pos = Pos2::ZERO; // star K_{1,n-1}: vertex 0 in the center
+        }
+        Ok(g)
+    }
+
+    /// Add the cover relations [`Poset::infer_digit_relations`] finds.
+    fn infer_digit_relations(&mut self) -> usize {
+        let mut p = self.to_poset();
+        let before = p.edges.len();
+        let added = p.infer_digit_relations();
+        let ids: Vec<NodeId> = self.nodes.iter().map(|n| n.id).collect();
+        self.edges.extend(p.edges[before..].iter().map(|&(a, b)| (ids[a], ids[b])));
+        added
+    }
+
     // -- layouts ---------------------------------------------------------------
 
-    /// Hasse-diagram layout: rows by rank, centered.
+    /// Hasse-diagram layout: rows by rank, ordered to avoid crossings
+    /// (layout::hasse).
     fn layout_by_rank(&mut self) {
-        let Ok(ranks) = self.ranks() else { return };
-        let mut rows: HashMap<usize, Vec<NodeId>> = HashMap::new();
-        for node in &self.nodes {
-            rows.entry(ranks[&node.id]).or_default().push(node.id);
-        }
+        let Ok(positions) = layout::hasse(&self.to_poset()) else { return };
         let (x_step, y_step) = (90.0, 90.0);
-        for (rank, ids) in rows {
-            let row_width = (ids.len() as f32 - 1.0) * x_step;
-            for (k, id) in ids.into_iter().enumerate() {
-                let pos = egui::pos2(
-                    -row_width / 2.0 + k as f32 * x_step,
-                    -(rank as f32) * y_step,
-                );
-                if let Some(i) = self.index_of(id) {
-                    self.nodes[i].pos = pos;
-                }
-            }
+        for (node, (x, y)) in self.nodes.iter_mut().zip(positions) {
+            node.pos = egui::pos2(x as f32 * x_step, -(y as f32) * y_step);
         }
     }
 
@@ -201,511 +190,14 @@ impl PosetGraph {
         }
     }
 
-    // -- poset generators --------------------------------------------------------
-
-    /// Product of chains. `"211"` gives C3 x C2 x C2 (per-digit); inputs with
-    /// separators like `"12,3"` allow multi-digit chain lengths.
-    fn grid(input: &str) -> Option<Self> {
-        let dims: Vec<u32> = if input.chars().all(|c| c.is_ascii_digit()) {
-            input.chars().filter_map(|c| c.to_digit(10)).collect()
-        } else {
-            input
-                .split(|c: char| !c.is_ascii_digit())
-                .filter(|t| !t.is_empty())
-                .filter_map(|t| t.parse().ok())
-                .collect()
-        };
-        if dims.is_empty() {
-            return None;
-        }
-
-        // all points of the box [0, d_0] x ... x [0, d_k]
-        let mut points: Vec<Vec<u32>> = vec![vec![]];
-        for &d in &dims {
-            points = points
-                .into_iter()
-                .flat_map(|p| {
-                    (0..=d).map(move |i| {
-                        let mut q = p.clone();
-                        q.push(i);
-                        q
-                    })
-                })
-                .collect();
-        }
-
-        let sep = if dims.iter().all(|&d| d <= 9) { "" } else { "," };
-        let mut g = PosetGraph::default();
-        for p in &points {
-            let label = p.iter().map(u32::to_string).collect::<Vec<_>>().join(sep);
-            g.add_node(label, Pos2::ZERO);
-        }
-        // covers: differ by +1 in exactly one coordinate
-        for (i, p) in points.iter().enumerate() {
-            for (j, q) in points.iter().enumerate() {
-                let mut up_by_one = None;
-                let mut ok = true;
-                for k in 0..p.len() {
-                    if p[k] != q[k] {
-                        if up_by_one.is_none() && q[k] == p[k] + 1 {
-                            up_by_one = Some(k);
-                        } else {
-                            ok = false;
-                            break;
-                        }
-                    }
-                }
-                if ok && up_by_one.is_some() {
-                    g.edges.push((g.nodes[i].id, g.nodes[j].id));
-                }
-            }
-        }
-        g.layout_by_rank();
-        Some(g)
-    }
-
-    /// Face lattice of the d-cube (without the empty face): all words over
-    /// {0, 1, *}, covers replace one fixed coordinate by *.
-    fn cube_lattice(d: usize) -> Result<Self, String> {
-        if !(1..=5).contains(&d) {
-            return Err("Cube dimension must be between 1 and 5.".to_string());
-        }
-        let mut faces: Vec<String> = (0..3usize.pow(d as u32))
-            .map(|mut k| {
-                (0..d)
-                    .map(|_| {
-                        let c = ['0', '1', '*'][k % 3];
-                        k /= 3;
-                        c
-                    })
-                    .collect()
-            })
-            .collect();
-        faces.sort_by_key(|f: &String| (f.matches('*').count(), f.clone()));
-        let idx: HashMap<String, usize> =
-            faces.iter().enumerate().map(|(i, f)| (f.clone(), i)).collect();
-
-        let mut g = PosetGraph::default();
-        for f in &faces {
-            g.add_node(f.clone(), Pos2::ZERO);
-        }
-        for (i, f) in faces.iter().enumerate() {
-            for (k, c) in f.chars().enumerate() {
-                if c != '*' {
-                    let mut upper: Vec<char> = f.chars().collect();
-                    upper[k] = '*';
-                    let j = idx[&upper.into_iter().collect::<String>()];
-                    g.edges.push((g.nodes[i].id, g.nodes[j].id));
-                }
-            }
-        }
-        g.layout_by_rank();
-        Ok(g)
-    }
-
-    /// Face lattice of the d-simplex (without the empty face): nonempty
-    /// subsets of {0, ..., d}, covers add one element.
-    fn simplex_lattice(d: usize) -> Result<Self, String> {
-        if !(1..=6).contains(&d) {
-            return Err("Simplex dimension must be between 1 and 6.".to_string());
-        }
-        let n = d + 1;
-        let mut masks: Vec<u64> = (1..(1u64 << n)).collect();
-        masks.sort_by_key(|m| (m.count_ones(), *m));
-        let idx: HashMap<u64, usize> =
-            masks.iter().enumerate().map(|(i, &m)| (m, i)).collect();
-
-        let label = |m: u64| -> String {
-            (0..n).filter(|&v| (m >> v) & 1 == 1).map(|v| v.to_string()).collect()
-        };
-        let mut g = PosetGraph::default();
-        for &m in &masks {
-            g.add_node(label(m), Pos2::ZERO);
-        }
-        for (i, &m) in masks.iter().enumerate() {
-            for v in 0..n {
-                if (m >> v) & 1 == 0 {
-                    let j = idx[&(m | (1 << v))];
-                    g.edges.push((g.nodes[i].id, g.nodes[j].id));
-                }
-            }
-        }
-        g.layout_by_rank();
-        Ok(g)
-    }
-
-    /// Infer cover relations from all-digit labels: same length, digit sums
-    /// differing by one, and exactly one differing position.
-    fn infer_digit_relations(&mut self) -> usize {
-        let digit_sum = |s: &str| -> Option<i64> {
-            s.chars()
-                .map(|c| c.to_digit(10).map(|d| d as i64))
-                .sum::<Option<i64>>()
-        };
-        let mut added = 0;
-        for i in 0..self.nodes.len() {
-            for j in 0..self.nodes.len() {
-                if i == j {
-                    continue;
-                }
-                let (a, b) = (&self.nodes[i], &self.nodes[j]);
-                if a.label.len() != b.label.len() {
-                    continue;
-                }
-                let (Some(sa), Some(sb)) = (digit_sum(&a.label), digit_sum(&b.label)) else {
-                    continue;
-                };
-                if sb != sa + 1 {
-                    continue;
-                }
-                let diff = a
-                    .label
-                    .chars()
-                    .zip(b.label.chars())
-                    .filter(|(x, y)| x != y)
-                    .count();
-                if diff == 1 && !self.edges.contains(&(a.id, b.id)) {
-                    self.edges.push((a.id, b.id));
-                    added += 1;
-                }
-            }
-        }
-        added
-    }
-
-    /// The distributive lattice J(P) of order ideals of the current poset.
-    fn distributive(&self) -> Result<Self, String> {
-        let n = self.nodes.len();
-        if n > 20 {
-            return Err(format!("Poset too large ({} > 20 elements) for J(P).", n));
-        }
-        let idx: HashMap<NodeId, usize> =
-            self.nodes.iter().enumerate().map(|(i, node)| (node.id, i)).collect();
-        let mut down_mask = vec![0u64; n]; // direct lower covers as bitmask
-        for &(a, b) in &self.edges {
-            let (Some(&ia), Some(&ib)) = (idx.get(&a), idx.get(&b)) else { continue };
-            down_mask[ib] |= 1 << ia;
-        }
-
-        // enumerate ideals: subsets closed under going down (covers suffice)
-        let mut ideals: Vec<u64> = Vec::new();
-        for mask in 0u64..(1 << n) {
-            let ok = (0..n).all(|i| (mask >> i) & 1 == 0 || (mask & down_mask[i]) == down_mask[i]);
-            if ok {
-                ideals.push(mask);
-            }
-        }
-
-        let mut g = PosetGraph::default();
-        for &mask in &ideals {
-            let mut labels: Vec<&str> = (0..n)
-                .filter(|&i| (mask >> i) & 1 == 1)
-                .map(|i| self.nodes[i].label.as_str())
-                .collect();
-            labels.sort_unstable();
-            let label = if labels.is_empty() {
-                "0".to_string()
-            } else {
-                format!("{{{}}}", labels.join(","))
-            };
-            g.add_node(label, Pos2::ZERO);
-        }
-        for (i, &ma) in ideals.iter().enumerate() {
-            for (j, &mb) in ideals.iter().enumerate() {
-                if mb.count_ones() == ma.count_ones() + 1 && (ma & mb) == ma {
-                    g.edges.push((g.nodes[i].id, g.nodes[j].id));
-                }
-            }
-        }
-        g.layout_by_rank();
-        Ok(g)
-    }
-
-    // -- graph generators (graph mode) ------------------------------------------
-
-    fn graph_path(n: usize) -> Self {
-        let mut g = Self::graph_with_vertices(n);
-        for i in 0..n.saturating_sub(1) {
-            g.edges.push((g.nodes[i].id, g.nodes[i + 1].id));
-        }
-        for (i, node) in g.nodes.iter_mut().enumerate() {
-            node.pos = egui::pos2(i as f32 * 80.0 - (n as f32 - 1.0) * 40.0, 0.0);
-        }
-        g
-    }
-
-    fn graph_cycle(n: usize) -> Self {
-        let mut g = Self::graph_with_vertices(n);
-        for i in 0..n {
-            g.edges.push((g.nodes[i].id, g.nodes[(i + 1) % n].id));
-        }
-        g.layout_circle();
-        g
-    }
-
-    fn graph_complete(n: usize) -> Self {
-        let mut g = Self::graph_with_vertices(n);
-        for i in 0..n {
-            for j in (i + 1)..n {
-                g.edges.push((g.nodes[i].id, g.nodes[j].id));
-            }
-        }
-        g.layout_circle();
-        g
-    }
-
-    /// Star K_{1,n-1}: vertex 0 in the center.
-    fn graph_star(n: usize) -> Self {
-        let mut g = Self::graph_with_vertices(n);
-        for i in 1..n {
-            g.edges.push((g.nodes[0].id, g.nodes[i].id));
-        }
-        g.layout_circle();
-        if let Some(center) = g.nodes.first_mut() {
-            center.pos = Pos2::ZERO;
-        }
-        g
-    }
-
-    fn graph_with_vertices(n: usize) -> Self {
-        let mut g = PosetGraph::default();
-        for i in 0..n {
-            g.add_node(i.to_string(), Pos2::ZERO);
-        }
-        g
-    }
-
     // -- persistence -----------------------------------------------------------
 
     /// Serialize in the lattice file format (`dim: label: {upset}, {downset}`).
     fn to_lattice_file(&self) -> Result<String, String> {
-        let (faces, _) = self.to_faces()?;
-        let fmt_set = |s: &[FaceId]| {
-            s.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")
-        };
-        let mut out = String::new();
-        for face in &faces {
-            let label = face.label().replace(": ", "-"); // ": " is the field separator
-            out.push_str(&format!(
-                "{}: {}: {{{}}}, {{{}}}\n",
-                face.dim(),
-                label,
-                fmt_set(face.upset()),
-                fmt_set(face.downset())
-            ));
-        }
-        Ok(out)
+        self.to_poset().to_lattice_file()
     }
 }
 
-// ===========================================================================
-// Graph associahedra: tubes and tubings (Carr–Devadoss)
-// ===========================================================================
-
-type Mask = u64;
-
-/// Adjacency of a drawn graph as one neighbour bitmask per vertex
-/// (vertices in node order).
-fn adjacency_masks(g: &PosetGraph) -> Vec<Mask> {
-    let idx: HashMap<NodeId, usize> =
-        g.nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
-    let mut adj = vec![0u64; g.nodes.len()];
-    for &(a, b) in &g.edges {
-        let (Some(&ia), Some(&ib)) = (idx.get(&a), idx.get(&b)) else { continue };
-        if ia != ib {
-            adj[ia] |= 1 << ib;
-            adj[ib] |= 1 << ia;
-        }
-    }
-    adj
-}
-
-/// Does `mask` induce a connected subgraph?
-fn mask_connected(mask: Mask, adj: &[Mask]) -> bool {
-    if mask == 0 {
-        return false;
-    }
-    let mut seen = 1u64 << mask.trailing_zeros();
-    loop {
-        let mut grow = seen;
-        let mut m = seen;
-        while m != 0 {
-            let v = m.trailing_zeros() as usize;
-            grow |= adj[v] & mask;
-            m &= m - 1;
-        }
-        if grow == seen {
-            break;
-        }
-        seen = grow;
-    }
-    seen == mask
-}
-
-/// Vertices outside `mask` adjacent to it.
-fn mask_neighbors(mask: Mask, adj: &[Mask]) -> Mask {
-    let mut nb = 0;
-    let mut m = mask;
-    while m != 0 {
-        let v = m.trailing_zeros() as usize;
-        nb |= adj[v];
-        m &= m - 1;
-    }
-    nb & !mask
-}
-
-/// Two tubes are compatible iff they are nested, or disjoint and non-adjacent.
-fn tubes_compatible(a: Mask, b: Mask, adj: &[Mask]) -> bool {
-    if a & b != 0 {
-        let u = a | b;
-        u == a || u == b
-    } else {
-        mask_neighbors(a, adj) & b == 0
-    }
-}
-
-/// All tubings (sets of pairwise compatible tubes, including the empty one),
-/// as sorted index vectors into `tubes`. Errors above `cap` tubings.
-fn enumerate_tubings(
-    tubes: &[Mask],
-    adj: &[Mask],
-    cap: usize,
-) -> Result<Vec<Vec<usize>>, String> {
-    let m = tubes.len();
-    let words = m.div_ceil(64);
-    let mut compat = vec![vec![0u64; words]; m];
-    for i in 0..m {
-        for j in (i + 1)..m {
-            if tubes_compatible(tubes[i], tubes[j], adj) {
-                compat[i][j / 64] |= 1 << (j % 64);
-                compat[j][i / 64] |= 1 << (i % 64);
-            }
-        }
-    }
-    let is_compat = |i: usize, j: usize| compat[i][j / 64] >> (j % 64) & 1 == 1;
-
-    fn rec(
-        start: usize,
-        current: &mut Vec<usize>,
-        out: &mut Vec<Vec<usize>>,
-        m: usize,
-        cap: usize,
-        is_compat: &dyn Fn(usize, usize) -> bool,
-    ) -> Result<(), String> {
-        out.push(current.clone());
-        if out.len() > cap {
-            return Err(format!("More than {} tubings — aborting.", cap));
-        }
-        for j in start..m {
-            if current.iter().all(|&i| is_compat(i, j)) {
-                current.push(j);
-                rec(j + 1, current, out, m, cap, is_compat)?;
-                current.pop();
-            }
-        }
-        Ok(())
-    }
-
-    let mut out = Vec::new();
-    rec(0, &mut Vec::new(), &mut out, m, cap, &is_compat)?;
-    Ok(out)
-}
-
-/// Label of a tube: the labels of its vertices, concatenated (or
-/// comma-separated when labels are longer than one character).
-fn tube_label(mask: Mask, vertex_labels: &[&str]) -> String {
-    let single = vertex_labels.iter().all(|l| l.chars().count() == 1);
-    let parts: Vec<&str> = (0..vertex_labels.len())
-        .filter(|&v| (mask >> v) & 1 == 1)
-        .map(|v| vertex_labels[v])
-        .collect();
-    parts.join(if single { "" } else { "," })
-}
-
-fn checked_graph(g: &PosetGraph, max_n: usize) -> Result<(usize, Vec<Mask>, Mask), String> {
-    let n = g.nodes.len();
-    if n < 2 {
-        return Err("Draw a graph with at least 2 vertices first.".to_string());
-    }
-    if n > max_n {
-        return Err(format!("Graph too large ({} > {} vertices).", n, max_n));
-    }
-    let adj = adjacency_masks(g);
-    let full: Mask = (1 << n) - 1;
-    if !mask_connected(full, &adj) {
-        return Err("Graph must be connected.".to_string());
-    }
-    Ok((n, adj, full))
-}
-
-/// The poset of tubes of the drawn graph under inclusion. Includes the
-/// singletons at the bottom and the full vertex set at the top; graded by
-/// cardinality, covers add one vertex.
-fn tube_poset(g: &PosetGraph) -> Result<PosetGraph, String> {
-    let (_, adj, full) = checked_graph(g, 10)?;
-    let mut subs: Vec<Mask> = (1..=full).filter(|&m| mask_connected(m, &adj)).collect();
-    subs.sort_by_key(|m| (m.count_ones(), *m));
-
-    let vertex_labels: Vec<&str> = g.nodes.iter().map(|n| n.label.as_str()).collect();
-    let mut out = PosetGraph::default();
-    for &m in &subs {
-        out.add_node(tube_label(m, &vertex_labels), Pos2::ZERO);
-    }
-    for (i, &a) in subs.iter().enumerate() {
-        for (j, &b) in subs.iter().enumerate() {
-            if b.count_ones() == a.count_ones() + 1 && a & b == a {
-                out.edges.push((out.nodes[i].id, out.nodes[j].id));
-            }
-        }
-    }
-    out.layout_by_rank();
-    Ok(out)
-}
-
-/// The face lattice of the graph associahedron of the drawn graph: faces are
-/// tubings ordered by reverse inclusion. Vertices of the polytope = maximal
-/// tubings; the empty tubing (label `*`) is the full polytope on top.
-/// Path -> associahedron, complete graph -> permutahedron, cycle ->
-/// cyclohedron, star -> stellahedron.
-fn graph_associahedron(g: &PosetGraph) -> Result<PosetGraph, String> {
-    let (_, adj, full) = checked_graph(g, 12)?;
-    // proper connected subsets
-    let tubes: Vec<Mask> = (1..full).filter(|&m| mask_connected(m, &adj)).collect();
-    let tubings = enumerate_tubings(&tubes, &adj, 20_000)?;
-
-    let vertex_labels: Vec<&str> = g.nodes.iter().map(|n| n.label.as_str()).collect();
-    let tubing_label = |t: &[usize]| -> String {
-        if t.is_empty() {
-            return "*".to_string();
-        }
-        let mut sorted = t.to_vec();
-        sorted.sort_by_key(|&i| (tubes[i].count_ones(), tubes[i]));
-        sorted
-            .iter()
-            .map(|&i| tube_label(tubes[i], &vertex_labels))
-            .collect::<Vec<_>>()
-            .join("|")
-    };
-
-    let mut out = PosetGraph::default();
-    let mut index: HashMap<Vec<usize>, usize> = HashMap::new();
-    for (i, t) in tubings.iter().enumerate() {
-        out.add_node(tubing_label(t), Pos2::ZERO);
-        index.insert(t.clone(), i); // tubings come as sorted index vectors
-    }
-    // face(T) is covered by face(T \ {t}): removing a tube goes one dim up
-    for (i, t) in tubings.iter().enumerate() {
-        for k in 0..t.len() {
-            let mut sup = t.clone();
-            sup.remove(k);
-            let j = index[&sup];
-            out.edges.push((out.nodes[i].id, out.nodes[j].id));
-        }
-    }
-    out.layout_by_rank();
-    Ok(out)
-}
-
 // ===========================================================================
 // Background worker: streams strips over a bounded channel
 // ===========================================================================
@@ -723,7 +215,7 @@ enum WorkerMsg {
         edges: Vec<(FaceId, FaceId)>,
         cyclic_edges: Vec<(FaceId, FaceId)>,
     },
-    Progress(usize),
+    Progress(Report),
     Done(usize),
 }
 
@@ -734,9 +226,15 @@ struct Job {
     /// FaceId (index into the worker's lattice) -> NodeId in the editor.
     id_map: Vec<NodeId>,
     started: Instant,
-    live_count: usize,
+    progress: Option<Report>,
 }
 
+/// How often a job reports its progress.
+const PROGRESS_EVERY: Duration = Duration::from_millis(250);
+
+/// Seeds the search is split into, for its progress (and the count's threads).
+const PROGRESS_SEEDS: usize = 64;
+
 impl Job {
     fn spawn(faces: Vec<Face>, id_map: Vec<NodeId>, cyclic: bool, kind: JobKind) -> Self {
         let (tx, rx) = mpsc::sync_channel::<WorkerMsg>(64); // backpressure
@@ -745,36 +243,128 @@ impl Job {
 
         std::thread::spawn(move || {
             let l = Lattice::from_faces(faces);
+            let seeds = l.ham_path_seeds(cyclic, PROGRESS_SEEDS);
+            let progress = Arc::new(Progress::new(&seeds));
+            let started = Instant::now();
+            if kind == JobKind::Count {
+                // in parallel, the seeds spread over rayon's threads
+                let n = std::thread::scope(|s| {
+                    let counting = s.spawn(|| {
+                        progress.count_strips(&l, cyclic, seeds, &|| cancelled.load(Ordering::Relaxed))
+                    });
+                    let mut ticked = Instant::now();
+                    while !counting.is_finished() {
+                        std::thread::sleep(Duration::from_millis(20));
+                        if ticked.elapsed() >= PROGRESS_EVERY {
+                            let _ = tx.try_send(WorkerMsg::Progress(progress.report(started.elapsed())));
+                            ticked = Instant::now();
+                        }
+                    }
+                    counting.join().expect("the counting thread panicked")
+                });
+                if !cancelled.load(Ordering::Relaxed) {
+                    let _ = tx.send(WorkerMsg::Done(n as usize));
+                }
+                return;
+            }
             let mut n = 0usize;
-            for strip in rhombic::strips(&l, cyclic) {
+            let mut ticked = Instant::now();
+            let stop = cancelled.clone();
+            for strip in progress.strips(&l, cyclic, seeds, move || stop.load(Ordering::Relaxed)) {
                 if cancelled.load(Ordering::Relaxed) {
                     return;
                 }
                 n += 1;
-                match kind {
-                    JobKind::Count => {
-                        if n % 1024 == 0 {
-                            let _ = tx.try_send(WorkerMsg::Progress(n));
-                        }
-                    }
-                    JobKind::Exists | JobKind::Enumerate => {
-                        let (edges, cyclic_edges) = plotting::edges_strip(&strip, &l, cyclic);
-                        if tx
-                            .send(WorkerMsg::Strip { layers: strip, edges, cyclic_edges })
-                            .is_err()
-                        {
-                            return; // receiver dropped
-                        }
-                        if kind == JobKind::Exists {
-                            break;
-                        }
-                    }
+                if ticked.elapsed() >= PROGRESS_EVERY {
+                    let _ = tx.try_send(WorkerMsg::Progress(progress.report(started.elapsed())));
+                    ticked = Instant::now();
+                }
+                let (edges, cyclic_edges) = plotting::edges_strip(&strip, &l, cyclic);
+                if tx
+                    .send(WorkerMsg::Strip { layers: strip, edges, cyclic_edges })
+                    .is_err()
+                {
+                    return; // receiver dropped
+                }
+                if kind == JobKind::Exists {
+                    break;
                 }
             }
             let _ = tx.send(WorkerMsg::Done(n));
         });
 
-        Job { kind, rx, cancel, id_map, started: Instant::now(), live_count: 0 }
+        Job { kind, rx, cancel, id_map, started: Instant::now(), progress: None }
+    }
+
+    fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Hamiltonian paths the level survey counts per level, at most.
+const LEVEL_PATHS: usize = 100_000;
+
+/// One row of the level survey: a level's bridge graph and, once the
+/// worker got to it, its number of hamiltonian paths (cycles).
+struct LevelRow {
+    vertices: usize,
+    edges: usize,
+    degrees: (usize, usize), // min, max
+    components: usize,
+    paths: Option<usize>,
+}
+
+/// The bridge graph of every level, with the hamiltonian paths counted
+/// level by level on a worker thread.
+struct LevelSurvey {
+    cyclic: bool,
+    rows: Vec<LevelRow>,
+    rx: mpsc::Receiver<usize>, // counts, in level order
+    cancel: Arc<AtomicBool>,
+}
+
+impl LevelSurvey {
+    fn spawn(l: &Lattice, cyclic: bool) -> Self {
+        let graphs: Vec<BridgeGraph> = (0..l.num_levels()).map(|d| l.bridge_graph(d)).collect();
+        let rows = graphs
+            .iter()
+            .map(|g| {
+                let degrees = g.degree_sequence();
+                LevelRow {
+                    vertices: g.num_vertices(),
+                    edges: g.num_edges(),
+                    degrees: (degrees.last().copied().unwrap_or(0), degrees.first().copied().unwrap_or(0)),
+                    components: g.components().len(),
+                    paths: None,
+                }
+            })
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancelled = cancel.clone();
+        std::thread::spawn(move || {
+            for g in graphs {
+                let n = g
+                    .ham_paths(cyclic)
+                    .take_while(|_| !cancelled.load(Ordering::Relaxed))
+                    .take(LEVEL_PATHS)
+                    .count();
+                if cancelled.load(Ordering::Relaxed) || tx.send(n).is_err() {
+                    return;
+                }
+            }
+        });
+        LevelSurvey { cyclic, rows, rx, cancel }
+    }
+
+    /// Take the counts that arrived; true while some are still missing.
+    fn poll(&mut self) -> bool {
+        while let Ok(n) = self.rx.try_recv() {
+            if let Some(row) = self.rows.iter_mut().find(|r| r.paths.is_none()) {
+                row.paths = Some(n);
+            }
+        }
+        self.rows.iter().any(|r| r.paths.is_none())
     }
 
     fn cancel(&self) {
@@ -815,13 +405,21 @@ pub struct LatticeApp {
     undo_stack: Vec<PosetGraph>,
     label_input: String,
     grid_input: String,
+    building_input: String,
     file_path: String,
     example_n: usize,
+    example_m: usize,
     cyclic: bool,
     log: String,
+    tikz: TikzOptions,
+    /// `shape` / `fill` of the TikZ options as typed (`a/b/...`).
+    tikz_shapes: String,
+    tikz_fills: String,
+    dot_level: usize,
 
     // computation
     job: Option<Job>,
+    levels: Option<LevelSurvey>,
     strips: Vec<StripView>,
     strip_cursor: usize,
     total_strips: Option<usize>, // known once a job finished
@@ -842,12 +440,19 @@ impl LatticeApp {
             undo_stack: Vec::new(),
             label_input: String::new(),
             grid_input: String::new(),
+            building_input: "12 123 1234".to_string(),
             file_path: "lattice.txt".to_string(),
             example_n: 3,
+            example_m: 2,
             cyclic: false,
             log: "Welcome. Double-click the canvas to add nodes, click two nodes to relate them."
                 .to_string(),
+            tikz: TikzOptions::default(),
+            tikz_shapes: "rectangle".to_string(),
+            tikz_fills: "white".to_string(),
+            dot_level: 0,
             job: None,
+            levels: None,
             strips: Vec::new(),
             strip_cursor: 0,
             total_strips: None,
@@ -917,6 +522,19 @@ impl LatticeApp {
         self.fit_view();
     }
 
+    /// Load the output of a poset generator, laid out by rank.
+    fn load_generated(&mut self, name: &str, poset: Result<Poset, String>) {
+        match poset {
+            Ok(p) => {
+                let mut g = PosetGraph::from_poset(&p);
+                g.layout_by_rank();
+                let msg = format!("{}: {} elements.", name, g.nodes.len());
+                self.replace_graph(g, EditMode::Poset, msg);
+            }
+            Err(e) => self.log = e,
+        }
+    }
+
     // -- job handling ------------------------------------------------------------
 
     /// Any structural change invalidates running jobs and cached strips,
@@ -926,6 +544,9 @@ impl LatticeApp {
             job.cancel();
         }
         self.job = None;
+        if let Some(survey) = self.levels.take() {
+            survey.cancel();
+        }
         self.strips.clear();
         self.strip_cursor = 0;
         self.total_strips = None;
@@ -993,7 +614,7 @@ impl LatticeApp {
                         break;
                     }
                 }
-                Ok(WorkerMsg::Progress(n)) => job.live_count = n,
+                Ok(WorkerMsg::Progress(report)) => job.progress = Some(report),
                 Ok(WorkerMsg::Done(n)) => {
                     self.total_strips = Some(n);
                     self.log = match job.kind {
@@ -1031,31 +652,41 @@ impl LatticeApp {
         }
     }
 
-    /// Layered layout of the currently displayed strip.
+    /// Lay the diagram out as strip `strip_idx`: layer by layer, or in
+    /// rings for cyclic strips (plotting::strip_layout, as in the exports).
     fn arrange_as_strip(&mut self, strip_idx: usize) {
-        let Some(view) = self.strips.get(strip_idx) else { return };
-        let (x_step, y_step) = (90.0, 100.0);
-        let positions: Vec<(NodeId, Pos2)> = view
-            .layers
-            .iter()
-            .enumerate()
-            .flat_map(|(layer_idx, layer)| {
-                let count = layer.len() as f32;
-                layer.iter().enumerate().map(move |(i, &id)| {
-                    let x = (i as f32 - (count - 1.0) / 2.0) * x_step;
-                    let y = -(layer_idx as f32) * y_step;
-                    (id, egui::pos2(x, y))
-                })
-            })
-            .collect();
-        for (id, pos) in positions {
-            if let Some(i) = self.graph.index_of(id) {
-                self.graph.nodes[i].pos = pos;
+        let (strip, l, id_map) = match self.strip_on_lattice(strip_idx) {
+            Ok(s) => s,
+            Err(e) => {
+                self.log = e;
+                return;
+            }
+        };
+        let layout = plotting::strip_layout(&strip, &l, self.cyclic);
+        let (x_step, y_step) = if self.cyclic { (90.0, 90.0) } else { (90.0, 100.0) };
+        for (f, x, y) in layout.nodes {
+            if let Some(i) = self.graph.index_of(id_map[f]) {
+                self.graph.nodes[i].pos = egui::pos2(x as f32 * x_step, -(y as f32) * y_step);
             }
         }
         self.fit_view();
     }
 
+    /// Strip `strip_idx` over the lattice of the current graph, with the
+    /// node id of every face.
+    fn strip_on_lattice(&self, strip_idx: usize) -> Result<(Strip, Lattice, Vec<NodeId>), String> {
+        let view = self.strips.get(strip_idx).ok_or("No strip displayed.")?;
+        let (faces, id_map) = self.graph.to_faces()?;
+        let rev: HashMap<NodeId, FaceId> = id_map.iter().enumerate().map(|(f, &id)| (id, f)).collect();
+        let strip: Option<Strip> = view
+            .layers
+            .iter()
+            .map(|layer| layer.iter().map(|id| rev.get(id).copied()).collect::<Option<Vec<_>>>())
+            .collect();
+        let strip = strip.ok_or("Graph changed since the strip was computed.")?;
+        Ok((strip, Lattice::from_faces(faces), id_map))
+    }
+
     fn current_strip(&self) -> Option<&StripView> {
         self.viewing_strip
             .then(|| self.strips.get(self.strip_cursor))
@@ -1064,99 +695,192 @@ impl LatticeApp {
 
     /// Render the displayed strip through plotting::show_strip (pdflatex).
     fn render_strip_pdf(&mut self) {
-        let Some(view) = self.current_strip() else {
+        if self.current_strip().is_none() {
             self.log = "No strip displayed.".to_string();
             return;
-        };
-        match self.graph.to_faces() {
-            Ok((faces, id_map)) => {
-                let rev: HashMap<NodeId, FaceId> =
-                    id_map.iter().enumerate().map(|(f, &id)| (id, f)).collect();
-                let strip: Option<Strip> = view
-                    .layers
-                    .iter()
-                    .map(|layer| {
-                        layer.iter().map(|id| rev.get(id).copied()).collect::<Option<Vec<_>>>()
-                    })
-                    .collect();
-                match strip {
-                    Some(s) => {
-                        let l = Lattice::from_faces(faces);
-                        plotting::show_strip(&s, &l, self.cyclic);
-                        self.log = "Strip rendered (see strip_visualization*.pdf).".to_string();
-                    }
-                    None => self.log = "Graph changed since the strip was computed.".to_string(),
-                }
+        }
+        match self.strip_on_lattice(self.strip_cursor).and_then(|s| Ok((s, self.tikz_options()?))) {
+            Ok(((s, l, _), o)) => {
+                plotting::show_strip(&s, &l, self.cyclic, &o);
+                self.log = "Strip rendered (see strip_visualization*.pdf).".to_string();
             }
             Err(e) => self.log = e,
         }
     }
 
+    /// Save the displayed strip as strip_visualization[_cyclic].`ext`
+    /// (svg, png or dot), without pdflatex.
+    fn save_strip_image(&mut self, ext: &str) {
+        if self.current_strip().is_none() {
+            self.log = "No strip displayed.".to_string();
+            return;
+        }
+        let name = if self.cyclic { "strip_visualization_cyclic" } else { "strip_visualization" };
+        let path = format!("{}.{}", name, ext);
+        self.log = match self.strip_on_lattice(self.strip_cursor) {
+            Ok((s, l, _)) => match self.tikz_options().and_then(|o| plotting::render_strip(&s, &l, self.cyclic, &path, &o)) {
+                Ok(()) => format!("Strip saved to {}", path),
+                Err(e) => e,
+            },
+            Err(e) => e,
+        };
+    }
+
+    /// The construction of the displayed strip, bridges then gap faces, as
+    /// an animated SVG, PNG frames or a TikZ document.
+    fn save_strip_steps(&mut self, ext: &str) {
+        if self.current_strip().is_none() {
+            self.log = "No strip displayed.".to_string();
+            return;
+        }
+        let name = if self.cyclic { "strip_steps_cyclic" } else { "strip_steps" };
+        let path = format!("{}.{}", name, ext);
+        self.log = match self.strip_on_lattice(self.strip_cursor) {
+            Ok((s, l, _)) => match plotting::render_construction(&s, &l, self.cyclic, &path) {
+                Ok(files) if files.len() == 1 => format!("Construction saved to {}", files[0]),
+                Ok(files) => format!("{} frames saved to {} … {}", files.len(), files[0], files[files.len() - 1]),
+                Err(e) => e,
+            },
+            Err(e) => e,
+        };
+    }
+
     // -- TikZ export ---------------------------------------------------------------
 
-    fn export_tikz(&mut self) {
-        let strip = self.current_strip();
+    /// The TikZ options of the panel, shapes and fills included.
+    fn tikz_options(&self) -> Result<TikzOptions, String> {
+        let lists = TikzOptions::parse(&format!("shape={},fill={}", self.tikz_shapes, self.tikz_fills))?;
+        Ok(TikzOptions { shapes: lists.shapes, fills: lists.fills, ..self.tikz.clone() })
+    }
 
-        // which nodes/edges to export
-        let (node_ids, edges): (Vec<NodeId>, Vec<(NodeId, NodeId)>) = match strip {
-            Some(v) => (
-                v.layers.iter().flatten().copied().collect(),
-                v.edges.iter().chain(v.cyclic_edges.iter()).copied().collect(),
-            ),
-            None => (
-                self.graph.nodes.iter().map(|n| n.id).collect(),
-                self.graph.edges.clone(),
-            ),
+    /// TikZ of the displayed strip (in the strip layout, like every other
+    /// strip export), or of the diagram as it is on the canvas.
+    fn export_tikz(&mut self) {
+        let picture = if self.current_strip().is_some() {
+            self.strip_on_lattice(self.strip_cursor).map(|(s, l, _)| {
+                Picture::of_strip(&s, &plotting::strip_layout(&s, &l, self.cyclic), &l)
+            })
+        } else {
+            // canvas pixels to layout units, y up
+            let positions: Vec<(f64, f64)> =
+                self.graph.nodes.iter().map(|n| (n.pos.x as f64 / 90.0, -n.pos.y as f64 / 90.0)).collect();
+            Picture::of_poset(&self.graph.to_poset(), &positions)
+        };
+        let tex = picture.and_then(|p| tikz::tikz(&p, &self.tikz_options()?));
+        self.log = match tex.map(|t| std::fs::write("lattice_output.tex", t)) {
+            Ok(Ok(())) => "Exported to lattice_output.tex".to_string(),
+            Ok(Err(e)) => format!("Export failed: {}", e),
+            Err(e) => e,
         };
+    }
 
-        let safe = |s: &str| {
-            s.replace(['{', '}'], "")
-                .replace([',', '|'], "_")
-                .replace('*', "top")
-                .replace('?', "empty")
+    /// The bridge graph of `dot_level` as level_<d>.dot, with the layer of
+    /// the displayed strip drawn on it.
+    fn export_level_dot(&mut self) {
+        let d = self.dot_level;
+        let written = (|| {
+            let (strip, l) = if self.current_strip().is_some() {
+                let (s, l, _) = self.strip_on_lattice(self.strip_cursor)?;
+                (Some(s), l)
+            } else {
+                (None, Lattice::from_faces(self.graph.to_faces()?.0))
+            };
+            if d >= l.num_levels() {
+                return Err(format!("The lattice has levels 0 to {}.", l.num_levels().saturating_sub(1)));
+            }
+            let path = format!("level_{}.dot", d);
+            let layer = strip.as_ref().map(|s| s[d].as_slice());
+            std::fs::write(&path, dot::level_dot(&l, d, layer, self.cyclic))
+                .map_err(|e| format!("Export failed: {}", e))?;
+            Ok(path)
+        })();
+        self.log = match written {
+            Ok(path) => format!("Exported to {}", path),
+            Err(e) => e,
         };
+    }
 
-        let mut tex = String::new();
-        tex.push_str("\\documentclass[tikz, border=1cm]{standalone}\n\\begin{document}\n");
-        tex.push_str("\\begin{tikzpicture}[y=-1cm]\n\n% Coordinates\n");
-        let scale = 0.02;
-        for &id in &node_ids {
-            let Some(node) = self.graph.node(id) else { continue };
-            tex.push_str(&format!(
-                "\\coordinate ({}) at ({:.2}, {:.2});\n",
-                safe(&node.label),
-                node.pos.x * scale,
-                node.pos.y * scale
-            ));
+    /// Count the hamiltonian paths (cycles) of every level's bridge graph.
+    fn start_level_survey(&mut self) {
+        if let Some(survey) = self.levels.take() {
+            survey.cancel();
+        }
+        match self.graph.to_faces() {
+            Ok((faces, _)) => self.levels = Some(LevelSurvey::spawn(&Lattice::from_faces(faces), self.cyclic)),
+            Err(e) => self.log = e,
         }
+    }
 
-        tex.push_str("\n% Edges\n\\foreach \\a/\\b in {");
-        let edge_strs: Vec<String> = edges
-            .iter()
-            .filter_map(|&(a, b)| {
-                let (na, nb) = (self.graph.node(a)?, self.graph.node(b)?);
-                Some(format!("{}/{}", safe(&na.label), safe(&nb.label)))
-            })
-            .collect();
-        tex.push_str(&edge_strs.join(", "));
-        tex.push_str("} {\n    \\draw (\\a) -- (\\b);\n}\n\n% Nodes\n\\foreach \\v/\\l in {");
-        let label_strs: Vec<String> = node_ids
-            .iter()
-            .filter_map(|&id| {
-                let n = self.graph.node(id)?;
-                Some(format!("{}/{{{}}}", safe(&n.label), n.label.replace('|', "$|$")))
-            })
-            .collect();
-        tex.push_str(&label_strs.join(", "));
-        tex.push_str(
-            "} {\n    \\node[draw, circle, fill=white, inner sep=2pt] at (\\v) {\\footnotesize \\l};\n}\n",
-        );
-        tex.push_str("\\end{tikzpicture}\n\\end{document}\n");
-
-        self.log = match std::fs::write("lattice_output.tex", tex) {
-            Ok(_) => "Exported to lattice_output.tex".to_string(),
-            Err(e) => format!("Export failed: {}", e),
-        };
+    fn levels_panel(&mut self, ui: &mut egui::Ui) {
+        let counting = self.levels.as_mut().is_some_and(LevelSurvey::poll);
+        ui.horizontal(|ui| {
+            let what = if self.cyclic { "Count hamiltonian cycles" } else { "Count hamiltonian paths" };
+            if ui.button(what).on_hover_text(format!("At most {} per level", LEVEL_PATHS)).clicked() {
+                self.start_level_survey();
+            }
+            if counting {
+                ui.spinner();
+                if ui.button("Cancel").clicked() {
+                    if let Some(survey) = self.levels.take() {
+                        survey.cancel();
+                    }
+                }
+            }
+        });
+        let Some(survey) = &self.levels else { return };
+        egui::Grid::new("bridge_graphs").striped(true).show(ui, |ui| {
+            for heading in ["level", "faces", "edges", "degrees", "parts", if survey.cyclic { "cycles" } else { "paths" }] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for (d, row) in survey.rows.iter().enumerate() {
+                ui.label(d.to_string());
+                ui.label(row.vertices.to_string());
+                ui.label(row.edges.to_string());
+                ui.label(format!("{}..{}", row.degrees.0, row.degrees.1));
+                ui.label(row.components.to_string());
+                ui.label(match row.paths {
+                    Some(n) if n >= LEVEL_PATHS => format!("≥{}", n),
+                    Some(n) => n.to_string(),
+                    None => "…".to_string(),
+                });
+                ui.end_row();
+            }
+        });
+    }
+
+    fn tikz_panel(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.tikz.standalone, "Whole document (else a snippet)");
+        ui.horizontal(|ui| {
+            let mut fit = self.tikz.scale.is_none();
+            if ui.checkbox(&mut fit, "Fit to A4").changed() {
+                self.tikz.scale = if fit { None } else { Some(1.0) };
+            }
+            if let Some(scale) = &mut self.tikz.scale {
+                ui.add(egui::DragValue::new(scale).speed(0.05).range(0.1..=10.0).suffix(" cm"));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Shapes:");
+            ui.text_edit_singleline(&mut self.tikz_shapes)
+                .on_hover_text("By level, e.g. rectangle/circle (rectangle, rounded, circle, ellipse)");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Fills:");
+            ui.text_edit_singleline(&mut self.tikz_fills)
+                .on_hover_text("By level, e.g. white/blue!10/#E66100");
+        });
+        ui.checkbox(&mut self.tikz.colour_edges, "Colour bridge and gap edges");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.tikz.escape, "Escape labels");
+            ui.checkbox(&mut self.tikz.math, "Math mode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Highlight boundary:");
+            ui.radio_value(&mut self.tikz.boundary, None, "none");
+            ui.radio_value(&mut self.tikz.boundary, Some(Side::Left), "left");
+            ui.radio_value(&mut self.tikz.boundary, Some(Side::Right), "right");
+        });
     }
 
     // -- UI panels -------------------------------------------------------------------
@@ -1216,68 +940,143 @@ impl LatticeApp {
         ui.collapsing("Examples", |ui| {
             ui.horizontal(|ui| {
                 ui.label("n =");
-                ui.add(egui::DragValue::new(&mut self.example_n).range(1..=12));
+                ui.add(egui::DragValue::new(&mut self.example_n).range(1..=20));
+                ui.label("m =");
+                ui.add(egui::DragValue::new(&mut self.example_m).range(0..=20))
+                    .on_hover_text("Second parameter: claws C_n × C_m, Young L(n,m), rooted tree #m");
             });
 
             ui.label("Face lattices:");
             ui.horizontal_wrapped(|ui| {
+                let n = self.example_n;
                 if ui.button("n-cube").clicked() {
-                    match PosetGraph::cube_lattice(self.example_n) {
-                        Ok(g) => {
-                            let msg = format!("{}-cube face lattice: {} faces.", self.example_n, g.nodes.len());
-                            self.replace_graph(g, EditMode::Poset, msg);
-                        }
-                        Err(e) => self.log = e,
-                    }
+                    self.load_generated(&format!("{}-cube face lattice", n), generators::cube(n));
                 }
                 if ui.button("n-simplex").clicked() {
-                    match PosetGraph::simplex_lattice(self.example_n) {
-                        Ok(g) => {
-                            let msg = format!("{}-simplex face lattice: {} faces.", self.example_n, g.nodes.len());
-                            self.replace_graph(g, EditMode::Poset, msg);
-                        }
-                        Err(e) => self.log = e,
-                    }
+                    self.load_generated(&format!("{}-simplex face lattice", n), generators::simplex(n));
                 }
             });
             ui.horizontal_wrapped(|ui| {
-                let assoc_of = |app: &mut Self, name: &str, g: PosetGraph| {
-                    match graph_associahedron(&g) {
-                        Ok(fl) => {
-                            let msg = format!("{}: {} faces.", name, fl.nodes.len());
-                            app.replace_graph(fl, EditMode::Poset, msg);
-                        }
-                        Err(e) => app.log = e,
-                    }
+                let assoc_of = |app: &mut Self, name: &str, kind: &str| {
+                    let fl = generators::graph(kind, app.example_n)
+                        .and_then(|g| generators::graph_associahedron(&g));
+                    app.load_generated(name, fl);
                 };
                 if ui.button("Permutahedron").clicked() {
-                    assoc_of(self, "Permutahedron (tubings of K_n)", PosetGraph::graph_complete(self.example_n));
+                    assoc_of(self, "Permutahedron (tubings of K_n)", "complete");
                 }
                 if ui.button("Associahedron").clicked() {
-                    assoc_of(self, "Associahedron (tubings of a path)", PosetGraph::graph_path(self.example_n));
+                    assoc_of(self, "Associahedron (tubings of a path)", "path");
                 }
                 if ui.button("Cyclohedron").clicked() {
-                    assoc_of(self, "Cyclohedron (tubings of a cycle)", PosetGraph::graph_cycle(self.example_n));
+                    assoc_of(self, "Cyclohedron (tubings of a cycle)", "cycle");
                 }
                 if ui.button("Stellahedron").clicked() {
-                    assoc_of(self, "Stellahedron (tubings of a star)", PosetGraph::graph_star(self.example_n));
+                    assoc_of(self, "Stellahedron (tubings of a star)", "star");
+                }
+            });
+
+            ui.label("Nestohedra (building set):");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.building_input).on_hover_text(
+                    "Subsets separated by spaces, e.g. 12 23 123; 1,2;2,10 for multi-digit.\n\
+                     Singletons are implicit.",
+                );
+            });
+            ui.horizontal_wrapped(|ui| {
+                let spec = self.building_input.trim().to_string();
+                if ui
+                    .button("Nestohedron")
+                    .on_hover_text("The list must be a building set (closed under unions of intersecting sets)")
+                    .clicked()
+                {
+                    self.load_generated(&format!("Nestohedron of {}", spec), generators::nestohedron(&spec));
+                }
+                if ui
+                    .button("Hypergraph closure")
+                    .on_hover_text("Nestohedron of the building set generated by the sets as hyperedges")
+                    .clicked()
+                {
+                    self.load_generated(&format!("Hypergraphic nestohedron of {}", spec), generators::hypergraph_nestohedron(&spec));
+                }
+            });
+
+            ui.label("Lattice families (lattices/*.py):");
+            ui.horizontal_wrapped(|ui| {
+                let (n, m) = (self.example_n, self.example_m);
+                if ui.button("Fence J(F_n)").clicked() {
+                    self.load_generated(&format!("J(F_{})", n), generators::fence(n));
+                }
+                if ui.button("Boolean B_n").clicked() {
+                    self.load_generated(&format!("B_{}", n), generators::boolean(n));
+                }
+                if ui.button("Young (sizes 2..n-1)").clicked() {
+                    self.load_generated("Young's lattice", generators::int_partitions(n));
+                }
+                if ui.button("Refinement Par(n)").clicked() {
+                    self.load_generated(&format!("Partitions of {} by refinement", n), generators::int_partitions_ref(n));
+                }
+                if ui.button("Claws C_n × C_m").clicked() {
+                    self.load_generated(&format!("C_{} × C_{}", n, m), generators::prod_of_claws(n, m));
+                }
+            });
+
+            ui.label("Classic lattices:");
+            ui.horizontal_wrapped(|ui| {
+                let (n, m) = (self.example_n, self.example_m);
+                if ui.button("Tamari T_n").clicked() {
+                    self.load_generated(&format!("Tamari T_{}", n), generators::tamari(n));
+                }
+                if ui.button("Weak order S_n").clicked() {
+                    self.load_generated(&format!("Weak order on S_{}", n), generators::weak_order(n));
+                }
+                if ui.button("Partitions Π_n").clicked() {
+                    self.load_generated(&format!("Π_{}", n), generators::set_partitions(n));
+                }
+                if ui.button("Noncrossing NC(n)").clicked() {
+                    self.load_generated(&format!("NC({})", n), generators::noncrossing(n));
+                }
+                if ui.button("Young L(n,m)").clicked() {
+                    self.load_generated(&format!("L({}, {})", n, m), generators::young_interval(n, m));
+                }
+                if ui.button("Subspaces of F_2^n").clicked() {
+                    self.load_generated(&format!("Subspaces of F_2^{}", n), generators::subspaces(n));
                 }
             });
 
             ui.label("Graphs (opens the graph editor):");
             ui.horizontal_wrapped(|ui| {
                 let n = self.example_n;
+                let graph = |app: &mut Self, kind: &str, msg: String| match PosetGraph::graph(kind, n) {
+                    Ok(g) => app.replace_graph(g, EditMode::Graph, msg),
+                    Err(e) => app.log = e,
+                };
                 if ui.button("Path").clicked() {
-                    self.replace_graph(PosetGraph::graph_path(n), EditMode::Graph, format!("Path on {} vertices.", n));
+                    graph(self, "path", format!("Path on {} vertices.", n));
                 }
                 if ui.button("Cycle").clicked() {
-                    self.replace_graph(PosetGraph::graph_cycle(n), EditMode::Graph, format!("Cycle on {} vertices.", n));
+                    graph(self, "cycle", format!("Cycle on {} vertices.", n));
                 }
                 if ui.button("Complete").clicked() {
-                    self.replace_graph(PosetGraph::graph_complete(n), EditMode::Graph, format!("K_{}.", n));
+                    graph(self, "complete", format!("K_{}.", n));
                 }
                 if ui.button("Star").clicked() {
-                    self.replace_graph(PosetGraph::graph_star(n), EditMode::Graph, format!("Star K_(1,{}).", n.saturating_sub(1)));
+                    graph(self, "star", format!("Star K_(1,{}).", n.saturating_sub(1)));
+                }
+                if ui.button("Rooted tree #m").on_hover_text("The m-th rooted tree on n vertices (gen_trees.py order), root 0").clicked() {
+                    let m = self.example_m;
+                    match generators::rooted_trees(n) {
+                        Ok(trees) if m < trees.len() => {
+                            let mut g = PosetGraph::from_poset(&trees[m]);
+                            g.layout_circle();
+                            let msg = format!("Rooted tree #{} of {} on {} vertices.", m, trees.len(), n);
+                            self.replace_graph(g, EditMode::Graph, msg);
+                        }
+                        Ok(trees) => {
+                            self.log = format!("Only {} rooted trees on {} vertices (#0..#{}).", trees.len(), n, trees.len() - 1)
+                        }
+                        Err(e) => self.log = e,
+                    }
                 }
             });
         });
@@ -1314,13 +1113,8 @@ impl LatticeApp {
             .on_hover_text("Connected subsets of the drawn graph, ordered by inclusion")
             .clicked()
         {
-            match tube_poset(&self.graph) {
-                Ok(g) => {
-                    let msg = format!("Tube poset: {} tubes.", g.nodes.len());
-                    self.replace_graph(g, EditMode::Poset, msg);
-                }
-                Err(e) => self.log = e,
-            }
+            let tp = generators::tube_poset(&self.graph.to_poset());
+            self.load_generated("Tube poset", tp);
         }
         if ui
             .button("Graph associahedron (tubings)")
@@ -1329,13 +1123,8 @@ impl LatticeApp {
             )
             .clicked()
         {
-            match graph_associahedron(&self.graph) {
-                Ok(g) => {
-                    let msg = format!("Graph associahedron face lattice: {} faces.", g.nodes.len());
-                    self.replace_graph(g, EditMode::Poset, msg);
-                }
-                Err(e) => self.log = e,
-            }
+            let fl = generators::graph_associahedron(&self.graph.to_poset());
+            self.load_generated("Graph associahedron face lattice", fl);
         }
     }
 
@@ -1347,13 +1136,8 @@ impl LatticeApp {
             ui.text_edit_singleline(&mut self.grid_input)
                 .on_hover_text("e.g. 211 for C3 x C2 x C2, or 12,3 for multi-digit");
             if ui.button("Create").clicked() {
-                match PosetGraph::grid(self.grid_input.trim()) {
-                    Some(g) => {
-                        let msg = format!("Generated grid with {} elements.", g.nodes.len());
-                        self.replace_graph(g, EditMode::Poset, msg);
-                    }
-                    None => self.log = "Could not parse grid dimensions.".to_string(),
-                }
+                let grid = generators::grid(self.grid_input.trim());
+                self.load_generated("Grid", grid);
             }
         });
         ui.horizontal(|ui| {
@@ -1364,13 +1148,8 @@ impl LatticeApp {
                 self.log = format!("Inferred {} new relations.", added);
             }
             if ui.button("J(P)").on_hover_text("Distributive lattice of order ideals").clicked() {
-                match self.graph.distributive() {
-                    Ok(g) => {
-                        let msg = format!("J(P) has {} elements.", g.nodes.len());
-                        self.replace_graph(g, EditMode::Poset, msg);
-                    }
-                    Err(e) => self.log = e,
-                }
+                let j = generators::distributive(&self.graph.to_poset());
+                self.load_generated("J(P)", j);
             }
         });
         ui.separator();
@@ -1404,6 +1183,15 @@ impl LatticeApp {
                 self.export_tikz();
             }
         });
+        ui.collapsing("TikZ options", |ui| self.tikz_panel(ui));
+        ui.horizontal(|ui| {
+            ui.label("Bridge graph of level");
+            ui.add(egui::DragValue::new(&mut self.dot_level).range(0..=64));
+            if ui.button("Export DOT").on_hover_text("With the displayed strip's layer").clicked() {
+                self.export_level_dot();
+            }
+        });
+        ui.collapsing("Bridge graphs", |ui| self.levels_panel(ui));
         ui.separator();
 
         // --- computation ---
@@ -1425,11 +1213,15 @@ impl LatticeApp {
             let n_strips = self.strips.len();
             ui.horizontal(|ui| {
                 ui.spinner();
-                let status = match job.kind {
-                    JobKind::Count => format!("counted {} ...", job.live_count),
-                    JobKind::Enumerate => format!("found {} ...", n_strips),
-                    JobKind::Exists => "searching ...".to_string(),
+                let mut status = match job.kind {
+                    JobKind::Count => format!("counted {}", job.progress.as_ref().map_or(0, |r| r.count)),
+                    JobKind::Enumerate => format!("found {}", n_strips),
+                    JobKind::Exists => "searching".to_string(),
                 };
+                match &job.progress {
+                    Some(report) => status = format!("{}, {}", status, report.text()),
+                    None => status.push_str(" ..."),
+                }
                 ui.label(format!("{} ({:.0?})", status, job.started.elapsed()));
                 cancel_clicked = ui.button("Cancel").clicked();
             });
@@ -1476,9 +1268,32 @@ impl LatticeApp {
             });
 
             if self.viewing_strip {
-                if ui.button("Render strip PDF").on_hover_text("Runs pdflatex").clicked() {
-                    self.render_strip_pdf();
-                }
+                ui.horizontal(|ui| {
+                    if ui.button("Render PDF").on_hover_text("Runs pdflatex").clicked() {
+                        self.render_strip_pdf();
+                    }
+                    if ui.button("Save SVG").clicked() {
+                        self.save_strip_image("svg");
+                    }
+                    if ui.button("Save PNG").clicked() {
+                        self.save_strip_image("png");
+                    }
+                    if ui.button("Save DOT").on_hover_text("Graphviz, a cluster per layer").clicked() {
+                        self.save_strip_image("dot");
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Step by step:");
+                    if ui.button("Animated SVG").clicked() {
+                        self.save_strip_steps("svg");
+                    }
+                    if ui.button("PNG frames").clicked() {
+                        self.save_strip_steps("png");
+                    }
+                    if ui.button("TikZ pages").on_hover_text("One page per step with pdflatex").clicked() {
+                        self.save_strip_steps("tex");
+                    }
+                });
                 let mut copy_text: Option<String> = None;
                 if let Some(view) = self.current_strip() {
                     ui.collapsing("Strip layers", |ui| {
@@ -1751,7 +1566,8 @@ impl LatticeApp {
 impl eframe::App for LatticeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.poll_job();
-        if self.job.is_some() {
+        let surveying = self.levels.as_ref().is_some_and(|s| s.rows.iter().any(|r| r.paths.is_none()));
+        if self.job.is_some() || surveying {
             ctx.request_repaint_after(std::time::Duration::from_millis(50));
         }
 
@@ -1797,6 +1613,7 @@ pub fn interactive() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rhombic;
 
     fn drain_enumeration(faces: Vec<Face>, id_map: Vec<NodeId>, cyclic: bool) -> usize {
         let job = Job::spawn(faces, id_map, cyclic, JobKind::Enumerate);
@@ -1814,13 +1631,8 @@ mod tests {
         n
     }
 
-    #[test]
-    fn grid_generator_and_ranks() {
-        let g = PosetGraph::grid("211").unwrap();
-        assert_eq!(g.nodes.len(), 3 * 2 * 2);
-        let ranks = g.ranks().unwrap();
-        assert_eq!(*ranks.values().max().unwrap(), 4); // top element 211
-        assert_eq!(g.edges.len(), 20);
+    fn grid(spec: &str) -> PosetGraph {
+        PosetGraph::from_poset(&generators::grid(spec).unwrap())
     }
 
     #[test]
@@ -1835,7 +1647,7 @@ mod tests {
 
     #[test]
     fn save_load_roundtrip() {
-        let g = PosetGraph::grid("21").unwrap();
+        let g = grid("21");
         let file = g.to_lattice_file().unwrap();
         let l = Lattice::from_str_content(&file).unwrap();
         let g2 = PosetGraph::from_lattice(&l);
@@ -1849,100 +1661,21 @@ mod tests {
     }
 
     #[test]
-    fn distributive_of_antichain_is_boolean() {
-        let mut g = PosetGraph::default();
-        for i in 0..3 {
-            g.add_node(format!("{}", i), Pos2::ZERO);
-        }
-        let j = g.distributive().unwrap();
-        assert_eq!(j.nodes.len(), 8); // J(antichain_3) = boolean lattice B_3
-        assert_eq!(j.edges.len(), 12);
-    }
-
-    #[test]
-    fn cube_and_simplex_lattices() {
-        let sq = PosetGraph::cube_lattice(2).unwrap();
-        assert_eq!(sq.nodes.len(), 9); // 4 + 4 + 1
-        assert_eq!(sq.edges.len(), 12); // 8 vertex-edge + 4 edge-top covers
-        let c3 = PosetGraph::cube_lattice(3).unwrap();
-        assert_eq!(c3.nodes.len(), 27);
-
-        let tri = PosetGraph::simplex_lattice(2).unwrap();
-        assert_eq!(tri.nodes.len(), 7); // 3 + 3 + 1
-        let tet = PosetGraph::simplex_lattice(3).unwrap();
-        assert_eq!(tet.nodes.len(), 15); // 2^4 - 1
-    }
-
-    #[test]
-    fn tubes_of_a_path() {
-        let g = PosetGraph::graph_path(3);
-        let adj = adjacency_masks(&g);
-        // proper connected subsets: {0},{1},{2},{01},{12} — not {02}
-        let tubes: Vec<Mask> = (1..0b111).filter(|&m| mask_connected(m, &adj)).collect();
-        assert_eq!(tubes.len(), 5);
-        assert!(!tubes.contains(&0b101));
-    }
-
-    #[test]
-    fn tube_compatibility() {
-        let g = PosetGraph::graph_path(3); // 0 - 1 - 2
-        let adj = adjacency_masks(&g);
-        assert!(tubes_compatible(0b001, 0b011, &adj)); // nested
-        assert!(tubes_compatible(0b001, 0b100, &adj)); // disjoint, non-adjacent
-        assert!(!tubes_compatible(0b001, 0b010, &adj)); // disjoint but adjacent
-        assert!(!tubes_compatible(0b011, 0b110, &adj)); // properly overlapping
-    }
-
-    #[test]
-    fn small_polytopes_have_correct_face_counts() {
-        // P(path_3) = pentagon: 5 + 5 + 1 faces
-        let pent = graph_associahedron(&PosetGraph::graph_path(3)).unwrap();
-        assert_eq!(pent.nodes.len(), 11);
-        // P(K_3) = hexagon: 6 + 6 + 1 = Fubini(3)
-        let hex = graph_associahedron(&PosetGraph::graph_complete(3)).unwrap();
-        assert_eq!(hex.nodes.len(), 13);
-        // P(path_4) = 3d associahedron: 14 + 21 + 9 + 1
-        let assoc3 = graph_associahedron(&PosetGraph::graph_path(4)).unwrap();
-        assert_eq!(assoc3.nodes.len(), 45);
-        // P(K_4) = permutahedron Pi_3: Fubini(4)
-        let perm3 = graph_associahedron(&PosetGraph::graph_complete(4)).unwrap();
-        assert_eq!(perm3.nodes.len(), 75);
-        // ranks are the face dimensions: vertices at 0, top at n-1
-        let ranks = perm3.ranks().unwrap();
-        assert_eq!(*ranks.values().max().unwrap(), 3);
-    }
-
-    #[test]
-    fn tube_poset_of_path() {
-        let tp = tube_poset(&PosetGraph::graph_path(3)).unwrap();
-        assert_eq!(tp.nodes.len(), 6); // 5 proper tubes + full set
-        assert_eq!(tp.edges.len(), 6);
-        let ranks = tp.ranks().unwrap();
-        assert_eq!(*ranks.values().max().unwrap(), 2); // graded by cardinality - 1
-    }
-
-    #[test]
-    fn disconnected_graph_rejected() {
-        let mut g = PosetGraph::default();
-        g.add_node("a".into(), Pos2::ZERO);
-        g.add_node("b".into(), Pos2::ZERO);
-        assert!(graph_associahedron(&g).is_err());
-        assert!(tube_poset(&g).is_err());
-    }
-
-    #[test]
-    fn hexagon_face_lattice_strips() {
-        // hexagon = permutahedron of K_3; like the square, a polygon has
-        // exactly 2 cyclic rhombic strips (one per orientation)
-        let hex = graph_associahedron(&PosetGraph::graph_complete(3)).unwrap();
-        let (faces, _) = hex.to_faces().unwrap();
-        let l = Lattice::from_faces(faces);
-        assert_eq!(rhombic::count_strips(&l, true), 2);
+    fn poset_conversion_follows_node_ids() {
+        // after a deletion node ids and node indices differ
+        let mut g = grid("11");
+        let (top, id) = (g.nodes[3].id, g.nodes[0].id);
+        g.remove_node(id);
+        g.edges.clear();
+        assert_eq!(g.infer_digit_relations(), 2);
+        assert!(g.edges.iter().all(|&(_, b)| b == top));
+        assert_eq!(g.ranks().unwrap()[&top], 1);
+        assert_eq!(PosetGraph::from_poset(&g.to_poset()).to_poset(), g.to_poset());
     }
 
     #[test]
     fn worker_matches_direct_count() {
-        let g = PosetGraph::grid("11").unwrap();
+        let g = grid("11");
         let (faces, id_map) = g.to_faces().unwrap();
         let l = Lattice::from_faces(faces.clone());
         for cyclic in [false, true] {
@@ -1954,11 +1687,26 @@ mod tests {
 
     #[test]
     fn worker_exists_stops_after_first() {
-        let g = PosetGraph::grid("11").unwrap();
+        let g = grid("11");
         let (faces, id_map) = g.to_faces().unwrap();
         let job = Job::spawn(faces, id_map, false, JobKind::Exists);
         let first = job.rx.iter().next();
         assert!(matches!(first, Some(WorkerMsg::Strip { .. })));
         job.cancel();
     }
+
+    #[test]
+    fn level_survey_counts_every_level() {
+        let l = Lattice::from_file("lattices/cube3d").unwrap();
+        let survey = LevelSurvey::spawn(&l, false);
+        let counts: Vec<usize> = survey.rx.iter().collect();
+        assert_eq!(counts, [72, LEVEL_PATHS, 0]);
+        assert_eq!(survey.rows[2].components, 6);
+
+        let mut survey = LevelSurvey::spawn(&l, true);
+        while survey.poll() {
+            std::thread::yield_now();
+        }
+        assert_eq!(survey.rows[0].paths, Some(12));
+    }
 }
@@ -107,6 +107,20 @@ impl Lattice {
         Lattice { faces, levels, bridges, dim }
     }
 
+    /// Roughly the bytes [`from_faces`](Self::from_faces) takes for `faces`,
+    /// mostly the `n x n` bridge matrix: enough to refuse a giant poset
+    /// before building it.
+    pub fn estimate_bytes(faces: &[Face]) -> u64 {
+        let n = faces.len() as u64;
+        let per_face = (std::mem::size_of::<Face>() + std::mem::size_of::<FaceId>()) as u64;
+        let links: u64 = faces.iter().map(|f| (f.upset.len() + f.downset.len()) as u64).sum();
+        let labels: u64 = faces.iter().map(|f| f.label.len() as u64).sum();
+        n * n * std::mem::size_of::<Option<FaceId>>() as u64
+            + n * per_face
+            + links * std::mem::size_of::<FaceId>() as u64
+            + labels
+    }
+
     /// Parse a lattice file. One face per line:
     /// `dim: label: {upset}, {downset}`, e.g. `0: 000: {16, 10, 8}, {}`.
     /// Empty and malformed-header lines are skipped, matching the old parser.
@@ -199,6 +213,27 @@ impl Lattice {
         self.bridges[f1 * self.faces.len() + f2]
     }
 
+    /// A hash of the lattice as numbered (every face's dimension and
+    /// downset, by id), 16 hex digits that are the same on every machine.
+    /// Unlike the isomorphism key of [`crate::symmetry`] it changes with
+    /// the numbering, which is what sharded searches depend on.
+    pub fn fingerprint(&self) -> String {
+        // FNV-1a over little-endian words
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut write = |x: usize| {
+            for b in (x as u64).to_le_bytes() {
+                h = (h ^ b as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        write(self.faces.len());
+        for f in &self.faces {
+            write(f.dim);
+            write(f.downset.len());
+            f.downset.iter().for_each(|&d| write(d));
+        }
+        format!("{:016x}", h)
+    }
+
     // -- hamiltonian paths on a level -----------------------------------------
 
     /// Lazily generate hamiltonian paths (or cycles) on the bridge graph of
@@ -210,16 +245,11 @@ impl Lattice {
     /// Same as `ham_paths`, but on an arbitrary level: vertices are the faces
     /// of dimension `d`, edges are pairs with a common cover (a bridge).
     pub fn ham_paths_on_level(&self, d: usize, cyclic: bool) -> HamiltonianIter {
-        let (nodes, adj) = self.level_graph(d);
-        if nodes.is_empty() {
-            return HamiltonianIter::empty();
-        }
-        HamiltonianIter::new(nodes, adj, cyclic)
+        self.bridge_graph(d).ham_paths(cyclic)
     }
 
-    /// Bridge graph of level `d`: its vertices and an adjacency list indexed
-    /// directly by FaceId.
-    fn level_graph(&self, d: usize) -> (Vec<FaceId>, Vec<Vec<FaceId>>) {
+    /// The bridge graph of level `d` (no vertices if `d` is out of range).
+    pub fn bridge_graph(&self, d: usize) -> BridgeGraph {
         let nodes: Vec<FaceId> = self.level(d).to_vec();
         let mut adj: Vec<Vec<FaceId>> = vec![vec![]; self.num_faces()];
         for (i, &u) in nodes.iter().enumerate() {
@@ -230,7 +260,7 @@ impl Lattice {
                 }
             }
         }
-        (nodes, adj)
+        BridgeGraph { level: d, nodes, adj }
     }
 
     /// Split `ham_paths(cyclic)` into independent iterators whose outputs
@@ -247,29 +277,174 @@ impl Lattice {
     /// single sequential DFS producer is the bottleneck and all cores but
     /// one sit idle whenever generating paths dominates.
     pub fn ham_path_seeds(&self, cyclic: bool, target: usize) -> Vec<HamiltonianIter> {
-        let (nodes, adj) = self.level_graph(0);
-        let n = nodes.len();
-        if n == 0 {
+        let graph = self.bridge_graph(0);
+        if graph.num_vertices() <= 3 || target <= 1 {
+            return vec![graph.ham_paths(cyclic)];
+        }
+        let prefixes = graph.ham_path_prefixes(cyclic, target);
+        if prefixes.is_empty() {
             return vec![HamiltonianIter::empty()];
         }
-        if n <= 3 || target <= 1 {
-            return vec![HamiltonianIter::new(nodes, adj, cyclic)];
+        let BridgeGraph { nodes, adj, .. } = graph;
+        let nodes = std::sync::Arc::new(nodes);
+        let adj = std::sync::Arc::new(adj);
+        prefixes
+            .into_iter()
+            .map(|p| HamiltonianIter::with_prefix(nodes.clone(), adj.clone(), cyclic, p))
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// BridgeGraph: the faces of one level and their bridges
+// ---------------------------------------------------------------------------
+
+/// The bridge graph of a level: its faces, with an edge between two of them
+/// wherever they have a bridge (a common cover). The layers of a strip are
+/// hamiltonian paths of these graphs, or cycles for cyclic strips.
+///
+/// Faces are the lattice's `FaceId`s; asking about a face of another level
+/// is allowed and answers as for an isolated face outside the graph.
+#[derive(Clone, Debug)]
+pub struct BridgeGraph {
+    level: usize,
+    nodes: Vec<FaceId>,
+    adj: Vec<Vec<FaceId>>, // indexed by FaceId, empty off the level
+}
+
+impl BridgeGraph {
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// The faces of the level, by increasing id.
+    pub fn vertices(&self) -> &[FaceId] {
+        &self.nodes
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn contains(&self, f: FaceId) -> bool {
+        self.nodes.binary_search(&f).is_ok()
+    }
+
+    /// The faces sharing a bridge with `f`.
+    pub fn neighbours(&self, f: FaceId) -> &[FaceId] {
+        self.adj.get(f).map_or(&[], |v| v.as_slice())
+    }
+
+    pub fn degree(&self, f: FaceId) -> usize {
+        self.neighbours(f).len()
+    }
+
+    pub fn has_edge(&self, a: FaceId, b: FaceId) -> bool {
+        self.neighbours(a).contains(&b)
+    }
+
+    /// Every edge once, as `(a, b)` with `a < b`.
+    pub fn edges(&self) -> impl Iterator<Item = (FaceId, FaceId)> + '_ {
+        self.nodes
+            .iter()
+            .flat_map(move |&a| self.adj[a].iter().filter(move |&&b| a < b).map(move |&b| (a, b)))
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.nodes.iter().map(|&f| self.adj[f].len()).sum::<usize>() / 2
+    }
+
+    /// The degrees of the vertices, largest first.
+    pub fn degree_sequence(&self) -> Vec<usize> {
+        let mut degrees: Vec<usize> = self.nodes.iter().map(|&f| self.degree(f)).collect();
+        degrees.sort_unstable_by(|a, b| b.cmp(a));
+        degrees
+    }
+
+    /// The connected components, each by increasing id, ordered by their
+    /// smallest face.
+    pub fn components(&self) -> Vec<Vec<FaceId>> {
+        let mut seen = vec![false; self.adj.len()];
+        let mut components = Vec::new();
+        for &start in &self.nodes {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = vec![start];
+            let mut i = 0;
+            while i < component.len() {
+                for &v in &self.adj[component[i]] {
+                    if !seen[v] {
+                        seen[v] = true;
+                        component.push(v);
+                    }
+                }
+                i += 1;
+            }
+            component.sort_unstable();
+            components.push(component);
         }
+        components
+    }
+
+    /// Connected, counting a level without faces as connected.
+    pub fn is_connected(&self) -> bool {
+        self.components().len() <= 1
+    }
 
+    /// Necessary conditions that already exclude a hamiltonian path (cycle):
+    /// a disconnected graph, a vertex of degree < 2 on a cycle of 3 or more,
+    /// more than two vertices of degree 1 on a path.
+    fn rules_out(&self, cyclic: bool) -> bool {
+        let n = self.nodes.len();
+        if n == 0 {
+            return true;
+        }
+        if !self.is_connected() {
+            return true;
+        }
+        let leaves = self.nodes.iter().filter(|&&f| self.degree(f) < 2).count();
+        if cyclic {
+            n >= 3 && leaves > 0
+        } else {
+            n >= 3 && leaves > 2
+        }
+    }
+
+    /// Lazily generate the hamiltonian paths (or cycles), exactly as
+    /// [`Lattice::ham_paths_on_level`]: paths up to reversal, cycles through
+    /// the first vertex, once per direction.
+    pub fn ham_paths(&self, cyclic: bool) -> HamiltonianIter {
+        if self.rules_out(cyclic) {
+            return HamiltonianIter::empty();
+        }
+        HamiltonianIter::new(self.nodes.clone(), self.adj.clone(), cyclic)
+    }
+
+    /// At least `target` prefixes (when there are that many), all of one
+    /// length, whose subtrees partition the search of
+    /// [`ham_paths`](Self::ham_paths); see [`Lattice::ham_path_seeds`].
+    /// Empty when there is no hamiltonian path (cycle) at all.
+    pub fn ham_path_prefixes(&self, cyclic: bool, target: usize) -> Vec<Vec<FaceId>> {
+        if self.rules_out(cyclic) {
+            return vec![];
+        }
         // Uniform-depth prefix expansion. Cycles are anchored at nodes[0]
         // (every hamiltonian cycle is a rotation of one through it), paths
         // may start anywhere; both exactly as in `push_start_node`.
+        let n = self.nodes.len();
         let mut prefixes: Vec<Vec<FaceId>> = if cyclic {
-            vec![vec![nodes[0]]]
+            vec![vec![self.nodes[0]]]
         } else {
-            nodes.iter().map(|&u| vec![u]).collect()
+            self.nodes.iter().map(|&u| vec![u]).collect()
         };
         let mut depth = 1;
         while prefixes.len() < target && depth < n - 1 {
             let mut next = Vec::with_capacity(prefixes.len() * 4);
             for p in &prefixes {
                 let last = *p.last().expect("prefix non-empty");
-                for &v in &adj[last] {
+                for &v in &self.adj[last] {
                     if !p.contains(&v) {
                         let mut q = p.clone();
                         q.push(v);
@@ -280,18 +455,51 @@ impl Lattice {
             if next.is_empty() {
                 // No simple path of length depth+1 <= n-1: no hamiltonian
                 // path exists at all.
-                return vec![HamiltonianIter::empty()];
+                return vec![];
             }
             prefixes = next;
             depth += 1;
         }
-
-        let nodes = std::sync::Arc::new(nodes);
-        let adj = std::sync::Arc::new(adj);
         prefixes
-            .into_iter()
-            .map(|p| HamiltonianIter::with_prefix(nodes.clone(), adj.clone(), cyclic, p))
-            .collect()
+    }
+
+    /// The hamiltonian paths (cycles) that begin with `prefix`, as
+    /// [`ham_paths`](Self::ham_paths) would yield them. The prefix must be a
+    /// simple path of the graph, for cycles one from the first vertex: a
+    /// prefix of [`ham_path_prefixes`](Self::ham_path_prefixes) or of
+    /// [`HamiltonianIter::release`].
+    pub fn ham_paths_from(&self, cyclic: bool, prefix: Vec<FaceId>) -> Result<HamiltonianIter, String> {
+        let simple = !prefix.is_empty()
+            && prefix.len() <= self.nodes.len()
+            && prefix.iter().all(|&f| self.contains(f))
+            && prefix.iter().enumerate().all(|(i, f)| !prefix[..i].contains(f))
+            && prefix.windows(2).all(|w| self.has_edge(w[0], w[1]));
+        if !simple || (cyclic && prefix[0] != self.nodes[0]) {
+            return Err(format!("{:?} is not a prefix of the level {} search", prefix, self.level));
+        }
+        let nodes = std::sync::Arc::new(self.nodes.clone());
+        let adj = std::sync::Arc::new(self.adj.clone());
+        Ok(HamiltonianIter::with_prefix(nodes, adj, cyclic, prefix))
+    }
+
+    pub fn has_ham_path(&self, cyclic: bool) -> bool {
+        self.ham_paths(cyclic).next().is_some()
+    }
+
+    /// The number of paths [`ham_paths`](Self::ham_paths) yields, counting
+    /// at most `limit` of them.
+    pub fn count_ham_paths(&self, cyclic: bool, limit: Option<usize>) -> usize {
+        self.ham_paths(cyclic).take(limit.unwrap_or(usize::MAX)).count()
+    }
+
+    /// Whether `path` visits every vertex exactly once along edges, and with
+    /// `cyclic` closes up (in any direction and rotation).
+    pub fn is_ham_path(&self, path: &[FaceId], cyclic: bool) -> bool {
+        let mut sorted = path.to_vec();
+        sorted.sort_unstable();
+        sorted == self.nodes
+            && path.windows(2).all(|w| self.has_edge(w[0], w[1]))
+            && (!cyclic || path.len() < 3 || self.has_edge(path[0], path[path.len() - 1]))
     }
 }
 
@@ -405,36 +613,145 @@ impl HamiltonianIter {
     }
 }
 
-impl Iterator for HamiltonianIter {
-    type Item = Vec<FaceId>;
+/// What [`HamiltonianIter::next_within`] got to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Path(Vec<FaceId>),
+    /// The search is over.
+    Done,
+    /// The budget ran out first; call again to go on.
+    Paused,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
+impl HamiltonianIter {
+    /// Like `next`, but give up after about `budget` DFS steps, so that a
+    /// caller can look up between paths that are far apart.
+    pub fn next_within(&mut self, budget: usize) -> Step {
+        self.search(budget)
+    }
+
+    /// The paths left while `stop` does not hold. It is asked before every
+    /// path and after every `budget` DFS steps without one, so a search
+    /// through a long stretch with no paths still stops when told.
+    pub fn until<F: Fn() -> bool>(mut self, budget: usize, stop: F) -> impl Iterator<Item = Vec<FaceId>> {
+        std::iter::from_fn(move || loop {
+            if stop() {
+                return None;
+            }
+            match self.next_within(budget) {
+                Step::Path(path) => return Some(path),
+                Step::Done => return None,
+                Step::Paused => {}
+            }
+        })
+    }
+
+    /// Stop, and hand over what is left of the search as prefixes for
+    /// [`BridgeGraph::ham_paths_from`]: the untried neighbours of every
+    /// vertex on the current path. Their subtrees are exactly the paths this
+    /// iterator has not yet yielded.
+    pub fn release(&mut self) -> Vec<Vec<FaceId>> {
+        if self.finished {
+            return vec![];
+        }
+        let mut rest = Vec::new();
+        for (j, &(u, cursor)) in self.stack.iter().enumerate() {
+            let head = &self.path[..=j];
+            for &v in self.adj[u].get(cursor..).unwrap_or(&[]) {
+                if !head.contains(&v) {
+                    let mut prefix = head.to_vec();
+                    prefix.push(v);
+                    rest.push(prefix);
+                }
+            }
+        }
+        // a complete seed (or single vertex) not yet yielded
+        if self.path.len() == self.nodes.len() {
+            rest.push(self.path.clone());
+        }
+        if !self.prefix_mode && !self.cyclic {
+            rest.extend(self.nodes.iter().skip(self.start_node_index + 1).map(|&u| vec![u]));
+        }
+        self.stack.clear();
+        self.finished = true;
+        rest
+    }
+
+    /// Knuth's estimate of the size of the search left below the current
+    /// path: the average, over `probes` random descents, of the sum of the
+    /// products of the branching factors met. Unbiased but noisy; good
+    /// enough to weigh seeds against each other. Deterministic: the
+    /// descents are seeded by the path.
+    pub fn estimate_size(&self, probes: usize) -> f64 {
+        if self.finished || self.path.is_empty() {
+            return 0.0;
+        }
+        let mut state =
+            self.path.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &f| (h ^ f as u64).wrapping_mul(0x100_0000_01b3));
+        let mut visited = self.visited.clone();
+        let mut total = 0.0;
+        for _ in 0..probes.max(1) {
+            let mut walk = vec![*self.path.last().unwrap()];
+            let (mut size, mut branches) = (1.0, 1.0);
+            loop {
+                let u = *walk.last().unwrap();
+                let next: Vec<FaceId> = self.adj[u].iter().copied().filter(|&v| !visited[v]).collect();
+                if next.is_empty() {
+                    break;
+                }
+                branches *= next.len() as f64;
+                size += branches;
+                let v = next[(splitmix(&mut state) % next.len() as u64) as usize];
+                visited[v] = true;
+                walk.push(v);
+            }
+            for &v in &walk[1..] {
+                visited[v] = false;
+            }
+            total += size;
+        }
+        total / probes.max(1) as f64
+    }
+
+    /// Whether the full-length `path` is one to yield: closed for cycles
+    /// (a single vertex counts), `start <= end` for paths.
+    fn complete(&self) -> bool {
+        let (first, last) = (self.path[0], *self.path.last().unwrap());
+        if self.cyclic {
+            self.nodes.len() == 1 || self.adj[last].contains(&first)
+        } else {
+            first <= last
+        }
+    }
+
+    fn search(&mut self, mut budget: usize) -> Step {
         if self.finished {
-            return None;
+            return Step::Done;
         }
 
-        // trivial single-vertex level
-        if self.nodes.len() == 1 {
+        // a single-vertex level, or a seed that is already a full path
+        if self.path.len() == self.nodes.len() {
             self.finished = true;
-            return if self.path.len() == 1 {
-                Some(std::mem::take(&mut self.path))
-            } else {
-                None
-            };
+            return if self.complete() { Step::Path(std::mem::take(&mut self.path)) } else { Step::Done };
         }
 
         loop {
+            if budget == 0 {
+                return Step::Paused;
+            }
+            budget -= 1;
+
             // exhausted current start node -> advance to next one
             // (a seeded iterator owns exactly one subtree: it is done)
             if self.stack.is_empty() {
                 if self.prefix_mode {
                     self.finished = true;
-                    return None;
+                    return Step::Done;
                 }
                 self.start_node_index += 1;
                 self.push_start_node();
                 if self.finished {
-                    return None;
+                    return Step::Done;
                 }
                 continue;
             }
@@ -462,23 +779,126 @@ impl Iterator for HamiltonianIter {
             self.stack.push((v, 0));
 
             if self.path.len() == self.nodes.len() {
-                let result = if self.cyclic {
-                    // must close up to the start node
-                    self.adj[v].contains(&self.path[0]).then(|| self.path.clone())
-                } else {
-                    // symmetry breaking for paths: start <= end
-                    (self.path[0] <= *self.path.last().unwrap()).then(|| self.path.clone())
-                };
+                let result = self.complete().then(|| self.path.clone());
 
                 // backtrack immediately so the search can continue
                 self.stack.pop();
                 self.path.pop();
                 self.visited[v] = false;
 
-                if result.is_some() {
-                    return result;
+                if let Some(path) = result {
+                    return Step::Path(path);
+                }
+            }
+        }
+    }
+}
+
+/// SplitMix64 step, for [`HamiltonianIter::estimate_size`].
+fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Iterator for HamiltonianIter {
+    type Item = Vec<FaceId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.search(usize::MAX) {
+            Step::Path(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+
+    #[test]
+    fn bridge_graphs_of_the_cube() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let g = l.bridge_graph(0);
+        assert_eq!((g.level(), g.num_vertices(), g.num_edges()), (0, 8, 12));
+        assert_eq!(g.degree_sequence(), vec![3; 8]);
+        assert_eq!(g.edges().count(), 12);
+        assert!(g.edges().all(|(a, b)| a < b && g.has_edge(b, a)));
+        assert!(g.is_connected());
+        assert_eq!(g.count_ham_paths(false, None), 72);
+        assert_eq!(g.count_ham_paths(false, Some(10)), 10);
+        assert!(g.ham_paths(true).all(|p| g.is_ham_path(&p, true)));
+        let path = g.ham_paths(false).next().unwrap();
+        assert!(!g.is_ham_path(&path[1..], false));
+        assert!(!g.contains(l.level(1)[0]) && g.neighbours(l.level(1)[0]).is_empty());
+
+        assert_eq!(l.bridge_graph(3).count_ham_paths(true, None), 1);
+        assert_eq!(l.bridge_graph(4).num_vertices(), 0);
+
+        // without the solid cube on top, no two squares have a bridge
+        let l = Lattice::from_file("lattices/cube3d").unwrap();
+        let squares = l.bridge_graph(2);
+        assert_eq!(squares.components().len(), 6);
+        assert!(!squares.has_ham_path(false));
+    }
+
+    #[test]
+    fn ruling_out_paths_loses_none() {
+        let lattices = [
+            generators::fence(5).unwrap().to_lattice().unwrap(),
+            generators::boolean(3).unwrap().to_lattice().unwrap(),
+            generators::tamari(3).unwrap().to_lattice().unwrap(),
+        ];
+        for l in &lattices {
+            for d in 0..l.num_levels() {
+                let g = l.bridge_graph(d);
+                for cyclic in [false, true] {
+                    let search = HamiltonianIter::new(g.nodes.clone(), g.adj.clone(), cyclic);
+                    assert_eq!(g.count_ham_paths(cyclic, None), search.count(), "level {}", d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn released_searches_lose_and_repeat_nothing() {
+        let lattices = [
+            generators::cube(3).unwrap().to_lattice().unwrap(),
+            generators::fence(5).unwrap().to_lattice().unwrap(),
+            generators::boolean(3).unwrap().to_lattice().unwrap(),
+        ];
+        for l in &lattices {
+            for d in 0..l.num_levels() {
+                let g = l.bridge_graph(d);
+                if g.num_vertices() > 8 {
+                    continue; // the cube's edges: 302304 paths
+                }
+                for cyclic in [false, true] {
+                    let mut all: Vec<_> = g.ham_paths(cyclic).collect();
+                    all.sort();
+                    // take a few steps of every piece, then hand the rest on
+                    let mut found = Vec::new();
+                    let mut work = vec![g.ham_paths(cyclic)];
+                    while let Some(mut search) = work.pop() {
+                        for _ in 0..3 {
+                            if let Step::Path(path) = search.next_within(200) {
+                                found.push(path);
+                            }
+                        }
+                        for prefix in search.release() {
+                            work.push(g.ham_paths_from(cyclic, prefix).unwrap());
+                        }
+                    }
+                    found.sort();
+                    assert_eq!(found, all, "level {}, cyclic {}", d, cyclic);
                 }
             }
         }
+        let g = lattices[0].bridge_graph(0);
+        assert_eq!(g.ham_path_prefixes(false, 20).len(), 24);
+        assert!(g.ham_paths_from(false, vec![0, 0]).is_err());
     }
 }
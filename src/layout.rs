@@ -0,0 +1,431 @@
+//! Layered (Sugiyama-style) drawings of posets and strips.
+//!
+//! Nodes sit on rows by rank. Edges that skip rows get dummy nodes, so every
+//! edge joins neighbouring rows, and then:
+//!
+//! 1. **crossing reduction**: sweeps up and down the rows, sorting each row
+//!    by the median position of its neighbours in the row just placed (ties
+//!    by barycentre), then swapping adjacent nodes while that removes
+//!    crossings; the best ordering seen wins;
+//! 2. **coordinate assignment**: every node is pulled towards the mean x of
+//!    its neighbours, keeping the row order and a gap of at least 1 —
+//!    an exact least-squares step per row (pool adjacent violators).
+//!
+//! [`hasse_along_strip`] and [`layered`] with fixed rows skip step 1: the
+//! rows keep the order of a strip's layers, and only the coordinates are
+//! computed. Coordinates are in units of the minimal gap, y = rank, centred
+//! on x = 0; the front ends scale them.
+
+use crate::generators::Poset;
+
+type Rows = Vec<Vec<usize>>;
+
+/// Crossing-reduction sweeps at most; the search stops earlier after
+/// `PATIENCE` sweeps without improvement.
+const MAX_SWEEPS: usize = 24;
+const PATIENCE: usize = 4;
+const COORDINATE_SWEEPS: usize = 8;
+
+/// Positions `(x, rank)` of the Hasse diagram of `p` (its cover relations,
+/// ranks as [`Poset::ranks`]).
+pub fn hasse(p: &Poset) -> Result<Vec<(f64, f64)>, String> {
+    let rank = p.ranks()?;
+    Ok(layered(&rank, &p.edges, None))
+}
+
+/// The Hasse diagram of `p` with every rank in the order of the strip's
+/// layer of that level (node indices, as strips over `p.to_faces()` are).
+pub fn hasse_along_strip(p: &Poset, strip: &[Vec<usize>]) -> Result<Vec<(f64, f64)>, String> {
+    let rank = p.ranks()?;
+    check_rows(&rank, strip)?;
+    Ok(layered(&rank, &p.edges, Some(strip)))
+}
+
+/// Lay out the nodes `0..rank.len()`, node `i` on row `rank[i]`, joined by
+/// `edges` (either direction; edges within a row are ignored). With `fixed`
+/// (one order per row, covering every node), the rows keep those orders.
+pub fn layered(rank: &[usize], edges: &[(usize, usize)], fixed: Option<&[Vec<usize>]>) -> Vec<(f64, f64)> {
+    let n = rank.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut g = Graph::new(rank, edges);
+    match fixed {
+        Some(rows) => g.fix_rows(rows),
+        None => g.reduce_crossings(),
+    }
+    let x = g.coordinates();
+    let (lo, hi) = x[..n].iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), &v| (a.min(v), b.max(v)));
+    let mid = (lo + hi) / 2.0;
+    (0..n).map(|v| (x[v] - mid, rank[v] as f64)).collect()
+}
+
+/// Total number of edge crossings of the drawing with these row orders
+/// (`rows[r]` lists the nodes of rank `r`, left to right).
+pub fn crossings(rank: &[usize], edges: &[(usize, usize)], rows: &[Vec<usize>]) -> usize {
+    let mut g = Graph::new(rank, edges);
+    g.fix_rows(rows);
+    g.crossings()
+}
+
+fn check_rows(rank: &[usize], rows: &[Vec<usize>]) -> Result<(), String> {
+    let mut seen = vec![false; rank.len()];
+    for (r, row) in rows.iter().enumerate() {
+        for &v in row {
+            if v >= rank.len() || seen[v] {
+                return Err(format!("layer {} repeats or misses a node", r));
+            }
+            if rank[v] != r {
+                return Err(format!("node {} has rank {}, not {}", v, rank[v], r));
+            }
+            seen[v] = true;
+        }
+    }
+    if seen.iter().any(|&s| !s) {
+        return Err("the strip does not cover every node".to_string());
+    }
+    Ok(())
+}
+
+/// The layered graph with dummies.
+struct Graph {
+    /// Nodes `real..` are dummies.
+    real: usize,
+    rows: Rows,
+    rank: Vec<usize>,
+    below: Vec<Vec<usize>>,
+    above: Vec<Vec<usize>>,
+    /// Position of every node in its row.
+    pos: Vec<usize>,
+}
+
+impl Graph {
+    fn new(rank: &[usize], edges: &[(usize, usize)]) -> Self {
+        let n = rank.len();
+        let height = rank.iter().max().map_or(0, |&r| r + 1);
+        let mut g = Graph {
+            real: n,
+            rows: vec![vec![]; height],
+            rank: rank.to_vec(),
+            below: vec![vec![]; n],
+            above: vec![vec![]; n],
+            pos: vec![0; n],
+        };
+        for (v, &r) in rank.iter().enumerate() {
+            g.rows[r].push(v);
+        }
+        for &(a, b) in edges {
+            let (a, b) = if rank[a] <= rank[b] { (a, b) } else { (b, a) };
+            if rank[a] == rank[b] {
+                continue;
+            }
+            // a chain of dummies up to b
+            let mut last = a;
+            for r in rank[a] + 1..rank[b] {
+                let d = g.rank.len();
+                g.rank.push(r);
+                g.below.push(vec![last]);
+                g.above.push(vec![]);
+                g.pos.push(0);
+                g.rows[r].push(d);
+                g.above[last].push(d);
+                last = d;
+            }
+            g.above[last].push(b);
+            g.below[b].push(last);
+        }
+        g.index();
+        g
+    }
+
+    fn index(&mut self) {
+        for row in &self.rows {
+            for (i, &v) in row.iter().enumerate() {
+                self.pos[v] = i;
+            }
+        }
+    }
+
+    /// Put the real nodes of every row in the given order, and slot each
+    /// dummy in at the relative position of its lower neighbour.
+    fn fix_rows(&mut self, rows: &[Vec<usize>]) {
+        for (r, row) in rows.iter().enumerate() {
+            let dummies: Vec<usize> = self.rows[r].iter().copied().filter(|&v| v >= self.real).collect();
+            self.rows[r] = row.iter().copied().chain(dummies).collect();
+        }
+        self.index();
+        for r in 1..self.rows.len() {
+            let mut row: Vec<(f64, usize)> = Vec::with_capacity(self.rows[r].len());
+            let reals: Vec<usize> = self.rows[r].iter().copied().filter(|&v| v < self.real).collect();
+            let width = reals.len().max(1) as f64;
+            let prev = self.rows[r - 1].len().max(1) as f64;
+            for (i, &v) in reals.iter().enumerate() {
+                row.push((i as f64 / width, v));
+            }
+            for &v in self.rows[r].iter().filter(|&&v| v >= self.real) {
+                row.push((self.pos[self.below[v][0]] as f64 / prev, v));
+            }
+            row.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            self.rows[r] = row.into_iter().map(|(_, v)| v).collect();
+            for (i, &v) in self.rows[r].iter().enumerate() {
+                self.pos[v] = i;
+            }
+        }
+    }
+
+    fn reduce_crossings(&mut self) {
+        // a first upward pass orders the rows from the initial row 0
+        for r in 1..self.rows.len() {
+            self.sort_row(r, true);
+        }
+        let mut best = (self.crossings(), self.rows.clone());
+        let mut stale = 0;
+        for sweep in 0..MAX_SWEEPS {
+            if best.0 == 0 || stale >= PATIENCE {
+                break;
+            }
+            let upward = sweep % 2 == 0;
+            let rows: Vec<usize> = if upward {
+                (1..self.rows.len()).collect()
+            } else {
+                (0..self.rows.len().saturating_sub(1)).rev().collect()
+            };
+            for r in rows {
+                self.sort_row(r, upward);
+            }
+            self.transpose();
+            let c = self.crossings();
+            if c < best.0 {
+                best = (c, self.rows.clone());
+                stale = 0;
+            } else {
+                stale += 1;
+            }
+        }
+        self.rows = best.1;
+        self.index();
+    }
+
+    /// Sort row `r` by the median position of its neighbours below
+    /// (`upward`) or above; nodes without such neighbours keep their place.
+    fn sort_row(&mut self, r: usize, upward: bool) {
+        let mut keyed: Vec<(f64, f64, usize)> = self.rows[r]
+            .iter()
+            .map(|&v| {
+                let nbrs = if upward { &self.below[v] } else { &self.above[v] };
+                let mut p: Vec<usize> = nbrs.iter().map(|&u| self.pos[u]).collect();
+                if p.is_empty() {
+                    let here = self.pos[v] as f64;
+                    return (here, here, v);
+                }
+                p.sort_unstable();
+                let m = p.len() / 2;
+                let median = if p.len() % 2 == 1 { p[m] as f64 } else { (p[m - 1] + p[m]) as f64 / 2.0 };
+                let mean = p.iter().sum::<usize>() as f64 / p.len() as f64;
+                (median, mean, v)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.partial_cmp(&b.1).unwrap()));
+        self.rows[r] = keyed.into_iter().map(|k| k.2).collect();
+        for (i, &v) in self.rows[r].iter().enumerate() {
+            self.pos[v] = i;
+        }
+    }
+
+    /// Swap neighbours in a row while that lowers the crossings.
+    fn transpose(&mut self) {
+        let mut improved = true;
+        let mut rounds = 0;
+        while improved && rounds < 8 {
+            improved = false;
+            rounds += 1;
+            for r in 0..self.rows.len() {
+                for i in 0..self.rows[r].len().saturating_sub(1) {
+                    let (u, v) = (self.rows[r][i], self.rows[r][i + 1]);
+                    if self.pair_crossings(v, u) < self.pair_crossings(u, v) {
+                        self.rows[r].swap(i, i + 1);
+                        self.pos[u] = i + 1;
+                        self.pos[v] = i;
+                        improved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Crossings between the edges of `u` and of `v` with `u` left of `v`.
+    fn pair_crossings(&self, u: usize, v: usize) -> usize {
+        let count = |a: &[usize], b: &[usize]| {
+            a.iter().map(|&x| b.iter().filter(|&&y| self.pos[y] < self.pos[x]).count()).sum::<usize>()
+        };
+        count(&self.below[u], &self.below[v]) + count(&self.above[u], &self.above[v])
+    }
+
+    /// All crossings, row pair by row pair (inversions, counted with a
+    /// Fenwick tree).
+    fn crossings(&self) -> usize {
+        let mut total = 0;
+        for r in 0..self.rows.len().saturating_sub(1) {
+            let mut ends: Vec<(usize, usize)> = self.rows[r]
+                .iter()
+                .flat_map(|&u| self.above[u].iter().map(move |&v| (u, v)))
+                .map(|(u, v)| (self.pos[u], self.pos[v]))
+                .collect();
+            ends.sort_unstable();
+            let width = self.rows[r + 1].len();
+            let mut tree = vec![0usize; width + 1];
+            for (seen, &(_, q)) in ends.iter().enumerate() {
+                // edges so far ending at or left of q
+                let mut i = q + 1;
+                let mut not_crossing = 0;
+                while i > 0 {
+                    not_crossing += tree[i];
+                    i &= i - 1;
+                }
+                total += seen - not_crossing;
+                let mut i = q + 1;
+                while i <= width {
+                    tree[i] += 1;
+                    i += i & i.wrapping_neg();
+                }
+            }
+        }
+        total
+    }
+
+    /// x of every node: rows keep their order and a gap of 1.
+    fn coordinates(&self) -> Vec<f64> {
+        let mut x: Vec<f64> = self.pos.iter().map(|&p| p as f64).collect();
+        // centre the rows on each other first
+        for row in &self.rows {
+            let shift = (row.len() as f64 - 1.0) / 2.0;
+            for &v in row {
+                x[v] -= shift;
+            }
+        }
+        for sweep in 0..COORDINATE_SWEEPS {
+            let both = sweep + 1 == COORDINATE_SWEEPS;
+            let upward = sweep % 2 == 0;
+            let rows: Vec<usize> = if upward {
+                (0..self.rows.len()).collect()
+            } else {
+                (0..self.rows.len()).rev().collect()
+            };
+            for r in rows {
+                let wanted: Vec<f64> = self.rows[r]
+                    .iter()
+                    .map(|&v| {
+                        let nbrs: Vec<usize> = if both {
+                            self.below[v].iter().chain(&self.above[v]).copied().collect()
+                        } else if upward {
+                            self.below[v].clone()
+                        } else {
+                            self.above[v].clone()
+                        };
+                        if nbrs.is_empty() {
+                            x[v]
+                        } else {
+                            nbrs.iter().map(|&u| x[u]).sum::<f64>() / nbrs.len() as f64
+                        }
+                    })
+                    .collect();
+                for (&v, p) in self.rows[r].iter().zip(spaced(&wanted)) {
+                    x[v] = p;
+                }
+            }
+        }
+        x
+    }
+}
+
+/// The closest points to `wanted` (least squares) that keep the order and
+/// a gap of at least 1: isotonic regression of `wanted[i] - i`.
+fn spaced(wanted: &[f64]) -> Vec<f64> {
+    // blocks of (sum, count) with non-decreasing means
+    let mut blocks: Vec<(f64, usize)> = Vec::new();
+    for (i, &w) in wanted.iter().enumerate() {
+        blocks.push((w - i as f64, 1));
+        while blocks.len() > 1 {
+            let (s2, c2) = blocks[blocks.len() - 1];
+            let (s1, c1) = blocks[blocks.len() - 2];
+            if s1 / c1 as f64 <= s2 / c2 as f64 {
+                break;
+            }
+            blocks.pop();
+            *blocks.last_mut().unwrap() = (s1 + s2, c1 + c2);
+        }
+    }
+    let mut out = Vec::with_capacity(wanted.len());
+    for (s, c) in blocks {
+        for _ in 0..c {
+            out.push(s / c as f64 + out.len() as f64);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::rhombic;
+
+    /// Row orders read back off the positions.
+    fn rows_of(pos: &[(f64, f64)]) -> Vec<Vec<usize>> {
+        let height = pos.iter().map(|p| p.1 as usize + 1).max().unwrap_or(0);
+        let mut rows = vec![vec![]; height];
+        for (v, p) in pos.iter().enumerate() {
+            rows[p.1 as usize].push(v);
+        }
+        for row in &mut rows {
+            row.sort_by(|&a, &b| pos[a].0.partial_cmp(&pos[b].0).unwrap());
+        }
+        rows
+    }
+
+    fn gaps_ok(pos: &[(f64, f64)]) -> bool {
+        rows_of(pos).iter().all(|row| row.windows(2).all(|w| pos[w[1]].0 - pos[w[0]].0 >= 1.0 - 1e-9))
+    }
+
+    #[test]
+    fn crossings_are_reduced() {
+        // a < d, b < c: the naive order crosses once, the layout not at all
+        let labels = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let p = Poset { labels, edges: vec![(0, 3), (1, 2)], ranks: None };
+        let rank = p.ranks().unwrap();
+        assert_eq!(crossings(&rank, &p.edges, &[vec![0, 1], vec![2, 3]]), 1);
+        let pos = hasse(&p).unwrap();
+        assert_eq!(crossings(&rank, &p.edges, &rows_of(&pos)), 0);
+
+        let posets = [generators::tamari(5), generators::boolean(4), generators::fence(9)];
+        for p in posets.map(Result::unwrap) {
+            let rank = p.ranks().unwrap();
+            let naive = rows_of(&rank.iter().map(|&r| (0.0, r as f64)).collect::<Vec<_>>());
+            let pos = hasse(&p).unwrap();
+            assert!(gaps_ok(&pos));
+            assert!(pos.iter().zip(&rank).all(|(p, &r)| p.1 == r as f64));
+            assert!(crossings(&rank, &p.edges, &rows_of(&pos)) <= crossings(&rank, &p.edges, &naive));
+        }
+    }
+
+    #[test]
+    fn strip_order_is_kept() {
+        let p = generators::cube(3).unwrap();
+        let l = p.to_lattice().unwrap();
+        let strip = rhombic::strips(&l, false).next().unwrap();
+        let pos = hasse_along_strip(&p, &strip).unwrap();
+        assert!(gaps_ok(&pos));
+        assert_eq!(rows_of(&pos), strip);
+        assert!(hasse_along_strip(&p, &strip[1..]).is_err());
+    }
+
+    #[test]
+    fn long_edges_and_spacing() {
+        // a < b < c and a < c drawn with a dummy on row 1
+        let pos = layered(&[0, 1, 2], &[(0, 1), (1, 2), (0, 2)], None);
+        assert_ne!(pos[1].0, pos[0].0);
+        assert_eq!(spaced(&[0.0, 0.0, 0.0]), [-1.0, 0.0, 1.0]);
+        assert_eq!(spaced(&[5.0, 0.0]), [2.0, 3.0]);
+        assert_eq!(spaced(&[0.0, 4.0]), [0.0, 4.0]);
+    }
+}
@@ -6,30 +6,88 @@
 //! * the desktop egui explorer ([`gui`], native only), and
 //! * the browser build ([`web`], compiled to wasm for `www/`).
 //!
-//! The generator and strip-search logic that the website needs lives in
-//! [`web::api`] as plain `Result<String, String>` functions, so it is unit
-//! tested on the host (see `tests/generators.rs`) without a browser in the
-//! loop. The `#[wasm_bindgen]` layer in [`web`] is a thin shell over it.
+//! All three build their posets with [`generators`], which returns one
+//! common [`generators::Poset`] type and is tested on the host in
+//! `tests/generators.rs`. The website reaches it (and the strip search)
+//! through [`web::api`], plain `Result<String, String>` JSON functions; the
+//! `#[wasm_bindgen]` layer in [`web`] is a thin shell over those.
 
+/// Results tables (CSV / JSON) for the CLI's `batch` command.
+pub mod batch;
+pub mod bitset;
+
+/// On-disk results cache keyed by poset isomorphism class, shared by the
+/// CLI, batch runs and `strip_stream`.
+pub mod cache;
+
+/// Compact binary encoding of strip batches (delta-coded varint layers),
+/// for `strip_stream` jobs that ask for it and the browser's decoder.
+pub mod codec;
+
+/// Filters (boundaries, layer widths, bridges, also cyclic) and
+/// aggregations (width histogram, boundary pairs, strips per path) of
+/// enumerated strips, evaluated where the search runs.
+pub mod filter;
+
+/// Poset, lattice, graph and nestohedron generators shared by the CLI, the
+/// GUI and the browser.
+pub mod generators;
 pub mod lattice;
+/// Layered (Sugiyama) layouts of Hasse diagrams and strips, shared by the
+/// GUI, the browser and the exporters.
+pub mod layout;
+
+/// Progress of a search split into seeds: share done, strips per second and
+/// time left, for `strip_stream`, the CLI, the GUI and the browser.
+pub mod progress;
 pub mod rhombic;
 
-/// TikZ/pdflatex rendering plus `edges_strip` (the strip's draw edges, used by
-/// both the GUI and the browser). Compiled on every target: `edges_strip` is
-/// pure, and the `std::process`/`std::fs` rendering paths compile for wasm too
-/// (they're simply never called in the browser). If your `plotting.rs` pulls in
-/// a *native-only crate* at module level and fails to compile for wasm, gate
-/// those items with `#[cfg(not(target_arch = "wasm32"))]` and keep `edges_strip`
-/// ungated.
+/// Splitting one count or enumeration over the tasks of a Slurm array
+/// (`--shard i/N`), and merging the parts.
+pub mod shard;
+
+/// The summary record of a finished search, and its JSONL log (`--log`).
+/// Native only.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod summary;
+
+/// Invariant poset hashing, isomorphisms, automorphisms and orbits of
+/// hamiltonian paths.
+pub mod symmetry;
+
+/// Strip drawing: `edges_strip` (the strip's draw edges), `strip_layout`
+/// (linear and radial layouts, shared by the GUI, the browser and every
+/// export), the TikZ, SVG and PNG renderers, and step-by-step construction
+/// sequences (`construction_steps`). Compiled on every target:
+/// the layout and SVG are pure, and the `std::process`/`std::fs` paths
+/// compile for wasm too (they're simply never called in the browser). PNG
+/// output needs the `raster` feature, which `gui` turns on.
 pub mod plotting;
 
+/// Graphviz DOT of level bridge graphs and of strips (a cluster per layer).
+pub mod dot;
+
+/// Configurable TikZ (snippet or document, styles per level, edge colours,
+/// label escaping), the same from the CLI, the GUI and the browser.
+pub mod tikz;
+
+/// Loopback HTTP relay of `strip_stream --serve`: pairs with the web page and
+/// runs each job in its own process, directly or under `srun`. Native only.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod relay;
+
+/// Work-stealing search over TCP: `strip_stream --coordinate` leases prefixes
+/// of the path search to `strip_stream --work` processes. Native only.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod distributed;
+
 /// Desktop egui explorer. It pulls in `eframe` and spawns worker threads, so
 /// it is excluded from the wasm build (the browser gets [`web`] instead) and
 /// from `--no-default-features` builds (the headless cluster binary).
 #[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
 pub mod gui;
 
-/// Browser bindings plus the host-testable [`web::api`] core.
+/// Browser bindings plus the host-testable [`web::api`] JSON layer.
 pub mod web;
 
 /// Batch "scripts" for the browser's Scripts panel: computations over
@@ -1,104 +1,1814 @@
-use rhombic_strips::{gui, lattice, plotting, rhombic};
+//! Command-line front end: `rhombic_strips <command> [options] [<input>]`.
+//!
+//! Every command reads one lattice — a lattice file, stdin (`-`, a lattice
+//! file or a poset in JSON), or a generator via `--gen` — and answers in
+//! plain text, one JSON document or NDJSON. The search runs on a worker
+//! thread so `--timeout` can abandon it; the exit code says how it went
+//! (see `USAGE`).
 
-use crate::lattice::Lattice;
-use crate::rhombic::{count_strips, extensions, strip_exists, strips};
+use std::collections::HashMap;
+use std::io::{IsTerminal, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use rhombic_strips::batch;
+use rhombic_strips::cache::{Cache, Known};
+use rhombic_strips::filter::{self, Filter, Tally};
+use rhombic_strips::generators::{self, Poset};
+use rhombic_strips::lattice::{BridgeGraph, FaceId, Lattice};
+use rhombic_strips::progress::{Progress, Report};
+use rhombic_strips::rhombic::{self, Strip};
+use rhombic_strips::shard::{self, Part, Shard};
+use rhombic_strips::summary::Summary;
+use rhombic_strips::tikz::TikzOptions;
+use rhombic_strips::symmetry;
+use rhombic_strips::{dot, plotting};
+
+const USAGE: &str = "\
+usage: rhombic_strips <command> [options] [<lattice file> | -]
+
+commands:
+  exists       is there a rhombic strip?                 (exit 1 if not)
+  count        number of strips
+  paths        strips per hamiltonian path (cycle) of level 0, and how
+               many paths extend to no strip; --orbits groups the paths
+               by automorphism, --sort count|path orders them
+  enumerate    list the strips, at most --limit
+  sample       --limit strips (default 1) drawn uniformly at random
+  info         faces per level, cover relations, and the bridge graph of
+               every level: degrees, components and how many hamiltonian
+               paths (cycles with --cyclic) it has, at most --limit
+               (default 100000) counted per level
+  dot          the bridge graph of level --level (default 0) as Graphviz
+               DOT; --strips FILE draws the first strip's layer on it
+  generate <family> <params>
+               write a generated lattice (`generate help` lists families)
+  convert      write the input as a lattice file (text) or poset JSON
+  verify       check the strips in --strips (as written by enumerate or
+               sample) against the lattice               (exit 1 if invalid)
+  merge FILE...  add up the results of count or enumerate --shard i/N
+               (--format json), checking that every shard of the same
+               lattice is there                          (exit 2 if not)
+  batch        run exists or count (--mode) on many inputs: files,
+               directories and --range generator calls; one table row each
+  cache export everything in the results cache, as CSV or JSON
+  cache path   where the results cache lives
+  interactive  open the desktop explorer
+
+input: a lattice file, `-` for stdin (lattice file or poset JSON), or
+       --gen \"<family> <params>\" to search a generated lattice directly
+
+options:
+  --cyclic                 cyclic strips
+  --format text|json|ndjson|csv   (csv: paths, batch, cache export)
+  --limit N                enumerate: stop after N strips; sample: sample size;
+                           info: hamiltonian paths counted per level
+  --threads N              worker threads for exists, count and paths
+  --timeout SECS           give up after SECS seconds    (exit 3)
+  --show, --show-cyclic    render found strips with pdflatex (linear or
+                           cyclic layout; exists, enumerate, sample)
+  --render FILE            write found strips as .svg, .png, .tex or .dot; the
+                           cyclic layout with --cyclic. enumerate and
+                           sample number the files: strip-<index>.svg
+                           (.dot: a Graphviz cluster per layer)
+  --steps                  with --render: the construction layer by layer,
+                           bridges then gap faces (animated .svg, a .tex
+                           page per step, or frames strip-00.png, ...)
+  --tikz SPEC              TikZ of --show and --render x.tex, e.g.
+                           snippet,scale=1.2,shape=rectangle/circle,
+                           fill=white/blue!10,edges,math,raw,
+                           boundary=left|right (default: a document
+                           fitted to a4)
+  --seed N                 sample: random seed
+  --shard i/N              count, enumerate: only part i (0 to N-1) of the
+                           search, e.g. one task of a Slurm array; the
+                           parts' results add up with `merge`
+  --filter SPEC            enumerate, sample: only the strips that pass, e.g.
+                           left=012,right=210 (boundary chains, as
+                           --aggregate boundaries writes them),
+                           widths=7/-/1 (bridges of the layers from level
+                           1 up, - for any), bridges=01/12, gaps=02 (faces
+                           that must be bridges, or sit in gaps), cyclic
+                           or not-cyclic (linear strips that are, or are
+                           not, also cyclic)
+  --aggregate KINDS        enumerate: instead of the strips, tables of the
+                           ones kept, in parallel: widths (per level),
+                           boundaries (pairs of chains), paths (strips per
+                           hamiltonian path of level 0), comma-separated
+  --progress               count, enumerate --aggregate: percent done,
+                           strips/s and time left on stderr once a second
+                           (always on a terminal)
+  --log FILE               exists, count, enumerate, sample: append the
+                           result's summary (lattice hash, threads, paths
+                           tried, wall time, host, version) to FILE, a JSON
+                           line per run; JSON output carries it as well
+  --strips FILE            verify: the strips to check, `-` for stdin;
+                           dot: the strip to draw
+  --level D                dot: the level to draw
+  --cache DIR, --no-cache  results cache to use (default $RHOMBIC_CACHE or
+                           ~/.cache/rhombic_strips/results), or none
+
+batch options:
+  --mode exists|count      what to run on each instance (default exists)
+  --range \"<family> <params>\"
+                           generator instances; params may be ranges a..b
+  --out FILE               results table, CSV or .json; instances already
+                           in it are skipped (resume)
+  --retry-timeouts         rerun instances that timed out before
+  --timeout SECS           per instance
+
+exit codes: 0 done, 1 negative answer (no strip, invalid strip),
+            2 usage or input error, 3 timeout";
+
+const GENERATE_USAGE: &str = "\
+usage: rhombic_strips generate <family> <params>
+
+  fence n | boolean n | int_partitions n | int_partitions_ref n
+  prod_of_claws n m | trees n
+  tamari n | weak_order n | set_partitions n | noncrossing n | young m n
+  subspaces n | grid <chains, e.g. 211 or 12,3> | cube d | simplex d
+  nestohedron <sets> | hypergraph <sets>      (sets like \"12 23 123\")
+  graph_associahedron <path|cycle|complete|star> n
+  tube_poset <path|cycle|complete|star> n
+
+`trees` writes one edge list per line, as gen_trees.py does; everything
+else is a lattice file (or poset JSON with --format json).";
+
+const EXIT_NO: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_TIMEOUT: i32 = 3;
 
 fn main() {
-    let interactive_mode = std::env::args().any(|arg| arg == "--interactive");
-    if interactive_mode {
-        gui::interactive();
-        return;
-    }
-
-    let source = std::env::args()
-        .nth(1)
-        .expect("Please provide a file from which to read in the lattice.");
-
-    let cyclic = std::env::args().any(|arg| arg == "--cyclic"); // restrict to cyclic rhombic strips
-    let count = std::env::args().any(|arg| arg == "--count"); // find all rhombic strips and print their number
-    let show = std::env::args().any(|arg| arg == "--show"); // render the first found strip
-    let enumerate = std::env::args().any(|arg| arg == "--enumerate"); // split the count among the hamilton paths/cycles
-    let show_all = std::env::args().any(|arg| arg == "--show-all"); // render all found strips
-    let show_cyclic = std::env::args().any(|arg| arg == "--show-cyclic"); // render in cyclic layout
-
-    process_lattice(
-        &source,
-        cyclic,
-        count,
-        show,
-        enumerate,
-        show_all,
-        show_cyclic,
-    );
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = run(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        EXIT_USAGE
+    });
+    std::process::exit(code);
+}
+
+fn run(args: &[String]) -> Result<i32, String> {
+    let o = Options::parse(args)?;
+    if let Some(n) = o.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build_global()
+            .map_err(|e| e.to_string())?;
+    }
+    match o.command.as_str() {
+        "exists" => exists(&o),
+        "count" => count(&o),
+        "paths" => paths(&o),
+        "enumerate" | "sample" => enumerate(&o),
+        "info" => info(&o),
+        "dot" => dot_command(&o),
+        "generate" => generate_command(&o),
+        "convert" => convert(&o),
+        "verify" => verify(&o),
+        "batch" => batch(&o),
+        "merge" => merge(&o),
+        "cache" => cache_command(&o),
+        #[cfg(feature = "gui")]
+        "interactive" | "--interactive" => {
+            rhombic_strips::gui::interactive();
+            Ok(0)
+        }
+        #[cfg(not(feature = "gui"))]
+        "interactive" | "--interactive" => Err("this build has no desktop explorer (built without the gui feature)".to_string()),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(0)
+        }
+        c => Err(format!("unknown command '{}'\n\n{}", c, USAGE)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Ndjson,
+    /// `paths` only; `batch` and `cache export` write CSV as text anyway.
+    Csv,
 }
 
-fn process_lattice(
-    source: &str,
+struct Options {
+    command: String,
+    positional: Vec<String>,
+    gen: Option<String>,
     cyclic: bool,
-    count: bool,
+    format: Format,
+    limit: Option<usize>,
+    threads: Option<usize>,
+    timeout: Option<Duration>,
     show: bool,
-    enumerate: bool,
-    show_all: bool,
     show_cyclic: bool,
-) {
-    let l = match Lattice::from_file(source) {
-        Ok(l) => l,
-        Err(e) => {
+    render: Option<String>,
+    steps: bool,
+    tikz: TikzOptions,
+    seed: Option<u64>,
+    orbits: bool,
+    sort: Option<String>,
+    strips: Option<String>,
+    level: usize,
+    mode: String,
+    ranges: Vec<String>,
+    out: Option<String>,
+    retry_timeouts: bool,
+    cache: Option<String>,
+    no_cache: bool,
+    shard: Option<Shard>,
+    progress: bool,
+    log: Option<String>,
+    filter: Filter,
+    aggregate: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        let command = args.next().ok_or(USAGE)?.clone();
+        let mut o = Options {
+            command,
+            positional: vec![],
+            gen: None,
+            cyclic: false,
+            format: Format::Text,
+            limit: None,
+            threads: None,
+            timeout: None,
+            show: false,
+            show_cyclic: false,
+            render: None,
+            steps: false,
+            tikz: TikzOptions::default(),
+            seed: None,
+            orbits: false,
+            sort: None,
+            strips: None,
+            level: 0,
+            mode: "exists".to_string(),
+            ranges: vec![],
+            out: None,
+            retry_timeouts: false,
+            cache: None,
+            no_cache: false,
+            shard: None,
+            progress: false,
+            log: None,
+            filter: Filter::default(),
+            aggregate: vec![],
+        };
+        let number = |name: &str, v: String| -> Result<u64, String> {
+            v.parse().map_err(|_| format!("{}: '{}' is not a number", name, v))
+        };
+
+        let mut seen: Vec<String> = Vec::new();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                o.positional.push(arg.clone());
+                continue;
+            }
+            let (name, inline) = match arg.split_once('=') {
+                Some((n, v)) => (n, Some(v.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let mut value = || -> Result<String, String> {
+                inline.clone().or_else(|| args.next().cloned()).ok_or(format!("{} needs a value", name))
+            };
+            match name {
+                "--cyclic" => o.cyclic = true,
+                "--format" => {
+                    o.format = match value()?.as_str() {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        "ndjson" => Format::Ndjson,
+                        "csv" => Format::Csv,
+                        f => return Err(format!("--format: unknown format '{}'", f)),
+                    }
+                }
+                "--gen" => o.gen = Some(value()?),
+                "--limit" => o.limit = Some(number(name, value()?)? as usize),
+                "--threads" => o.threads = Some(number(name, value()?)?.max(1) as usize),
+                "--timeout" => {
+                    let v = value()?;
+                    let secs = v.parse::<f64>().ok().and_then(|s| Duration::try_from_secs_f64(s).ok());
+                    o.timeout = Some(secs.ok_or_else(|| format!("--timeout: bad '{}', expected seconds", v))?);
+                }
+                "--show" => o.show = true,
+                "--show-cyclic" => o.show_cyclic = true,
+                "--render" => o.render = Some(value()?),
+                "--steps" => o.steps = true,
+                "--tikz" => o.tikz = TikzOptions::parse(&value()?)?,
+                "--seed" => o.seed = Some(number(name, value()?)?),
+                "--orbits" => o.orbits = true,
+                "--sort" => {
+                    let by = value()?;
+                    if by != "count" && by != "path" {
+                        return Err(format!("--sort: expected count or path, not '{}'", by));
+                    }
+                    o.sort = Some(by);
+                }
+                "--strips" => o.strips = Some(value()?),
+                "--level" => o.level = number(name, value()?)? as usize,
+                "--mode" => {
+                    o.mode = value()?;
+                    if o.mode != "exists" && o.mode != "count" {
+                        return Err(format!("--mode: expected exists or count, not '{}'", o.mode));
+                    }
+                }
+                "--range" => o.ranges.push(value()?),
+                "--out" => o.out = Some(value()?),
+                "--retry-timeouts" => o.retry_timeouts = true,
+                "--cache" => o.cache = Some(value()?),
+                "--no-cache" => o.no_cache = true,
+                "--shard" => o.shard = Some(Shard::parse(&value()?)?),
+                "--progress" => o.progress = true,
+                "--log" => o.log = Some(value()?),
+                "--filter" => o.filter = Filter::parse(&value()?)?,
+                "--aggregate" => {
+                    o.aggregate = value()?.split(',').map(str::trim).filter(|k| !k.is_empty()).map(str::to_string).collect();
+                    Tally::new(&o.aggregate)?;
+                }
+                _ => return Err(format!("unknown option {}\n\n{}", name, USAGE)),
+            }
+            seen.push(name.to_string());
+        }
+
+        // options that only make sense for some commands
+        let only: [(&str, &[&str]); 23] = [
+            ("--limit", &["enumerate", "sample", "info"]),
+            ("--seed", &["sample"]),
+            ("--orbits", &["paths"]),
+            ("--sort", &["paths"]),
+            ("--strips", &["verify", "dot"]),
+            ("--level", &["dot"]),
+            ("--show", &["exists", "enumerate", "sample"]),
+            ("--show-cyclic", &["exists", "enumerate", "sample"]),
+            ("--render", &["exists", "enumerate", "sample"]),
+            ("--steps", &["exists", "enumerate", "sample"]),
+            ("--tikz", &["exists", "enumerate", "sample"]),
+            ("--threads", &["exists", "count", "paths", "enumerate", "batch"]),
+            ("--timeout", &["exists", "count", "paths", "enumerate", "sample", "batch", "info"]),
+            ("--mode", &["batch"]),
+            ("--range", &["batch"]),
+            ("--out", &["batch"]),
+            ("--retry-timeouts", &["batch"]),
+            ("--no-cache", &["exists", "count", "enumerate", "sample", "batch"]),
+            ("--shard", &["count", "enumerate"]),
+            ("--progress", &["count", "enumerate"]),
+            ("--log", &["exists", "count", "enumerate", "sample"]),
+            ("--filter", &["enumerate", "sample"]),
+            ("--aggregate", &["enumerate"]),
+        ];
+        if let Some(path) = &o.render {
+            let ext = std::path::Path::new(path).extension().and_then(|e| e.to_str());
+            if !ext.is_some_and(|e| ["svg", "png", "tex", "dot", "gv"].contains(&e.to_ascii_lowercase().as_str())) {
+                return Err(format!("--render: expected a .svg, .png, .tex or .dot file, not '{}'", path));
+            }
+        }
+        if o.steps && o.render.is_none() {
+            return Err("--steps needs --render FILE".to_string());
+        }
+        if !o.aggregate.is_empty() {
+            for opt in ["--limit", "--show", "--show-cyclic", "--render"] {
+                if seen.iter().any(|s| s == opt) {
+                    return Err(format!("{} does not go with --aggregate, which lists no strips", opt));
+                }
+            }
+        } else if o.command == "enumerate" && seen.iter().any(|s| s == "--progress") {
+            return Err("--progress goes with count and enumerate --aggregate".to_string());
+        }
+        if o.format == Format::Csv && !["paths", "batch", "cache"].contains(&o.command.as_str()) {
+            return Err(format!("--format csv does not apply to '{}'", o.command));
+        }
+        for (opt, commands) in only {
+            if seen.iter().any(|s| s == opt) && !commands.contains(&o.command.as_str()) {
+                return Err(format!("{} does not apply to '{}'", opt, o.command));
+            }
+        }
+        Ok(o)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Input
+// ---------------------------------------------------------------------------
+
+enum Input {
+    /// Lattice file content.
+    File(String),
+    Poset(Poset),
+}
+
+impl Input {
+    fn lattice(&self) -> Result<Lattice, String> {
+        let l = match self {
+            Input::File(content) => Lattice::from_str_content(content)?,
+            Input::Poset(p) => p.to_lattice()?,
+        };
+        if l.num_faces() == 0 {
+            return Err("the input has no faces".to_string());
+        }
+        Ok(l)
+    }
+
+    fn poset(&self) -> Result<Poset, String> {
+        match self {
+            Input::File(content) => Poset::from_lattice_file(content),
+            Input::Poset(p) => Ok(p.clone()),
+        }
+    }
+}
+
+/// The input of a command: `--gen`, a file or stdin (`-`); files and stdin
+/// may hold a lattice file or a poset in JSON.
+fn load(o: &Options) -> Result<Input, String> {
+    if let Some(spec) = &o.gen {
+        if !o.positional.is_empty() {
+            return Err("give either --gen or an input file, not both".to_string());
+        }
+        return load_gen(spec);
+    }
+    match o.positional.as_slice() {
+        [path] => load_path(path),
+        [] => Err(format!("no input given\n\n{}", USAGE)),
+        _ => Err("only one input allowed".to_string()),
+    }
+}
+
+fn load_gen(spec: &str) -> Result<Input, String> {
+    let args: Vec<String> = spec.split_whitespace().map(str::to_string).collect();
+    Ok(Input::Poset(generate(&args)?))
+}
+
+/// A file or stdin (`-`).
+fn load_path(path: &str) -> Result<Input, String> {
+    let content = if path == "-" {
+        read_stdin()?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("reading {} failed: {}", path, e))?
+    };
+    if content.trim_start().starts_with('{') {
+        Ok(Input::Poset(Poset::parse(&content)?))
+    } else {
+        Ok(Input::File(content))
+    }
+}
+
+fn read_stdin() -> Result<String, String> {
+    let mut content = String::new();
+    std::io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|e| format!("reading stdin failed: {}", e))?;
+    Ok(content)
+}
+
+/// The generators of [`generators`] by family name (all but `trees`).
+fn generate(args: &[String]) -> Result<Poset, String> {
+    let family = args.first().ok_or(GENERATE_USAGE)?;
+    let param = |k: usize| -> Result<usize, String> {
+        let arg = args.get(k).ok_or(GENERATE_USAGE)?;
+        arg.parse().map_err(|_| format!("bad size '{}'", arg))
+    };
+    match family.as_str() {
+        "fence" => generators::fence(param(1)?),
+        "boolean" => generators::boolean(param(1)?),
+        "int_partitions" => generators::int_partitions(param(1)?),
+        "int_partitions_ref" => generators::int_partitions_ref(param(1)?),
+        "prod_of_claws" => generators::prod_of_claws(param(1)?, param(2)?),
+        "tamari" => generators::tamari(param(1)?),
+        "weak_order" => generators::weak_order(param(1)?),
+        "set_partitions" => generators::set_partitions(param(1)?),
+        "noncrossing" => generators::noncrossing(param(1)?),
+        "young" => generators::young_interval(param(1)?, param(2)?),
+        "subspaces" => generators::subspaces(param(1)?),
+        "grid" => generators::grid(args.get(1).ok_or(GENERATE_USAGE)?),
+        "nestohedron" => generators::nestohedron(&args[1..].join(" ")),
+        "hypergraph" => generators::hypergraph_nestohedron(&args[1..].join(" ")),
+        "graph_associahedron" | "tube_poset" => {
+            let kind = args.get(1).ok_or(GENERATE_USAGE)?;
+            let graph = generators::graph(kind, param(2)?)?;
+            if family == "tube_poset" {
+                generators::tube_poset(&graph)
+            } else {
+                generators::graph_associahedron(&graph)
+            }
+        }
+        "cube" => generators::cube(param(1)?),
+        "simplex" => generators::simplex(param(1)?),
+        "trees" => Err("trees are graphs, not a lattice; use `generate trees <n>`".to_string()),
+        f => Err(format!("unknown family '{}'\n\n{}", f, GENERATE_USAGE)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+/// The final record of a command: the common fields plus `fields`.
+fn record(o: &Options, fields: Value) -> Value {
+    let mut v = json!({ "command": o.command, "cyclic": o.cyclic });
+    if let (Value::Object(v), Value::Object(f)) = (&mut v, fields) {
+        v.extend(f);
+    }
+    v
+}
+
+/// Print the final result: `text`, or `record` as one JSON line (tagged
+/// `"type": "result"` in NDJSON).
+fn finish(o: &Options, text: &str, mut record: Value) {
+    match o.format {
+        Format::Text | Format::Csv => println!("{}", text),
+        Format::Json => println!("{}", record),
+        Format::Ndjson => {
+            record["type"] = json!("result");
+            println!("{}", record);
+        }
+    }
+}
+
+/// The summary of this command's search of `l`, to fill in.
+fn summary(o: &Options, l: &Lattice) -> Summary {
+    Summary {
+        shard: o.shard.map(|s| format!("{}/{}", s.index, s.of)),
+        filter: Some(o.filter.clone()).filter(|f| !f.is_empty()),
+        ..Summary::new(&o.command, Some(l), o.cyclic)
+    }
+}
+
+/// `summary` with the seeds and paths of the search behind `progress`, if it
+/// got that far.
+fn with_search(summary: Summary, progress: &OnceLock<Arc<Progress>>) -> Summary {
+    match progress.get() {
+        Some(p) => Summary { seeds: Some(p.seeds()), paths: Some(p.paths()), ..summary },
+        None => summary,
+    }
+}
+
+/// Put the `summary` of a run that began at `started` in the record's
+/// `fields`, and append it to `--log`.
+fn summarize(o: &Options, fields: &mut Value, summary: Summary, started: Instant) {
+    let summary = summary.finish(started);
+    if let Some(Err(e)) = o.log.as_ref().map(|path| summary.append(std::path::Path::new(path))) {
+        eprintln!("warning: {}", e);
+    }
+    fields["summary"] = json!(summary);
+}
+
+fn labels(l: &Lattice, layer: &[FaceId]) -> Vec<String> {
+    layer.iter().map(|&f| l.face(f).label().to_string()).collect()
+}
+
+fn strip_json(l: &Lattice, strip: &Strip, index: usize) -> Value {
+    let labels: Vec<Vec<String>> = strip.iter().map(|layer| labels(l, layer)).collect();
+    json!({ "index": index, "layers": strip, "labels": labels })
+}
+
+fn print_strip_text(l: &Lattice, strip: &Strip) {
+    for layer in strip {
+        println!("{:?}", labels(l, layer));
+    }
+    println!();
+}
+
+/// `--show` / `--show-cyclic` and `--render` (with `--steps`) for one found
+/// strip; `index` numbers the file when a command can find several.
+fn show_strip(o: &Options, l: &Lattice, strip: &Strip, index: Option<usize>) -> Result<(), String> {
+    if o.show || o.show_cyclic {
+        plotting::show_strip(strip, l, o.show_cyclic, &o.tikz);
+    }
+    let Some(path) = &o.render else { return Ok(()) };
+    let path = match index {
+        Some(i) => {
+            let p = std::path::Path::new(path);
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("strip");
+            let name = match p.extension().and_then(|e| e.to_str()) {
+                Some(ext) => format!("{}-{}.{}", stem, i, ext),
+                None => format!("{}-{}", stem, i),
+            };
+            p.with_file_name(name).to_string_lossy().into_owned()
+        }
+        None => path.clone(),
+    };
+    if o.steps {
+        plotting::render_construction(strip, l, o.cyclic, &path).map(|_| ())
+    } else {
+        plotting::render_strip(strip, l, o.cyclic, &path, &o.tikz)
+    }
+}
+
+fn timeout_text(o: &Options) -> String {
+    format!("Timed out after {:?}.", o.timeout.unwrap_or_default())
+}
+
+// ---------------------------------------------------------------------------
+// Running searches with a deadline
+// ---------------------------------------------------------------------------
+
+enum Finish<T> {
+    Done(T),
+    /// `on_msg` asked to stop.
+    Stopped,
+    TimedOut,
+}
+
+/// Run `job` on a worker thread, passing what it emits to `on_msg` (which
+/// returns false to stop) until it returns or `--timeout` runs out. A job
+/// that is stopped or timed out is abandoned; its `emit` returns false from
+/// then on, and the process exits soon after anyway.
+fn run_job<M, T>(
+    timeout: Option<Duration>,
+    job: impl FnOnce(&mut dyn FnMut(M) -> bool) -> T + Send + 'static,
+    mut on_msg: impl FnMut(M) -> bool,
+) -> Result<Finish<T>, String>
+where
+    M: Send + 'static,
+    T: Send + 'static,
+{
+    enum Event<M, T> {
+        Msg(M),
+        Done(T),
+    }
+    let (tx, rx) = mpsc::sync_channel::<Event<M, T>>(256);
+    std::thread::spawn(move || {
+        let out = job(&mut |m| tx.send(Event::Msg(m)).is_ok());
+        let _ = tx.send(Event::Done(out));
+    });
+
+    let deadline = timeout.map(|t| Instant::now() + t);
+    loop {
+        let event = match deadline {
+            Some(d) => match rx.recv_timeout(d.saturating_duration_since(Instant::now())) {
+                Ok(e) => e,
+                Err(mpsc::RecvTimeoutError::Timeout) => return Ok(Finish::TimedOut),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err("the search thread failed".to_string())
+                }
+            },
+            None => rx.recv().map_err(|_| "the search thread failed".to_string())?,
+        };
+        match event {
+            Event::Msg(m) => {
+                if !on_msg(m) {
+                    return Ok(Finish::Stopped);
+                }
+            }
+            Event::Done(out) => return Ok(Finish::Done(out)),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+fn exists(o: &Options) -> Result<i32, String> {
+    let started = Instant::now();
+    let l = Arc::new(load(o)?.lattice()?);
+    let (lj, cyclic) = (l.clone(), o.cyclic);
+    let show = o.show || o.show_cyclic || o.render.is_some();
+    let answer = |found: bool, strip: Option<Strip>, cached: bool| {
+        if let Some(strip) = &strip {
+            show_strip(o, &l, strip, None)?;
+        }
+        let mut text = if found { "A rhombic strip was found" } else { "No rhombic strip exists!" }
+            .to_string();
+        let mut fields = json!({ "exists": found });
+        if cached {
+            text += " (results cache)";
+            fields["cached"] = json!(true);
+        }
+        let threads = if show { 1 } else { rayon::current_num_threads() };
+        let summary = Summary {
+            count: found as u64,
+            cached,
+            threads: Some(threads).filter(|_| !cached),
+            ..summary(o, &l)
+        };
+        summarize(o, &mut fields, summary, started);
+        finish(o, &text, record(o, fields));
+        Ok(if found { 0 } else { EXIT_NO })
+    };
+    if let Some(known) = recall(o, &l) {
+        let found = known.exists == Some(true);
+        if !show || !found || known.witness.is_some() {
+            return answer(found, known.witness.filter(|_| show), true);
+        }
+    }
+
+    let job = move |_: &mut dyn FnMut(()) -> bool| {
+        if show {
+            // the sequential search, to have a strip to show
+            let strip = rhombic::strips(&lj, cyclic).next();
+            (strip.is_some(), strip)
+        } else {
+            (rhombic::strip_exists(&lj, cyclic), None)
+        }
+    };
+    let (found, strip) = match run_job(o.timeout, job, |_| true)? {
+        Finish::Done(r) => r,
+        _ => {
+            let mut fields = json!({ "timeout": true });
+            let threads = if show { 1 } else { rayon::current_num_threads() };
+            let summary = Summary { complete: false, threads: Some(threads), ..summary(o, &l) };
+            summarize(o, &mut fields, summary, started);
+            finish(o, &timeout_text(o), record(o, fields));
+            return Ok(EXIT_TIMEOUT);
+        }
+    };
+    remember(o, &l, Known { exists: Some(found), count: None, witness: strip.clone() });
+    answer(found, strip, false)
+}
+
+fn count(o: &Options) -> Result<i32, String> {
+    let started = Instant::now();
+    let l = Arc::new(load(o)?.lattice()?);
+    let (lj, cyclic) = (l.clone(), o.cyclic);
+
+    if let Some(n) = recall(o, &l).and_then(|k| k.count) {
+        let text = format!("Number of rhombic strips found: {} (results cache)", n);
+        let mut fields = json!({ "count": n, "cached": true });
+        summarize(o, &mut fields, Summary { count: n, cached: true, ..summary(o, &l) }, started);
+        finish(o, &text, record(o, fields));
+        return Ok(0);
+    }
+    let shard = o.shard;
+    let tally: Arc<OnceLock<Arc<Progress>>> = Arc::default();
+    let job_tally = tally.clone();
+    let job = move |emit: &mut dyn FnMut(Report) -> bool| {
+        let seeds = match shard {
+            Some(s) => s.seeds(&lj, cyclic),
+            None => lj.ham_path_seeds(cyclic, rayon::current_num_threads() * 16),
+        };
+        let progress = job_tally.get_or_init(|| Arc::new(Progress::new(&seeds)));
+        reporting(progress, emit, |stop| progress.count_strips(&lj, cyclic, seeds, &|| stop.load(Ordering::Relaxed)))
+            as usize
+    };
+    let mut line = ProgressLine::new(o);
+    let finished = run_job(o.timeout, job, |report| {
+        line.show(&report);
+        true
+    })?;
+    line.clear();
+    let summary = with_search(Summary { threads: Some(rayon::current_num_threads()), ..summary(o, &l) }, &tally);
+    Ok(match finished {
+        Finish::Done(n) => {
+            remember(o, &l, Known { count: Some(n as u64), ..Known::default() });
+            let (mut text, mut fields) = (format!("Number of rhombic strips found: {}", n), json!({ "count": n }));
+            shard_part(o, &l, &mut text, &mut fields, n, true);
+            summarize(o, &mut fields, Summary { count: n as u64, ..summary }, started);
+            finish(o, &text, record(o, fields));
+            0
+        }
+        _ => {
+            let (mut text, mut fields) = (timeout_text(o), json!({ "timeout": true }));
+            shard_part(o, &l, &mut text, &mut fields, 0, false);
+            let count = tally.get().map_or(0, |p| p.count());
+            summarize(o, &mut fields, Summary { count, complete: false, ..summary }, started);
+            finish(o, &text, record(o, fields));
+            EXIT_TIMEOUT
+        }
+    })
+}
+
+/// How often `count` reports its progress.
+const PROGRESS_EVERY: Duration = Duration::from_secs(1);
+
+/// Run `work` on a thread of its own, passing `emit` a report of `progress`
+/// every [`PROGRESS_EVERY`]; `work` should stop once its flag is set, which
+/// happens when `emit` returns false.
+fn reporting<T: Send>(
+    progress: &Progress,
+    emit: &mut dyn FnMut(Report) -> bool,
+    work: impl FnOnce(&AtomicBool) -> T + Send,
+) -> T {
+    let stop = AtomicBool::new(false);
+    let started = Instant::now();
+    std::thread::scope(|s| {
+        let working = s.spawn(|| work(&stop));
+        let mut next = PROGRESS_EVERY;
+        while !working.is_finished() {
+            std::thread::sleep(Duration::from_millis(20));
+            if started.elapsed() >= next {
+                next += PROGRESS_EVERY;
+                if !emit(progress.report(started.elapsed())) {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+        working.join().expect("the search thread panicked")
+    })
+}
+
+/// `--progress` on stderr: one line rewritten in place on a terminal, a
+/// line per report otherwise (a batch job's log).
+struct ProgressLine {
+    show: bool,
+    terminal: bool,
+    shown: bool,
+}
+
+impl ProgressLine {
+    fn new(o: &Options) -> Self {
+        let terminal = std::io::stderr().is_terminal();
+        ProgressLine { show: o.progress || terminal, terminal, shown: false }
+    }
+
+    fn show(&mut self, report: &Report) {
+        if !self.show {
+            return;
+        }
+        let text = format!("{} strips, {}", report.count, report.text());
+        if self.terminal {
+            eprint!("\r\x1b[K{}", text);
+        } else {
+            eprintln!("{}", text);
+        }
+        self.shown = true;
+    }
+
+    fn clear(&mut self) {
+        if self.terminal && self.shown {
+            eprint!("\r\x1b[K");
+        }
+    }
+}
+
+/// A shard's record gets its [`Part`] for `merge`, and its text the shard.
+fn shard_part(o: &Options, l: &Lattice, text: &mut String, fields: &mut Value, count: usize, complete: bool) {
+    if let Some(s) = o.shard {
+        fields["shard"] = json!(s.part(l, o.cyclic, &o.command, count as u64, complete));
+        *text = format!("Shard {}/{}: {}", s.index, s.of, text);
+    }
+}
+
+/// `merge`: add up the `--shard` results in the files given (JSON or NDJSON
+/// output of `count` or `enumerate`, or `strip_stream`'s), checking that all
+/// shards of one search of one lattice are there.
+fn merge(o: &Options) -> Result<i32, String> {
+    if o.positional.is_empty() {
+        return Err(format!("merge: no shard results given\n\n{}", USAGE));
+    }
+    let mut parts = Vec::new();
+    for path in &o.positional {
+        let content = if path == "-" {
+            read_stdin()?
+        } else {
+            std::fs::read_to_string(path).map_err(|e| format!("reading {} failed: {}", path, e))?
+        };
+        let part = Part::find(&content)
+            .ok_or_else(|| format!("{}: no shard result (from --shard with --format json)", path))?;
+        parts.push((path.clone(), part));
+    }
+    let merged = shard::merge(&parts)?;
+    let text = format!("Number of rhombic strips found: {} ({} shards)", merged.count, merged.shards);
+    finish(o, &text, record(o, json!(merged)));
+    Ok(0)
+}
+
+/// Largest automorphism group `paths --orbits` enumerates.
+const AUTOMORPHISM_LIMIT: usize = 100_000;
+
+/// `paths`: the number of strips over every hamiltonian path (cycle) of
+/// level 0, counted in parallel, optionally grouped into orbits of the
+/// automorphism group. The headline number is how many paths are dead —
+/// extend to no strip at all.
+fn paths(o: &Options) -> Result<i32, String> {
+    let l = Arc::new(load(o)?.lattice()?);
+    let (lj, cyclic) = (l.clone(), o.cyclic);
+    let job = move |_: &mut dyn FnMut(()) -> bool| rhombic::strips_per_path(&lj, cyclic);
+    let per_path = match run_job(o.timeout, job, |_| true)? {
+        Finish::Done(p) => p,
+        _ => {
+            finish(o, &timeout_text(o), record(o, json!({ "timeout": true })));
+            return Ok(EXIT_TIMEOUT);
+        }
+    };
+    let strips: usize = per_path.iter().map(|(_, n)| n).sum();
+    let dead = per_path.iter().filter(|(_, n)| *n == 0).count();
+    remember(o, &l, Known { count: Some(strips as u64), ..Known::default() });
+
+    // orbits: (automorphism count, orbit of every path)
+    let orbits = if !o.orbits {
+        None
+    } else if symmetry::ambiguous_bridges(&l) {
+        eprintln!("warning: some faces have several common covers; no orbits");
+        None
+    } else if let Some(autos) = symmetry::automorphisms(&l, AUTOMORPHISM_LIMIT) {
+        let paths: Vec<Vec<FaceId>> = per_path.iter().map(|(p, _)| p.clone()).collect();
+        Some((autos.len(), symmetry::path_orbits(&l, &paths, cyclic, &autos)))
+    } else {
+        eprintln!("warning: more than {} automorphisms; no orbits", AUTOMORPHISM_LIMIT);
+        None
+    };
+
+    // rows: one per path, or one per orbit (its first path standing for it)
+    struct Row {
+        index: usize,
+        orbit: Option<usize>,
+        size: usize,
+        count: usize,
+    }
+    let mut rows: Vec<Row> = match &orbits {
+        None => (0..per_path.len())
+            .map(|i| Row { index: i, orbit: None, size: 1, count: per_path[i].1 })
+            .collect(),
+        Some((_, orbit)) => {
+            let mut rows: Vec<Row> = Vec::new();
+            for (i, &k) in orbit.iter().enumerate() {
+                match rows.get_mut(k) {
+                    Some(row) => row.size += 1,
+                    None => rows.push(Row { index: i, orbit: Some(k), size: 1, count: per_path[i].1 }),
+                }
+            }
+            rows
+        }
+    };
+    if o.sort.as_deref() != Some("path") {
+        rows.sort_by_key(|r| (std::cmp::Reverse(r.count), r.index));
+    }
+    let dead_orbits = orbits.as_ref().map(|_| rows.iter().filter(|r| r.count == 0).count());
+
+    let row_json = |r: &Row| {
+        let path = &per_path[r.index].0;
+        let mut v = json!({ "index": r.index, "count": r.count, "path": path, "labels": labels(&l, path) });
+        if let Some(k) = r.orbit {
+            v["orbit"] = json!(k);
+            v["size"] = json!(r.size);
+        }
+        v
+    };
+    match o.format {
+        Format::Text => {
+            for r in &rows {
+                let size = r.orbit.map(|_| format!(" ×{}", r.size)).unwrap_or_default();
+                println!("{:>8}{}  {:?}", r.count, size, labels(&l, &per_path[r.index].0));
+            }
+        }
+        Format::Csv => {
+            println!("{}", if orbits.is_some() { "orbit,size,count,path" } else { "index,count,path" });
+            for r in &rows {
+                let path = labels(&l, &per_path[r.index].0).join(" ");
+                let path = if path.contains([',', '"']) {
+                    format!("\"{}\"", path.replace('"', "\"\""))
+                } else {
+                    path
+                };
+                match r.orbit {
+                    Some(k) => println!("{},{},{},{}", k, r.size, r.count, path),
+                    None => println!("{},{},{}", r.index, r.count, path),
+                }
+            }
+            return Ok(0);
+        }
+        Format::Ndjson => {
+            for r in &rows {
+                let mut v = row_json(r);
+                v["type"] = json!(if r.orbit.is_some() { "orbit" } else { "path" });
+                println!("{}", v);
+            }
+        }
+        Format::Json => {}
+    }
+
+    let kind = if cyclic { "cycles" } else { "paths" };
+    let mut text = format!(
+        "Hamiltonian {}: {}; rhombic strips: {}; {} extending to no strip: {}",
+        kind,
+        per_path.len(),
+        strips,
+        kind,
+        dead
+    );
+    let mut fields = json!({ "paths": per_path.len(), "strips": strips, "dead": dead });
+    if let (Some((autos, _)), Some(dead_orbits)) = (&orbits, dead_orbits) {
+        text += &format!(
+            "\n{} orbits under {} automorphisms, {} of them dead",
+            rows.len(),
+            autos,
+            dead_orbits
+        );
+        fields["automorphisms"] = json!(autos);
+        fields["orbits"] = json!(rows.len());
+        fields["dead_orbits"] = json!(dead_orbits);
+    }
+    if o.format == Format::Json {
+        fields["rows"] = rows.iter().map(row_json).collect();
+    }
+    finish(o, &text, record(o, fields));
+    Ok(0)
+}
+
+/// Seeds the paths of `enumerate` are split into, for its summary.
+const ENUMERATE_SEEDS: usize = 64;
+
+/// `enumerate` and `sample`: both stream the sequential search; `sample`
+/// keeps a uniform reservoir of `--limit` strips instead of the first ones.
+fn enumerate(o: &Options) -> Result<i32, String> {
+    if !o.aggregate.is_empty() {
+        return aggregate(o);
+    }
+    let started = Instant::now();
+    let l = Arc::new(load(o)?.lattice()?);
+    o.filter.matcher(&l, o.cyclic)?;
+    let (lj, cyclic, shard, filter) = (l.clone(), o.cyclic, o.shard, o.filter.clone());
+    let tally: Arc<OnceLock<Arc<Progress>>> = Arc::default();
+    let job_tally = tally.clone();
+    let job = move |emit: &mut dyn FnMut(Strip) -> bool| {
+        // the order of `rhombic::strips` (or of the shard's), seed by seed
+        let seeds = match shard {
+            Some(s) => s.seeds(&lj, cyclic),
+            None => lj.ham_path_seeds(cyclic, ENUMERATE_SEEDS),
+        };
+        let progress = job_tally.get_or_init(|| Arc::new(Progress::new(&seeds)));
+        let matcher = filter.matcher(&lj, cyclic).expect("the filter was checked");
+        for strip in progress.strips(&lj, cyclic, seeds, || false).filter(|s| matcher.matches(s)) {
+            if !emit(strip) {
+                break;
+            }
+        }
+    };
+
+    let sampling = o.command == "sample";
+    let seed = o.seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64)
+    });
+    let mut rng = Rng(seed);
+    let limit = if sampling { Some(o.limit.unwrap_or(1)) } else { o.limit };
+    let mut kept: Vec<(usize, Strip)> = Vec::new();
+    let mut seen = 0;
+
+    let output = |index: usize, strip: &Strip, kept: &mut Vec<(usize, Strip)>| {
+        if let Err(e) = show_strip(o, &l, strip, Some(index)) {
             eprintln!("{}", e);
-            std::process::exit(1);
         }
+        match o.format {
+            Format::Text | Format::Csv => print_strip_text(&l, strip),
+            Format::Ndjson => {
+                let mut v = strip_json(&l, strip, index);
+                v["type"] = json!("strip");
+                println!("{}", v);
+            }
+            Format::Json => kept.push((index, strip.clone())),
+        }
+    };
+
+    let finished = run_job(o.timeout, job, |strip| {
+        if !sampling && limit.is_some_and(|k| seen >= k) {
+            return false;
+        }
+        let index = seen;
+        seen += 1;
+        if sampling {
+            // reservoir sampling (algorithm R)
+            let k = limit.unwrap_or(1);
+            if kept.len() < k {
+                kept.push((index, strip));
+            } else if k > 0 {
+                let j = rng.below(seen as u64) as usize;
+                if j < k {
+                    kept[j] = (index, strip);
+                }
+            }
+            true
+        } else {
+            output(index, &strip, &mut kept);
+            limit.is_none_or(|k| seen < k)
+        }
+    })?;
+
+    let sampled = kept.len();
+    let mut shown: Vec<(usize, Strip)> = Vec::new();
+    if sampling {
+        kept.sort_by_key(|(i, _)| *i);
+        for (index, strip) in &kept {
+            output(*index, strip, &mut shown);
+        }
+    } else {
+        shown = kept;
+    }
+
+    let timed_out = matches!(finished, Finish::TimedOut);
+    let complete = matches!(finished, Finish::Done(()));
+    let filtered = !o.filter.is_empty();
+    if complete && !filtered {
+        remember(o, &l, Known { count: Some(seen as u64), ..Known::default() });
+    }
+    let mut fields = json!({ "count": seen, "complete": complete });
+    // the strips looked at: all that went past the filter
+    let searched = tally.get().map_or(0, |p| p.count());
+    if filtered {
+        fields["searched"] = json!(searched);
+    }
+    if sampling {
+        fields["seed"] = json!(seed);
+        fields["sample"] = json!(sampled);
+    }
+    if o.format == Format::Json {
+        fields["strips"] = shown.iter().map(|(i, s)| strip_json(&l, s, *i)).collect();
+    }
+    let mut text = if sampling {
+        format!("Sampled {} of {} rhombic strips (seed {}).", sampled, seen, seed)
+    } else if complete && filtered {
+        format!("Number of rhombic strips found: {} (of {} searched)", seen, searched)
+    } else if complete {
+        format!("Number of rhombic strips found: {}", seen)
+    } else {
+        format!("Listed {} rhombic strips (--limit reached).", seen)
     };
+    if timed_out {
+        fields["timeout"] = json!(true);
+        text = format!("{} {}", text.replace(" (--limit reached).", "."), timeout_text(o));
+    }
+    shard_part(o, &l, &mut text, &mut fields, seen, complete);
+    let summary = Summary { count: seen as u64, complete, threads: Some(1), ..summary(o, &l) };
+    summarize(o, &mut fields, with_search(summary, &tally), started);
+    finish(o, &text, record(o, fields));
+    Ok(if timed_out {
+        EXIT_TIMEOUT
+    } else if sampling && seen == 0 {
+        EXIT_NO
+    } else {
+        0
+    })
+}
 
-    let labels = |layer: &[lattice::FaceId]| -> Vec<String> {
-        layer
-            .iter()
-            .map(|&x| l.face(x).label().to_string())
-            .collect()
+/// `enumerate --aggregate`: tables of the strips `--filter` keeps, tallied
+/// in parallel over the seeds of the path search, instead of the strips.
+fn aggregate(o: &Options) -> Result<i32, String> {
+    let started = Instant::now();
+    let l = Arc::new(load(o)?.lattice()?);
+    o.filter.matcher(&l, o.cyclic)?;
+    let (lj, cyclic, shard, filter, kinds) = (l.clone(), o.cyclic, o.shard, o.filter.clone(), o.aggregate.clone());
+    let tally: Arc<OnceLock<Arc<Progress>>> = Arc::default();
+    let job_tally = tally.clone();
+    let job = move |emit: &mut dyn FnMut(Report) -> bool| {
+        let seeds = match shard {
+            Some(s) => s.seeds(&lj, cyclic),
+            None => lj.ham_path_seeds(cyclic, rayon::current_num_threads() * 16),
+        };
+        let progress = job_tally.get_or_init(|| Arc::new(Progress::new(&seeds)));
+        let matcher = filter.matcher(&lj, cyclic).expect("the filter was checked");
+        let empty = Tally::new(&kinds).expect("the aggregations were checked");
+        reporting(progress, emit, |stop| {
+            filter::tally_strips(&lj, cyclic, seeds, &matcher, &empty, progress, &|| stop.load(Ordering::Relaxed))
+        })
+    };
+    let mut line = ProgressLine::new(o);
+    let finished = run_job(o.timeout, job, |report| {
+        line.show(&report);
+        true
+    })?;
+    line.clear();
+    let summary = with_search(Summary { threads: Some(rayon::current_num_threads()), ..summary(o, &l) }, &tally);
+    let Finish::Done(Some(tally)) = finished else {
+        let (mut text, mut fields) = (timeout_text(o), json!({ "timeout": true }));
+        shard_part(o, &l, &mut text, &mut fields, 0, false);
+        let count = tally.get().map_or(0, |p| p.count());
+        summarize(o, &mut fields, Summary { count, complete: false, ..summary }, started);
+        finish(o, &text, record(o, fields));
+        return Ok(EXIT_TIMEOUT);
     };
 
-    if enumerate {
-        // split the total count among the hamilton paths/cycles of level 0
-        let mut total = 0;
-        for ham in l.ham_paths(cyclic) {
-            let n = extensions(vec![ham.clone()], &l, l.dim(), cyclic).count();
-            println!("{:?}: {}", labels(&ham), n);
-            total += n;
+    if o.filter.is_empty() {
+        remember(o, &l, Known { count: Some(tally.count), ..Known::default() });
+    }
+    let tables = tally.out(&l);
+    let mut text = format!("Number of rhombic strips found: {}", tally.count);
+    if !o.filter.is_empty() {
+        text += &format!(" (of {} searched)", tally.searched);
+    }
+    let mut fields = json!({ "count": tally.count, "complete": true });
+    if !o.filter.is_empty() {
+        fields["searched"] = json!(tally.searched);
+    }
+    match o.format {
+        Format::Ndjson => {
+            let rows = [("width", json!(tables.widths)), ("boundary", json!(tables.boundaries)), ("path", json!(tables.paths))];
+            for (kind, rows) in rows {
+                for mut row in rows.as_array().cloned().unwrap_or_default() {
+                    row["type"] = json!(kind);
+                    println!("{}", row);
+                }
+            }
+        }
+        Format::Json => {
+            if let (Value::Object(fields), Value::Object(tables)) = (&mut fields, json!(tables)) {
+                fields.extend(tables);
+            }
+        }
+        Format::Text | Format::Csv => {
+            if let Some(widths) = &tables.widths {
+                println!("Layer widths (bridges per layer):");
+                for w in widths {
+                    println!("{:>8}  level {}: {} bridges", w.count, w.level, w.width);
+                }
+            }
+            if let Some(pairs) = &tables.boundaries {
+                println!("Boundary chains (left | right):");
+                for p in pairs {
+                    println!("{:>8}  {} | {}", p.count, p.left, p.right);
+                }
+            }
+            if let Some(paths) = &tables.paths {
+                println!("Strips per hamiltonian path of level 0:");
+                for p in paths {
+                    println!("{:>8}  {:?}", p.count, p.labels);
+                }
+            }
         }
-        println!("Number of rhombic strips found: {}", total);
-        return;
     }
+    shard_part(o, &l, &mut text, &mut fields, tally.count as usize, true);
+    summarize(o, &mut fields, Summary { count: tally.count, ..summary }, started);
+    finish(o, &text, record(o, fields));
+    Ok(0)
+}
+
+/// Hamiltonian paths `info` counts per level without `--limit`.
+const INFO_PATHS: usize = 100_000;
+
+fn info(o: &Options) -> Result<i32, String> {
+    let l = load(o)?.lattice()?;
+    let levels: Vec<usize> = (0..l.num_levels()).map(|d| l.level(d).len()).collect();
+    let covers: usize = l.faces().map(|(_, f)| f.downset().len()).sum();
+    let graphs: Vec<BridgeGraph> = (0..l.num_levels()).map(|d| l.bridge_graph(d)).collect();
 
-    if count {
-        println!(
-            "Number of rhombic strips found: {}",
-            count_strips(&l, cyclic)
+    // hamiltonian paths per level, level by level until --timeout
+    let (cyclic, limit) = (o.cyclic, o.limit.unwrap_or(INFO_PATHS));
+    let searched = graphs.clone();
+    let mut counts: Vec<usize> = Vec::new();
+    let finished = run_job(
+        o.timeout,
+        move |emit| {
+            for g in &searched {
+                if !emit(g.count_ham_paths(cyclic, Some(limit))) {
+                    return;
+                }
+            }
+        },
+        |n| {
+            counts.push(n);
+            true
+        },
+    )?;
+    let timed_out = matches!(finished, Finish::TimedOut);
+
+    let kind = if o.cyclic { "cycles" } else { "paths" };
+    let mut text = format!(
+        "faces: {}\ndimension: {}\nfaces per level: {:?}\ncover relations: {}\n\
+         level-0 bridge graph: {} vertices, {} edges\n\
+         bridge graphs (hamiltonian {}, at most {} counted):",
+        l.num_faces(),
+        l.dim(),
+        levels,
+        covers,
+        graphs[0].num_vertices(),
+        graphs[0].num_edges(),
+        kind,
+        limit
+    );
+    let mut rows = Vec::new();
+    for (d, g) in graphs.iter().enumerate() {
+        let degrees = g.degree_sequence();
+        let components = g.components().len();
+        let count = counts.get(d).copied();
+        text += &format!(
+            "\n  level {}: {} vertices, {} edges, degrees {}..{}, {}, {}",
+            d,
+            g.num_vertices(),
+            g.num_edges(),
+            degrees.last().unwrap_or(&0),
+            degrees.first().unwrap_or(&0),
+            if components == 1 { "connected".to_string() } else { format!("{} components", components) },
+            match count {
+                Some(n) if n >= limit => format!("at least {} {}", n, kind),
+                Some(n) => format!("{} {}", n, kind),
+                None => format!("{} not counted", kind),
+            }
         );
-        return;
+        rows.push(json!({
+            "level": d,
+            "vertices": g.num_vertices(),
+            "edges": g.num_edges(),
+            "degrees": degrees,
+            "components": components,
+            "hamiltonian": count,
+            "complete": count.is_some_and(|n| n < limit),
+        }));
+    }
+    let mut fields = json!({
+        "faces": l.num_faces(),
+        "dim": l.dim(),
+        "levels": levels,
+        "covers": covers,
+        "bridge_graph": { "vertices": graphs[0].num_vertices(), "edges": graphs[0].num_edges() },
+        "bridge_graphs": rows,
+    });
+    if timed_out {
+        fields["timeout"] = json!(true);
+        text = format!("{}\n{}", text, timeout_text(o));
     }
+    finish(o, &text, record(o, fields));
+    Ok(if timed_out { EXIT_TIMEOUT } else { 0 })
+}
 
-    if show || show_cyclic || show_all {
-        let mut found = 0;
-        for strip in strips(&l, cyclic) {
-            plotting::show_strip(&strip, &l, show_cyclic);
-            for layer in &strip {
-                println!("{:?}", labels(layer));
+/// The bridge graph of `--level` in DOT, with that layer of the first strip
+/// of `--strips` drawn on it.
+fn dot_command(o: &Options) -> Result<i32, String> {
+    if o.strips.as_deref() == Some("-") && o.positional.iter().any(|p| p == "-") {
+        return Err("the lattice and the strips cannot both come from stdin".to_string());
+    }
+    let l = load(o)?.lattice()?;
+    if o.level >= l.num_levels() {
+        return Err(format!("--level {}: the lattice has levels 0 to {}", o.level, l.num_levels().saturating_sub(1)));
+    }
+    let strip = match &o.strips {
+        Some(path) => {
+            let (_, strip) = read_strips(path, &l)?.into_iter().next().ok_or("no strip in --strips")?;
+            rhombic::check_strip(&strip, &l, o.cyclic)?;
+            Some(strip)
+        }
+        None => None,
+    };
+    let path = strip.as_ref().map(|s| s[o.level].as_slice());
+    let dot = dot::level_dot(&l, o.level, path, o.cyclic);
+    match o.format {
+        Format::Text | Format::Csv => print!("{}", dot),
+        _ => finish(o, "", record(o, json!({ "level": o.level, "dot": dot }))),
+    }
+    Ok(0)
+}
+
+fn generate_command(o: &Options) -> Result<i32, String> {
+    let args = &o.positional;
+    if args.first().is_none_or(|f| f == "help") {
+        println!("{}", GENERATE_USAGE);
+        return Ok(if args.is_empty() { EXIT_USAGE } else { 0 });
+    }
+    if args[0] == "trees" {
+        let n = args.get(1).ok_or(GENERATE_USAGE)?;
+        let trees = generators::rooted_trees(n.parse().map_err(|_| format!("bad size '{}'", n))?)?;
+        match o.format {
+            Format::Text | Format::Csv => {
+                for t in &trees {
+                    let edges: Vec<String> =
+                        t.edges.iter().map(|(u, v)| format!("({}, {})", u, v)).collect();
+                    println!("[{}]", edges.join(", "));
+                }
             }
-            found += 1;
-            if !show_all {
-                break;
+            Format::Json => println!("{}", serde_json::to_string(&trees).unwrap()),
+            Format::Ndjson => trees.iter().for_each(|t| println!("{}", t.to_json())),
+        }
+        return Ok(0);
+    }
+    let poset = generate(args)?;
+    match o.format {
+        Format::Text | Format::Csv => print!("{}", poset.to_lattice_file()?),
+        Format::Json | Format::Ndjson => println!("{}", poset.to_json()),
+    }
+    Ok(0)
+}
+
+fn convert(o: &Options) -> Result<i32, String> {
+    let poset = load(o)?.poset()?;
+    match o.format {
+        Format::Text | Format::Csv => print!("{}", poset.to_lattice_file()?),
+        Format::Json | Format::Ndjson => println!("{}", poset.to_json()),
+    }
+    Ok(0)
+}
+
+fn verify(o: &Options) -> Result<i32, String> {
+    let path = o.strips.as_deref().ok_or("verify needs --strips FILE (or - for stdin)")?;
+    if path == "-" && o.positional.iter().any(|p| p == "-") {
+        return Err("the lattice and the strips cannot both come from stdin".to_string());
+    }
+    let l = load(o)?.lattice()?;
+    let strips = read_strips(path, &l)?;
+    if strips.is_empty() {
+        return Err("no strips to verify".to_string());
+    }
+
+    let mut errors = Vec::new();
+    for (index, strip) in &strips {
+        let result = rhombic::check_strip(strip, &l, o.cyclic);
+        match o.format {
+            Format::Text | Format::Csv => match &result {
+                Ok(()) => println!("strip {}: ok", index),
+                Err(e) => println!("strip {}: {}", index, e),
+            },
+            Format::Ndjson => println!(
+                "{}",
+                json!({ "type": "verify", "index": index, "valid": result.is_ok(),
+                        "error": result.as_ref().err() })
+            ),
+            Format::Json => {}
+        }
+        if let Err(e) = result {
+            errors.push(json!({ "index": index, "error": e }));
+        }
+    }
+
+    let valid = strips.len() - errors.len();
+    let text = format!("{} of {} strips are valid rhombic strips.", valid, strips.len());
+    let fields = json!({ "checked": strips.len(), "valid": valid, "errors": errors });
+    finish(o, &text, record(o, fields));
+    Ok(if errors.is_empty() { 0 } else { EXIT_NO })
+}
+
+/// The strips in `path` (`-`: stdin), see [`parse_strips`].
+fn read_strips(path: &str, l: &Lattice) -> Result<Vec<(usize, Strip)>, String> {
+    let content = if path == "-" {
+        read_stdin()?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("reading {} failed: {}", path, e))?
+    };
+    parse_strips(&content, l)
+}
+
+/// Strips as written by `enumerate` / `sample`: one JSON document with a
+/// `"strips"` array, or NDJSON lines (non-strip records are skipped), or
+/// bare arrays of face-id layers, one per line. A strip given by `"labels"`
+/// only is mapped back to face ids.
+fn parse_strips(content: &str, l: &Lattice) -> Result<Vec<(usize, Strip)>, String> {
+    let mut by_label: HashMap<&str, Option<FaceId>> = HashMap::new();
+    for (id, face) in l.faces() {
+        by_label
+            .entry(face.label())
+            .and_modify(|e| *e = None) // ambiguous
+            .or_insert(Some(id));
+    }
+    let to_strip = |v: &Value, fallback: usize| -> Result<Option<(usize, Strip)>, String> {
+        let bad = || format!("strip {}: expected layers of face ids or labels", fallback);
+        let (index, layers) = match v {
+            Value::Array(_) => (fallback, v),
+            Value::Object(m) => {
+                if m.get("type").is_some_and(|t| t != "strip") {
+                    return Ok(None);
+                }
+                let index = m.get("index").and_then(Value::as_u64).map_or(fallback, |i| i as usize);
+                match (m.get("layers"), m.get("labels")) {
+                    (Some(layers), _) => (index, layers),
+                    (None, Some(labels)) => {
+                        let labels: Vec<Vec<String>> =
+                            serde_json::from_value(labels.clone()).map_err(|_| bad())?;
+                        let strip = labels
+                            .iter()
+                            .map(|layer| {
+                                layer
+                                    .iter()
+                                    .map(|s| match by_label.get(s.as_str()) {
+                                        Some(Some(id)) => Ok(*id),
+                                        Some(None) => Err(format!("label '{}' is not unique; give face ids", s)),
+                                        None => Err(format!("unknown label '{}'", s)),
+                                    })
+                                    .collect()
+                            })
+                            .collect::<Result<Strip, String>>()?;
+                        return Ok(Some((index, strip)));
+                    }
+                    (None, None) => return Err(bad()),
+                }
             }
-            println!();
+            _ => return Err(bad()),
+        };
+        let strip: Strip = serde_json::from_value(layers.clone()).map_err(|_| bad())?;
+        Ok(Some((index, strip)))
+    };
+
+    if let Ok(Value::Object(doc)) = serde_json::from_str::<Value>(content) {
+        if let Some(Value::Array(strips)) = doc.get("strips") {
+            return strips
+                .iter()
+                .enumerate()
+                .filter_map(|(i, v)| to_strip(v, i).transpose())
+                .collect();
         }
-        if found == 0 {
-            println!("No rhombic strip exists!");
+    }
+    let mut out = Vec::new();
+    for (i, line) in content.lines().filter(|s| !s.trim().is_empty()).enumerate() {
+        let v: Value =
+            serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        out.extend(to_strip(&v, out.len())?);
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Results cache
+// ---------------------------------------------------------------------------
+
+fn open_cache(o: &Options) -> Option<Cache> {
+    match &o.cache {
+        // a shard's count is a part of one, not the lattice's
+        _ if o.no_cache || o.shard.is_some() => None,
+        Some(dir) => Some(Cache::open(dir)),
+        None => Cache::from_env(),
+    }
+}
+
+/// What the results cache knows about `l`.
+fn recall(o: &Options, l: &Lattice) -> Option<Known> {
+    open_cache(o)?.get(l, o.cyclic)
+}
+
+/// Add to the results cache; failing to is worth a warning, not an error.
+fn remember(o: &Options, l: &Lattice, known: Known) {
+    let Some(cache) = open_cache(o) else { return };
+    let name = o.gen.clone().or_else(|| o.positional.first().filter(|p| *p != "-").cloned());
+    if let Err(e) = cache.put(l, o.cyclic, name.as_deref(), known) {
+        eprintln!("warning: {}", e);
+    }
+}
+
+fn cache_command(o: &Options) -> Result<i32, String> {
+    let cache = open_cache(o).ok_or("the results cache is off (RHOMBIC_CACHE=off)")?;
+    match o.positional.first().map(String::as_str) {
+        Some("path") => println!("{}", cache.dir().display()),
+        Some("export") => {
+            let rows = cache.export()?;
+            match o.format {
+                Format::Text | Format::Csv => {
+                    println!("key,name,faces,dim,cyclic,exists,count");
+                    for r in &rows {
+                        let opt = |v: Option<String>| v.unwrap_or_default();
+                        let name = r.name.clone().unwrap_or_default();
+                        let name = if name.contains([',', '"']) {
+                            format!("\"{}\"", name.replace('"', "\"\""))
+                        } else {
+                            name
+                        };
+                        println!(
+                            "{},{},{},{},{},{},{}",
+                            r.key,
+                            name,
+                            r.faces,
+                            r.dim,
+                            r.cyclic,
+                            opt(r.exists.map(|e| e.to_string())),
+                            opt(r.count.map(|c| c.to_string()))
+                        );
+                    }
+                }
+                Format::Json => println!("{}", serde_json::to_string(&rows).unwrap()),
+                Format::Ndjson => rows.iter().for_each(|r| println!("{}", serde_json::to_string(r).unwrap())),
+            }
         }
-        return;
+        _ => return Err(format!("usage: rhombic_strips cache export|path\n\n{}", USAGE)),
     }
+    Ok(0)
+}
 
-    // default: existence check with early exit
-    if strip_exists(&l, cyclic) {
-        println!("A rhombic strip was found");
+// ---------------------------------------------------------------------------
+// Batch
+// ---------------------------------------------------------------------------
+
+/// `batch`: run `--mode` on every instance, each in its own child process
+/// (so a time limit really stops it, rayon pool and all), and collect a
+/// [`batch::Row`] table.
+fn batch(o: &Options) -> Result<i32, String> {
+    // instances: (name, `--gen` spec or file path)
+    let mut instances: Vec<(String, bool)> = Vec::new();
+    for spec in &o.ranges {
+        instances.extend(batch::expand_range(spec)?.into_iter().map(|g| (g, true)));
+    }
+    for path in &o.positional {
+        let p = std::path::Path::new(path);
+        if p.is_dir() {
+            // every lattice file in the directory; the scripts next to them are skipped
+            let mut files: Vec<String> = std::fs::read_dir(p)
+                .map_err(|e| format!("reading {} failed: {}", path, e))?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|f| f.is_file() && f.extension().is_none_or(|e| e == "json"))
+                .map(|f| f.to_string_lossy().into_owned())
+                .collect();
+            files.sort_by(|a, b| natural_order(a, b));
+            instances.extend(files.into_iter().map(|f| (f, false)));
+        } else {
+            instances.push((path.clone(), false));
+        }
+    }
+    if instances.is_empty() {
+        return Err(format!("batch: no instances (give files, directories or --range)\n\n{}", USAGE));
+    }
+
+    let out = o.out.as_ref().map(std::path::PathBuf::from);
+    let mut rows = match &out {
+        Some(path) => batch::read_table(path)?,
+        None => vec![],
+    };
+    let done = batch::done(&rows, o.retry_timeouts);
+    if out.is_none() && o.format == Format::Text {
+        println!("{}", batch::csv_header());
+    }
+
+    let (mut ran, mut skipped) = (0, 0);
+    for (name, generated) in &instances {
+        if done.contains(&(name.clone(), o.mode.clone(), o.cyclic)) {
+            skipped += 1;
+            continue;
+        }
+        let row = batch_instance(o, name, *generated);
+        ran += 1;
+        if let Some(path) = &out {
+            eprintln!(
+                "{}: {}{} ({:.2}s)",
+                row.instance,
+                row.result,
+                row.count.map(|c| format!(", {} strips", c)).unwrap_or_default(),
+                row.wall_time
+            );
+            rows.retain(|r| r.key() != row.key());
+            rows.push(row);
+            batch::write_table(path, &rows)?;
+        } else {
+            match o.format {
+                Format::Text | Format::Csv => println!("{}", batch::csv_line(&row)),
+                Format::Ndjson => println!("{}", serde_json::to_string(&row).unwrap()),
+                Format::Json => {}
+            }
+            rows.push(row);
+        }
+    }
+    if out.is_none() && o.format == Format::Json {
+        println!("{}", serde_json::to_string(&rows).unwrap());
+    }
+    eprintln!("batch: {} run, {} already in the table", ran, skipped);
+    Ok(0)
+}
+
+/// One batch instance: its size in-process, the search in a child.
+fn batch_instance(o: &Options, name: &str, generated: bool) -> batch::Row {
+    let mut row = batch::Row {
+        instance: name.to_string(),
+        faces: 0,
+        dim: 0,
+        mode: o.mode.clone(),
+        cyclic: o.cyclic,
+        result: "error".to_string(),
+        count: None,
+        wall_time: 0.0,
+        timeout: false,
+    };
+    let input = if generated { load_gen(name) } else { load_path(name) };
+    match input.and_then(|i| i.lattice()) {
+        Ok(l) => (row.faces, row.dim) = (l.num_faces(), l.dim()),
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            return row;
+        }
+    }
+
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            return row;
+        }
+    };
+    let mut cmd = std::process::Command::new(exe);
+    cmd.args([o.mode.as_str(), "--format", "json"]);
+    if o.cyclic {
+        cmd.arg("--cyclic");
+    }
+    if let Some(n) = o.threads {
+        cmd.args(["--threads", &n.to_string()]);
+    }
+    if let Some(t) = o.timeout {
+        cmd.args(["--timeout", &t.as_secs_f64().to_string()]);
+    }
+    if let Some(dir) = &o.cache {
+        cmd.args(["--cache", dir]);
+    }
+    if o.no_cache {
+        cmd.arg("--no-cache");
+    }
+    if generated {
+        cmd.args(["--gen", name]);
     } else {
-        println!("No rhombic strip exists!");
+        cmd.arg(name);
+    }
+    cmd.stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+
+    let start = Instant::now();
+    let child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            return row;
+        }
+    };
+    // The child enforces --timeout itself; this only backs it up (a child
+    // stuck outside the search, say building a huge lattice, is killed).
+    let grace = o.timeout.map(|t| t + Duration::from_secs(5));
+    let output = wait_with_deadline(child, grace);
+    row.wall_time = start.elapsed().as_secs_f64();
+    let Some(output) = output else {
+        (row.result, row.timeout) = ("timeout".to_string(), true);
+        return row;
+    };
+    let record: Value = serde_json::from_slice(&output.stdout).unwrap_or(Value::Null);
+    match output.status.code() {
+        Some(0) | Some(EXIT_NO) if o.mode == "exists" => {
+            row.result = if record["exists"] == true { "yes" } else { "no" }.to_string();
+        }
+        Some(0) if o.mode == "count" => {
+            row.count = record["count"].as_u64();
+            row.result = if row.count > Some(0) { "yes" } else { "no" }.to_string();
+        }
+        Some(EXIT_TIMEOUT) => (row.result, row.timeout) = ("timeout".to_string(), true),
+        _ => eprintln!("{}: {}", name, String::from_utf8_lossy(&output.stderr).trim()),
+    }
+    row
+}
+
+/// Wait for `child`, killing it once `limit` has passed (then `None`).
+fn wait_with_deadline(
+    mut child: std::process::Child,
+    limit: Option<Duration>,
+) -> Option<std::process::Output> {
+    let Some(limit) = limit else {
+        return child.wait_with_output().ok();
+    };
+    // read the pipes on the side so a chatty child cannot block on them
+    let stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut s) = stdout {
+            let _ = s.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let deadline = Instant::now() + limit;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                let mut stderr = Vec::new();
+                if let Some(mut s) = child.stderr.take() {
+                    let _ = s.read_to_end(&mut stderr);
+                }
+                let stdout = reader.join().unwrap_or_default();
+                return Some(std::process::Output { status, stdout, stderr });
+            }
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(20))
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+}
+
+/// Compare names with embedded numbers by value, so `fence_distributed_9`
+/// comes before `fence_distributed_10`.
+fn natural_order(a: &str, b: &str) -> std::cmp::Ordering {
+    fn chunks(s: &str) -> Vec<(String, u64)> {
+        let mut out = Vec::new();
+        let mut chars = s.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            let digit = c.is_ascii_digit();
+            let mut chunk = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() == digit) {
+                chunk.push(c);
+                chars.next();
+            }
+            let value = if digit { chunk.parse().unwrap_or(u64::MAX) } else { 0 };
+            out.push(if digit { (String::new(), value) } else { (chunk, 0) });
+        }
+        out
+    }
+    chunks(a).cmp(&chunks(b)).then_with(|| a.cmp(b))
+}
+
+/// SplitMix64: small, seedable and plenty for picking sample strips.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next() as u128 * n as u128) >> 64) as u64
     }
 }
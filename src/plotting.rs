@@ -4,8 +4,16 @@ use std::fs::File;
 use std::io::Write;
 use std::process::Command;
 
+use serde::Serialize;
+
+use crate::dot;
 use crate::lattice::{FaceId, Lattice};
+use crate::layout;
 use crate::rhombic::{Layer, Strip};
+use crate::tikz::{self, Picture, TikzOptions};
+
+/// Edges as pairs of face ids.
+pub type EdgeList = Vec<(FaceId, FaceId)>;
 
 /// Edges of a rhombic strip as pairs of face ids.
 /// Cyclic closing edges are returned separately, since they are drawn
@@ -75,22 +83,66 @@ pub fn edges_strip(
     (edges, cyclic_edges)
 }
 
-// takes a rhombic strip as layers and generates TikZ code to visualize it,
-// sized to fit a4 landscape with 10mm margins; the code is compiled with
-// pdflatex and the resulting PDF is opened with the default viewer
+/// A strip laid out for drawing: face positions (y grows with the layer)
+/// and the edges between consecutive layers. The same layout feeds the
+/// TikZ of [`show_strip`], [`strip_svg`], [`strip_png`], the desktop GUI
+/// and the browser.
+#[derive(Serialize, Clone, Debug)]
+pub struct StripLayout {
+    pub cyclic: bool,
+    /// `(face, x, y)`, layer by layer.
+    pub nodes: Vec<(FaceId, f64, f64)>,
+    pub edges: Vec<LaidEdge>,
+}
 
-pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LaidEdge {
+    /// The face in the lower layer.
+    pub from: FaceId,
+    pub to: FaceId,
+    /// One of the closing edges of a cyclic strip.
+    pub closing: bool,
+    /// Cyclic layout: the curve leaves `from` at the first angle and
+    /// enters `to` from the second (degrees, as TikZ's `to[out=, in=]`).
+    /// Straight lines in the linear layout.
+    pub bend: Option<(f64, f64)>,
+}
+
+impl StripLayout {
+    /// `(min_x, min_y, max_x, max_y)`; the unit square around the origin
+    /// for an empty strip.
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        if self.nodes.is_empty() {
+            return (-1.0, -1.0, 1.0, 1.0);
+        }
+        self.nodes.iter().fold(
+            (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            |(x0, y0, x1, y1), &(_, x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        )
+    }
+
+    fn position(&self, face: FaceId) -> (f64, f64) {
+        self.nodes
+            .iter()
+            .find(|n| n.0 == face)
+            .map(|&(_, x, y)| (x, y))
+            .expect("edge between faces of the layout")
+    }
+}
+
+/// Lay out a strip: layers as rows in strip order, spaced by the layered
+/// layout of [`crate::layout`] (the linear layout), or as concentric rings
+/// around level 0 with every face placed at the mean angle of its
+/// neighbours below and curved, staggered edges (`cyclic`, for cyclic
+/// strips). `cyclic` also selects the closing edges, as in [`edges_strip`].
+pub fn strip_layout(layers: &[Layer], l: &Lattice, cyclic: bool) -> StripLayout {
     let (non_cyclic_edges, cyclic_edges) = edges_strip(layers, l, cyclic);
+    let closing_count = cyclic_edges.len();
     let edges = [non_cyclic_edges, cyclic_edges].concat();
+    let first_closing = edges.len() - closing_count;
 
-    // 1. Calculate Layout (Coordinates)
     let mut coords: HashMap<FaceId, (f64, f64)> = HashMap::new();
-
-    // Bounds for viewport calculation
-    let mut max_x: f64 = f64::NEG_INFINITY;
-    let mut min_x: f64 = f64::INFINITY;
-    let mut max_y: f64 = f64::NEG_INFINITY;
-    let mut min_y: f64 = f64::INFINITY;
+    let mut nodes = Vec::new();
 
     // Map face_idx -> layer_idx for quick lookup
     let mut face_to_layer_idx: HashMap<FaceId, usize> = HashMap::new();
@@ -111,6 +163,20 @@ pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
         a
     };
 
+    // Linear layout: the layers in strip order, x from the layered layout
+    // of the strip's edges
+    let mut linear_x: HashMap<FaceId, f64> = HashMap::new();
+    if !cyclic {
+        let faces: Vec<FaceId> = layers.iter().flatten().copied().collect();
+        let local: HashMap<FaceId, usize> = faces.iter().enumerate().map(|(i, &f)| (f, i)).collect();
+        let rank: Vec<usize> = faces.iter().map(|f| face_to_layer_idx[f]).collect();
+        let local_edges: Vec<(usize, usize)> = edges.iter().map(|(u, v)| (local[u], local[v])).collect();
+        let rows: Vec<Vec<usize>> =
+            layers.iter().map(|layer| layer.iter().map(|f| local[f]).collect()).collect();
+        let xs = layout::layered(&rank, &local_edges, Some(&rows));
+        linear_x = faces.iter().zip(xs).map(|(&f, (x, _))| (f, x)).collect();
+    }
+
     // Store calculated angles (radians)
     let mut face_angles: HashMap<FaceId, f64> = HashMap::new();
     let mut layer_radii: Vec<f64> = Vec::with_capacity(layers.len());
@@ -218,58 +284,47 @@ pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
                 y = radius * theta.sin();
             } else {
                 y = layer_idx as f64;
-                x = (i as f64) - (count - 1.0) / 2.0;
+                x = linear_x.get(&face_idx).copied().unwrap_or((i as f64) - (count - 1.0) / 2.0);
             }
 
             coords.insert(face_idx, (x, y));
-
-            if x > max_x {
-                max_x = x;
-            }
-            if x < min_x {
-                min_x = x;
-            }
-            if y > max_y {
-                max_y = y;
-            }
-            if y < min_y {
-                min_y = y;
-            }
+            nodes.push((face_idx, x, y));
         }
     }
 
-    // Safety fallback
-    if min_x == f64::INFINITY {
-        min_x = -1.0;
-        max_x = 1.0;
-        min_y = -1.0;
-        max_y = 1.0;
+    // Edges oriented upwards, each once
+    let mut laid: Vec<LaidEdge> = Vec::new();
+    for (k, &(u, v)) in edges.iter().enumerate() {
+        let (from, to) = if face_to_layer_idx[&u] <= face_to_layer_idx[&v] { (u, v) } else { (v, u) };
+        if !laid.iter().any(|e| (e.from, e.to) == (from, to)) {
+            laid.push(LaidEdge { from, to, closing: k >= first_closing, bend: None });
+        }
     }
 
-    // --- Prepare Edge Staggering (Cyclic Mode) ---
-    // Maps (source, target) -> (out_angle, in_angle) in degrees
-    let mut edge_draw_angles: HashMap<(FaceId, FaceId), (f64, f64)> = HashMap::new();
-
+    // --- Edge Staggering (Cyclic Mode) ---
     if cyclic {
         // Build Adjacency Lists
         // outgoing: u -> [v1, v2...] (where v is in next layer)
         // incoming: v -> [u1, u2...] (where u is in prev layer)
         let mut outgoing: HashMap<FaceId, Vec<FaceId>> = HashMap::new();
         let mut incoming: HashMap<FaceId, Vec<FaceId>> = HashMap::new();
-
-        for (u, v) in &edges {
-            let u_layer = face_to_layer_idx[u];
-            let v_layer = face_to_layer_idx[v];
-
-            if u_layer + 1 == v_layer {
-                outgoing.entry(*u).or_default().push(*v);
-                incoming.entry(*v).or_default().push(*u);
-            } else if v_layer + 1 == u_layer {
-                outgoing.entry(*v).or_default().push(*u);
-                incoming.entry(*u).or_default().push(*v);
+        for e in &laid {
+            if face_to_layer_idx[&e.from] + 1 == face_to_layer_idx[&e.to] {
+                outgoing.entry(e.from).or_default().push(e.to);
+                incoming.entry(e.to).or_default().push(e.from);
             }
         }
 
+        // Maps (source, target) -> (out_angle, in_angle) in degrees
+        let mut edge_draw_angles: HashMap<(FaceId, FaceId), (f64, f64)> = HashMap::new();
+        let spread = 25.0; // Total spread in degrees
+        let stagger = |n: usize| {
+            let n = n as f64;
+            let start_offset = if n > 1.0 { -spread / 2.0 } else { 0.0 };
+            let step = if n > 1.0 { spread / (n - 1.0) } else { 0.0 };
+            (start_offset, step)
+        };
+
         // 1. Calculate Outgoing Angles (Staggered)
         for (u, targets) in &outgoing {
             let u_angle = face_angles[u];
@@ -283,11 +338,7 @@ pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
                 da.partial_cmp(&db).unwrap()
             });
 
-            let n = sorted_targets.len() as f64;
-            let spread = 25.0; // Total spread in degrees
-            let start_offset = if n > 1.0 { -spread / 2.0 } else { 0.0 };
-            let step = if n > 1.0 { spread / (n - 1.0) } else { 0.0 };
-
+            let (start_offset, step) = stagger(sorted_targets.len());
             for (i, &v) in sorted_targets.iter().enumerate() {
                 let out_deg: f64;
                 if u_is_center {
@@ -298,8 +349,6 @@ pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
                     let base_deg = u_angle.to_degrees();
                     out_deg = base_deg + start_offset + (i as f64 * step);
                 }
-
-                // Store partially
                 edge_draw_angles.insert((*u, v), (out_deg, 0.0));
             }
         }
@@ -316,134 +365,41 @@ pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
                 da.partial_cmp(&db).unwrap()
             });
 
-            let n = sorted_sources.len() as f64;
-            let spread = 25.0;
-            let start_offset = if n > 1.0 { -spread / 2.0 } else { 0.0 };
-            let step = if n > 1.0 { spread / (n - 1.0) } else { 0.0 };
-
+            let (start_offset, step) = stagger(sorted_sources.len());
             for (i, &u) in sorted_sources.iter().enumerate() {
                 // Radial IN (pointing to center) is angle + 180
-                let base_deg = v_angle.to_degrees() + 180.0;
-                let in_deg = base_deg + start_offset + (i as f64 * step);
-
-                // Update the tuple
+                let in_deg = v_angle.to_degrees() + 180.0 + start_offset + (i as f64 * step);
                 if let Some(entry) = edge_draw_angles.get_mut(&(u, *v)) {
                     entry.1 = in_deg;
                 }
             }
         }
-    }
-
-    // --- Standard Visualization Code ---
 
-    // Calculate max label length
-    let mut max_label_len: usize = 0;
-    for &face_idx in coords.keys() {
-        max_label_len = max_label_len.max(l.face(face_idx).label().len());
-    }
-
-    // Calculate Scaling
-    let paper_w_cm = 27.7;
-    let paper_h_cm = 17.0;
-
-    let content_w = (max_x - min_x).max(1.0) + 2.0;
-    let content_h = (max_y - min_y).max(1.0) + 2.0;
-
-    let mut scale_x = paper_w_cm / content_w;
-    let mut scale_y = paper_h_cm / content_h;
-
-    if cyclic {
-        let min_scale = scale_x.min(scale_y);
-        scale_x = min_scale;
-        scale_y = min_scale;
-    }
-
-    // Calculate Font Size
-    let char_width_cm = 0.22;
-    let node_padding_cm = 0.4;
-    let needed_width_cm = (max_label_len as f64 * char_width_cm) + node_padding_cm;
-    let needed_height_cm = 0.8;
-
-    let available_width_cm = scale_x;
-    let available_height_cm = scale_y;
-    let ratio_x = available_width_cm / needed_width_cm;
-    let ratio_y = available_height_cm / needed_height_cm;
-
-    let font_scale_factor = ratio_x.min(ratio_y).min(1.0);
-    let font_size_pt = (10.0 * font_scale_factor).max(2.0);
-
-    // Generate LaTeX
-    let mut tikz = String::new();
-    tikz.push_str("\\documentclass{article}\n");
-    tikz.push_str("\\usepackage{tikz}\n");
-    tikz.push_str("\\usepackage[landscape, a4paper, margin=10mm]{geometry}\n");
-    tikz.push_str("\\begin{document}\n");
-    tikz.push_str("\\thispagestyle{empty}\n");
-
-    tikz.push_str(&format!(
-        "\\begin{{tikzpicture}}[xscale={:.4}, yscale={:.4}]\n",
-        scale_x, scale_y
-    ));
-
-    let inner_sep = if font_size_pt < 4.0 { 1.0 } else { 2.5 };
-
-    tikz.push_str(&format!(
-        "  \\tikzset{{every node/.style={{draw, fill=white, inner sep={}pt, font=\\fontsize{{{}pt}}{{{}pt}}\\selectfont}}}}\n",
-        inner_sep, font_size_pt, font_size_pt * 1.2
-    ));
-
-    // Define Coordinates
-    for (face_idx, (x, y)) in &coords {
-        tikz.push_str(&format!(
-            "  \\coordinate (n{}) at ({:.3},{:.3});\n",
-            face_idx, x, y
-        ));
-    }
-
-    // Draw Edges
-    if cyclic {
-        // Only draw processed edges, keyed (u, v) with u in layer k, v in k+1
-        for ((u, v), (out_deg, in_deg)) in &edge_draw_angles {
-            tikz.push_str(&format!(
-                "  \\draw (n{}) to[out={:.1}, in={:.1}] (n{});\n",
-                u, out_deg, in_deg, v
-            ));
-        }
-    } else {
-        // Non-cyclic: Straight lines
-        let edge_list: Vec<String> = edges
-            .iter()
-            .map(|(a, b)| format!("n{}/n{}", a, b))
-            .collect();
-
-        if !edge_list.is_empty() {
-            tikz.push_str(&format!(
-                "  \\foreach \\u/\\v in {{{}}} {{\n",
-                edge_list.join(",")
-            ));
-            tikz.push_str("    \\draw (\\u) -- (\\v);\n");
-            tikz.push_str("  }\n");
+        // Only edges between consecutive layers are drawn
+        laid.retain(|e| edge_draw_angles.contains_key(&(e.from, e.to)));
+        for e in &mut laid {
+            e.bend = edge_draw_angles.get(&(e.from, e.to)).copied();
         }
     }
 
-    // Draw Nodes
-    let mut node_defs = Vec::new();
-    for &face_idx in coords.keys() {
-        let safe_label = l.face(face_idx).label().replace('_', "\\_");
-        node_defs.push(format!("n{}/{}", face_idx, safe_label));
-    }
+    StripLayout { cyclic, nodes, edges: laid }
+}
 
-    if !node_defs.is_empty() {
-        tikz.push_str(&format!(
-            "  \\foreach \\ref/\\lbl in {{{}}} {{\n",
-            node_defs.join(",")
-        ));
-        tikz.push_str("    \\node at (\\ref) {\\lbl};\n");
-        tikz.push_str("  }\n");
-    }
+// takes a rhombic strip as layers and generates TikZ code to visualize it
+// (by default sized to fit a4 landscape with 10mm margins); the code is
+// compiled with pdflatex and the resulting PDF is opened with the default
+// viewer
 
-    tikz.push_str("\\end{tikzpicture}\n");
-    tikz.push_str("\\end{document}\n");
+pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool, options: &TikzOptions) {
+    let options = TikzOptions { standalone: true, ..options.clone() };
+    let picture = Picture::of_strip(layers, &strip_layout(layers, l, cyclic), l);
+    let tikz = match tikz::tikz(&picture, &options) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
 
     // Output and Compile
     let filename = if cyclic {
@@ -491,3 +447,678 @@ pub fn show_strip(layers: &Strip, l: &Lattice, cyclic: bool) {
 
     let _ = command.spawn();
 }
+
+// ---------------------------------------------------------------------------
+// Step-by-step construction
+// ---------------------------------------------------------------------------
+
+/// One step of building a strip: the faces and edges it adds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub caption: String,
+    pub faces: Vec<FaceId>,
+    pub edges: EdgeList,
+}
+
+/// How each layer arises from the one below: level 0 first, then for every
+/// further level its bridges (with the edges to the two faces each covers)
+/// and then its gap faces (with their edges and, for cyclic strips, the
+/// closing edge). The edges are those of [`edges_strip`]; steps that add
+/// nothing are left out.
+pub fn construction_steps(layers: &[Layer], l: &Lattice, cyclic: bool) -> Vec<Step> {
+    let Some(first) = layers.first() else { return vec![] };
+    let (edges, closing) = edges_strip(layers, l, cyclic);
+    let path = if cyclic { "hamiltonian cycle" } else { "hamiltonian path" };
+    let mut steps = vec![Step { caption: format!("Level 0: the {}", path), faces: first.clone(), edges: vec![] }];
+
+    for (k, pair) in layers.windows(2).enumerate() {
+        let (below, above) = (&pair[0], &pair[1]);
+        let bridges: Vec<FaceId> = below.windows(2).filter_map(|w| l.bridge(w[0], w[1])).collect();
+        // the end of an edge between the two layers that lies in `above`
+        let upper = |&(u, v): &(FaceId, FaceId)| {
+            if below.contains(&u) && above.contains(&v) {
+                Some(v)
+            } else if below.contains(&v) && above.contains(&u) {
+                Some(u)
+            } else {
+                None
+            }
+        };
+        let (bridge_edges, gap_edges): (EdgeList, EdgeList) = edges
+            .iter()
+            .filter(|e| upper(e).is_some())
+            .partition(|e| upper(e).is_some_and(|f| bridges.contains(&f)));
+        let (bridge_faces, gap_faces): (Vec<FaceId>, Vec<FaceId>) =
+            above.iter().partition(|f| bridges.contains(f));
+
+        steps.push(Step { caption: format!("Level {}: bridges", k + 1), faces: bridge_faces, edges: bridge_edges });
+        let mut gap = Step { caption: format!("Level {}: gap faces", k + 1), faces: gap_faces, edges: gap_edges };
+        let closes: Vec<_> = closing.iter().filter(|e| upper(e).is_some()).collect();
+        if !closes.is_empty() {
+            gap.caption += " and closing edge";
+            gap.edges.extend(closes);
+        }
+        steps.push(gap);
+    }
+    steps.retain(|s| !s.faces.is_empty() || !s.edges.is_empty());
+    steps
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Look {
+    Hidden,
+    Plain,
+    /// Added in the step shown.
+    New,
+}
+
+/// The frames of a construction: one per step, then the finished strip.
+struct Frames {
+    captions: Vec<String>,
+    /// Step adding each face, and each edge (both orientations).
+    faces: HashMap<FaceId, usize>,
+    edges: HashMap<(FaceId, FaceId), usize>,
+}
+
+impl Frames {
+    fn new(steps: &[Step], cyclic: bool) -> Self {
+        let mut frames = Frames { captions: vec![], faces: HashMap::new(), edges: HashMap::new() };
+        for (k, step) in steps.iter().enumerate() {
+            frames.captions.push(step.caption.clone());
+            frames.faces.extend(step.faces.iter().map(|&f| (f, k)));
+            for &(u, v) in &step.edges {
+                frames.edges.insert((u, v), k);
+                frames.edges.insert((v, u), k);
+            }
+        }
+        frames.captions.push(if cyclic { "The cyclic strip" } else { "The strip" }.to_string());
+        frames
+    }
+
+    fn len(&self) -> usize {
+        self.captions.len()
+    }
+}
+
+/// What a picture shows: the whole strip, or one frame of a construction.
+#[derive(Clone, Copy)]
+enum Shown<'a> {
+    Strip,
+    Frame(&'a Frames, usize),
+}
+
+impl<'a> Shown<'a> {
+    fn look(self, step: impl FnOnce(&Frames) -> Option<usize>) -> Look {
+        match self {
+            Shown::Strip => Look::Plain,
+            Shown::Frame(frames, frame) => match step(frames) {
+                Some(s) if s == frame => Look::New,
+                Some(s) if s < frame => Look::Plain,
+                _ => Look::Hidden,
+            },
+        }
+    }
+
+    fn face(self, face: FaceId) -> Look {
+        self.look(|f| f.faces.get(&face).copied())
+    }
+
+    fn edge(self, from: FaceId, to: FaceId) -> Look {
+        self.look(|f| f.edges.get(&(from, to)).copied())
+    }
+
+    fn caption(self) -> Option<&'a str> {
+        match self {
+            Shown::Strip => None,
+            Shown::Frame(frames, frame) => Some(&frames.captions[frame]),
+        }
+    }
+}
+
+fn tex_escape(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '_' | '{' | '}' | '&' | '%' | '#' | '$' => format!("\\{}", c),
+            '|' => "\\textbar{}".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// A LaTeX document with one page per frame of the construction (a
+/// `standalone` PDF of equally sized pages): what is built so far, the new
+/// faces and edges in colour, and the step as a caption.
+pub fn construction_tikz(layout: &StripLayout, l: &Lattice, steps: &[Step]) -> String {
+    let frames = Frames::new(steps, layout.cyclic);
+    let (min_x, min_y, max_x, max_y) = layout.bounds();
+    let max_label_len = layout.nodes.iter().map(|n| l.face(n.0).label().len()).max().unwrap_or(0);
+    let xscale = (max_label_len as f64 * 0.2 + 0.5).max(1.2);
+    let (xscale, yscale) = if layout.cyclic { (xscale / 1.2, xscale / 1.2) } else { (xscale, 1.5) };
+
+    let mut tex = String::new();
+    tex.push_str("\\documentclass[tikz, border=5mm]{standalone}\n");
+    tex.push_str("\\definecolor{new}{HTML}{E66100}\n");
+    tex.push_str("\\begin{document}\n");
+    for frame in 0..frames.len() {
+        let shown = Shown::Frame(&frames, frame);
+        tex.push_str(&format!(
+            "\\begin{{tikzpicture}}[xscale={:.3}, yscale={:.3}, face/.style={{draw, fill=white, inner sep=2pt, font=\\small}}]\n",
+            xscale, yscale
+        ));
+        // every page gets the same size and the caption the same place
+        let (left, top) = (min_x - 0.8, max_y + 1.0);
+        tex.push_str(&format!(
+            "  \\path[use as bounding box] ({:.3},{:.3}) rectangle ({:.3},{:.3});\n",
+            left,
+            min_y - 0.5,
+            max_x + 0.8,
+            top
+        ));
+        tex.push_str(&format!(
+            "  \\node[anchor=north west] at ({:.3},{:.3}) {{{}}};\n",
+            left,
+            top,
+            tex_escape(&frames.captions[frame])
+        ));
+        for (face, x, y) in &layout.nodes {
+            tex.push_str(&format!("  \\coordinate (n{}) at ({:.3},{:.3});\n", face, x, y));
+        }
+        for e in &layout.edges {
+            let mut style = match shown.edge(e.from, e.to) {
+                Look::Hidden => continue,
+                Look::Plain if e.closing => vec!["new"],
+                Look::Plain => vec![],
+                Look::New => vec!["new", "very thick"],
+            };
+            if e.closing {
+                style.push("dashed");
+            }
+            let path = match e.bend {
+                Some((out_deg, in_deg)) => format!("to[out={:.1}, in={:.1}]", out_deg, in_deg),
+                None => "--".to_string(),
+            };
+            tex.push_str(&format!("  \\draw[{}] (n{}) {} (n{});\n", style.join(", "), e.from, path, e.to));
+        }
+        for &(face, _, _) in &layout.nodes {
+            let style = match shown.face(face) {
+                Look::Hidden => continue,
+                Look::Plain => "face",
+                Look::New => "face, draw=new, thick",
+            };
+            tex.push_str(&format!("  \\node[{}] at (n{}) {{{}}};\n", style, face, tex_escape(l.face(face).label())));
+        }
+        tex.push_str("\\end{tikzpicture}\n");
+    }
+    tex.push_str("\\end{document}\n");
+    tex
+}
+
+// ---------------------------------------------------------------------------
+// SVG and PNG
+// ---------------------------------------------------------------------------
+
+const INK: (u8, u8, u8) = (0x23, 0x26, 0x2e);
+const VERMILLION: (u8, u8, u8) = (0xe6, 0x61, 0x00);
+const FONT_PX: f64 = 12.0;
+/// Advance of a character of the monospace font, in em.
+const CHAR_EM: f64 = 0.6;
+const MARGIN_PX: f64 = 16.0;
+/// Room for the caption line of construction frames.
+const CAPTION_PX: f64 = 24.0;
+/// How long the animated SVG shows each frame.
+const FRAME_SECONDS: f64 = 1.5;
+
+type Point = (f64, f64);
+
+struct SceneNode {
+    face: FaceId,
+    at: Point,
+    label: String,
+    /// Width of the box.
+    width: f64,
+}
+
+struct SceneEdge {
+    from: FaceId,
+    to: FaceId,
+    start: Point,
+    /// Bezier control points; none for straight lines.
+    controls: Option<[Point; 2]>,
+    end: Point,
+    closing: bool,
+}
+
+/// A laid-out strip in pixels (y down), what both renderers draw.
+struct Scene {
+    width: f64,
+    height: f64,
+    nodes: Vec<SceneNode>,
+    edges: Vec<SceneEdge>,
+}
+
+impl Scene {
+    /// With `caption`, a line is left free above the strip.
+    fn new(layout: &StripLayout, l: &Lattice, caption: bool) -> Self {
+        let label_width = |s: &str| s.chars().count() as f64 * CHAR_EM * FONT_PX + 8.0;
+        let widest = layout.nodes.iter().map(|n| label_width(l.face(n.0).label())).fold(0.0, f64::max);
+        // pixels per layout unit: neighbouring boxes must not overlap
+        let sx = (widest + 16.0).max(48.0);
+        let sy = if layout.cyclic { sx / 1.2 } else { 64.0 };
+        let sx = if layout.cyclic { sy } else { sx };
+
+        let (min_x, min_y, max_x, max_y) = layout.bounds();
+        let pad_x = MARGIN_PX + widest / 2.0;
+        let pad_y = MARGIN_PX + FONT_PX;
+        let top = pad_y + if caption { CAPTION_PX } else { 0.0 };
+        let at = |x: f64, y: f64| ((x - min_x) * sx + pad_x, (max_y - y) * sy + top);
+
+        let nodes = layout
+            .nodes
+            .iter()
+            .map(|&(face, x, y)| {
+                let label = l.face(face).label().to_string();
+                SceneNode { face, at: at(x, y), width: label_width(&label), label }
+            })
+            .collect();
+        let edges = layout
+            .edges
+            .iter()
+            .map(|e| {
+                let (a, b) = (layout.position(e.from), layout.position(e.to));
+                let (p, q) = (at(a.0, a.1), at(b.0, b.1));
+                // TikZ's `to[out, in]`: control points at 0.3915 times the distance
+                let controls = e.bend.map(|(out_deg, in_deg)| {
+                    let d = 0.3915 * (q.0 - p.0).hypot(q.1 - p.1);
+                    let (o, i) = (out_deg.to_radians(), in_deg.to_radians());
+                    [(p.0 + d * o.cos(), p.1 - d * o.sin()), (q.0 + d * i.cos(), q.1 - d * i.sin())]
+                });
+                SceneEdge { from: e.from, to: e.to, start: p, controls, end: q, closing: e.closing }
+            })
+            .collect();
+        Scene {
+            width: ((max_x - min_x) * sx + 2.0 * pad_x).ceil(),
+            height: ((max_y - min_y) * sy + pad_y + top).ceil(),
+            nodes,
+            edges,
+        }
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn svg_header(scene: &Scene) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"Hack, DejaVu Sans Mono, monospace\" font-size=\"{f}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        w = scene.width,
+        h = scene.height,
+        f = FONT_PX
+    )
+}
+
+/// The picture of `scene` as SVG elements: the caption, then edges, boxes
+/// and labels; new elements are drawn in vermillion.
+fn svg_picture(scene: &Scene, shown: Shown) -> String {
+    let mut svg = String::new();
+    if let Some(caption) = shown.caption() {
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+            MARGIN_PX,
+            MARGIN_PX + FONT_PX,
+            hex(INK),
+            xml_escape(caption)
+        );
+    }
+    for look in [Look::Plain, Look::New] {
+        for closing in [false, true] {
+            let edges: Vec<&SceneEdge> = scene
+                .edges
+                .iter()
+                .filter(|e| e.closing == closing && shown.edge(e.from, e.to) == look)
+                .collect();
+            if edges.is_empty() {
+                continue;
+            }
+            let colour = if closing || look == Look::New { VERMILLION } else { INK };
+            let width = if look == Look::New { 2.5 } else { 1.5 };
+            let dash = if closing { " stroke-dasharray=\"6 4\"" } else { "" };
+            svg += &format!("<g fill=\"none\" stroke-width=\"{}\" stroke=\"{}\"{}>\n", width, hex(colour), dash);
+            for e in edges {
+                let (p, q) = (e.start, e.end);
+                let path = match e.controls {
+                    Some([c, d]) => format!(
+                        "M{:.1} {:.1}C{:.1} {:.1} {:.1} {:.1} {:.1} {:.1}",
+                        p.0, p.1, c.0, c.1, d.0, d.1, q.0, q.1
+                    ),
+                    None => format!("M{:.1} {:.1}L{:.1} {:.1}", p.0, p.1, q.0, q.1),
+                };
+                svg += &format!("<path d=\"{}\"/>\n", path);
+            }
+            svg += "</g>\n";
+        }
+    }
+    let h = FONT_PX + 8.0;
+    for look in [Look::Plain, Look::New] {
+        let nodes: Vec<&SceneNode> = scene.nodes.iter().filter(|n| shown.face(n.face) == look).collect();
+        if nodes.is_empty() {
+            continue;
+        }
+        let (colour, width) = if look == Look::New { (VERMILLION, 2.0) } else { (INK, 1.0) };
+        svg += &format!("<g stroke=\"{}\" stroke-width=\"{}\" fill=\"white\">\n", hex(colour), width);
+        for n in nodes {
+            svg += &format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"3\"/>\n",
+                n.at.0 - n.width / 2.0,
+                n.at.1 - h / 2.0,
+                n.width,
+                h
+            );
+        }
+        svg += "</g>\n";
+    }
+    svg += &format!("<g fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n", hex(INK));
+    for n in scene.nodes.iter().filter(|n| shown.face(n.face) != Look::Hidden) {
+        svg += &format!("<text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n", n.at.0, n.at.1, xml_escape(&n.label));
+    }
+    svg += "</g>\n";
+    svg
+}
+
+/// An SVG image of `layout`: labelled boxes, closing edges dashed.
+pub fn strip_svg(layout: &StripLayout, l: &Lattice) -> String {
+    let scene = Scene::new(layout, l, false);
+    svg_header(&scene) + &svg_picture(&scene, Shown::Strip) + "</svg>\n"
+}
+
+/// An animated SVG of the construction: the frames of [`construction_tikz`]
+/// in a loop, switched by SMIL animations. Viewers without SMIL show the
+/// finished strip.
+pub fn construction_svg(layout: &StripLayout, l: &Lattice, steps: &[Step]) -> String {
+    let frames = Frames::new(steps, layout.cyclic);
+    let scene = Scene::new(layout, l, true);
+    let n = frames.len();
+    let mut svg = svg_header(&scene);
+    for frame in 0..n {
+        // visible from frame/n to (frame+1)/n of the loop
+        let mut values = vec![];
+        let mut times = vec![];
+        if frame > 0 {
+            values.push("hidden");
+            times.push(0.0);
+        }
+        values.push("visible");
+        times.push(frame as f64 / n as f64);
+        if frame + 1 < n {
+            values.push("hidden");
+            times.push((frame + 1) as f64 / n as f64);
+        }
+        let times: Vec<String> = times.iter().map(|t| format!("{:.4}", t)).collect();
+        svg += &format!(
+            "<g visibility=\"{}\">\n<animate attributeName=\"visibility\" values=\"{}\" keyTimes=\"{}\" \
+             dur=\"{}s\" calcMode=\"discrete\" repeatCount=\"indefinite\"/>\n",
+            if frame + 1 == n { "visible" } else { "hidden" },
+            values.join(";"),
+            times.join(";"),
+            n as f64 * FRAME_SECONDS
+        );
+        svg += &svg_picture(&scene, Shown::Frame(&frames, frame));
+        svg += "</g>\n";
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+/// A PNG image of `layout`, the picture of [`strip_svg`] rendered at
+/// `scale` pixels per SVG pixel.
+#[cfg(feature = "raster")]
+pub fn strip_png(layout: &StripLayout, l: &Lattice, scale: f64) -> Result<Vec<u8>, String> {
+    raster(&Scene::new(layout, l, false), Shown::Strip, scale)
+}
+
+/// The frames of the construction as PNG images, as [`strip_png`] draws.
+#[cfg(feature = "raster")]
+pub fn construction_pngs(layout: &StripLayout, l: &Lattice, steps: &[Step], scale: f64) -> Result<Vec<Vec<u8>>, String> {
+    let frames = Frames::new(steps, layout.cyclic);
+    let scene = Scene::new(layout, l, true);
+    (0..frames.len()).map(|frame| raster(&scene, Shown::Frame(&frames, frame), scale)).collect()
+}
+
+#[cfg(feature = "raster")]
+fn raster(scene: &Scene, shown: Shown, scale: f64) -> Result<Vec<u8>, String> {
+    use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+    use tiny_skia::{Color, Paint, PathBuilder, Pixmap, Rect, Stroke, StrokeDash, Transform};
+
+    let (w, h) = ((scene.width * scale).ceil() as u32, (scene.height * scale).ceil() as u32);
+    let mut pixmap = Pixmap::new(w, h).ok_or(format!("cannot render a {}x{} image", w, h))?;
+    pixmap.fill(Color::WHITE);
+    let s = scale as f32;
+    let pt = |(x, y): Point| ((x * scale) as f32, (y * scale) as f32);
+    let paint = |(r, g, b): (u8, u8, u8)| {
+        let mut p = Paint::default();
+        p.set_color_rgba8(r, g, b, 255);
+        p.anti_alias = true;
+        p
+    };
+
+    // new edges on top of the others
+    for look in [Look::Plain, Look::New] {
+        for e in scene.edges.iter().filter(|e| shown.edge(e.from, e.to) == look) {
+            let mut path = PathBuilder::new();
+            let (a, b) = pt(e.start);
+            path.move_to(a, b);
+            let (c, d) = pt(e.end);
+            match e.controls {
+                Some([u, v]) => {
+                    let ((ux, uy), (vx, vy)) = (pt(u), pt(v));
+                    path.cubic_to(ux, uy, vx, vy, c, d);
+                }
+                None => path.line_to(c, d),
+            }
+            let Some(path) = path.finish() else { continue };
+            let width = if look == Look::New { 2.5 } else { 1.5 };
+            let mut stroke = Stroke { width: width * s, ..Stroke::default() };
+            if e.closing {
+                stroke.dash = StrokeDash::new(vec![6.0 * s, 4.0 * s], 0.0);
+            }
+            let colour = if e.closing || look == Look::New { VERMILLION } else { INK };
+            pixmap.stroke_path(&path, &paint(colour), &stroke, Transform::identity(), None);
+        }
+    }
+
+    let font = FontRef::try_from_slice(epaint_default_fonts::HACK_REGULAR).map_err(|e| e.to_string())?;
+    let font = font.as_scaled(PxScale::from((FONT_PX * scale) as f32));
+    if let Some(caption) = shown.caption() {
+        let (x, y) = pt((MARGIN_PX, MARGIN_PX + FONT_PX));
+        draw_text(&mut pixmap, &font, caption, x, y);
+    }
+    let box_h = FONT_PX + 8.0;
+    for n in &scene.nodes {
+        let look = shown.face(n.face);
+        if look == Look::Hidden {
+            continue;
+        }
+        let (colour, line) = if look == Look::New { (VERMILLION, 2.0 * s) } else { (INK, s) };
+        let (left, top) = pt((n.at.0 - n.width / 2.0, n.at.1 - box_h / 2.0));
+        let (bw, bh) = ((n.width * scale) as f32, (box_h * scale) as f32);
+        let (Some(outer), Some(inner)) = (
+            Rect::from_xywh(left, top, bw, bh),
+            Rect::from_xywh(left + line, top + line, bw - 2.0 * line, bh - 2.0 * line),
+        ) else {
+            continue;
+        };
+        pixmap.fill_rect(outer, &paint(colour), Transform::identity(), None);
+        pixmap.fill_rect(inner, &paint((255, 255, 255)), Transform::identity(), None);
+
+        // text centred on the node
+        let width: f32 = n.label.chars().map(|c| font.h_advance(font.glyph_id(c))).sum();
+        let (cx, cy) = pt(n.at);
+        draw_text(&mut pixmap, &font, &n.label, cx - width / 2.0, cy + (font.ascent() + font.descent()) / 2.0);
+    }
+
+    // every pixel is opaque, so premultiplied RGBA is plain RGBA
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, w, h);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(pixmap.data()).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// Blend `text` into `pixmap` in ink, starting at `pen` on the baseline.
+#[cfg(feature = "raster")]
+fn draw_text<F: ab_glyph::Font>(pixmap: &mut tiny_skia::Pixmap, font: &ab_glyph::PxScaleFont<F>, text: &str, mut pen: f32, baseline: f32) {
+    use ab_glyph::ScaleFont;
+
+    let (w, h) = (pixmap.width() as i64, pixmap.height() as i64);
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        let glyph = id.with_scale_and_position(font.scale(), ab_glyph::point(pen, baseline));
+        pen += font.h_advance(id);
+        let Some(outline) = font.outline_glyph(glyph) else { continue };
+        let bounds = outline.px_bounds();
+        let pixels = pixmap.pixels_mut();
+        outline.draw(|gx, gy, coverage| {
+            let (px, py) = (bounds.min.x as i64 + gx as i64, bounds.min.y as i64 + gy as i64);
+            if px < 0 || py < 0 || px >= w || py >= h {
+                return;
+            }
+            let pixel = &mut pixels[(py * w + px) as usize];
+            let mix = |bg: u8, fg: u8| (bg as f32 + (fg as f32 - bg as f32) * coverage.min(1.0)) as u8;
+            *pixel = tiny_skia::PremultipliedColorU8::from_rgba(
+                mix(pixel.red(), INK.0),
+                mix(pixel.green(), INK.1),
+                mix(pixel.blue(), INK.2),
+                255,
+            )
+            .unwrap();
+        });
+    }
+}
+
+fn extension(path: &str) -> String {
+    std::path::Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase()
+}
+
+fn write_file(path: &str, content: &[u8]) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("writing {} failed: {}", path, e))
+}
+
+/// Write `layers` to `path` as SVG, PNG (two pixels per SVG pixel), TikZ
+/// (as `options` say) or Graphviz DOT, chosen by the file extension.
+pub fn render_strip(layers: &[Layer], l: &Lattice, cyclic: bool, path: &str, options: &TikzOptions) -> Result<(), String> {
+    let layout = strip_layout(layers, l, cyclic);
+    let content = match extension(path).as_str() {
+        "svg" => strip_svg(&layout, l).into_bytes(),
+        #[cfg(feature = "raster")]
+        "png" => strip_png(&layout, l, 2.0)?,
+        "tex" => tikz::tikz(&Picture::of_strip(layers, &layout, l), options)?.into_bytes(),
+        "dot" | "gv" => dot::strip_dot(layers, l, cyclic).into_bytes(),
+        _ => {
+            let kinds = if cfg!(feature = "raster") { ".svg, .png, .tex or .dot" } else { ".svg, .tex or .dot" };
+            return Err(format!("{}: expected a {} file", path, kinds));
+        }
+    };
+    write_file(path, &content)
+}
+
+/// Write the step-by-step construction of `layers`, chosen by the extension
+/// of `path`: an animated SVG, a TikZ document with a page per step, or PNG
+/// frames `<stem>-00.png`, `<stem>-01.png`, …. Returns the files written.
+pub fn render_construction(layers: &[Layer], l: &Lattice, cyclic: bool, path: &str) -> Result<Vec<String>, String> {
+    let layout = strip_layout(layers, l, cyclic);
+    let steps = construction_steps(layers, l, cyclic);
+    match extension(path).as_str() {
+        "svg" => write_file(path, construction_svg(&layout, l, &steps).as_bytes()).map(|()| vec![path.to_string()]),
+        "tex" => write_file(path, construction_tikz(&layout, l, &steps).as_bytes()).map(|()| vec![path.to_string()]),
+        #[cfg(feature = "raster")]
+        "png" => {
+            let stem = &path[..path.len() - ".png".len()];
+            let mut written = vec![];
+            for (i, png) in construction_pngs(&layout, l, &steps, 2.0)?.iter().enumerate() {
+                let frame = format!("{}-{:02}.png", stem, i);
+                write_file(&frame, png)?;
+                written.push(frame);
+            }
+            Ok(written)
+        }
+        _ => {
+            let kinds = if cfg!(feature = "raster") { ".svg, .png or .tex" } else { ".svg or .tex" };
+            Err(format!("{}: expected a {} file", path, kinds))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::rhombic;
+
+    #[test]
+    fn layouts_place_every_face_once() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        for cyclic in [false, true] {
+            let strip = rhombic::strips(&l, cyclic).next().unwrap();
+            let layout = strip_layout(&strip, &l, cyclic);
+            assert_eq!(layout.nodes.len(), l.num_faces());
+            assert!(layout.edges.iter().all(|e| e.bend.is_some() == cyclic));
+            assert_eq!(layout.edges.iter().any(|e| e.closing), cyclic);
+            // layer k sits on row k, or ring k further out
+            let radius = |f: FaceId| {
+                let &(_, x, y) = layout.nodes.iter().find(|n| n.0 == f).unwrap();
+                if cyclic { x.hypot(y) } else { y }
+            };
+            for pair in strip.windows(2) {
+                assert!(radius(pair[0][0]) < radius(pair[1][0]));
+            }
+
+            let svg = strip_svg(&layout, &l);
+            assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+            assert_eq!(svg.matches("<text").count(), l.num_faces());
+            assert_eq!(svg.matches("<path").count(), layout.edges.len());
+            #[cfg(feature = "raster")]
+            {
+                let png = strip_png(&layout, &l, 1.0).unwrap();
+                assert_eq!(&png[1..4], b"PNG");
+            }
+        }
+    }
+
+    #[test]
+    fn construction_adds_every_face_and_edge_once() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        for cyclic in [false, true] {
+            let strip = rhombic::strips(&l, cyclic).next().unwrap();
+            let steps = construction_steps(&strip, &l, cyclic);
+            assert_eq!(steps[0].faces, strip[0]);
+            let mut faces: Vec<FaceId> = steps.iter().flat_map(|s| s.faces.clone()).collect();
+            faces.sort();
+            assert_eq!(faces, (0..l.num_faces()).collect::<Vec<_>>());
+            // bridges come before the gap faces of their level
+            assert!(steps[1].caption.ends_with("bridges"));
+            for f in &steps[1].faces {
+                assert!(strip[0].windows(2).any(|w| l.bridge(w[0], w[1]) == Some(*f)));
+            }
+
+            let (edges, closing) = edges_strip(&strip, &l, cyclic);
+            let added: Vec<_> = steps.iter().flat_map(|s| s.edges.clone()).collect();
+            assert!(edges.iter().chain(&closing).all(|e| added.contains(e)));
+            assert_eq!(steps.iter().any(|s| s.caption.contains("closing")), cyclic);
+
+            let layout = strip_layout(&strip, &l, cyclic);
+            let svg = construction_svg(&layout, &l, &steps);
+            assert_eq!(svg.matches("<animate").count(), steps.len() + 1);
+            let tex = construction_tikz(&layout, &l, &steps);
+            assert_eq!(tex.matches("\\begin{tikzpicture}").count(), steps.len() + 1);
+        }
+    }
+}
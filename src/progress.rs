@@ -0,0 +1,275 @@
+//! Progress of a strip search split into the seeds of
+//! [`Lattice::ham_path_seeds`]: the share of the work done, the strips found
+//! per second and the time left.
+//!
+//! The seeds' subtrees differ in size by orders of magnitude, so every seed
+//! is weighed by its estimated size ([`HamiltonianIter::estimate_size`]) and
+//! the search is as far along as the finished seeds weigh. That share only
+//! grows when a seed finishes, and the estimates are rough; the time left
+//! is the time taken so far scaled by it, an estimate and no promise.
+//!
+//! The clock is the caller's: `Instant` does not exist in the browser, where
+//! `web` times steps with `Date.now()`.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::lattice::{HamiltonianIter, Lattice};
+use crate::rhombic::{extensions, Strip};
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+/// Random descents per seed for its size estimate.
+pub const PROBES: usize = 16;
+/// What all the weights add up to (about).
+const SCALE: f64 = 1e9;
+/// DFS steps of the path search between two looks at whether to stop,
+/// while no path turns up ([`HamiltonianIter::until`]).
+pub const CHECK_EVERY: usize = 100_000;
+
+/// The seeds' weights and what is done; shared by the threads searching.
+pub struct Progress {
+    weights: Vec<u64>,
+    total: u64,
+    done: AtomicU64,
+    finished: AtomicUsize,
+    count: AtomicU64,
+    paths: AtomicU64,
+}
+
+impl Progress {
+    /// Weigh `seeds` (before any is searched) by their estimated sizes.
+    pub fn new(seeds: &[HamiltonianIter]) -> Self {
+        let sizes: Vec<f64> = seeds.iter().map(|s| s.estimate_size(PROBES)).collect();
+        let sum: f64 = sizes.iter().sum();
+        let weights: Vec<u64> = sizes
+            .iter()
+            .map(|&size| if sum > 0.0 { (size / sum * SCALE).round().max(1.0) as u64 } else { 1 })
+            .collect();
+        Progress {
+            total: weights.iter().sum(),
+            weights,
+            done: AtomicU64::new(0),
+            finished: AtomicUsize::new(0),
+            count: AtomicU64::new(0),
+            paths: AtomicU64::new(0),
+        }
+    }
+
+    /// `n` more strips found.
+    pub fn add(&self, n: u64) {
+        self.count.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Seed `i` is searched to the end.
+    pub fn finish(&self, i: usize) {
+        self.done.fetch_add(self.weights[i], Ordering::Relaxed);
+        self.finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// One more hamiltonian path tried as a strip's first layer.
+    pub fn tried(&self) {
+        self.paths.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// The seeds the search is split into.
+    pub fn seeds(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Hamiltonian paths tried so far.
+    pub fn paths(&self) -> u64 {
+        self.paths.load(Ordering::Relaxed)
+    }
+
+    /// Where the search stands after `elapsed`.
+    pub fn report(&self, elapsed: Duration) -> Report {
+        let fraction = match self.total {
+            0 => 1.0,
+            total => self.done.load(Ordering::Relaxed) as f64 / total as f64,
+        };
+        let mut report = Report::new(self.count(), fraction, elapsed);
+        report.branches = Some([self.finished.load(Ordering::Relaxed), self.weights.len()]);
+        report
+    }
+
+    /// The strips below `seeds` (the ones weighed), one seed after the
+    /// other — for the seeds of `ham_path_seeds` the order of
+    /// [`rhombic::strips`](crate::rhombic::strips) — counting them and
+    /// finishing every seed as it runs out. They end early once `stop`
+    /// holds, which is asked before every path and every [`CHECK_EVERY`]
+    /// steps of the path search between them.
+    pub fn strips<'a>(
+        self: &Arc<Self>,
+        l: &'a Lattice,
+        cyclic: bool,
+        seeds: Vec<HamiltonianIter>,
+        stop: impl Fn() -> bool + Clone + 'a,
+    ) -> impl Iterator<Item = Strip> + 'a {
+        let max_dim = l.dim();
+        let progress = self.clone();
+        seeds.into_iter().enumerate().flat_map(move |(i, paths)| {
+            let (tried, counted, finished) = (progress.clone(), progress.clone(), progress.clone());
+            let stopped = stop.clone();
+            paths
+                .until(CHECK_EVERY, stop.clone())
+                .inspect(move |_| tried.tried())
+                .flat_map(move |path| extensions(vec![path], l, max_dim, cyclic))
+                .inspect(move |_| counted.add(1))
+                .chain(std::iter::from_fn(move || {
+                    if !stopped() {
+                        finished.finish(i);
+                    }
+                    None
+                }))
+        })
+    }
+
+    /// Count the strips below `seeds` (the ones weighed) in parallel, as
+    /// [`rhombic::count_strips`](crate::rhombic::count_strips) does. Stops
+    /// once `cancelled`, with the count so far; it is asked at every path
+    /// and strip, and every [`CHECK_EVERY`] steps of the path search.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn count_strips(
+        &self,
+        l: &Lattice,
+        cyclic: bool,
+        seeds: Vec<HamiltonianIter>,
+        cancelled: &(dyn Fn() -> bool + Sync),
+    ) -> u64 {
+        let max_dim = l.dim();
+        seeds.into_par_iter().enumerate().for_each(|(i, paths)| {
+            for path in paths.until(CHECK_EVERY, cancelled) {
+                self.tried();
+                for _ in extensions(vec![path], l, max_dim, cyclic) {
+                    if cancelled() {
+                        return;
+                    }
+                    self.add(1);
+                }
+            }
+            if !cancelled() {
+                self.finish(i);
+            }
+        });
+        self.count()
+    }
+}
+
+/// One progress report: `strip_stream`'s `progress` message, and a line of
+/// text for the CLI and the GUI.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Report {
+    pub count: u64,
+    /// Estimated share of the search done, 0 to 100.
+    pub percent: f64,
+    /// Strips found per second so far.
+    pub rate: f64,
+    /// Estimated seconds left; none before anything is done.
+    pub eta: Option<f64>,
+    /// Search branches finished, of how many.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branches: Option<[usize; 2]>,
+}
+
+impl Report {
+    /// `count` strips and a `fraction` of the work done after `elapsed`.
+    pub fn new(count: u64, fraction: f64, elapsed: Duration) -> Self {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let secs = elapsed.as_secs_f64();
+        Report {
+            count,
+            percent: (fraction * 1000.0).round() / 10.0,
+            rate: if secs > 0.0 { (count as f64 / secs).round() } else { 0.0 },
+            eta: (fraction > 0.0).then(|| (secs * (1.0 - fraction) / fraction).round()),
+            branches: None,
+        }
+    }
+
+    /// `{"type":"progress",...}`.
+    pub fn message(&self) -> Value {
+        let mut v = json!(self);
+        v["type"] = json!("progress");
+        v
+    }
+
+    /// "42.5% done, 1.2k strips/s, about 3m 20s left".
+    pub fn text(&self) -> String {
+        let left = match self.eta {
+            Some(secs) => format!("about {} left", duration_text(secs)),
+            None => "time left unknown yet".to_string(),
+        };
+        format!("{:.1}% done, {} strips/s, {}", self.percent, amount_text(self.rate), left)
+    }
+}
+
+/// "45s", "3m 20s", "2h 05m", "3d 04h".
+pub fn duration_text(secs: f64) -> String {
+    let s = secs.max(0.0).round() as u64;
+    match s {
+        0..=59 => format!("{}s", s),
+        60..=3599 => format!("{}m {:02}s", s / 60, s % 60),
+        3600..=86_399 => format!("{}h {:02}m", s / 3600, s % 3600 / 60),
+        _ => format!("{}d {:02}h", s / 86_400, s % 86_400 / 3600),
+    }
+}
+
+/// "950", "12.3k", "4.1M".
+fn amount_text(x: f64) -> String {
+    match x {
+        x if x >= 1e9 => format!("{:.1}G", x / 1e9),
+        x if x >= 1e6 => format!("{:.1}M", x / 1e6),
+        x if x >= 1e3 => format!("{:.1}k", x / 1e3),
+        x => format!("{:.0}", x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::rhombic;
+
+    #[test]
+    fn finished_seeds_add_up_to_the_whole() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let seeds = l.ham_path_seeds(false, 16);
+        let n = seeds.len();
+        let progress = Arc::new(Progress::new(&seeds));
+        assert_eq!(progress.report(Duration::from_secs(1)).eta, None);
+
+        // in the order of the sequential search, seed by seed
+        let found: Vec<Strip> = progress.strips(&l, false, seeds, || false).collect();
+        assert_eq!(found, rhombic::strips(&l, false).collect::<Vec<_>>());
+        let report = progress.report(Duration::from_secs(2));
+        assert_eq!((report.count, report.percent, report.eta), (found.len() as u64, 100.0, Some(0.0)));
+        assert_eq!(report.branches, Some([n, n]));
+        assert_eq!(report.rate, (found.len() / 2) as f64);
+        assert_eq!(progress.paths(), l.ham_paths(false).count() as u64);
+
+        let seeds = l.ham_path_seeds(true, 16);
+        let progress = Progress::new(&seeds);
+        let count = progress.count_strips(&l, true, seeds, &|| false);
+        assert_eq!(count as usize, rhombic::count_strips(&l, true));
+        assert_eq!(progress.paths(), l.ham_paths(true).count() as u64);
+        assert_eq!(progress.report(Duration::from_secs(1)).percent, 100.0);
+    }
+
+    #[test]
+    fn reports_read_well() {
+        let report = Report::new(12_345, 0.25, Duration::from_secs(60));
+        assert_eq!((report.percent, report.rate, report.eta), (25.0, 206.0, Some(180.0)));
+        assert_eq!(report.text(), "25.0% done, 206 strips/s, about 3m 00s left");
+        assert_eq!(report.message()["type"], "progress");
+        assert_eq!(duration_text(7322.0), "2h 02m");
+        assert_eq!(amount_text(45_600.0), "45.6k");
+    }
+}
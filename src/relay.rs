@@ -0,0 +1,321 @@
+//! Loopback HTTP relay between the web page and `strip_stream` jobs: the
+//! `strip_stream --serve` mode that `cluster/serve.sh` starts.
+//!
+//! Protocol (every response carries permissive CORS headers and Chrome's
+//! Private-Network opt-in; the pairing token is the gate):
+//!
+//! ```text
+//! GET  /ping  -> {"ok":true,"mode":"slurm"|"direct","threads":n,"host":...,
+//!                 "paired":bool}     paired = X-Rhombic-Token matched
+//! POST /job   -> needs X-Rhombic-Token; the body is one strip_stream job
+//!                {"graph":...,"cyclic":...,"mode":...,"cap":...}.
+//!                The response streams the job's NDJSON as it is written
+//!                (chunked), the process's stderr (srun's chatter) as
+//!                {"type":"note",...} lines. When the page aborts the fetch
+//!                the job's process group is killed, which also releases a
+//!                Slurm allocation.
+//! ```
+//!
+//! Every job runs in a process of its own, this binary in job mode with the
+//! relay's limits and log ([`Relay::args`]), started directly or through
+//! `srun`.
+//!
+//! The relay binds 127.0.0.1 only: on a cluster the page reaches it through
+//! the user's own `ssh -L` tunnel, so jobs run under that user's account.
+//! The token keeps other users of a shared login node, and web pages probing
+//! localhost, from submitting jobs; it is read from the environment because
+//! command lines are visible to everyone.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+/// The port `www/app.js` looks for.
+pub const PORT: u16 = 8642;
+
+const MAX_JOB_BYTES: usize = 32 * 1024 * 1024;
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+pub struct Relay {
+    /// 0 picks a free port (printed on startup).
+    pub port: u16,
+    pub token: String,
+    /// Run every job under `srun --unbuffered <args>`; `None` runs them here.
+    pub srun: Option<Vec<String>>,
+    /// The job binary, normally this very `strip_stream`.
+    pub bin: PathBuf,
+    /// Passed to every job process: `strip_stream`'s limits and `--log`.
+    pub args: Vec<String>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+impl Relay {
+    fn mode(&self) -> &'static str {
+        if self.srun.is_some() {
+            "slurm"
+        } else {
+            "direct"
+        }
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = match &self.srun {
+            Some(args) => {
+                let mut cmd = Command::new("srun");
+                cmd.arg("--unbuffered").args(args).arg(&self.bin);
+                cmd
+            }
+            None => Command::new(&self.bin),
+        };
+        cmd.args(&self.args);
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0); // its own group, so `kill` reaches srun's step too
+        }
+        cmd
+    }
+
+    /// Listen on 127.0.0.1 and answer every connection on a thread of its
+    /// own. Returns only if the port cannot be bound.
+    pub fn serve(self) -> Result<(), String> {
+        let listener = TcpListener::bind(("127.0.0.1", self.port))
+            .map_err(|e| format!("cannot listen on 127.0.0.1:{}: {}", self.port, e))?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        let mode = match &self.srun {
+            Some(args) if !args.is_empty() => format!("slurm ({})", args.join(" ")),
+            Some(_) => "slurm (site defaults)".to_string(),
+            None => "direct".to_string(),
+        };
+        println!("relay: listening on 127.0.0.1:{}  [{}, bin={}]", port, mode, self.bin.display());
+        let _ = io::stdout().flush();
+
+        let relay = Arc::new(self);
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let relay = relay.clone();
+            std::thread::spawn(move || {
+                let _ = relay.handle(stream); // the page went away
+            });
+        }
+        Ok(())
+    }
+
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut out = stream;
+        let request = match read_head(&mut reader)? {
+            Ok(r) => r,
+            Err(e) => return respond(&mut out, 400, &json!({ "error": e })),
+        };
+        let paired =
+            !self.token.is_empty() && request.header("X-Rhombic-Token") == Some(self.token.as_str());
+        let path = request.path.split('?').next().unwrap_or("");
+        match (request.method.as_str(), path) {
+            ("OPTIONS", _) => head(&mut out, 204, "Content-Length: 0\r\n"),
+            ("GET", "/ping") => respond(
+                &mut out,
+                200,
+                &json!({
+                    "ok": true,
+                    "mode": self.mode(),
+                    "threads": std::thread::available_parallelism().map_or(1, |n| n.get()),
+                    "host": host(),
+                    "paired": paired,
+                }),
+            ),
+            ("POST", "/job") if !paired => {
+                respond(&mut out, 401, &json!({ "error": "bad or missing pairing token" }))
+            }
+            ("POST", "/job") => self.run_job(&mut reader, out, &request),
+            _ => respond(&mut out, 404, &json!({ "error": "unknown path" })),
+        }
+    }
+
+    /// Start the job's process and relay its output as a chunked NDJSON
+    /// body until it exits or the page goes away.
+    fn run_job(&self, reader: &mut impl Read, mut out: TcpStream, request: &Request) -> io::Result<()> {
+        let length = request.header("Content-Length").and_then(|v| v.trim().parse::<usize>().ok());
+        let Some(length) = length.filter(|n| (1..=MAX_JOB_BYTES).contains(n)) else {
+            return respond(&mut out, 400, &json!({ "error": "bad job size" }));
+        };
+        let mut job = vec![0; length];
+        reader.read_exact(&mut job)?;
+        // reject garbage before burning an allocation
+        if serde_json::from_slice::<Value>(&job).is_err() {
+            return respond(&mut out, 400, &json!({ "error": "job is not valid JSON" }));
+        }
+
+        let mut cmd = self.command();
+        let program = cmd.get_program().to_string_lossy().into_owned();
+        let mut child = match cmd.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+            Ok(c) => c,
+            Err(e) => {
+                return respond(&mut out, 500, &json!({ "error": format!("cannot start {}: {}", program, e) }))
+            }
+        };
+        head(
+            &mut out,
+            200,
+            "Content-Type: application/x-ndjson\r\nCache-Control: no-store\r\nTransfer-Encoding: chunked\r\n",
+        )?;
+
+        let pid = child.id();
+        let out = Arc::new(Mutex::new(out)); // stdout and stderr share the socket
+        let stderr = child.stderr.take().expect("piped stderr");
+        let notes = {
+            let out = out.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines() {
+                    let Ok(line) = line else { return };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let note = format!("{}\n", json!({ "type": "note", "message": line.trim() }));
+                    if !chunk(&out, note.as_bytes()) {
+                        kill(pid);
+                        return;
+                    }
+                }
+            })
+        };
+
+        let mut stdin = child.stdin.take().expect("piped stdin");
+        let fed = stdin.write_all(&job).and_then(|_| stdin.write_all(b"\n"));
+        drop(stdin); // one-shot job: EOF is fine for strip_stream
+        if fed.is_err() {
+            kill(pid);
+        }
+        let stdout = child.stdout.take().expect("piped stdout");
+        for line in BufReader::new(stdout).split(b'\n') {
+            let Ok(mut line) = line else { break };
+            line.push(b'\n');
+            if !chunk(&out, &line) {
+                kill(pid);
+                break;
+            }
+        }
+        let _ = child.wait();
+        let _ = notes.join();
+        let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+        out.write_all(b"0\r\n\r\n")?; // end of the chunked body
+        out.flush()
+    }
+}
+
+/// The request line and headers; the inner error is a bad request.
+fn read_head(reader: &mut impl BufRead) -> io::Result<Result<Request, String>> {
+    let mut lines = Vec::new();
+    let mut size = 0;
+    loop {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line)?;
+        size += n;
+        if n == 0 || size > MAX_HEAD_BYTES {
+            return Ok(Err("incomplete or oversized request head".to_string()));
+        }
+        let line = line.trim_end_matches(['\r', '\n']).to_string();
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    let Some(line) = lines.first() else {
+        return Ok(Err("no request line".to_string()));
+    };
+    let mut first = line.split_whitespace();
+    let (Some(method), Some(path)) = (first.next(), first.next()) else {
+        return Ok(Err(format!("bad request line '{}'", line)));
+    };
+    let headers = lines[1..]
+        .iter()
+        .filter_map(|h| h.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+    Ok(Ok(Request { method: method.to_string(), path: path.to_string(), headers }))
+}
+
+fn head(out: &mut TcpStream, code: u16, headers: &str) -> io::Result<()> {
+    let reason = match code {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    write!(
+        out,
+        "HTTP/1.1 {} {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type, X-Rhombic-Token\r\n\
+         Access-Control-Allow-Private-Network: true\r\n\
+         Connection: close\r\n{}\r\n",
+        code, reason, headers
+    )?;
+    out.flush()
+}
+
+fn respond(out: &mut TcpStream, code: u16, body: &Value) -> io::Result<()> {
+    let body = body.to_string();
+    head(out, code, &format!("Content-Type: application/json\r\nContent-Length: {}\r\n", body.len()))?;
+    out.write_all(body.as_bytes())?;
+    out.flush()
+}
+
+/// One chunk of the response body; false once the page has gone away.
+fn chunk(out: &Mutex<TcpStream>, data: &[u8]) -> bool {
+    let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+    write!(out, "{:x}\r\n", data.len())
+        .and_then(|_| out.write_all(data))
+        .and_then(|_| out.write_all(b"\r\n"))
+        .and_then(|_| out.flush())
+        .is_ok()
+}
+
+/// Stop a job and everything it started: its whole process group.
+fn kill(pid: u32) {
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = Command::new("kill");
+        cmd.args(["-TERM", &format!("-{}", pid)]);
+        cmd
+    };
+    #[cfg(not(unix))]
+    let mut cmd = {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/PID", &pid.to_string(), "/T", "/F"]);
+        cmd
+    };
+    let _ = cmd.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
+
+/// The short host name, for the page's status line.
+pub(crate) fn host() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| {
+            let out = Command::new("hostname").output().ok()?;
+            Some(String::from_utf8_lossy(&out.stdout).into_owned())
+        })
+        .and_then(|h| h.trim().split('.').next().map(str::to_string))
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
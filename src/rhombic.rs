@@ -8,9 +8,11 @@
 //! Entry points, all lazy where possible:
 //! * [`strips`] / [`strips_parallel`] — all strips of a lattice
 //! * [`count_strips`] — number of strips without storing them
+//! * [`strips_per_path`] — strip count of every level-0 hamiltonian path
 //! * [`strip_exists`] — existence check with early exit
 //! * [`extensions`] — all completions of a partial strip
 //! * [`next_layers`] — all valid successor layers of a single layer
+//! * [`check_strip`] — validate a strip read back from outside
 
 use crate::lattice::{FaceId, Lattice};
 // rayon needs OS threads, which wasm32-unknown-unknown lacks. The parallel
@@ -220,6 +222,36 @@ impl Iterator for GapAssignmentIterator {
 // Layer successors
 // ---------------------------------------------------------------------------
 
+/// How many consecutive pairs a layer of `n` faces has to bridge.
+fn bridge_count(n: usize, cyclic: bool) -> usize {
+    match (cyclic, n) {
+        (true, 1) => 0,      // single face: nothing to bridge
+        (true, 2) => 1,      // the one bridge already closes the cycle
+        (true, _) => n,      // cyclic: as many bridges as faces
+        (false, _) => n - 1, // linear: one less
+    }
+}
+
+/// The bridges of every layer of `strip` above level 0, each once, in
+/// order: the faces that cover two consecutive faces of the layer below.
+/// The layer's other faces sit in gaps.
+pub fn layer_bridges(strip: &[Layer], l: &Lattice, cyclic: bool) -> Vec<Vec<FaceId>> {
+    strip
+        .windows(2)
+        .map(|pair| {
+            let below = &pair[0];
+            let mut bridges: Vec<FaceId> = Vec::new();
+            for x in 0..bridge_count(below.len(), cyclic) {
+                let b = l.bridge(below[x], below[(x + 1) % below.len()]);
+                if let Some(b) = b.filter(|b| !bridges.contains(b)) {
+                    bridges.push(b);
+                }
+            }
+            bridges
+        })
+        .collect()
+}
+
 /// Lazily enumerate all valid layers of dimension `d+1` following the given
 /// layer of dimension `d`. The returned iterator owns all its data.
 pub fn next_layers(
@@ -231,15 +263,9 @@ pub fn next_layers(
 
     let dim = l.face(last_layer[0]).dim();
     let n = last_layer.len();
-    let num_bridges = match (cyclic, n) {
-        (true, 1) => 0,      // single face: nothing to bridge
-        (true, 2) => 1,      // the one bridge already closes the cycle
-        (true, _) => n,      // cyclic: as many bridges as faces
-        (false, _) => n - 1, // linear: one less
-    };
 
     // bridges between consecutive layer faces; all must exist
-    let bridges: Option<Vec<FaceId>> = (0..num_bridges)
+    let bridges: Option<Vec<FaceId>> = (0..bridge_count(n, cyclic))
         .map(|x| l.bridge(last_layer[x], last_layer[(x + 1) % n]))
         .collect();
 
@@ -369,6 +395,32 @@ pub fn count_strips(l: &Lattice, cyclic: bool) -> usize {
         .sum()
 }
 
+/// Every hamiltonian path (cycle) of level 0 with the number of strips it
+/// starts, in the order of [`Lattice::ham_paths`]. Parallel like
+/// [`count_strips`]; the seeds partition the paths in that order, so
+/// concatenating their results keeps it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn strips_per_path(l: &Lattice, cyclic: bool) -> Vec<(Layer, usize)> {
+    let max_dim = l.dim();
+    let per_seed: Vec<Vec<(Layer, usize)>> = l
+        .ham_path_seeds(cyclic, seed_target())
+        .into_par_iter()
+        .map(|paths| {
+            paths
+                .map(|path| {
+                    let n = if max_dim == 0 {
+                        1
+                    } else {
+                        extensions(vec![path.clone()], l, max_dim, cyclic).count()
+                    };
+                    (path, n)
+                })
+                .collect()
+        })
+        .collect();
+    per_seed.into_iter().flatten().collect()
+}
+
 /// Does the given layer of dimension `current_dim` extend to a full strip up
 /// to `max_dim`? Sequential with early exit.
 pub fn layer_extends(
@@ -411,3 +463,49 @@ pub fn strip_exists(l: &Lattice, cyclic: bool) -> bool {
             false
         })
 }
+
+/// Check that `strip` is a rhombic strip of `l`: one layer per level, the
+/// first a hamiltonian path (cycle) of the bridge graph of level 0, every
+/// further layer one of the [`next_layers`] of the layer below. Strips in
+/// either direction are accepted; the error names the first bad layer.
+pub fn check_strip(strip: &[Layer], l: &Lattice, cyclic: bool) -> Result<(), String> {
+    if strip.len() != l.dim() + 1 {
+        return Err(format!(
+            "expected {} layers (levels 0..={}), got {}",
+            l.dim() + 1,
+            l.dim(),
+            strip.len()
+        ));
+    }
+    if let Some(&f) = strip.iter().flatten().find(|&&f| f >= l.num_faces()) {
+        return Err(format!("face {} out of range", f));
+    }
+
+    let first = &strip[0];
+    let mut sorted = first.clone();
+    sorted.sort_unstable();
+    let mut level = l.level(0).to_vec();
+    level.sort_unstable();
+    if sorted != level {
+        return Err("layer 0 does not contain every face of level 0 exactly once".to_string());
+    }
+    let n = first.len();
+    let closing = cyclic && n > 2;
+    for i in 0..n.saturating_sub(1) + usize::from(closing) {
+        let (a, b) = (first[i], first[(i + 1) % n]);
+        if l.bridge(a, b).is_none() {
+            return Err(format!(
+                "layer 0: {} and {} have no common cover",
+                l.face(a).label(),
+                l.face(b).label()
+            ));
+        }
+    }
+
+    for d in 1..strip.len() {
+        if !next_layers(&strip[d - 1], l, cyclic).any(|next| next == strip[d]) {
+            return Err(format!("layer {} does not follow layer {}", d, d - 1));
+        }
+    }
+    Ok(())
+}
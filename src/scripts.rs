@@ -16,22 +16,35 @@
 //! (plain Rust, unit-tested on the host), and the `#[wasm_bindgen]` steppers
 //! below expose it to `www/worker.js` with the same `step(budget_ms)`
 //! contract as `web::StripEnumerator`, so long runs stay sliceable,
-//! streamable and cancellable.
+//! streamable and cancellable. Natively, [`api::SurveyCore::run`] and
+//! [`tally_boundaries`] do the same work in parallel for `strip_stream`'s
+//! `survey` and `bounds` modes, reporting in the steppers' shapes.
 
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Mutex;
 
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
 use crate::lattice::Lattice;
 use crate::rhombic::{self, Strip};
-use crate::web::{api as webapi, now_ms};
+use crate::generators::Poset;
+use crate::web::now_ms;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::progress::CHECK_EVERY;
 
 pub mod api {
     //! Pure, host-testable implementations.
 
     use std::collections::HashSet;
+    #[cfg(not(target_arch = "wasm32"))]
+    use std::sync::Mutex;
 
+    #[cfg(not(target_arch = "wasm32"))]
+    use rayon::prelude::*;
     use serde::Serialize;
 
     use crate::lattice::{Face, FaceId, Lattice};
@@ -201,7 +214,7 @@ pub mod api {
     /// The tube poset of the graph (connected vertex subsets under inclusion)
     /// as a [`Lattice`]. Tubes are ranked by `|tube| - 1`, so the singletons
     /// form level 0; covers add one neighbouring vertex. Face labels are the
-    /// vertex digits, matching `web::api::gen_tube_poset` for ≤ 10 vertices.
+    /// vertex digits, matching [`generators::tube_poset`] for ≤ 10 vertices.
     pub fn tube_poset(n: usize, adj: &[u64]) -> Lattice {
         let full = (1u64 << n) - 1;
         let mut subs: Vec<u64> = (1..=full).filter(|&m| subset_connected(m, adj)).collect();
@@ -364,9 +377,61 @@ pub mod api {
                     break;
                 }
             }
+            self.report(fresh)
+        }
+
+        /// The whole survey at once, natively: the graphs are generated as
+        /// in [`step`](Self::step) and then checked in parallel. `emit` gets
+        /// steps in `step`'s shape about every `every_ms`, the last with
+        /// `done`; results arrive in no particular order. False if
+        /// `cancelled` stopped it.
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn run(
+            mut self,
+            every_ms: f64,
+            emit: &(dyn Fn(SurveyStep) + Sync),
+            cancelled: &(dyn Fn() -> bool + Sync),
+        ) -> bool {
+            let mut last = now_ms();
+            while matches!(self.phase, Phase::Generate) {
+                if cancelled() {
+                    return false;
+                }
+                self.generate_one();
+                if now_ms() - last >= every_ms {
+                    emit(self.report(vec![]));
+                    last = now_ms();
+                }
+            }
+
+            let this = &self;
+            let pending = Mutex::new((vec![], 0, now_ms()));
+            let step = |results, checked, done| SurveyStep { checked, done, ..this.report(results) };
+            this.targets.par_iter().for_each(|&(n, mask)| {
+                if cancelled() {
+                    return;
+                }
+                let result = this.check(n, mask);
+                super::update_then_emit(&pending, emit, |pending| {
+                    pending.0.push(result);
+                    pending.1 += 1;
+                    (now_ms() - pending.2 >= every_ms).then(|| {
+                        pending.2 = now_ms();
+                        step(std::mem::take(&mut pending.0), pending.1, false)
+                    })
+                });
+            });
+            if cancelled() {
+                return false;
+            }
+            let (results, checked, _) = pending.into_inner().unwrap();
+            emit(step(results, checked, true));
+            true
+        }
 
+        fn report(&self, results: Vec<GraphResult>) -> SurveyStep {
             SurveyStep {
-                results: fresh,
+                results,
                 phase: match self.phase {
                     Phase::Generate => "generate",
                     _ => "check",
@@ -438,10 +503,14 @@ pub mod api {
             }
             let (n, mask) = self.targets[self.next_target];
             self.next_target += 1;
+            Some(self.check(n, mask))
+        }
 
+        /// The checks of one graph on `n` vertices.
+        fn check(&self, n: usize, mask: u64) -> GraphResult {
             let adj = adjacency(n, mask);
             let lat = tube_poset(n, &adj);
-            Some(GraphResult {
+            GraphResult {
                 n,
                 edges: edges_of(mask, n),
                 ham_path: ham_path(n, &adj),
@@ -453,7 +522,7 @@ pub mod api {
                     .check_cyclic
                     .then(|| rhombic::strips(&lat, true).next().is_some()),
                 tubes: lat.num_faces(),
-            })
+            }
         }
     }
 
@@ -555,21 +624,116 @@ impl GraphSurvey {
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct PairOut {
-    left: String,
-    right: String,
-    count: usize,
+pub struct PairOut {
+    pub left: String,
+    pub right: String,
+    pub count: usize,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct BoundaryStep {
+pub struct BoundaryStep {
     /// Full tally so far, sorted by count (desc), then lexicographically.
-    pairs: Vec<PairOut>,
+    pub pairs: Vec<PairOut>,
     /// Strips enumerated so far.
-    count: usize,
-    distinct: usize,
-    done: bool,
+    pub count: usize,
+    pub distinct: usize,
+    pub done: bool,
+}
+
+impl BoundaryStep {
+    fn new(pairs: &HashMap<(String, String), usize>, count: usize, done: bool) -> Self {
+        let pairs = pair_rows(pairs);
+        BoundaryStep { distinct: pairs.len(), pairs, count, done }
+    }
+}
+
+/// A tally of boundary pairs as rows, by count (desc), then lexicographically.
+pub(crate) fn pair_rows(pairs: &HashMap<(String, String), usize>) -> Vec<PairOut> {
+    let mut pairs: Vec<PairOut> = pairs
+        .iter()
+        .map(|((left, right), &count)| PairOut {
+            left: left.clone(),
+            right: right.clone(),
+            count,
+        })
+        .collect();
+    pairs.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.left.cmp(&b.left))
+            .then_with(|| a.right.cmp(&b.right))
+    });
+    pairs
+}
+
+/// The boundaries of a linear strip, bottom to top, as permutations.
+pub(crate) fn boundary_pair(l: &Lattice, strip: &Strip) -> (String, String) {
+    let chain = |pick: fn(&[usize]) -> usize| -> Vec<String> {
+        strip
+            .iter()
+            .map(|layer| l.face(pick(layer)).label().to_string())
+            .collect()
+    };
+    let left = api::chain_to_perm(&chain(|layer| layer[0]));
+    let right = api::chain_to_perm(&chain(|layer| *layer.last().unwrap()));
+    (left, right)
+}
+
+/// Update the tally shared by the threads with `update`, and `emit` the step
+/// it returns, if one is due, after releasing the lock: `emit` may block on
+/// a slow pipe, and every thread would wait for it.
+#[cfg(not(target_arch = "wasm32"))]
+fn update_then_emit<S, T>(shared: &Mutex<S>, emit: &(dyn Fn(T) + Sync), update: impl FnOnce(&mut S) -> Option<T>) {
+    let due = update(&mut shared.lock().unwrap());
+    if let Some(step) = due {
+        emit(step);
+    }
+}
+
+/// The tally of [`BoundaryEnumerator`] at once, natively: the strips of `l`
+/// are enumerated in parallel over the seeds of the path search. `emit` gets
+/// the tally so far about every `every_ms`, the last with `done`. False if
+/// `cancelled` stopped it; it is asked at every path and strip, and every
+/// [`CHECK_EVERY`] steps of the path search.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn tally_boundaries(
+    l: &Lattice,
+    every_ms: f64,
+    emit: &(dyn Fn(BoundaryStep) + Sync),
+    cancelled: &(dyn Fn() -> bool + Sync),
+) -> bool {
+    let max_dim = l.dim();
+    let seeds = l.ham_path_seeds(false, rayon::current_num_threads() * 16);
+    let tally = Mutex::new((HashMap::new(), 0, now_ms()));
+    let paths = seeds.into_par_iter().flat_map_iter(|paths| paths.until(CHECK_EVERY, cancelled));
+    paths.for_each(|path| {
+        let mut pairs: HashMap<(String, String), usize> = HashMap::new();
+        let mut count = 0;
+        for strip in rhombic::extensions(vec![path], l, max_dim, false) {
+            if cancelled() {
+                return;
+            }
+            *pairs.entry(boundary_pair(l, &strip)).or_insert(0) += 1;
+            count += 1;
+        }
+        update_then_emit(&tally, emit, |tally| {
+            for (pair, n) in pairs {
+                *tally.0.entry(pair).or_insert(0) += n;
+            }
+            tally.1 += count;
+            (now_ms() - tally.2 >= every_ms).then(|| {
+                tally.2 = now_ms();
+                BoundaryStep::new(&tally.0, tally.1, false)
+            })
+        });
+    });
+    if cancelled() {
+        return false;
+    }
+    let (pairs, count, _) = tally.into_inner().unwrap();
+    emit(BoundaryStep::new(&pairs, count, true));
+    true
 }
 
 /// Enumerates all linear rhombic strips of one poset and tallies the pairs
@@ -605,16 +769,7 @@ impl BoundaryEnumerator {
             loop {
                 match iter.next() {
                     Some(strip) => {
-                        let chain = |pick: fn(&[usize]) -> usize| -> Vec<String> {
-                            strip
-                                .iter()
-                                .map(|layer| l.face(pick(layer)).label().to_string())
-                                .collect()
-                        };
-                        let left = api::chain_to_perm(&chain(|layer| layer[0]));
-                        let right =
-                            api::chain_to_perm(&chain(|layer| *layer.last().unwrap()));
-                        *self.pairs.entry((left, right)).or_insert(0) += 1;
+                        *self.pairs.entry(boundary_pair(l, &strip)).or_insert(0) += 1;
                         self.count += 1;
                     }
                     None => {
@@ -629,35 +784,14 @@ impl BoundaryEnumerator {
             }
         }
 
-        let mut pairs: Vec<PairOut> = self
-            .pairs
-            .iter()
-            .map(|((left, right), &count)| PairOut {
-                left: left.clone(),
-                right: right.clone(),
-                count,
-            })
-            .collect();
-        pairs.sort_by(|a, b| {
-            b.count
-                .cmp(&a.count)
-                .then_with(|| a.left.cmp(&b.left))
-                .then_with(|| a.right.cmp(&b.right))
-        });
-        let out = BoundaryStep {
-            distinct: pairs.len(),
-            pairs,
-            count: self.count,
-            done: self.done,
-        };
+        let out = BoundaryStep::new(&self.pairs, self.count, self.done);
         serde_json::to_string(&out).expect("BoundaryStep serializes")
     }
 }
 
 impl BoundaryEnumerator {
     fn create(graph_json: &str) -> Result<Self, String> {
-        let g = webapi::WireGraph::parse(graph_json)?;
-        let faces = webapi::wire_to_faces(&g)?;
+        let faces = Poset::parse(graph_json)?.to_faces()?;
         let lattice: *mut Lattice = Box::into_raw(Box::new(Lattice::from_faces(faces)));
         // SAFETY: the iterator borrows the leaked lattice; it is dropped
         // before the lattice in `Drop`, and `lattice` is never moved.
@@ -688,6 +822,7 @@ impl Drop for BoundaryEnumerator {
 mod tests {
     use super::api::*;
     use super::*;
+    use crate::generators;
 
     fn run_survey(max_n: usize, linear: bool, cyclic: bool) -> Vec<GraphResult> {
         let mut core = SurveyCore::new(max_n, linear, cyclic).expect("survey builds");
@@ -740,9 +875,9 @@ mod tests {
     }
 
     #[test]
-    fn tube_poset_of_p3_matches_web_generator() {
+    fn tube_poset_of_p3_matches_generator() {
         // Same graph through both code paths: scripts::api::tube_poset and
-        // web::api::gen_tube_poset must agree on faces per level.
+        // generators::tube_poset must agree on faces per level.
         let adj = adjacency(3, edges_to_mask(3, &[(0, 1), (1, 2)]));
         let lat = tube_poset(3, &adj);
         assert_eq!(lat.num_faces(), 6); // {0},{1},{2},{01},{12},{012}
@@ -750,11 +885,7 @@ mod tests {
         assert_eq!(lat.level(1).len(), 2);
         assert_eq!(lat.level(2).len(), 1);
 
-        let via_web = crate::web::api::gen_tube_poset(
-            &crate::web::api::gen_graph("path", 3).unwrap(),
-        )
-        .unwrap();
-        let g = crate::web::api::WireGraph::parse(&via_web).unwrap();
+        let g = generators::tube_poset(&generators::graph("path", 3).unwrap()).unwrap();
         assert_eq!(g.labels.len(), lat.num_faces());
         assert_eq!(
             g.edges.len(),
@@ -801,11 +932,8 @@ mod tests {
     fn boundary_pairs_of_p3_tube_poset() {
         // Tube poset of the path 0-1-2 has exactly one strip:
         // [0,1,2] / [01,12] / [012]; boundaries 0<01<012 and 2<12<012.
-        let g = crate::web::api::gen_tube_poset(
-            &crate::web::api::gen_graph("path", 3).unwrap(),
-        )
-        .unwrap();
-        let mut en = BoundaryEnumerator::create(&g).expect("enumerator builds");
+        let g = generators::tube_poset(&generators::graph("path", 3).unwrap()).unwrap();
+        let mut en = BoundaryEnumerator::create(&g.to_json()).expect("enumerator builds");
         let out: serde_json::Value =
             serde_json::from_str(&en.step(f64::INFINITY)).unwrap();
         assert_eq!(out["done"], true);
@@ -816,19 +944,44 @@ mod tests {
         assert_eq!(out["pairs"][0]["count"], 1);
     }
 
+    #[test]
+    fn native_runs_match_the_steppers() {
+        let mut stepped = run_survey(5, true, true);
+        let found = Mutex::new(vec![]);
+        let last = Mutex::new(None);
+        let emit = |step: SurveyStep| {
+            found.lock().unwrap().extend(step.results);
+            *last.lock().unwrap() = Some((step.checked, step.total, step.done));
+        };
+        assert!(SurveyCore::new(5, true, true).unwrap().run(0.0, &emit, &|| false));
+        let mut found = found.into_inner().unwrap();
+        let key = |r: &GraphResult| (r.n, r.edges.clone());
+        stepped.sort_by_key(key);
+        found.sort_by_key(key);
+        let json = |rs: &[GraphResult]| serde_json::to_value(rs).unwrap();
+        assert_eq!(json(&found), json(&stepped));
+        assert_eq!(last.into_inner().unwrap(), Some((stepped.len(), stepped.len(), true)));
+        assert!(!SurveyCore::new(5, true, true).unwrap().run(0.0, &|_| {}, &|| true));
+
+        let g = generators::cube(3).unwrap();
+        let mut en = BoundaryEnumerator::create(&g.to_json()).unwrap();
+        let stepped: serde_json::Value = serde_json::from_str(&en.step(f64::INFINITY)).unwrap();
+        let tallied = Mutex::new(serde_json::Value::Null);
+        let emit = |step: BoundaryStep| *tallied.lock().unwrap() = serde_json::to_value(step).unwrap();
+        assert!(tally_boundaries(&g.to_lattice().unwrap(), 1e9, &emit, &|| false));
+        assert_eq!(tallied.into_inner().unwrap(), stepped);
+    }
+
     #[test]
     fn boundary_counts_sum_to_strip_count() {
         // Boolean lattice B3 (simplex face lattice): pair counts must sum to
         // the total number of strips reported by the sequential search.
-        let g = crate::web::api::gen_simplex(2).unwrap(); // subsets of {0,1,2}
-        let faces =
-            crate::web::api::wire_to_faces(&crate::web::api::WireGraph::parse(&g).unwrap())
-                .unwrap();
-        let lat = crate::lattice::Lattice::from_faces(faces);
+        let g = generators::simplex(2).unwrap(); // subsets of {0,1,2}
+        let lat = g.to_lattice().unwrap();
         let total = crate::rhombic::strips(&lat, false).count();
         assert!(total > 0);
 
-        let mut en = BoundaryEnumerator::create(&g).expect("enumerator builds");
+        let mut en = BoundaryEnumerator::create(&g.to_json()).expect("enumerator builds");
         let out: serde_json::Value =
             serde_json::from_str(&en.step(f64::INFINITY)).unwrap();
         assert_eq!(out["count"], total as u64);
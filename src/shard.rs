@@ -0,0 +1,200 @@
+//! Sharded searches: one count or enumeration split over the tasks of a
+//! Slurm array (`--shard i/N`), and the merge of their results.
+//!
+//! [`Lattice::ham_path_seeds`] already partitions the hamiltonian paths of
+//! level 0, and with them the strips. Every shard asks for the same number
+//! of seeds, which depends on `N` alone, and takes every `N`-th of them from
+//! seed `i` on. The split therefore depends only on `N` and on the lattice
+//! as numbered, which is why each [`Part`] carries the lattice's
+//! [`Lattice::fingerprint`] and [`merge`] insists they all agree.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::lattice::{HamiltonianIter, Lattice};
+
+/// Seeds asked for per shard: enough that a shard's own threads balance.
+const SEEDS_PER_SHARD: usize = 64;
+
+/// Part `index` (from 0) of `of`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shard {
+    pub index: usize,
+    pub of: usize,
+}
+
+impl Shard {
+    /// `i/N` with `0 <= i < N`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let bad = || format!("shard '{}': expected i/N with 0 <= i < N", spec);
+        let (i, n) = spec.split_once('/').ok_or_else(bad)?;
+        let index: usize = i.trim().parse().map_err(|_| bad())?;
+        let of: usize = n.trim().parse().map_err(|_| bad())?;
+        if index >= of {
+            return Err(bad());
+        }
+        Ok(Shard { index, of })
+    }
+
+    /// This shard's seeds of the hamiltonian path search.
+    pub fn seeds(&self, l: &Lattice, cyclic: bool) -> Vec<HamiltonianIter> {
+        l.ham_path_seeds(cyclic, self.of * SEEDS_PER_SHARD)
+            .into_iter()
+            .skip(self.index)
+            .step_by(self.of)
+            .collect()
+    }
+
+    /// The record of a finished (or stopped) shard, for [`merge`].
+    pub fn part(&self, l: &Lattice, cyclic: bool, mode: &str, count: u64, complete: bool) -> Part {
+        Part {
+            index: self.index,
+            of: self.of,
+            lattice: l.fingerprint(),
+            mode: mode.to_string(),
+            cyclic,
+            count,
+            complete,
+        }
+    }
+}
+
+/// One shard's result, the `"shard"` field of the CLI's JSON record and of
+/// `strip_stream`'s `done` message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Part {
+    pub index: usize,
+    pub of: usize,
+    /// [`Lattice::fingerprint`] of the lattice searched.
+    pub lattice: String,
+    /// `"count"` or `"enumerate"`.
+    pub mode: String,
+    pub cyclic: bool,
+    pub count: u64,
+    /// False if the shard stopped early (time limit, `--limit`, cap).
+    pub complete: bool,
+}
+
+impl Part {
+    /// The last shard record in CLI or `strip_stream` output: a JSON
+    /// document, or JSON lines of which the last with a `"shard"` counts.
+    pub fn find(output: &str) -> Option<Part> {
+        output
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .find_map(|v| serde_json::from_value(v.get("shard")?.clone()).ok())
+    }
+}
+
+/// The merged result of all shards.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Merged {
+    pub shards: usize,
+    pub lattice: String,
+    pub mode: String,
+    pub cyclic: bool,
+    pub count: u64,
+}
+
+/// Add up the parts (each named by its file, for errors), checking that they
+/// are the complete parts `0..N` of one search of one lattice.
+pub fn merge(parts: &[(String, Part)]) -> Result<Merged, String> {
+    let (_, first) = parts.first().ok_or("no shards to merge")?;
+    let mut seen: Vec<Option<&str>> = vec![None; first.of];
+    for (name, p) in parts {
+        if p.of != first.of || p.index >= first.of {
+            return Err(format!("{}: shard {}/{}, but the others are of {}", name, p.index, p.of, first.of));
+        }
+        if p.lattice != first.lattice {
+            return Err(format!(
+                "{}: a different lattice (hash {}, the first shard's is {})",
+                name, p.lattice, first.lattice
+            ));
+        }
+        if (p.mode.as_str(), p.cyclic) != (first.mode.as_str(), first.cyclic) {
+            return Err(format!("{}: a different search ({}{})", name, p.mode, if p.cyclic { ", cyclic" } else { "" }));
+        }
+        if !p.complete {
+            return Err(format!("{}: shard {}/{} stopped early", name, p.index, p.of));
+        }
+        if let Some(other) = seen[p.index].replace(name) {
+            return Err(format!("shard {}/{} twice: {} and {}", p.index, p.of, other, name));
+        }
+    }
+    let missing: Vec<String> =
+        seen.iter().enumerate().filter(|(_, s)| s.is_none()).map(|(i, _)| i.to_string()).collect();
+    if !missing.is_empty() {
+        return Err(format!("missing shards {} of {}", missing.join(", "), first.of));
+    }
+    Ok(Merged {
+        shards: first.of,
+        lattice: first.lattice.clone(),
+        mode: first.mode.clone(),
+        cyclic: first.cyclic,
+        count: parts.iter().map(|(_, p)| p.count).sum(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::generators;
+    use crate::progress::Progress;
+    use crate::rhombic::{self, Strip};
+
+    /// The shard's count, searched as `--shard` searches it.
+    fn count(shard: Shard, l: &Lattice, cyclic: bool) -> u64 {
+        let seeds = shard.seeds(l, cyclic);
+        Progress::new(&seeds).count_strips(l, cyclic, seeds, &|| false)
+    }
+
+    #[test]
+    fn shards_partition_the_strips() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        for cyclic in [false, true] {
+            let mut all: Vec<Strip> = rhombic::strips(&l, cyclic).collect();
+            all.sort();
+            for of in [1, 3, 7] {
+                let shards: Vec<Shard> = (0..of).map(|index| Shard { index, of }).collect();
+                let mut found: Vec<Strip> = shards
+                    .iter()
+                    .flat_map(|s| {
+                        let seeds = s.seeds(&l, cyclic);
+                        Arc::new(Progress::new(&seeds)).strips(&l, cyclic, seeds, || false).collect::<Vec<_>>()
+                    })
+                    .collect();
+                found.sort();
+                assert_eq!(found, all, "{} shards", of);
+                let counts: u64 = shards.iter().map(|&s| count(s, &l, cyclic)).sum();
+                assert_eq!(counts, rhombic::count_strips(&l, cyclic) as u64);
+            }
+        }
+        assert_eq!(Shard::parse("2/8"), Ok(Shard { index: 2, of: 8 }));
+        assert!(Shard::parse("8/8").is_err() && Shard::parse("1").is_err());
+    }
+
+    #[test]
+    fn merging_checks_the_parts() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let part = |index: usize| {
+            let shard = Shard { index, of: 3 };
+            (format!("s{}", index), shard.part(&l, false, "count", count(shard, &l, false), true))
+        };
+        let parts = vec![part(2), part(0), part(1)];
+        assert_eq!(merge(&parts).unwrap().count, rhombic::count_strips(&l, false) as u64);
+        let output = format!("{{\"type\":\"note\"}}\n{}\n", serde_json::json!({ "shard": parts[0].1 }));
+        assert_eq!(Part::find(&output), Some(parts[0].1.clone()));
+
+        assert!(merge(&parts[..2]).unwrap_err().contains("missing shards 1 of 3"));
+        assert!(merge(&[part(0), part(0)]).unwrap_err().contains("twice"));
+        let mut other = parts.clone();
+        other[1].1.lattice = generators::boolean(3).unwrap().to_lattice().unwrap().fingerprint();
+        assert!(merge(&other).unwrap_err().contains("different lattice"));
+        other[1] = part(0);
+        other[1].1.complete = false;
+        assert!(merge(&other).unwrap_err().contains("stopped early"));
+    }
+}
@@ -0,0 +1,134 @@
+//! The record a finished search leaves behind: what was searched (the
+//! lattice's [`Lattice::fingerprint`], the mode, cyclic or not, the shard,
+//! the filter), what came of it, and how (threads, seeds, hamiltonian paths
+//! tried, wall time, host, crate version).
+//!
+//! `strip_stream` adds one to every `done` message and the CLI to the JSON
+//! record of `exists`, `count` and `enumerate`; both append it to a JSONL
+//! file with `--log FILE`, one line per job, so a run of experiments leaves
+//! a log that says how to reproduce each result. Every field is always
+//! there (null where it does not apply), which keeps the lines easy to load
+//! as a table.
+
+use std::io::Write;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::filter::Filter;
+use crate::lattice::Lattice;
+
+/// One finished job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Summary {
+    /// Of this crate.
+    pub version: String,
+    pub host: String,
+    /// `exists`, `count`, `enumerate`, `sample`, `survey` or `bounds`.
+    pub mode: String,
+    /// The lattice's fingerprint; none for a job over many posets (survey).
+    pub lattice: Option<String>,
+    pub faces: Option<usize>,
+    pub cyclic: bool,
+    /// `i/N` of a sharded search.
+    pub shard: Option<String>,
+    /// The strips an enumeration kept, if not all.
+    pub filter: Option<Filter>,
+    /// Strips found (graphs checked in a survey).
+    pub count: u64,
+    /// Searched to the end: not capped, stopped or timed out.
+    pub complete: bool,
+    /// Answered from the results cache.
+    pub cached: bool,
+    /// Threads searching; none when nothing was searched here.
+    pub threads: Option<usize>,
+    /// Workers of a coordinated search.
+    pub workers: Option<usize>,
+    /// Search branches ([`Lattice::ham_path_seeds`]) the paths were split into.
+    pub seeds: Option<usize>,
+    /// Hamiltonian paths of level 0 tried as a strip's first layer.
+    pub paths: Option<u64>,
+    /// Wall time of the whole job, building the lattice included.
+    pub seconds: f64,
+    /// When it finished, in seconds since the Unix epoch.
+    pub finished: u64,
+}
+
+impl Summary {
+    /// A complete `mode` job over `l`, with nothing found yet; the caller
+    /// fills in the rest and [`finish`](Self::finish)es it.
+    pub fn new(mode: &str, l: Option<&Lattice>, cyclic: bool) -> Self {
+        Summary {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            host: crate::relay::host(),
+            mode: mode.to_string(),
+            lattice: l.map(Lattice::fingerprint),
+            faces: l.map(Lattice::num_faces),
+            cyclic,
+            shard: None,
+            filter: None,
+            count: 0,
+            complete: true,
+            cached: false,
+            threads: None,
+            workers: None,
+            seeds: None,
+            paths: None,
+            seconds: 0.0,
+            finished: 0,
+        }
+    }
+
+    /// Stamped with the time since `started` and the time of day.
+    pub fn finish(mut self, started: Instant) -> Self {
+        self.seconds = started.elapsed().as_secs_f64();
+        self.finished = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        self
+    }
+
+    /// Append this summary to the JSONL file at `path` as one line, creating
+    /// the file if need be. One write per line, so jobs logging to the same
+    /// file side by side do not interleave.
+    pub fn append(&self, path: &Path) -> Result<(), String> {
+        let mut line = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("writing the log {} failed: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+
+    #[test]
+    fn summaries_append_as_lines() {
+        let l = generators::boolean(3).unwrap().to_lattice().unwrap();
+        let mut summary = Summary::new("count", Some(&l), false);
+        summary.count = 12;
+        let summary = summary.finish(Instant::now());
+        assert_eq!(summary.lattice, Some(l.fingerprint()));
+        assert_eq!(summary.faces, Some(l.num_faces()));
+        assert!(summary.finished > 0);
+
+        let path = std::env::temp_dir().join(format!("rhombic-summary-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        summary.append(&path).unwrap();
+        Summary { mode: "exists".to_string(), ..summary.clone() }.append(&path).unwrap();
+        let lines: Vec<Summary> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], summary);
+        assert_eq!(lines[1].mode, "exists");
+    }
+}
@@ -0,0 +1,375 @@
+//! Poset symmetry: an invariant hash, isomorphisms and automorphisms.
+//!
+//! Everything works on the cover graph with faces coloured by level. Colour
+//! refinement (Weisfeiler–Leman) splits the faces into classes no
+//! isomorphism can mix; its history hashes to a numbering-independent key
+//! (used by [`crate::cache`]), and backtracking over the classes finds the
+//! actual isomorphisms — the first one, or all of them for [`automorphisms`].
+//!
+//! Where two faces have more than one common cover, the strip search picks
+//! the smallest-id cover as their bridge, so relabelling can change its
+//! results. [`ambiguous_bridges`] detects that case; the key then falls back
+//! to the exact numbering and only the identity counts as a symmetry.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::lattice::{FaceId, Lattice};
+
+/// Give up on an isomorphism search after this many steps.
+const SEARCH_BUDGET: usize = 1_000_000;
+
+/// A poset as levels plus sorted cover relations `(lower, upper)`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Shape {
+    pub levels: Vec<usize>,
+    pub covers: Vec<(FaceId, FaceId)>,
+}
+
+/// FNV-1a, which (unlike `std`'s hasher) is stable across runs and builds.
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, x: usize) {
+        for byte in (x as u64).to_le_bytes() {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+}
+
+/// Some two faces of `l` have several common covers.
+pub fn ambiguous_bridges(l: &Lattice) -> bool {
+    Shape::of(l).ambiguous_bridges()
+}
+
+/// All automorphisms of `l` (each a map of face ids, the identity first),
+/// or `None` if there are more than `limit` or the search runs out of
+/// budget.
+pub fn automorphisms(l: &Lattice, limit: usize) -> Option<Vec<Vec<FaceId>>> {
+    let shape = Shape::of(l);
+    let (key, colours) = shape.key();
+    let mut all = Vec::new();
+    let complete = shape.search(&key, &colours, &shape, &mut |map| {
+        all.push(map.to_vec());
+        all.len() <= limit
+    })?;
+    (complete && all.len() <= limit).then(|| {
+        let identity: Vec<FaceId> = (0..shape.levels.len()).collect();
+        all.sort_by_key(|m| *m != identity);
+        all
+    })
+}
+
+/// Group hamiltonian paths (or cycles) of level 0 into orbits under
+/// `automorphisms`, as yielded by [`Lattice::ham_paths`]: linear paths up to
+/// reversal, cycles rotated to start at the first face of level 0. Returns
+/// the orbit number of every path; orbits are numbered in order of their
+/// first path. Images that are not in `paths` are ignored.
+pub fn path_orbits(
+    l: &Lattice,
+    paths: &[Vec<FaceId>],
+    cyclic: bool,
+    automorphisms: &[Vec<FaceId>],
+) -> Vec<usize> {
+    let anchor = l.level(0).first().copied();
+    let normalize = |mut p: Vec<FaceId>| {
+        if cyclic {
+            if let Some(i) = anchor.and_then(|a| p.iter().position(|&f| f == a)) {
+                p.rotate_left(i);
+            }
+        } else if p.first() > p.last() {
+            p.reverse();
+        }
+        p
+    };
+    let index: HashMap<&[FaceId], usize> =
+        paths.iter().enumerate().map(|(i, p)| (p.as_slice(), i)).collect();
+    let mut orbit = vec![usize::MAX; paths.len()];
+    let mut next = 0;
+    for i in 0..paths.len() {
+        if orbit[i] != usize::MAX {
+            continue;
+        }
+        for sigma in automorphisms {
+            let image = normalize(paths[i].iter().map(|&f| sigma[f]).collect());
+            if let Some(&j) = index.get(image.as_slice()) {
+                orbit[j] = next;
+            }
+        }
+        orbit[i] = next;
+        next += 1;
+    }
+    orbit
+}
+
+impl Shape {
+    pub fn of(l: &Lattice) -> Self {
+        let levels = l.faces().map(|(_, f)| f.dim()).collect();
+        let mut covers: Vec<(FaceId, FaceId)> =
+            l.faces().flat_map(|(b, f)| f.downset().iter().map(move |&a| (a, b))).collect();
+        covers.sort_unstable();
+        covers.dedup();
+        Shape { levels, covers }
+    }
+
+    fn neighbours(&self) -> (Vec<Vec<FaceId>>, Vec<Vec<FaceId>>) {
+        let n = self.levels.len();
+        let (mut down, mut up) = (vec![vec![]; n], vec![vec![]; n]);
+        for &(a, b) in &self.covers {
+            up[a].push(b);
+            down[b].push(a);
+        }
+        (down, up)
+    }
+
+    fn ambiguous_bridges(&self) -> bool {
+        let (down, _) = self.neighbours();
+        let mut pairs = HashSet::new();
+        down.iter().any(|below| {
+            below
+                .iter()
+                .enumerate()
+                .any(|(k, &i)| below[k + 1..].iter().any(|&j| !pairs.insert((i.min(j), i.max(j)))))
+        })
+    }
+
+    /// The invariant key (hex; `x`-prefixed for exact-numbering keys) and
+    /// the refined colour of every face.
+    pub fn key(&self) -> (String, Vec<usize>) {
+        let n = self.levels.len();
+        let mut h = Fnv(0xcbf2_9ce4_8422_2325);
+        h.write(n);
+        if self.ambiguous_bridges() {
+            // exact numbering: every face its own colour
+            for &(a, b) in &self.covers {
+                h.write(a);
+                h.write(b);
+            }
+            return (format!("x{:016x}", h.0), (0..n).collect());
+        }
+
+        let (down, up) = self.neighbours();
+        let mut colour = self.levels.clone();
+        let mut classes = 0;
+        loop {
+            let signature = |v: usize| {
+                let mut d: Vec<usize> = down[v].iter().map(|&w| colour[w]).collect();
+                let mut u: Vec<usize> = up[v].iter().map(|&w| colour[w]).collect();
+                d.sort_unstable();
+                u.sort_unstable();
+                (colour[v], d, u)
+            };
+            let signatures: Vec<_> = (0..n).map(signature).collect();
+            let mut sorted = signatures.clone();
+            sorted.sort();
+            for (c, d, u) in &sorted {
+                h.write(*c);
+                d.iter().chain(u).for_each(|&x| h.write(x));
+                h.write(usize::MAX); // separator
+            }
+            sorted.dedup();
+            colour = signatures.iter().map(|s| sorted.binary_search(s).unwrap()).collect();
+            if sorted.len() == classes {
+                return (format!("{:016x}", h.0), colour);
+            }
+            classes = sorted.len();
+        }
+    }
+
+    /// An isomorphism `self → other` as a map of face ids (`key` and
+    /// `colours` are `self`'s, from [`Shape::key`]). `None` if there is none
+    /// or the budget ran out.
+    pub fn isomorphism(&self, key: &str, colours: &[usize], other: &Shape) -> Option<Vec<FaceId>> {
+        let mut first = None;
+        self.search(key, colours, other, &mut |map| {
+            first = Some(map.to_vec());
+            false
+        })?;
+        first
+    }
+
+    /// Pass every isomorphism `self → other` to `found` until it returns
+    /// false. `Some(true)` if the search ran to the end, `Some(false)` if
+    /// `found` stopped it, `None` if it ran out of budget.
+    fn search(
+        &self,
+        key: &str,
+        colours: &[usize],
+        other: &Shape,
+        found: &mut dyn FnMut(&[FaceId]) -> bool,
+    ) -> Option<bool> {
+        let n = self.levels.len();
+        if other.levels.len() != n || other.covers.len() != self.covers.len() {
+            return Some(true);
+        }
+        let (other_key, other_colours) = other.key();
+        if other_key != key {
+            return Some(true); // a hash collision
+        }
+        if key.starts_with('x') {
+            if self == other {
+                let identity: Vec<FaceId> = (0..n).collect();
+                return Some(found(&identity));
+            }
+            return Some(true);
+        }
+
+        // visit order: breadth-first from the rarest colours, so most faces
+        // have a mapped neighbour that narrows their candidates
+        let mut class_size = vec![0; n];
+        colours.iter().for_each(|&c| class_size[c] += 1);
+        let mut starts: Vec<FaceId> = (0..n).collect();
+        starts.sort_by_key(|&v| (class_size[colours[v]], v));
+        let (down, up) = self.neighbours();
+        let mut order = Vec::with_capacity(n);
+        let mut seen = vec![false; n];
+        for s in starts {
+            if seen[s] {
+                continue;
+            }
+            seen[s] = true;
+            let mut queue = VecDeque::from([s]);
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+                for &w in down[v].iter().chain(&up[v]) {
+                    if !seen[w] {
+                        seen[w] = true;
+                        queue.push_back(w);
+                    }
+                }
+            }
+        }
+
+        let (other_down, other_up) = other.neighbours();
+        let mut by_colour: Vec<Vec<FaceId>> = vec![vec![]; n];
+        other_colours.iter().enumerate().for_each(|(v, &c)| by_colour[c].push(v));
+        let mut search = Search {
+            order,
+            colours,
+            other_colours,
+            down,
+            up,
+            other_down,
+            other_up,
+            edges: other.covers.iter().copied().collect(),
+            by_colour,
+            map: vec![None; n],
+            used: vec![false; n],
+            steps: 0,
+            found,
+        };
+        search.extend(0).map(|stopped| !stopped)
+    }
+}
+
+struct Search<'a> {
+    order: Vec<FaceId>,
+    colours: &'a [usize],
+    other_colours: Vec<usize>,
+    down: Vec<Vec<FaceId>>,
+    up: Vec<Vec<FaceId>>,
+    other_down: Vec<Vec<FaceId>>,
+    other_up: Vec<Vec<FaceId>>,
+    edges: HashSet<(FaceId, FaceId)>,
+    by_colour: Vec<Vec<FaceId>>,
+    map: Vec<Option<FaceId>>,
+    used: Vec<bool>,
+    steps: usize,
+    found: &'a mut dyn FnMut(&[FaceId]) -> bool,
+}
+
+impl Search<'_> {
+    /// Map `order[k..]`; `Some(true)` once `found` asks to stop.
+    fn extend(&mut self, k: usize) -> Option<bool> {
+        if k == self.order.len() {
+            let map: Vec<FaceId> = self.map.iter().map(|m| m.unwrap()).collect();
+            return Some(!(self.found)(&map));
+        }
+        let v = self.order[k];
+        // candidates: around an already mapped neighbour if there is one
+        let anchor = self.down[v]
+            .iter()
+            .map(|&w| (w, true))
+            .chain(self.up[v].iter().map(|&w| (w, false)))
+            .find_map(|(w, below)| self.map[w].map(|m| (m, below)));
+        let candidates: Vec<FaceId> = match anchor {
+            Some((m, true)) => self.other_up[m].clone(),
+            Some((m, false)) => self.other_down[m].clone(),
+            None => self.by_colour[self.colours[v]].clone(),
+        };
+        for c in candidates {
+            if self.used[c] || self.other_colours[c] != self.colours[v] {
+                continue;
+            }
+            self.steps += 1;
+            if self.steps > SEARCH_BUDGET {
+                return None;
+            }
+            // every cover of `v` to a mapped face must land on a cover; with
+            // equal cover counts that makes the final map an isomorphism
+            let fits = self.down[v]
+                .iter()
+                .all(|&w| self.map[w].is_none_or(|m| self.edges.contains(&(m, c))))
+                && self.up[v]
+                    .iter()
+                    .all(|&w| self.map[w].is_none_or(|m| self.edges.contains(&(c, m))));
+            if !fits {
+                continue;
+            }
+            self.map[v] = Some(c);
+            self.used[c] = true;
+            let stop = self.extend(k + 1)?;
+            self.map[v] = None;
+            self.used[c] = false;
+            if stop {
+                return Some(true);
+            }
+        }
+        Some(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::lattice::Lattice;
+
+    fn lattice(p: Result<generators::Poset, String>) -> Lattice {
+        p.unwrap().to_lattice().unwrap()
+    }
+
+    #[test]
+    fn key_ignores_numbering_but_not_structure() {
+        let p = generators::boolean(4).unwrap();
+        let mut q = p.clone();
+        let n = p.len();
+        q.labels.reverse();
+        q.edges = p.edges.iter().map(|&(a, b)| (n - 1 - a, n - 1 - b)).collect();
+        let a = Shape::of(&p.to_lattice().unwrap());
+        let b = Shape::of(&q.to_lattice().unwrap());
+        let (key, colours) = a.key();
+        assert_eq!(b.key().0, key);
+        assert!(a.isomorphism(&key, &colours, &b).is_some());
+
+        let fence = |n| Shape::of(&lattice(generators::fence(n))).key().0;
+        assert_ne!(fence(6), fence(7));
+    }
+
+    #[test]
+    fn automorphism_groups_and_path_orbits() {
+        // B_n has the n! coordinate permutations, the cube 2^d d! symmetries
+        assert_eq!(automorphisms(&lattice(generators::boolean(4)), 1000).unwrap().len(), 24);
+        let cube = lattice(generators::cube(3));
+        let autos = automorphisms(&cube, 1000).unwrap();
+        assert_eq!(autos.len(), 48);
+        assert!(autos[0].iter().enumerate().all(|(i, &f)| i == f));
+        assert!(automorphisms(&cube, 10).is_none());
+
+        // the hamiltonian paths of the cube graph, up to reversal: 72 of
+        // them, in 3 orbits of the symmetry group
+        let paths: Vec<_> = cube.ham_paths(false).collect();
+        assert_eq!(paths.len(), 72);
+        let orbits = path_orbits(&cube, &paths, false, &autos);
+        assert_eq!(orbits.iter().max(), Some(&2));
+    }
+}
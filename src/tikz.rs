@@ -0,0 +1,464 @@
+//! Configurable TikZ export, shared by the CLI (`--show`, `--render
+//! x.tex`), the desktop GUI and the browser so all three produce the same
+//! code for the same picture.
+//!
+//! A [`Picture`] is what gets drawn — a strip from
+//! [`plotting::strip_layout`], or a Hasse diagram at the positions the user
+//! sees — and [`TikzOptions`] how: a whole document or a snippet to paste,
+//! the scale, node shape and fill per level, colours for bridge and gap
+//! edges, how labels are escaped, and a strip boundary to highlight. The
+//! code names every style it uses (`face`, `level0`, `bridge`, `gap`,
+//! `closing`, `boundary`) in the `tikzpicture` options, so restyling a
+//! pasted picture means editing one line.
+//!
+//! The options also have a short text form, the one `--tikz` and the
+//! browser take: comma-separated items, e.g. `snippet,scale=1.2,
+//! shape=circle,fill=white/blue!10,edges,math,boundary=left`.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::generators::Poset;
+use crate::lattice::{FaceId, Lattice};
+use crate::plotting::StripLayout;
+use crate::rhombic::Layer;
+
+/// Colours of the coloured edges and the boundary (HTML hex).
+const BRIDGE: &str = "#0072B2";
+const GAP: &str = "#009E73";
+const CLOSING: &str = "#E66100";
+const BOUNDARY: &str = "#CC79A7";
+
+/// Paper area that `scale: None` fits: a4 landscape with 10mm margins.
+const FIT_CM: (f64, f64) = (27.7, 17.0);
+
+const SHAPES: [&str; 4] = ["rectangle", "rounded", "circle", "ellipse"];
+
+/// Which side of a strip: the first or the last face of every layer.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct TikzOptions {
+    /// A document (`standalone` class) rather than only the `tikzpicture`.
+    pub standalone: bool,
+    /// Centimetres per layout unit; `None` fits the picture to a4 landscape
+    /// and shrinks the font to match.
+    pub scale: Option<f64>,
+    /// Node shapes by level, repeated when there are more levels: one of
+    /// `rectangle`, `rounded`, `circle`, `ellipse`.
+    pub shapes: Vec<String>,
+    /// Node fills by level, repeated likewise: xcolor expressions
+    /// (`blue!10`) or `#rrggbb`.
+    pub fills: Vec<String>,
+    /// Draw the edges to bridges and to gap faces in two colours.
+    pub colour_edges: bool,
+    /// Escape TeX's special characters in labels; off passes labels as
+    /// they are, for labels that already are TeX.
+    pub escape: bool,
+    /// Typeset labels in math mode (`_` and `^` then make indices).
+    pub math: bool,
+    /// Highlight this boundary of a strip, faces and edges.
+    pub boundary: Option<Side>,
+}
+
+impl Default for TikzOptions {
+    fn default() -> Self {
+        TikzOptions {
+            standalone: true,
+            scale: None,
+            shapes: vec!["rectangle".to_string()],
+            fills: vec!["white".to_string()],
+            colour_edges: false,
+            escape: true,
+            math: false,
+            boundary: None,
+        }
+    }
+}
+
+impl TikzOptions {
+    /// Parse the text form (see the module docs); items not given keep
+    /// their defaults, so `""` is the default.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut o = TikzOptions::default();
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = match item.split_once('=') {
+                Some((k, v)) => (k.trim(), Some(v.trim())),
+                None => (item, None),
+            };
+            let list = |v: Option<&str>| -> Result<Vec<String>, String> {
+                let v = v.filter(|v| !v.is_empty()).ok_or(format!("tikz: {} needs a value", key))?;
+                Ok(v.split('/').map(|s| s.trim().to_string()).collect())
+            };
+            match (key, value) {
+                ("standalone", None) => o.standalone = true,
+                ("snippet", None) => o.standalone = false,
+                ("fit", None) => o.scale = None,
+                ("scale", Some(v)) => match v.parse::<f64>() {
+                    Ok(s) if s > 0.0 && s.is_finite() => o.scale = Some(s),
+                    _ => return Err(format!("tikz: bad scale '{}'", v)),
+                },
+                ("shape", v) => o.shapes = list(v)?,
+                ("fill", v) => o.fills = list(v)?,
+                ("edges", None) => o.colour_edges = true,
+                ("math", None) => o.math = true,
+                ("raw", None) => o.escape = false,
+                ("boundary", Some("left")) => o.boundary = Some(Side::Left),
+                ("boundary", Some("right")) => o.boundary = Some(Side::Right),
+                ("boundary", Some("none")) => o.boundary = None,
+                _ => return Err(format!("tikz: unknown option '{}'", item)),
+            }
+        }
+        o.check()?;
+        Ok(o)
+    }
+
+    /// The text form of `self`, for [`TikzOptions::parse`].
+    pub fn spec(&self) -> String {
+        let mut items = vec![if self.standalone { "standalone" } else { "snippet" }.to_string()];
+        items.push(match self.scale {
+            Some(s) => format!("scale={}", s),
+            None => "fit".to_string(),
+        });
+        items.push(format!("shape={}", self.shapes.join("/")));
+        items.push(format!("fill={}", self.fills.join("/")));
+        for (on, name) in [(self.colour_edges, "edges"), (self.math, "math"), (!self.escape, "raw")] {
+            if on {
+                items.push(name.to_string());
+            }
+        }
+        match self.boundary {
+            Some(Side::Left) => items.push("boundary=left".to_string()),
+            Some(Side::Right) => items.push("boundary=right".to_string()),
+            None => {}
+        }
+        items.join(",")
+    }
+
+    /// Shapes and colours that would break the document.
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(bad) = self.shapes.iter().find(|s| !SHAPES.contains(&s.as_str())) {
+            return Err(format!("tikz: unknown shape '{}' (expected {})", bad, SHAPES.join(", ")));
+        }
+        for fill in &self.fills {
+            colour(fill)?;
+        }
+        if self.shapes.is_empty() || self.fills.is_empty() {
+            return Err("tikz: shape and fill need at least one value".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A colour for TikZ options: `#rrggbb` as an rgb expression, anything
+/// else (an xcolor expression) as it is.
+fn colour(c: &str) -> Result<String, String> {
+    let Some(hex) = c.strip_prefix('#') else {
+        if c.is_empty() || c.contains(['{', '}', ',', '=', '\\']) {
+            return Err(format!("tikz: bad colour '{}'", c));
+        }
+        return Ok(c.to_string());
+    };
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok());
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(format!("{{rgb,255:red,{};green,{};blue,{}}}", r, g, b)),
+        _ => Err(format!("tikz: bad colour '{}'", c)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// A cover relation of a Hasse diagram.
+    Cover,
+    /// From a face to the bridge above it and its neighbour.
+    Bridge,
+    /// From a face to a gap face above it.
+    Gap,
+    /// A closing edge of a cyclic strip.
+    Closing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PictureNode {
+    /// Names the coordinate, `n<id>`.
+    pub id: usize,
+    pub label: String,
+    /// Layout units, y up.
+    pub x: f64,
+    pub y: f64,
+    pub level: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PictureEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+    /// Leave and enter at these angles (degrees) instead of a straight line.
+    pub bend: Option<(f64, f64)>,
+}
+
+/// What to draw: nodes and edges, plus a strip's two boundaries (the
+/// first and the last face of every layer, bottom up) if it is one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Picture {
+    pub nodes: Vec<PictureNode>,
+    pub edges: Vec<PictureEdge>,
+    pub boundaries: Option<[Vec<usize>; 2]>,
+}
+
+impl Picture {
+    /// A strip in the layout of [`plotting::strip_layout`](crate::plotting::strip_layout);
+    /// edges are told apart into bridge, gap and closing edges.
+    pub fn of_strip(layers: &[Layer], layout: &StripLayout, l: &Lattice) -> Self {
+        let mut level = vec![0; l.num_faces()];
+        let mut bridges: HashSet<FaceId> = HashSet::new();
+        for (k, layer) in layers.iter().enumerate() {
+            for &f in layer {
+                level[f] = k;
+            }
+            bridges.extend(layer.windows(2).filter_map(|w| l.bridge(w[0], w[1])));
+        }
+        let nodes = layout
+            .nodes
+            .iter()
+            .map(|&(f, x, y)| PictureNode { id: f, label: l.face(f).label().to_string(), x, y, level: level[f] })
+            .collect();
+        let edges = layout
+            .edges
+            .iter()
+            .map(|e| {
+                let kind = if e.closing {
+                    EdgeKind::Closing
+                } else if bridges.contains(&e.to) {
+                    EdgeKind::Bridge
+                } else {
+                    EdgeKind::Gap
+                };
+                PictureEdge { from: e.from, to: e.to, kind, bend: e.bend }
+            })
+            .collect();
+        let side = |pick: fn(&Layer) -> Option<&FaceId>| layers.iter().filter_map(pick).copied().collect();
+        Picture { nodes, edges, boundaries: Some([side(|l| l.first()), side(|l| l.last())]) }
+    }
+
+    /// The Hasse diagram of `p` with node `i` at `positions[i]` (layout
+    /// units, y up). Levels are the ranks, or all 0 if `p` has a cycle
+    /// (graph mode).
+    pub fn of_poset(p: &Poset, positions: &[(f64, f64)]) -> Result<Self, String> {
+        if positions.len() != p.len() {
+            return Err(format!("{} positions for {} nodes", positions.len(), p.len()));
+        }
+        let ranks = p.ranks().unwrap_or_else(|_| vec![0; p.len()]);
+        let nodes = p
+            .labels
+            .iter()
+            .zip(positions)
+            .enumerate()
+            .map(|(i, (label, &(x, y)))| PictureNode { id: i, label: label.clone(), x, y, level: ranks[i] })
+            .collect();
+        let edges = p
+            .edges
+            .iter()
+            .map(|&(a, b)| PictureEdge { from: a, to: b, kind: EdgeKind::Cover, bend: None })
+            .collect();
+        Ok(Picture { nodes, edges, boundaries: None })
+    }
+
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        let mut b = (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for n in &self.nodes {
+            b = (b.0.min(n.x), b.1.min(n.y), b.2.max(n.x), b.3.max(n.y));
+        }
+        if self.nodes.is_empty() {
+            (0.0, 0.0, 0.0, 0.0)
+        } else {
+            b
+        }
+    }
+}
+
+/// A label as TeX, following `o.escape` and `o.math`.
+fn tex_label(label: &str, o: &TikzOptions) -> String {
+    let text: String = if !o.escape {
+        label.to_string()
+    } else {
+        label
+            .chars()
+            .map(|c| match (c, o.math) {
+                ('{' | '}' | '&' | '%' | '#' | '$', _) => format!("\\{}", c),
+                ('_' | '^', true) => c.to_string(),
+                ('_', false) => "\\_".to_string(),
+                ('\\', true) => "\\backslash{}".to_string(),
+                ('\\', false) => "\\textbackslash{}".to_string(),
+                ('^' | '~', false) => format!("\\{}{{}}", c),
+                ('~', true) => "\\sim{}".to_string(),
+                ('|', false) => "\\textbar{}".to_string(),
+                (c, _) => c.to_string(),
+            })
+            .collect()
+    };
+    if o.math {
+        format!("${}$", text)
+    } else {
+        text
+    }
+}
+
+/// TikZ code drawing `picture` as `o` says.
+pub fn tikz(picture: &Picture, o: &TikzOptions) -> Result<String, String> {
+    o.check()?;
+    let (min_x, min_y, max_x, max_y) = picture.bounds();
+    let max_label_len = picture.nodes.iter().map(|n| n.label.chars().count()).max().unwrap_or(0);
+    let cyclic = picture.edges.iter().any(|e| e.bend.is_some());
+
+    // centimetres per unit and font; fitting shrinks the font so the
+    // widest label still fits between its neighbours
+    let (scale_x, scale_y, font) = match o.scale {
+        Some(s) => (s, s, "\\small".to_string()),
+        None => {
+            let mut scale_x = FIT_CM.0 / ((max_x - min_x).max(1.0) + 2.0);
+            let mut scale_y = FIT_CM.1 / ((max_y - min_y).max(1.0) + 2.0);
+            if cyclic {
+                scale_x = scale_x.min(scale_y);
+                scale_y = scale_x;
+            }
+            let needed_w = max_label_len as f64 * 0.22 + 0.4;
+            let ratio = (scale_x / needed_w).min(scale_y / 0.8).min(1.0);
+            let pt = (10.0 * ratio).max(2.0);
+            (scale_x, scale_y, format!("\\fontsize{{{:.1}pt}}{{{:.1}pt}}\\selectfont", pt, pt * 1.2))
+        }
+    };
+
+    let mut styles = vec![format!("x={:.4}cm", scale_x), format!("y={:.4}cm", scale_y)];
+    styles.push(format!("face/.style={{draw, fill=white, inner sep=2pt, font={}}}", font));
+    let levels = picture.nodes.iter().map(|n| n.level + 1).max().unwrap_or(0);
+    for k in 0..levels {
+        let shape = match o.shapes[k % o.shapes.len()].as_str() {
+            "rounded" => "rectangle, rounded corners=2pt",
+            shape => shape,
+        };
+        styles.push(format!("level{}/.style={{{}, fill={}}}", k, shape, colour(&o.fills[k % o.fills.len()])?));
+    }
+    let draw = |on: bool, c: &str| if on { format!("draw={}", colour(c).unwrap()) } else { String::new() };
+    styles.push(format!("bridge/.style={{{}}}", draw(o.colour_edges, BRIDGE)));
+    styles.push(format!("gap/.style={{{}}}", draw(o.colour_edges, GAP)));
+    styles.push(format!("closing/.style={{dashed, {}}}", draw(true, CLOSING)));
+    styles.push(format!("boundary/.style={{very thick, {}}}", draw(true, BOUNDARY)));
+
+    let boundary: Vec<usize> = match (o.boundary, &picture.boundaries) {
+        (Some(side), Some(sides)) => sides[side as usize].clone(),
+        _ => vec![],
+    };
+    let on_boundary = |a: usize, b: usize| {
+        boundary.windows(2).any(|w| (w[0], w[1]) == (a, b) || (w[1], w[0]) == (a, b))
+    };
+
+    let mut tex = String::new();
+    if o.standalone {
+        tex.push_str("\\documentclass[tikz, border=5mm]{standalone}\n");
+        tex.push_str("\\begin{document}\n");
+    }
+    tex.push_str(&format!("\\begin{{tikzpicture}}[\n  {}]\n", styles.join(",\n  ")));
+    for n in &picture.nodes {
+        tex.push_str(&format!("  \\coordinate (n{}) at ({:.3},{:.3});\n", n.id, n.x, n.y));
+    }
+    for e in &picture.edges {
+        let mut style = match e.kind {
+            EdgeKind::Cover => vec![],
+            EdgeKind::Bridge => vec!["bridge"],
+            EdgeKind::Gap => vec!["gap"],
+            EdgeKind::Closing => vec!["closing"],
+        };
+        if on_boundary(e.from, e.to) {
+            style.push("boundary");
+        }
+        let style = if style.is_empty() { String::new() } else { format!("[{}]", style.join(", ")) };
+        let path = match e.bend {
+            Some((out_deg, in_deg)) => format!("to[out={:.1}, in={:.1}]", out_deg, in_deg),
+            None => "--".to_string(),
+        };
+        tex.push_str(&format!("  \\draw{} (n{}) {} (n{});\n", style, e.from, path, e.to));
+    }
+    for n in &picture.nodes {
+        let extra = if boundary.contains(&n.id) { ", boundary" } else { "" };
+        tex.push_str(&format!(
+            "  \\node[face, level{}{}] at (n{}) {{{}}};\n",
+            n.level,
+            extra,
+            n.id,
+            tex_label(&n.label, o)
+        ));
+    }
+    tex.push_str("\\end{tikzpicture}\n");
+    if o.standalone {
+        tex.push_str("\\end{document}\n");
+    }
+    Ok(tex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::plotting;
+    use crate::rhombic;
+
+    #[test]
+    fn options_round_trip_and_reject_nonsense() {
+        assert_eq!(TikzOptions::parse("").unwrap(), TikzOptions::default());
+        let o = TikzOptions::parse("snippet, scale=1.5, shape=circle/rounded, fill=#E66100/blue!10, edges, math, boundary=right")
+            .unwrap();
+        assert_eq!((o.standalone, o.scale, o.boundary), (false, Some(1.5), Some(Side::Right)));
+        assert_eq!(TikzOptions::parse(&o.spec()).unwrap(), o);
+        for bad in ["scale=0", "shape=hexagon", "fill=#12345", "fill={red}", "boundary=top", "colour"] {
+            assert!(TikzOptions::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn strips_get_edge_kinds_levels_and_a_boundary() {
+        let l = generators::cube(3).unwrap().to_lattice().unwrap();
+        let strip = rhombic::strips(&l, true).next().unwrap();
+        let picture = Picture::of_strip(&strip, &plotting::strip_layout(&strip, &l, true), &l);
+        let [left, right] = picture.boundaries.clone().unwrap();
+        assert_eq!((left.len(), right.len()), (strip.len(), strip.len()));
+        for kind in [EdgeKind::Bridge, EdgeKind::Gap, EdgeKind::Closing] {
+            assert!(picture.edges.iter().any(|e| e.kind == kind), "{:?}", kind);
+        }
+
+        let o = TikzOptions::parse("snippet,scale=1,fill=white/#E66100,boundary=left").unwrap();
+        let tex = tikz(&picture, &o).unwrap();
+        assert!(tex.starts_with("\\begin{tikzpicture}") && tex.ends_with("\\end{tikzpicture}\n"));
+        assert!(tex.contains("level1/.style={rectangle, fill={rgb,255:red,230;green,97;blue,0}}"));
+        assert_eq!(tex.matches("\\node[").count(), l.num_faces());
+        // every face of the left boundary, and the edges between them
+        let chain = picture.edges.iter().filter(|e| {
+            left.windows(2).any(|w| (w[0], w[1]) == (e.from, e.to) || (w[1], w[0]) == (e.from, e.to))
+        });
+        assert_eq!(tex.matches(", boundary]").count(), left.len() + chain.count());
+    }
+
+    #[test]
+    fn labels_are_escaped_or_typeset_as_math() {
+        let text = TikzOptions::default();
+        assert_eq!(tex_label("{1,2}|{3}_x", &text), "\\{1,2\\}\\textbar{}\\{3\\}\\_x");
+        let math = TikzOptions { math: true, ..TikzOptions::default() };
+        assert_eq!(tex_label("x_1|{2}", &math), "$x_1|\\{2\\}$");
+        let raw = TikzOptions { escape: false, ..TikzOptions::default() };
+        assert_eq!(tex_label("\\alpha_1", &raw), "\\alpha_1");
+
+        let p = generators::boolean(2).unwrap();
+        let positions: Vec<(f64, f64)> = (0..p.len()).map(|i| (i as f64, 0.0)).collect();
+        let tex = tikz(&Picture::of_poset(&p, &positions).unwrap(), &text).unwrap();
+        assert!(tex.starts_with("\\documentclass") && tex.contains("\\draw (n0) -- (n1);"));
+        assert!(Picture::of_poset(&p, &positions[1..]).is_err());
+    }
+}
@@ -17,588 +17,187 @@
 //! [`StripEnumerator`], which replaces the native worker thread: the browser
 //! calls `step(budget_ms, max_strips)` in a Web Worker loop, so the search is
 //! sliceable, streamable and cancellable — same contract as the bounded
-//! channel in `gui.rs`, with `postMessage` instead of `mpsc`.
+//! channel in `gui.rs`, with `postMessage` instead of `mpsc`. Every step
+//! also reports the share of the search done, the strips per second and the
+//! time left ([`crate::progress`]).
+//!
+//! Strips a remote search sends compactly ([`crate::codec`]) come back to
+//! the page's shape through [`StripDecoder`].
+
+use std::sync::Arc;
+use std::time::Duration;
 
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
+use crate::codec;
 use crate::lattice::{FaceId, Lattice};
 use crate::plotting;
-use crate::rhombic::{self, Strip};
+use crate::progress::Progress;
+use crate::rhombic::Strip;
 
 pub mod api {
-    //! Pure, host-testable implementations.
-
-    use std::collections::HashMap;
+    //! JSON wrappers over [`crate::generators`], host-testable. The wire
+    //! graph is [`Poset`](crate::generators::Poset) serialized as
+    //! `{"labels": [...], "edges": [[lower, upper], ...], "ranks": [...]}`.
 
-    use serde::{Deserialize, Serialize};
+    use crate::generators::{self, Poset};
+    use crate::lattice::{Face, Lattice};
+    use crate::layout;
+    use crate::plotting;
+    use crate::rhombic::{self, Strip};
+    use crate::tikz::{self, Picture, TikzOptions};
 
-    use crate::lattice::{Face, FaceId, Lattice};
-
-    // -- wire format ---------------------------------------------------------
-
-    #[derive(Serialize, Deserialize, Default, Clone)]
-    pub struct WireGraph {
-        pub labels: Vec<String>,
-        /// Poset mode: cover relations `(lower, upper)`; graph mode: edges.
-        pub edges: Vec<(usize, usize)>,
-        /// Longest-path ranks (poset outputs only); minima have rank 0.
-        #[serde(default, skip_serializing_if = "Option::is_none")]
-        pub ranks: Option<Vec<usize>>,
-    }
-
-    impl WireGraph {
-        pub fn parse(json: &str) -> Result<Self, String> {
-            serde_json::from_str(json).map_err(|e| format!("bad graph JSON: {}", e))
-        }
-
-        pub fn to_json(&self) -> String {
-            serde_json::to_string(self).expect("WireGraph serializes")
-        }
-
-        fn with_ranks(mut self) -> Result<Self, String> {
-            self.ranks = Some(ranks(&self)?);
-            Ok(self)
-        }
-    }
-
-    /// Longest-path rank of every node (minimal elements have rank 0).
-    /// Errors if the relation is cyclic. Mirrors `PosetGraph::ranks`.
-    pub fn ranks(g: &WireGraph) -> Result<Vec<usize>, String> {
-        let n = g.labels.len();
-        let mut succ: Vec<Vec<usize>> = vec![vec![]; n];
-        let mut indeg = vec![0usize; n];
-        for &(a, b) in &g.edges {
-            if a >= n || b >= n {
-                return Err(format!("edge ({}, {}) out of range", a, b));
-            }
-            succ[a].push(b);
-            indeg[b] += 1;
-        }
-
-        let mut rank = vec![0usize; n];
-        let mut queue: Vec<usize> = (0..n).filter(|&i| indeg[i] == 0).collect();
-        let mut processed = 0;
-        while let Some(i) = queue.pop() {
-            processed += 1;
-            for &j in &succ[i] {
-                rank[j] = rank[j].max(rank[i] + 1);
-                indeg[j] -= 1;
-                if indeg[j] == 0 {
-                    queue.push(j);
-                }
-            }
-        }
-        if processed != n {
-            return Err("Relation contains a cycle — not a poset.".to_string());
-        }
-        Ok(rank)
-    }
+    pub use crate::generators::{limits, Limits, Poset as WireGraph};
 
     /// Convert a wire poset to faces for `Lattice::from_faces`.
     /// Faces are in node order, so FaceId == node index.
     pub fn wire_to_faces(g: &WireGraph) -> Result<Vec<Face>, String> {
-        let rank = ranks(g)?;
-        let n = g.labels.len();
-        let mut upsets: Vec<Vec<FaceId>> = vec![vec![]; n];
-        let mut downsets: Vec<Vec<FaceId>> = vec![vec![]; n];
-        for &(a, b) in &g.edges {
-            upsets[a].push(b);
-            downsets[b].push(a);
-        }
-        Ok(g.labels
-            .iter()
-            .enumerate()
-            .map(|(i, label)| {
-                Face::new(
-                    label.clone(),
-                    rank[i],
-                    std::mem::take(&mut upsets[i]),
-                    std::mem::take(&mut downsets[i]),
-                )
-            })
-            .collect())
+        g.to_faces()
     }
 
     /// Ranks as JSON `[r0, r1, ...]` (also validates acyclicity).
     pub fn poset_ranks(graph_json: &str) -> Result<String, String> {
-        let g = WireGraph::parse(graph_json)?;
-        Ok(serde_json::to_string(&ranks(&g)?).unwrap())
+        Ok(serde_json::to_string(&Poset::parse(graph_json)?.ranks()?).unwrap())
     }
 
-    // -- persistence: `dim: label: {upset}, {downset}` -------------------------
-
     pub fn to_lattice_file(graph_json: &str) -> Result<String, String> {
-        let g = WireGraph::parse(graph_json)?;
-        let faces = wire_to_faces(&g)?;
-        let fmt_set =
-            |s: &[FaceId]| s.iter().map(usize::to_string).collect::<Vec<_>>().join(", ");
-        let mut out = String::new();
-        for face in &faces {
-            let label = face.label().replace(": ", "-"); // ": " is the field separator
-            out.push_str(&format!(
-                "{}: {}: {{{}}}, {{{}}}\n",
-                face.dim(),
-                label,
-                fmt_set(face.upset()),
-                fmt_set(face.downset())
-            ));
-        }
-        Ok(out)
+        Poset::parse(graph_json)?.to_lattice_file()
     }
 
     pub fn from_lattice_file(content: &str) -> Result<String, String> {
-        let l = Lattice::from_str_content(content)?;
-        let mut g = WireGraph::default();
-        for (_, face) in l.faces() {
-            g.labels.push(face.label().to_string());
-        }
-        for (id, face) in l.faces() {
-            for &d in face.downset() {
-                g.edges.push((d, id));
-            }
-        }
-        Ok(g.with_ranks()?.to_json())
+        Ok(Poset::from_lattice_file(content)?.to_json())
     }
 
-    // -- poset generators (ports of the PosetGraph generators in gui.rs) -------
-
-    /// Product of chains. `"211"` gives C3 x C2 x C2 (per-digit); inputs with
-    /// separators like `"12,3"` allow multi-digit chain lengths.
-    pub fn gen_grid(spec: &str) -> Result<String, String> {
-        let dims: Vec<u32> = if spec.chars().all(|c| c.is_ascii_digit()) {
-            spec.chars().filter_map(|c| c.to_digit(10)).collect()
-        } else {
-            spec.split(|c: char| !c.is_ascii_digit())
-                .filter(|t| !t.is_empty())
-                .filter_map(|t| t.parse().ok())
-                .collect()
-        };
-        if dims.is_empty() {
-            return Err("Enter chain lengths, e.g. 211 or 12,3.".to_string());
-        }
-        let size: u64 = dims.iter().map(|&d| d as u64 + 1).product();
-        if size > 5_000 {
-            return Err(format!("Grid too large ({} elements).", size));
-        }
-
-        let mut points: Vec<Vec<u32>> = vec![vec![]];
-        for &d in &dims {
-            points = points
-                .into_iter()
-                .flat_map(|p| {
-                    (0..=d).map(move |i| {
-                        let mut q = p.clone();
-                        q.push(i);
-                        q
-                    })
-                })
-                .collect();
-        }
-
-        let sep = if dims.iter().all(|&d| d <= 9) { "" } else { "," };
-        let mut g = WireGraph::default();
-        for p in &points {
-            g.labels
-                .push(p.iter().map(u32::to_string).collect::<Vec<_>>().join(sep));
-        }
-        for (i, p) in points.iter().enumerate() {
-            for (j, q) in points.iter().enumerate() {
-                let mut up_by_one = None;
-                let mut ok = true;
-                for k in 0..p.len() {
-                    if p[k] != q[k] {
-                        if up_by_one.is_none() && q[k] == p[k] + 1 {
-                            up_by_one = Some(k);
-                        } else {
-                            ok = false;
-                            break;
-                        }
-                    }
-                }
-                if ok && up_by_one.is_some() {
-                    g.edges.push((i, j));
-                }
-            }
-        }
-        Ok(g.with_ranks()?.to_json())
-    }
-
-    /// Face lattice of the d-cube (without the empty face): all words over
-    /// {0, 1, *}, covers replace one fixed coordinate by *.
-    pub fn gen_cube(d: usize) -> Result<String, String> {
-        if !(1..=5).contains(&d) {
-            return Err("Cube dimension must be between 1 and 5.".to_string());
-        }
-        let mut faces: Vec<String> = (0..3usize.pow(d as u32))
-            .map(|mut k| {
-                (0..d)
-                    .map(|_| {
-                        let c = ['0', '1', '*'][k % 3];
-                        k /= 3;
-                        c
-                    })
-                    .collect()
-            })
-            .collect();
-        faces.sort_by_key(|f: &String| (f.matches('*').count(), f.clone()));
-        let idx: HashMap<String, usize> =
-            faces.iter().enumerate().map(|(i, f)| (f.clone(), i)).collect();
-
-        let mut g = WireGraph { labels: faces.clone(), ..Default::default() };
-        for (i, f) in faces.iter().enumerate() {
-            for (k, c) in f.chars().enumerate() {
-                if c != '*' {
-                    let mut upper: Vec<char> = f.chars().collect();
-                    upper[k] = '*';
-                    g.edges.push((i, idx[&upper.into_iter().collect::<String>()]));
-                }
-            }
-        }
-        Ok(g.with_ranks()?.to_json())
-    }
-
-    /// Face lattice of the d-simplex (without the empty face): nonempty
-    /// subsets of {0, ..., d}, covers add one element.
-    pub fn gen_simplex(d: usize) -> Result<String, String> {
-        if !(1..=6).contains(&d) {
-            return Err("Simplex dimension must be between 1 and 6.".to_string());
-        }
-        let n = d + 1;
-        let mut masks: Vec<u64> = (1..(1u64 << n)).collect();
-        masks.sort_by_key(|m| (m.count_ones(), *m));
-        let idx: HashMap<u64, usize> =
-            masks.iter().enumerate().map(|(i, &m)| (m, i)).collect();
-
-        let label = |m: u64| -> String {
-            (0..n).filter(|&v| (m >> v) & 1 == 1).map(|v| v.to_string()).collect()
-        };
-        let mut g = WireGraph::default();
-        for &m in &masks {
-            g.labels.push(label(m));
-        }
-        for (i, &m) in masks.iter().enumerate() {
-            for v in 0..n {
-                if (m >> v) & 1 == 0 {
-                    g.edges.push((i, idx[&(m | (1 << v))]));
-                }
-            }
-        }
-        Ok(g.with_ranks()?.to_json())
-    }
-
-    /// Infer cover relations from all-digit labels: same length, digit sums
-    /// differing by one, and exactly one differing position.
     /// Returns `{"graph": ..., "added": n}`.
     pub fn infer_digit_relations(graph_json: &str) -> Result<String, String> {
-        let mut g = WireGraph::parse(graph_json)?;
-        let digit_sum = |s: &str| -> Option<i64> {
-            s.chars().map(|c| c.to_digit(10).map(|d| d as i64)).sum::<Option<i64>>()
-        };
-        let mut added = 0usize;
-        let n = g.labels.len();
-        for i in 0..n {
-            for j in 0..n {
-                if i == j || g.labels[i].len() != g.labels[j].len() {
-                    continue;
-                }
-                let (Some(sa), Some(sb)) =
-                    (digit_sum(&g.labels[i]), digit_sum(&g.labels[j]))
-                else {
-                    continue;
-                };
-                if sb != sa + 1 {
-                    continue;
-                }
-                let diff = g.labels[i]
-                    .chars()
-                    .zip(g.labels[j].chars())
-                    .filter(|(x, y)| x != y)
-                    .count();
-                if diff == 1 && !g.edges.contains(&(i, j)) {
-                    g.edges.push((i, j));
-                    added += 1;
-                }
-            }
-        }
+        let mut g = Poset::parse(graph_json)?;
+        let added = g.infer_digit_relations();
         let g = g.with_ranks()?;
         Ok(format!("{{\"graph\":{},\"added\":{}}}", g.to_json(), added))
     }
 
-    /// The distributive lattice J(P) of order ideals of the given poset.
-    pub fn gen_distributive(graph_json: &str) -> Result<String, String> {
-        let g = WireGraph::parse(graph_json)?;
-        ranks(&g)?; // must be acyclic
-        let n = g.labels.len();
-        if n > 20 {
-            return Err(format!("Poset too large ({} > 20 elements) for J(P).", n));
-        }
-        let mut down_mask = vec![0u64; n];
-        for &(a, b) in &g.edges {
-            down_mask[b] |= 1 << a;
-        }
-
-        let mut ideals: Vec<u64> = Vec::new();
-        for mask in 0u64..(1 << n) {
-            let ok = (0..n)
-                .all(|i| (mask >> i) & 1 == 0 || (mask & down_mask[i]) == down_mask[i]);
-            if ok {
-                ideals.push(mask);
-            }
-        }
-
-        let mut out = WireGraph::default();
-        for &mask in &ideals {
-            let mut labels: Vec<&str> = (0..n)
-                .filter(|&i| (mask >> i) & 1 == 1)
-                .map(|i| g.labels[i].as_str())
-                .collect();
-            labels.sort_unstable();
-            out.labels.push(if labels.is_empty() {
-                "0".to_string()
-            } else {
-                format!("{{{}}}", labels.join(","))
-            });
-        }
-        for (i, &ma) in ideals.iter().enumerate() {
-            for (j, &mb) in ideals.iter().enumerate() {
-                if mb.count_ones() == ma.count_ones() + 1 && (ma & mb) == ma {
-                    out.edges.push((i, j));
-                }
-            }
-        }
-        Ok(out.with_ranks()?.to_json())
+    /// A JSON array of graphs with the root at vertex 0.
+    pub fn gen_rooted_trees(n: usize) -> Result<String, String> {
+        Ok(serde_json::to_string(&generators::rooted_trees(n)?).expect("Poset serializes"))
     }
 
-    // -- graph generators (graph mode) -----------------------------------------
-
-    /// `kind`: "path" | "cycle" | "complete" | "star", on n vertices.
-    pub fn gen_graph(kind: &str, n: usize) -> Result<String, String> {
-        if n == 0 || n > 32 {
-            return Err("n must be between 1 and 32.".to_string());
-        }
-        let mut g = WireGraph::default();
-        for i in 0..n {
-            g.labels.push(i.to_string());
-        }
-        match kind {
-            "path" => {
-                for i in 0..n.saturating_sub(1) {
-                    g.edges.push((i, i + 1));
-                }
-            }
-            "cycle" => {
-                if n > 2 {
-                    for i in 0..n {
-                        g.edges.push((i, (i + 1) % n));
-                    }
-                } else if n == 2 {
-                    g.edges.push((0, 1));
-                }
-            }
-            "complete" => {
-                for i in 0..n {
-                    for j in (i + 1)..n {
-                        g.edges.push((i, j));
-                    }
-                }
-            }
-            "star" => {
-                for i in 1..n {
-                    g.edges.push((0, i));
-                }
-            }
-            _ => return Err(format!("unknown graph kind '{}'", kind)),
-        }
-        Ok(g.to_json())
+    /// Update some limits, e.g. `{"max_faces": 100000}`; returns all of them.
+    pub fn set_limits(json: &str) -> Result<String, String> {
+        let mut current = serde_json::to_value(limits()).expect("Limits serializes");
+        let update: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(json).map_err(|e| format!("bad limits JSON: {}", e))?;
+        for (k, v) in update {
+            current[k] = v;
+        }
+        let new: Limits =
+            serde_json::from_value(current).map_err(|e| format!("bad limits: {}", e))?;
+        generators::set_limits(new);
+        get_limits()
     }
 
-    // -- graph associahedra: tubes and tubings (Carr–Devadoss) ------------------
-
-    type Mask = u64;
-
-    fn adjacency_masks(g: &WireGraph) -> Vec<Mask> {
-        let mut adj = vec![0u64; g.labels.len()];
-        for &(a, b) in &g.edges {
-            if a != b && a < adj.len() && b < adj.len() {
-                adj[a] |= 1 << b;
-                adj[b] |= 1 << a;
-            }
-        }
-        adj
+    /// The current limits as JSON.
+    pub fn get_limits() -> Result<String, String> {
+        Ok(serde_json::to_string(&limits()).expect("Limits serializes"))
     }
 
-    fn mask_connected(mask: Mask, adj: &[Mask]) -> bool {
-        if mask == 0 {
-            return false;
-        }
-        let mut seen = 1u64 << mask.trailing_zeros();
-        loop {
-            let mut grow = seen;
-            let mut m = seen;
-            while m != 0 {
-                let v = m.trailing_zeros() as usize;
-                grow |= adj[v] & mask;
-                m &= m - 1;
-            }
-            if grow == seen {
-                break;
-            }
-            seen = grow;
-        }
-        seen == mask
+    /// Positions `[[x, rank], ...]` of the Hasse diagram, in node order
+    /// ([`crate::layout::hasse`]).
+    pub fn hasse_layout(graph_json: &str) -> Result<String, String> {
+        Ok(serde_json::to_string(&layout::hasse(&Poset::parse(graph_json)?)?).unwrap())
     }
 
-    fn mask_neighbors(mask: Mask, adj: &[Mask]) -> Mask {
-        let mut nb = 0;
-        let mut m = mask;
-        while m != 0 {
-            let v = m.trailing_zeros() as usize;
-            nb |= adj[v];
-            m &= m - 1;
-        }
-        nb & !mask
+    /// The same with every rank in the order of the strip's layer
+    /// ([`crate::layout::hasse_along_strip`]).
+    pub fn hasse_layout_along(graph_json: &str, layers_json: &str) -> Result<String, String> {
+        let strip: Strip = serde_json::from_str(layers_json).map_err(|e| format!("bad strip JSON: {}", e))?;
+        Ok(serde_json::to_string(&layout::hasse_along_strip(&Poset::parse(graph_json)?, &strip)?).unwrap())
     }
 
-    fn tubes_compatible(a: Mask, b: Mask, adj: &[Mask]) -> bool {
-        if a & b != 0 {
-            let u = a | b;
-            u == a || u == b
-        } else {
-            mask_neighbors(a, adj) & b == 0
+    /// A strip (`layers_json`: layers of node indices) of the wire poset,
+    /// checked, with its lattice.
+    fn strip_on(graph_json: &str, layers_json: &str, cyclic: bool) -> Result<(Strip, Lattice), String> {
+        let l = Lattice::from_faces(Poset::parse(graph_json)?.to_faces()?);
+        let strip: Strip = serde_json::from_str(layers_json).map_err(|e| format!("bad strip JSON: {}", e))?;
+        if strip.iter().flatten().any(|&f| f >= l.num_faces()) {
+            return Err("strip refers to a node the graph does not have".to_string());
         }
+        rhombic::check_strip(&strip, &l, cyclic)?;
+        Ok((strip, l))
     }
 
-    fn enumerate_tubings(
-        tubes: &[Mask],
-        adj: &[Mask],
-        cap: usize,
-    ) -> Result<Vec<Vec<usize>>, String> {
-        let m = tubes.len();
-        let words = m.div_ceil(64);
-        let mut compat = vec![vec![0u64; words]; m];
-        for i in 0..m {
-            for j in (i + 1)..m {
-                if tubes_compatible(tubes[i], tubes[j], adj) {
-                    compat[i][j / 64] |= 1 << (j % 64);
-                    compat[j][i / 64] |= 1 << (i % 64);
-                }
-            }
-        }
-        let is_compat = |i: usize, j: usize| compat[i][j / 64] >> (j % 64) & 1 == 1;
-
-        fn rec(
-            start: usize,
-            current: &mut Vec<usize>,
-            out: &mut Vec<Vec<usize>>,
-            m: usize,
-            cap: usize,
-            is_compat: &dyn Fn(usize, usize) -> bool,
-        ) -> Result<(), String> {
-            out.push(current.clone());
-            if out.len() > cap {
-                return Err(format!("More than {} tubings — aborting.", cap));
-            }
-            for j in start..m {
-                if current.iter().all(|&i| is_compat(i, j)) {
-                    current.push(j);
-                    rec(j + 1, current, out, m, cap, is_compat)?;
-                    current.pop();
-                }
-            }
-            Ok(())
-        }
+    /// [`plotting::strip_layout`] as JSON: `{"cyclic": bool, "nodes":
+    /// [[index, x, y], ...], "edges": [{"from", "to", "closing", "bend"}]}`,
+    /// y growing with the layer.
+    pub fn strip_layout(graph_json: &str, layers_json: &str, cyclic: bool) -> Result<String, String> {
+        let (strip, l) = strip_on(graph_json, layers_json, cyclic)?;
+        Ok(serde_json::to_string(&plotting::strip_layout(&strip, &l, cyclic)).unwrap())
+    }
 
-        let mut out = Vec::new();
-        rec(0, &mut Vec::new(), &mut out, m, cap, &is_compat)?;
-        Ok(out)
+    /// The strip's edges ([`plotting::edges_strip`]): `{"edges": [[a, b],
+    /// ...], "cyclicEdges": [...]}`, as a strips message carries them.
+    pub fn edges_strip(graph_json: &str, layers_json: &str, cyclic: bool) -> Result<String, String> {
+        let (strip, l) = strip_on(graph_json, layers_json, cyclic)?;
+        let (edges, cyclic_edges) = plotting::edges_strip(&strip, &l, cyclic);
+        Ok(serde_json::json!({"edges": edges, "cyclicEdges": cyclic_edges}).to_string())
     }
 
-    fn tube_label(mask: Mask, vertex_labels: &[&str]) -> String {
-        let single = vertex_labels.iter().all(|l| l.chars().count() == 1);
-        let parts: Vec<&str> = (0..vertex_labels.len())
-            .filter(|&v| (mask >> v) & 1 == 1)
-            .map(|v| vertex_labels[v])
-            .collect();
-        parts.join(if single { "" } else { "," })
+    /// The strip as an SVG document ([`plotting::strip_svg`]).
+    pub fn strip_svg(graph_json: &str, layers_json: &str, cyclic: bool) -> Result<String, String> {
+        let (strip, l) = strip_on(graph_json, layers_json, cyclic)?;
+        Ok(plotting::strip_svg(&plotting::strip_layout(&strip, &l, cyclic), &l))
     }
 
-    fn checked_graph(g: &WireGraph, max_n: usize) -> Result<(usize, Vec<Mask>, Mask), String> {
-        let n = g.labels.len();
-        if n < 2 {
-            return Err("Draw a graph with at least 2 vertices first.".to_string());
-        }
-        if n > max_n {
-            return Err(format!("Graph too large ({} > {} vertices).", n, max_n));
-        }
-        let adj = adjacency_masks(g);
-        let full: Mask = (1 << n) - 1;
-        if !mask_connected(full, &adj) {
-            return Err("Graph must be connected.".to_string());
-        }
-        Ok((n, adj, full))
+    /// TikZ of the strip in its layout; `spec` is the text form of
+    /// [`TikzOptions`].
+    pub fn tikz_strip(graph_json: &str, layers_json: &str, cyclic: bool, spec: &str) -> Result<String, String> {
+        let (strip, l) = strip_on(graph_json, layers_json, cyclic)?;
+        let picture = Picture::of_strip(&strip, &plotting::strip_layout(&strip, &l, cyclic), &l);
+        tikz::tikz(&picture, &TikzOptions::parse(spec)?)
     }
 
-    /// The poset of tubes of the drawn graph under inclusion.
-    pub fn gen_tube_poset(graph_json: &str) -> Result<String, String> {
-        let g = WireGraph::parse(graph_json)?;
-        let (_, adj, full) = checked_graph(&g, 10)?;
-        let mut subs: Vec<Mask> = (1..=full).filter(|&m| mask_connected(m, &adj)).collect();
-        subs.sort_by_key(|m| (m.count_ones(), *m));
+    /// TikZ of the diagram with node `i` at `positions_json[i]` = `[x, y]`
+    /// (layout units, y up).
+    pub fn tikz_hasse(graph_json: &str, positions_json: &str, spec: &str) -> Result<String, String> {
+        let positions: Vec<(f64, f64)> =
+            serde_json::from_str(positions_json).map_err(|e| format!("bad positions JSON: {}", e))?;
+        let picture = Picture::of_poset(&Poset::parse(graph_json)?, &positions)?;
+        tikz::tikz(&picture, &TikzOptions::parse(spec)?)
+    }
 
-        let vertex_labels: Vec<&str> = g.labels.iter().map(String::as_str).collect();
-        let mut out = WireGraph::default();
-        for &m in &subs {
-            out.labels.push(tube_label(m, &vertex_labels));
-        }
-        for (i, &a) in subs.iter().enumerate() {
-            for (j, &b) in subs.iter().enumerate() {
-                if b.count_ones() == a.count_ones() + 1 && a & b == a {
-                    out.edges.push((i, j));
-                }
-            }
-        }
-        Ok(out.with_ranks()?.to_json())
-    }
-
-    /// The face lattice of the graph associahedron of the drawn graph.
-    /// Path -> associahedron, complete -> permutahedron, cycle -> cyclohedron,
-    /// star -> stellahedron.
-    pub fn gen_graph_associahedron(graph_json: &str) -> Result<String, String> {
-        let g = WireGraph::parse(graph_json)?;
-        let (_, adj, full) = checked_graph(&g, 12)?;
-        let tubes: Vec<Mask> = (1..full).filter(|&m| mask_connected(m, &adj)).collect();
-        let tubings = enumerate_tubings(&tubes, &adj, 20_000)?;
-
-        let vertex_labels: Vec<&str> = g.labels.iter().map(String::as_str).collect();
-        let tubing_label = |t: &[usize]| -> String {
-            if t.is_empty() {
-                return "*".to_string();
+    // `gen_foo(args)` is `generators::foo(args)` as JSON; `graph_json`
+    // arguments are parsed into a `Poset` first.
+    macro_rules! gen_json {
+        ($(fn $name:ident($($arg:ident : $ty:ty),*) = $gen:ident;)*) => {$(
+            pub fn $name($($arg: $ty),*) -> Result<String, String> {
+                generators::$gen($(gen_json!(@arg $arg, $arg)),*).map(|g| g.to_json())
             }
-            let mut sorted = t.to_vec();
-            sorted.sort_by_key(|&i| (tubes[i].count_ones(), tubes[i]));
-            sorted
-                .iter()
-                .map(|&i| tube_label(tubes[i], &vertex_labels))
-                .collect::<Vec<_>>()
-                .join("|")
-        };
+        )*};
+        (@arg graph_json, $json:ident) => { &Poset::parse($json)? };
+        (@arg $other:ident, $arg:ident) => { $arg };
+    }
 
-        let mut out = WireGraph::default();
-        let mut index: HashMap<Vec<usize>, usize> = HashMap::new();
-        for (i, t) in tubings.iter().enumerate() {
-            out.labels.push(tubing_label(t));
-            index.insert(t.clone(), i);
-        }
-        // face(T) is covered by face(T \ {t}): removing a tube goes one dim up
-        for (i, t) in tubings.iter().enumerate() {
-            for k in 0..t.len() {
-                let mut sup = t.clone();
-                sup.remove(k);
-                out.edges.push((i, index[&sup]));
-            }
-        }
-        Ok(out.with_ranks()?.to_json())
+    gen_json! {
+        fn gen_grid(spec: &str) = grid;
+        fn gen_cube(d: usize) = cube;
+        fn gen_simplex(d: usize) = simplex;
+        fn gen_distributive(graph_json: &str) = distributive;
+        fn gen_fence(n: usize) = fence;
+        fn gen_boolean(n: usize) = boolean;
+        fn gen_int_partitions(n: usize) = int_partitions;
+        fn gen_int_partitions_ref(n: usize) = int_partitions_ref;
+        fn gen_prod_of_claws(a: usize, b: usize) = prod_of_claws;
+        fn gen_tamari(n: usize) = tamari;
+        fn gen_weak_order(n: usize) = weak_order;
+        fn gen_set_partitions(n: usize) = set_partitions;
+        fn gen_noncrossing(n: usize) = noncrossing;
+        fn gen_young_interval(m: usize, n: usize) = young_interval;
+        fn gen_subspaces(n: usize) = subspaces;
+        fn gen_graph(kind: &str, n: usize) = graph;
+        fn gen_tube_poset(graph_json: &str) = tube_poset;
+        fn gen_graph_associahedron(graph_json: &str) = graph_associahedron;
+        fn gen_nestohedron(spec: &str) = nestohedron;
+        fn gen_hypergraph_nestohedron(spec: &str) = hypergraph_nestohedron;
     }
 }
 
@@ -624,9 +223,32 @@ js_api! {
     fn gen_simplex(d: usize);
     fn infer_digit_relations(graph_json: &str);
     fn gen_distributive(graph_json: &str);
+    fn gen_fence(n: usize);
+    fn gen_boolean(n: usize);
+    fn gen_int_partitions(n: usize);
+    fn gen_int_partitions_ref(n: usize);
+    fn gen_prod_of_claws(a: usize, b: usize);
+    fn gen_rooted_trees(n: usize);
+    fn gen_tamari(n: usize);
+    fn gen_weak_order(n: usize);
+    fn gen_set_partitions(n: usize);
+    fn gen_noncrossing(n: usize);
+    fn gen_young_interval(m: usize, n: usize);
+    fn gen_subspaces(n: usize);
     fn gen_graph(kind: &str, n: usize);
     fn gen_tube_poset(graph_json: &str);
     fn gen_graph_associahedron(graph_json: &str);
+    fn gen_nestohedron(spec: &str);
+    fn gen_hypergraph_nestohedron(spec: &str);
+    fn get_limits();
+    fn set_limits(json: &str);
+    fn strip_layout(graph_json: &str, layers_json: &str, cyclic: bool);
+    fn edges_strip(graph_json: &str, layers_json: &str, cyclic: bool);
+    fn strip_svg(graph_json: &str, layers_json: &str, cyclic: bool);
+    fn hasse_layout(graph_json: &str);
+    fn hasse_layout_along(graph_json: &str, layers_json: &str);
+    fn tikz_strip(graph_json: &str, layers_json: &str, cyclic: bool, spec: &str);
+    fn tikz_hasse(graph_json: &str, positions_json: &str, spec: &str);
 }
 
 // ===========================================================================
@@ -653,8 +275,17 @@ struct StepOut {
     strips: Vec<StripOut>,
     count: usize,
     done: bool,
+    /// Estimated share of the search done, 0 to 100.
+    percent: f64,
+    /// Strips per second so far.
+    rate: f64,
+    /// Estimated seconds left, once anything is done.
+    eta: Option<f64>,
 }
 
+/// Seeds the search is split into, for its progress.
+const PROGRESS_SEEDS: usize = 64;
+
 /// Owns a lattice and a lazy iterator over its rhombic strips.
 ///
 /// The lattice is heap-allocated and leaked so the iterator (which borrows
@@ -669,6 +300,9 @@ pub struct StripEnumerator {
     mode: Mode,
     count: usize,
     done: bool,
+    progress: Arc<Progress>,
+    /// `now_ms()` when the search started.
+    started: f64,
 }
 
 #[wasm_bindgen]
@@ -681,10 +315,12 @@ impl StripEnumerator {
 
     /// Advance the search for at most `budget_ms` milliseconds, collecting at
     /// most `max_strips` strips (ignored in count mode). Returns JSON:
-    /// `{"strips": [...], "count": n, "done": bool}`.
+    /// `{"strips": [...], "count": n, "done": bool, "percent": p, "rate": r,
+    /// "eta": secs}`.
     pub fn step(&mut self, budget_ms: f64, max_strips: usize) -> String {
-        let mut out = StepOut { strips: vec![], count: self.count, done: self.done };
+        let mut out = StepOut { strips: vec![], count: self.count, done: self.done, percent: 0.0, rate: 0.0, eta: None };
         if self.done {
+            self.report(&mut out);
             return serde_json::to_string(&out).unwrap();
         }
 
@@ -732,6 +368,7 @@ impl StripEnumerator {
         }
         out.count = self.count;
         out.done = self.done;
+        self.report(&mut out);
         serde_json::to_string(&out).unwrap()
     }
 }
@@ -748,12 +385,32 @@ impl StripEnumerator {
         let faces = api::wire_to_faces(&g)?;
 
         let lattice: *mut Lattice = Box::into_raw(Box::new(Lattice::from_faces(faces)));
+        let seeds = unsafe { &*lattice }.ham_path_seeds(cyclic, PROGRESS_SEEDS);
+        let progress = Arc::new(Progress::new(&seeds));
         // SAFETY: the iterator borrows the leaked lattice; it is dropped
         // before the lattice in `Drop`, and `lattice` is never moved.
+        // The seeds in order search as `rhombic::strips` does.
         let iter: Box<dyn Iterator<Item = Strip>> =
-            Box::new(rhombic::strips(unsafe { &*lattice }, cyclic));
+            Box::new(progress.strips(unsafe { &*lattice }, cyclic, seeds, || false));
+
+        Ok(StripEnumerator {
+            lattice,
+            iter: Some(iter),
+            cyclic,
+            mode,
+            count: 0,
+            done: false,
+            progress,
+            started: now_ms(),
+        })
+    }
 
-        Ok(StripEnumerator { lattice, iter: Some(iter), cyclic, mode, count: 0, done: false })
+    fn report(&self, out: &mut StepOut) {
+        let elapsed = Duration::from_secs_f64((now_ms() - self.started).max(0.0) / 1000.0);
+        let report = self.progress.report(elapsed);
+        // a search stopped at its first strip is done, whatever it skipped
+        let percent = if self.done { 100.0 } else { report.percent };
+        (out.percent, out.rate, out.eta) = (percent, report.rate, if self.done { Some(0.0) } else { report.eta });
     }
 }
 
@@ -768,6 +425,54 @@ impl Drop for StripEnumerator {
     }
 }
 
+// ===========================================================================
+// StripDecoder: compact strips from strip_stream
+// ===========================================================================
+
+/// Decodes the `data` of `strip_stream`'s compact `strips` messages
+/// ([`crate::codec`]) into the strips of a JSON one, edges recomputed here.
+/// Holds the job's lattice, so a stream of batches builds it once.
+#[wasm_bindgen]
+pub struct StripDecoder {
+    lattice: Lattice,
+    cyclic: bool,
+}
+
+#[wasm_bindgen]
+impl StripDecoder {
+    /// `graph_json` and `cyclic` as sent with the job.
+    #[wasm_bindgen(constructor)]
+    pub fn new(graph_json: &str, cyclic: bool) -> Result<StripDecoder, JsValue> {
+        Self::create(graph_json, cyclic).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// The batch in `data` (base64) as JSON `[{"layers", "edges",
+    /// "cyclicEdges"}, ...]`.
+    pub fn decode(&self, data: &str) -> Result<String, JsValue> {
+        self.decode_json(data).map_err(|e| JsValue::from_str(&e))
+    }
+}
+
+impl StripDecoder {
+    fn create(graph_json: &str, cyclic: bool) -> Result<Self, String> {
+        let faces = api::wire_to_faces(&api::WireGraph::parse(graph_json)?)?;
+        Ok(StripDecoder { lattice: Lattice::from_faces(faces), cyclic })
+    }
+
+    fn decode_json(&self, data: &str) -> Result<String, String> {
+        let (l, n) = (&self.lattice, self.lattice.num_faces());
+        let mut strips = Vec::new();
+        for strip in codec::decode_strips(&codec::decode_base64(data)?)? {
+            if strip.iter().flatten().any(|&f| f >= n) {
+                return Err("strip refers to a node the graph does not have".to_string());
+            }
+            let (edges, cyclic_edges) = plotting::edges_strip(&strip, l, self.cyclic);
+            strips.push(StripOut { layers: strip, edges, cyclic_edges });
+        }
+        Ok(serde_json::to_string(&strips).unwrap())
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 pub(crate) fn now_ms() -> f64 {
     js_sys::Date::now()
@@ -782,6 +487,7 @@ pub(crate) fn now_ms() -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rhombic;
 
     // Drive the real search path (parse -> faces -> rhombic::strips ->
     // plotting::edges_strip) the way the Web Worker does, and check we get a strip with a
@@ -843,10 +549,94 @@ mod tests {
                 serde_json::from_str(&en.step(5000.0, 0)).unwrap();
             if v["done"].as_bool().unwrap() {
                 assert_eq!(v["count"].as_u64().unwrap() as usize, native);
+                assert_eq!((v["percent"].clone(), v["eta"].clone()), (serde_json::json!(100.0), serde_json::json!(0.0)));
                 break;
             }
             guard += 1;
             assert!(guard < 1000, "count did not terminate");
         }
     }
+
+    #[test]
+    fn json_wrappers_match_generators() {
+        use crate::generators;
+        assert_eq!(api::gen_cube(2).unwrap(), generators::cube(2).unwrap().to_json());
+        let path = api::gen_graph("path", 4).unwrap();
+        assert_eq!(
+            api::gen_graph_associahedron(&path).unwrap(),
+            generators::graph_associahedron(&generators::graph("path", 4).unwrap())
+                .unwrap()
+                .to_json()
+        );
+        let trees: Vec<api::WireGraph> =
+            serde_json::from_str(&api::gen_rooted_trees(4).unwrap()).unwrap();
+        assert_eq!(trees, generators::rooted_trees(4).unwrap());
+        let file = api::to_lattice_file(&api::gen_cube(2).unwrap()).unwrap();
+        let back = api::WireGraph::parse(&api::from_lattice_file(&file).unwrap()).unwrap();
+        assert_eq!(back.to_lattice_file().unwrap(), file);
+        assert!(api::poset_ranks(r#"{"labels":["a","b"],"edges":[[0,1],[1,0]]}"#).is_err());
+    }
+
+    #[test]
+    fn strip_layout_and_svg_check_the_strip() {
+        let g = api::gen_cube(2).expect("gen_cube");
+        let v = run(&g, false);
+        let layers = v["strips"][0]["layers"].to_string();
+        let layout: serde_json::Value =
+            serde_json::from_str(&api::strip_layout(&g, &layers, false).unwrap()).unwrap();
+        assert_eq!(layout["nodes"].as_array().unwrap().len(), 9);
+        assert!(api::strip_svg(&g, &layers, false).unwrap().starts_with("<svg"));
+        assert!(api::strip_svg(&g, "[[0],[1]]", false).is_err());
+        assert!(api::strip_layout(&g, "[[99]]", false).is_err());
+
+        let hasse: Vec<(f64, f64)> = serde_json::from_str(&api::hasse_layout(&g).unwrap()).unwrap();
+        assert_eq!(hasse.len(), 9);
+        let along: Vec<(f64, f64)> =
+            serde_json::from_str(&api::hasse_layout_along(&g, &layers).unwrap()).unwrap();
+        let first: Vec<usize> = serde_json::from_value(v["strips"][0]["layers"][0].clone()).unwrap();
+        assert!(first.windows(2).all(|w| along[w[0]].0 < along[w[1]].0));
+
+        // the same TikZ as the CLI, from either picture
+        let tex = api::tikz_strip(&g, &layers, false, "snippet,edges").unwrap();
+        assert!(tex.starts_with("\\begin{tikzpicture}") && tex.contains("\\draw[bridge]"));
+        let positions = api::hasse_layout(&g).unwrap();
+        assert!(api::tikz_hasse(&g, &positions, "").unwrap().starts_with("\\documentclass"));
+        assert!(api::tikz_hasse(&g, &positions, "shape=hexagon").is_err());
+    }
+
+    #[test]
+    fn compact_strips_decode_to_the_enumerators() {
+        let g = api::gen_cube(2).expect("gen_cube");
+        let mut en = StripEnumerator::create(&g, true, "enumerate").unwrap();
+        let v: serde_json::Value = serde_json::from_str(&en.step(5000.0, 100)).unwrap();
+        let strips: Vec<Strip> = v["strips"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| serde_json::from_value(s["layers"].clone()).unwrap())
+            .collect();
+        let data = codec::encode_base64(&codec::encode_strips(&strips));
+        let decoder = StripDecoder::create(&g, true).unwrap();
+        let decoded: serde_json::Value = serde_json::from_str(&decoder.decode_json(&data).unwrap()).unwrap();
+        assert_eq!(decoded, v["strips"]);
+
+        let edges: serde_json::Value =
+            serde_json::from_str(&api::edges_strip(&g, &serde_json::to_string(&strips[0]).unwrap(), true).unwrap()).unwrap();
+        assert_eq!(edges["edges"], v["strips"][0]["edges"]);
+        assert_eq!(edges["cyclicEdges"], v["strips"][0]["cyclicEdges"]);
+
+        let stray = codec::encode_base64(&codec::encode_strips(&[vec![vec![99]]]));
+        assert!(decoder.decode_json(&stray).is_err());
+        assert!(decoder.decode_json("not base64!").is_err());
+    }
+
+    #[test]
+    fn limits_roundtrip_and_reject_unknown_keys() {
+        let current = api::limits();
+        let json = api::set_limits(&format!("{{\"max_faces\": {}}}", current.max_faces)).unwrap();
+        assert_eq!(serde_json::from_str::<api::Limits>(&json).unwrap(), current);
+        assert!(api::set_limits("{\"max_tubings\": 5}").is_err());
+        assert!(api::set_limits("{\"max_faces\": \"many\"}").is_err());
+        assert_eq!(api::limits(), current);
+    }
 }
@@ -0,0 +1,343 @@
+//! End-to-end tests of the `rhombic_strips` command line: output formats,
+//! stdin / `--gen` input and exit codes.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+/// Run the CLI with `args` (and `stdin`), returning exit code and stdout.
+fn cli(args: &[&str], stdin: Option<&str>) -> (i32, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rhombic_strips"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("RHOMBIC_CACHE", "off") // tests that want a cache pass --cache
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("start rhombic_strips");
+    let mut pipe = child.stdin.take().unwrap();
+    pipe.write_all(stdin.unwrap_or("").as_bytes()).unwrap();
+    drop(pipe);
+    let out = child.wait_with_output().unwrap();
+    (out.status.code().unwrap(), String::from_utf8(out.stdout).unwrap())
+}
+
+fn json(s: &str) -> Value {
+    serde_json::from_str(s).unwrap_or_else(|e| panic!("{}: {}", e, s))
+}
+
+#[test]
+fn exists_and_count() {
+    assert_eq!(cli(&["exists", "lattices/boolean_3"], None).0, 0);
+    let (code, out) = cli(&["count", "--format", "json", "lattices/boolean_3"], None);
+    assert_eq!(code, 0);
+    assert_eq!(json(&out)["count"], 12);
+}
+
+#[test]
+fn paths_counts_dead_paths_and_orbits() {
+    // the per-path counts add up to the total
+    let (_, out) = cli(&["paths", "--sort", "path", "--format=ndjson", "lattices/cube3d"], None);
+    let lines: Vec<Value> = out.lines().map(json).collect();
+    let (result, paths) = lines.split_last().unwrap();
+    let sum: u64 = paths.iter().map(|p| p["count"].as_u64().unwrap()).sum();
+    assert_eq!(result["type"], "result");
+    assert_eq!((result["strips"].as_u64(), sum), (Some(672), 672));
+    assert!(paths.windows(2).all(|w| w[0]["index"].as_u64() < w[1]["index"].as_u64()));
+
+    // the 3-dimensional associahedron: 384 paths up to reversal, 24 of them
+    // dead, in 36 orbits of its 12 symmetries
+    let args = ["paths", "--orbits", "--format", "json", "--gen", "graph_associahedron path 4"];
+    let (code, out) = cli(&args, None);
+    assert_eq!(code, 0);
+    let out = json(&out);
+    assert_eq!((out["paths"].as_u64(), out["dead"].as_u64()), (Some(384), Some(24)));
+    assert_eq!((out["orbits"].as_u64(), out["automorphisms"].as_u64()), (Some(36), Some(12)));
+    assert_eq!(out["dead_orbits"], 2);
+    let rows = out["rows"].as_array().unwrap();
+    let sizes: u64 = rows.iter().map(|r| r["size"].as_u64().unwrap()).sum();
+    assert_eq!(sizes, 384);
+    assert!(rows.windows(2).all(|w| w[0]["count"].as_u64() >= w[1]["count"].as_u64()));
+
+    let (_, csv) = cli(&["paths", "--format", "csv", "lattices/cube3d"], None);
+    assert_eq!(csv.lines().next(), Some("index,count,path"));
+    assert_eq!(csv.lines().count(), 73);
+}
+
+#[test]
+fn enumerate_respects_limit_and_verifies() {
+    let (code, out) =
+        cli(&["enumerate", "--limit", "3", "--format", "ndjson", "--gen", "boolean 3"], None);
+    assert_eq!(code, 0);
+    let lines: Vec<Value> = out.lines().map(json).collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[..3].iter().all(|l| l["type"] == "strip"));
+    assert_eq!(lines[3]["complete"], false);
+    let (_, none) = cli(&["enumerate", "--limit", "0", "--format", "ndjson", "--gen", "boolean 3"], None);
+    assert!(none.lines().map(json).all(|l| l["type"] != "strip"));
+
+    // verify reads enumerate's output back, by ids or by labels
+    assert_eq!(cli(&["verify", "--strips", "-", "lattices/boolean_3"], Some(&out)).0, 0);
+    let by_labels = r#"{"labels":[["000"],["001","010","100"],["101","011","110"],["111"]]}"#;
+    assert_eq!(cli(&["verify", "--strips", "-", "lattices/boolean_3"], Some(by_labels)).0, 0);
+    let broken = "[[0],[1,2,4],[3,5,6],[7]]";
+    let (code, out) = cli(&["verify", "--strips", "-", "lattices/boolean_3"], Some(broken));
+    assert_eq!(code, 1);
+    assert!(out.contains("layer 2 does not follow layer 1"), "{}", out);
+}
+
+#[test]
+fn sample_is_reproducible() {
+    let args = ["sample", "--limit", "2", "--seed", "7", "--format", "json", "lattices/cube3d"];
+    let (code, a) = cli(&args, None);
+    assert_eq!(code, 0);
+    // all but the summary's timings
+    let sampled = |out: &str| {
+        let mut v = json(out);
+        v.as_object_mut().unwrap().remove("summary");
+        v
+    };
+    let a = sampled(&a);
+    assert_eq!(a, sampled(&cli(&args, None).1));
+    assert_eq!(a["count"], 672);
+    assert_eq!(a["strips"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn render_writes_images_without_a_viewer() {
+    let dir = std::env::temp_dir().join(format!("rhombic_render_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let svg = dir.join("strip.svg");
+    let args = ["exists", "--cyclic", "--render", svg.to_str().unwrap(), "lattices/cube3d"];
+    assert_eq!(cli(&args, None).0, 0);
+    let content = std::fs::read_to_string(&svg).unwrap();
+    assert_eq!(content.matches("<text").count(), 26);
+    assert!(content.contains("stroke-dasharray"), "closing edges are dashed");
+
+    // several strips get numbered files
+    let png = dir.join("strip.png");
+    let args = ["enumerate", "--limit", "2", "--render", png.to_str().unwrap(), "lattices/boolean_3"];
+    assert_eq!(cli(&args, None).0, 0);
+    for i in 0..2 {
+        let bytes = std::fs::read(dir.join(format!("strip-{}.png", i))).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+    }
+    assert_eq!(cli(&["exists", "--render", "strip.pdf", "lattices/boolean_3"], None).0, 2);
+
+    // --steps: an animated SVG, or a frame per step
+    let steps = dir.join("steps.svg");
+    let args = ["exists", "--render", steps.to_str().unwrap(), "--steps", "lattices/boolean_3"];
+    assert_eq!(cli(&args, None).0, 0);
+    assert!(std::fs::read_to_string(&steps).unwrap().contains("<animate"));
+    let frames = dir.join("steps.png");
+    let args = ["exists", "--render", frames.to_str().unwrap(), "--steps", "lattices/boolean_3"];
+    assert_eq!(cli(&args, None).0, 0);
+    assert!(dir.join("steps-00.png").exists() && dir.join("steps-05.png").exists());
+    assert_eq!(cli(&["exists", "--steps", "lattices/boolean_3"], None).0, 2);
+
+    // --tikz configures the .tex
+    let tex = dir.join("strip.tex");
+    let args = ["exists", "--render", tex.to_str().unwrap(), "--tikz", "snippet,edges", "lattices/cube3d"];
+    assert_eq!(cli(&args, None).0, 0);
+    let content = std::fs::read_to_string(&tex).unwrap();
+    assert!(content.starts_with("\\begin{tikzpicture}") && content.contains("\\draw[bridge]"));
+    assert_eq!(cli(&["exists", "--tikz", "shape=hexagon", "lattices/boolean_3"], None).0, 2);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dot_draws_a_level_and_a_strip_layer() {
+    let (_, strip) = cli(&["enumerate", "--limit", "1", "--format", "json", "lattices/cube3d"], None);
+    let (code, dot) = cli(&["dot", "--level", "1", "--strips", "-", "lattices/cube3d"], Some(&strip));
+    assert_eq!(code, 0);
+    assert!(dot.starts_with("graph \"level 1\" {"));
+    assert_eq!(dot.matches("[label=").count(), 12);
+    assert_eq!(dot.matches("penwidth").count(), 11, "the layer's path");
+    let (_, plain) = cli(&["dot", "lattices/cube3d"], None);
+    assert_eq!((plain.matches("[label=").count(), plain.matches("penwidth").count()), (8, 0));
+    assert_eq!(cli(&["dot", "--level", "4", "lattices/cube3d"], None).0, 2);
+}
+
+#[test]
+fn info_counts_hamiltonian_paths_per_level() {
+    let (code, out) = cli(&["info", "--limit", "1000", "--format", "json", "lattices/cube3d"], None);
+    assert_eq!(code, 0);
+    let graphs = json(&out)["bridge_graphs"].clone();
+    assert_eq!(graphs[0]["degrees"], serde_json::json!([3, 3, 3, 3, 3, 3, 3, 3]));
+    assert_eq!((graphs[0]["hamiltonian"].clone(), graphs[0]["complete"].clone()), (72.into(), true.into()));
+    assert_eq!((graphs[1]["hamiltonian"].clone(), graphs[1]["complete"].clone()), (1000.into(), false.into()));
+    assert_eq!((graphs[2]["components"].clone(), graphs[2]["hamiltonian"].clone()), (6.into(), 0.into()));
+
+    let (_, out) = cli(&["info", "--cyclic", "lattices/cube3d"], None);
+    assert!(out.contains("level 0: 8 vertices, 12 edges, degrees 3..3, connected, 12 cycles"));
+}
+
+#[test]
+fn generate_convert_and_stdin() {
+    let (code, out) = cli(&["generate", "boolean", "3"], None);
+    assert_eq!(code, 0);
+    assert_eq!(out, std::fs::read_to_string("lattices/boolean_3").unwrap());
+
+    // poset JSON on stdin is accepted wherever a lattice file is
+    let (_, poset) = cli(&["convert", "--format", "json", "lattices/boolean_3"], None);
+    assert_eq!(cli(&["convert", "-"], Some(&poset)).1, out);
+    let (_, count) = cli(&["count", "-"], Some(&poset));
+    assert_eq!(count.trim(), "Number of rhombic strips found: 12");
+
+    let (_, info) = cli(&["info", "--format", "json", "--gen", "cube 3"], None);
+    assert_eq!(json(&info)["levels"], serde_json::json!([8, 12, 6, 1]));
+    let (_, trees) = cli(&["generate", "trees", "4", "--format", "json"], None);
+    assert_eq!(json(&trees).as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn exit_codes() {
+    assert_eq!(cli(&["frobnicate"], None).0, 2);
+    assert_eq!(cli(&["count"], None).0, 2);
+    assert_eq!(cli(&["count", "no/such/file"], None).0, 2);
+    assert_eq!(cli(&["count", "--seed", "1", "lattices/boolean_3"], None).0, 2);
+    assert_eq!(cli(&["generate", "nonsense", "3"], None).0, 2);
+    for secs in ["inf", "1e30", "-1", "soon"] {
+        assert_eq!(cli(&["count", "--timeout", secs, "lattices/boolean_3"], None).0, 2, "--timeout {}", secs);
+    }
+    let (code, out) = cli(&["count", "--timeout", "0.2", "--format", "json", "lattices/boolean_6"], None);
+    assert_eq!(code, 3);
+    assert_eq!(json(&out)["timeout"], true);
+}
+
+#[test]
+fn shards_merge_into_the_whole_count() {
+    let dir = std::env::temp_dir().join(format!("rhombic_shards_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut files = Vec::new();
+    for (i, command) in ["count", "enumerate", "count", "count"].iter().enumerate() {
+        let shard = format!("{}/4", i);
+        let (code, out) = cli(&[command, "--cyclic", "--shard", &shard, "--format", "ndjson", "lattices/cube3d"], None);
+        assert_eq!(code, 0);
+        let file = dir.join(format!("shard-{}.ndjson", i));
+        std::fs::write(&file, out).unwrap();
+        files.push(file.to_str().unwrap().to_string());
+    }
+    let (_, whole) = cli(&["count", "--cyclic", "--format", "json", "lattices/cube3d"], None);
+    fn args(files: &[String]) -> Vec<&str> {
+        let mut args = vec!["merge", "--format", "json"];
+        args.extend(files.iter().map(String::as_str));
+        args
+    }
+
+    // the modes differ: an enumerated shard is no count
+    assert_eq!(cli(&args(&files), None).0, 2);
+    let (_, out) = cli(&["count", "--cyclic", "--shard", "1/4", "--format", "json", "lattices/cube3d"], None);
+    std::fs::write(&files[1], out).unwrap();
+    let (code, merged) = cli(&args(&files), None);
+    assert_eq!(code, 0);
+    assert_eq!((json(&merged)["count"].clone(), json(&merged)["shards"].clone()), (json(&whole)["count"].clone(), 4.into()));
+    assert_eq!(json(&merged)["cyclic"], true);
+
+    assert_eq!(cli(&args(&files[1..]), None).0, 2);
+    let (_, other) = cli(&["count", "--cyclic", "--shard", "0/4", "--format", "json", "lattices/boolean_3"], None);
+    std::fs::write(&files[0], other).unwrap();
+    assert_eq!(cli(&args(&files), None).0, 2);
+    assert_eq!(cli(&["count", "--shard", "4/4", "lattices/cube3d"], None).0, 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn batch_writes_a_table_and_resumes() {
+    let dir = std::env::temp_dir().join(format!("rhombic_batch_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let table = dir.join("results.json");
+    let table = table.to_str().unwrap();
+
+    let args = ["batch", "--mode", "count", "--range", "fence 3..5", "--out", table];
+    assert_eq!(cli(&args, None).0, 0);
+    let rows = json(&std::fs::read_to_string(table).unwrap());
+    let counts: Vec<u64> = rows.as_array().unwrap().iter().map(|r| r["count"].as_u64().unwrap()).collect();
+    assert_eq!(counts, [2, 2, 4]);
+
+    // a wider range only runs the new instance
+    let args = ["batch", "--mode", "count", "--range", "fence 3..6", "lattices/cube3d", "--out", table];
+    assert_eq!(cli(&args, None).0, 0);
+    let rows = json(&std::fs::read_to_string(table).unwrap());
+    let rows = rows.as_array().unwrap();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[3]["instance"], "fence 6");
+    assert_eq!(rows[4]["count"], 672);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn results_cache_answers_isomorphic_inputs() {
+    let dir = std::env::temp_dir().join(format!("rhombic_cli_cache_{}", std::process::id()));
+    let dir = dir.to_str().unwrap();
+
+    let (_, first) = cli(&["count", "--format", "json", "--cache", dir, "lattices/boolean_4"], None);
+    assert_eq!(json(&first)["cached"], Value::Null);
+    // the same poset, numbered differently by another generator, comes from the cache
+    let (code, again) = cli(&["count", "--format", "json", "--cache", dir, "--gen", "grid 1111"], None);
+    assert_eq!(code, 0);
+    assert_eq!((json(&again)["count"].as_u64(), json(&again)["cached"].as_bool()), (Some(336), Some(true)));
+    let (_, exists) = cli(&["exists", "--format", "json", "--cache", dir, "--gen", "grid 1111"], None);
+    assert_eq!(json(&exists)["cached"], true);
+
+    let (_, table) = cli(&["cache", "export", "--format", "json", "--cache", dir], None);
+    let rows = json(&table);
+    assert_eq!(rows.as_array().unwrap().len(), 1);
+    assert_eq!(rows[0]["name"], "lattices/boolean_4");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn log_appends_a_summary_per_run() {
+    let log = std::env::temp_dir().join(format!("rhombic_cli_log_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+    let log = log.to_str().unwrap();
+
+    let (_, out) = cli(&["count", "--format", "json", "--log", log, "lattices/boolean_3"], None);
+    let summary = &json(&out)["summary"];
+    assert_eq!((summary["mode"].as_str(), summary["count"].as_u64()), (Some("count"), Some(12)));
+    assert!(summary["paths"].as_u64().unwrap() > 0);
+    cli(&["enumerate", "--limit", "2", "--log", log, "lattices/boolean_3"], None);
+    cli(&["exists", "--log", log, "lattices/boolean_3"], None);
+
+    let logged: Vec<Value> = std::fs::read_to_string(log).unwrap().lines().map(json).collect();
+    std::fs::remove_file(log).unwrap();
+    assert_eq!(logged.len(), 3);
+    assert_eq!(&logged[0], summary);
+    assert_eq!((logged[1]["count"].as_u64(), logged[1]["complete"].as_bool()), (Some(2), Some(false)));
+    assert_eq!(logged[2]["mode"], "exists");
+    assert!(logged.iter().all(|s| s["lattice"] == summary["lattice"] && s["version"] == summary["version"]));
+    assert_eq!(cli(&["info", "--log", log, "lattices/boolean_3"], None).0, 2);
+}
+
+#[test]
+fn filters_and_aggregations() {
+    let count = |args: &[&str]| json(&cli(&[&["enumerate", "--format", "json", "lattices/cube3d"], args].concat(), None).1);
+    let all = count(&["--aggregate", "widths,boundaries,paths"]);
+    assert_eq!((all["count"].as_u64(), all["searched"].as_u64()), (Some(672), None));
+    assert!(all["strips"].is_null());
+    let paths: u64 = all["paths"].as_array().unwrap().iter().map(|p| p["count"].as_u64().unwrap()).sum();
+    assert_eq!(paths, 672);
+
+    // every width of level 2 keeps its share of the strips, listed or tallied
+    for row in all["widths"].as_array().unwrap().iter().filter(|r| r["level"] == 2) {
+        let spec = format!("widths=-/{}", row["width"]);
+        let listed = count(&["--filter", &spec]);
+        assert_eq!((listed["count"].clone(), listed["searched"].as_u64()), (row["count"].clone(), Some(672)));
+        assert_eq!(listed["strips"].as_array().unwrap().len() as u64, row["count"].as_u64().unwrap());
+        assert_eq!(count(&["--filter", &spec, "--aggregate", "paths"])["count"], row["count"]);
+    }
+    let pair = &all["boundaries"][0];
+    let spec = format!("left={},right={}", pair["left"].as_str().unwrap(), pair["right"].as_str().unwrap());
+    assert_eq!(count(&["--filter", &spec, "--aggregate", "paths"])["count"], pair["count"]);
+
+    let (code, out) = cli(&["enumerate", "--aggregate", "widths", "--format", "ndjson", "lattices/cube3d"], None);
+    assert_eq!(code, 0);
+    assert!(out.lines().map(json).any(|v| v["type"] == "width"));
+    assert_eq!(cli(&["enumerate", "--filter", "bridges=nope", "lattices/cube3d"], None).0, 2);
+    assert_eq!(cli(&["enumerate", "--aggregate", "colours", "lattices/cube3d"], None).0, 2);
+    assert_eq!(cli(&["enumerate", "--aggregate", "paths", "--limit", "3", "lattices/cube3d"], None).0, 2);
+    assert_eq!(cli(&["count", "--filter", "cyclic", "lattices/cube3d"], None).0, 2);
+}
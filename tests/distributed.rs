@@ -0,0 +1,91 @@
+//! End-to-end tests of `strip_stream --coordinate` with several local
+//! `strip_stream --work` processes.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+
+use serde_json::{json, Value};
+
+use rhombic_strips::generators;
+use rhombic_strips::rhombic::{self, Strip};
+
+/// Run `job` on a coordinator on a free port with `workers` worker
+/// processes of two threads each; its messages, and the workers' exit codes.
+fn coordinate(job: Value, workers: usize, token: Option<&str>) -> (Vec<Value>, Vec<Option<i32>>) {
+    let mut coordinator = Command::new(env!("CARGO_BIN_EXE_strip_stream"))
+        .args(["--coordinate", "127.0.0.1:0", "--split-after", "0"])
+        .env("RHOMBIC_TOKEN", "sesame")
+        .env("RHOMBIC_CACHE", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("start strip_stream --coordinate");
+    writeln!(coordinator.stdin.take().unwrap(), "{}", job).unwrap();
+    let mut lines = BufReader::new(coordinator.stdout.take().unwrap()).lines();
+    let first: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    // "... waiting for workers on 127.0.0.1:<port>"
+    let addr = first["message"].as_str().unwrap().rsplit(' ').next().unwrap().to_string();
+
+    let workers: Vec<Child> = (0..workers)
+        .map(|_| {
+            let mut cmd = Command::new(env!("CARGO_BIN_EXE_strip_stream"));
+            cmd.args(["--work", &addr, "--threads", "2"]).stderr(Stdio::null());
+            match token {
+                Some(t) => cmd.env("RHOMBIC_TOKEN", t),
+                None => cmd.env_remove("RHOMBIC_TOKEN"),
+            };
+            cmd.spawn().expect("start strip_stream --work")
+        })
+        .collect();
+    let mut messages = vec![first];
+    if token == Some("sesame") {
+        for line in lines {
+            messages.push(serde_json::from_str(&line.unwrap()).unwrap());
+        }
+        assert!(coordinator.wait().unwrap().success());
+    }
+    let codes = workers.into_iter().map(|mut w| w.wait().unwrap().code()).collect();
+    let _ = coordinator.kill(); // still waiting for workers it refused
+    let _ = coordinator.wait();
+    (messages, codes)
+}
+
+#[test]
+fn workers_share_a_count_and_an_enumeration() {
+    let cube = generators::cube(3).unwrap();
+    let l = cube.to_lattice().unwrap();
+    for cyclic in [false, true] {
+        let (messages, codes) = coordinate(json!({ "graph": cube, "mode": "count", "cyclic": cyclic }), 3, Some("sesame"));
+        assert_eq!(codes, vec![Some(0); 3]);
+        let done = messages.last().unwrap();
+        assert_eq!((done["type"].clone(), done["workers"].clone()), (json!("done"), json!(3)));
+        assert_eq!(done["count"], rhombic::count_strips(&l, cyclic));
+    }
+
+    let (messages, codes) = coordinate(json!({ "graph": cube, "mode": "enumerate" }), 2, Some("sesame"));
+    assert_eq!(codes, vec![Some(0); 2]);
+    let mut found: Vec<Strip> = messages
+        .iter()
+        .filter(|m| m["type"] == "strips")
+        .flat_map(|m| m["strips"].as_array().unwrap().clone())
+        .map(|s| serde_json::from_value(s["layers"].clone()).unwrap())
+        .collect();
+    found.sort();
+    let mut all: Vec<Strip> = rhombic::strips(&l, false).collect();
+    all.sort();
+    assert_eq!(found, all);
+    assert_eq!(messages.last().unwrap()["count"], all.len());
+
+    let (messages, _) = coordinate(json!({ "graph": cube, "mode": "enumerate", "cap": 5 }), 1, Some("sesame"));
+    let done = messages.last().unwrap();
+    assert_eq!((done["count"].clone(), done["capped"].clone()), (json!(5), json!(true)));
+}
+
+#[test]
+fn workers_without_the_token_are_refused() {
+    let job = json!({ "graph": generators::cube(2).unwrap(), "mode": "count" });
+    let (_, codes) = coordinate(job, 1, Some("wrong"));
+    assert_eq!(codes, vec![Some(1)]);
+    let (_, codes) = coordinate(json!({ "graph": generators::cube(2).unwrap(), "mode": "count" }), 1, None);
+    assert_eq!(codes, vec![Some(1)]);
+}
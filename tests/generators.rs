@@ -0,0 +1,315 @@
+//! The generator suite: every front end builds its posets with
+//! `rhombic_strips::generators`, so these tests cover the GUI, the browser
+//! and the CLI at once.
+
+use rhombic_strips::generators::{self, Poset};
+use rhombic_strips::lattice::Lattice;
+use rhombic_strips::rhombic;
+
+fn size_and_covers(gen: Result<Poset, String>) -> (usize, usize) {
+    let g = gen.expect("generator");
+    (g.labels.len(), g.edges.len())
+}
+
+fn max_rank(g: &Poset) -> usize {
+    g.ranks().unwrap().into_iter().max().unwrap()
+}
+
+fn graph(kind: &str, n: usize) -> Poset {
+    generators::graph(kind, n).unwrap()
+}
+
+#[test]
+fn grid_generator_and_ranks() {
+    let g = generators::grid("211").unwrap();
+    assert_eq!((g.labels.len(), g.edges.len()), (3 * 2 * 2, 20));
+    assert_eq!(max_rank(&g), 4); // top element 211
+    assert_eq!(g.ranks, Some(g.ranks().unwrap()));
+    assert_eq!(size_and_covers(generators::grid("12,3")), (13 * 4, 12 * 4 + 13 * 3));
+    assert!(generators::grid("x").is_err());
+}
+
+#[test]
+fn cycle_detection() {
+    let cyc = Poset {
+        labels: vec!["a".into(), "b".into()],
+        edges: vec![(0, 1), (1, 0)],
+        ranks: None,
+    };
+    assert!(cyc.ranks().is_err());
+    assert!(cyc.to_faces().is_err());
+}
+
+#[test]
+fn save_load_roundtrip() {
+    let g = generators::cube(2).unwrap();
+    let file = g.to_lattice_file().unwrap();
+    let back = Poset::from_lattice_file(&file).unwrap();
+    assert_eq!((&back.labels, &back.ranks), (&g.labels, &g.ranks));
+    assert_eq!(back.to_lattice_file().unwrap(), file); // covers agree up to order
+    let l = Lattice::from_str_content(&file).unwrap();
+    assert_eq!(Poset::from_lattice(&l).to_lattice_file().unwrap(), file);
+    assert_eq!(Poset::parse(&g.to_json()).unwrap(), g);
+}
+
+#[test]
+fn infer_digit_relations_on_unrelated_grid() {
+    let mut g = generators::grid("21").unwrap();
+    let covers = g.edges.len();
+    g.edges.clear();
+    assert_eq!(g.infer_digit_relations(), covers);
+    assert_eq!(g.infer_digit_relations(), 0);
+}
+
+#[test]
+fn distributive_of_antichain_is_boolean() {
+    let anti = Poset { labels: vec!["0".into(), "1".into(), "2".into()], ..Default::default() };
+    // J(antichain_3) = boolean lattice B_3
+    assert_eq!(size_and_covers(generators::distributive(&anti)), (8, 12));
+}
+
+#[test]
+fn cube_and_simplex_lattices() {
+    assert_eq!(size_and_covers(generators::cube(2)), (9, 12)); // 4 + 4 + 1
+    assert_eq!(size_and_covers(generators::cube(3)).0, 27);
+    assert_eq!(size_and_covers(generators::simplex(2)).0, 7); // 3 + 3 + 1
+    assert_eq!(size_and_covers(generators::simplex(3)).0, 15); // 2^4 - 1
+}
+
+#[test]
+fn small_polytopes_have_correct_face_counts() {
+    let assoc = |kind, n| generators::graph_associahedron(&graph(kind, n)).unwrap();
+    // P(path_3) = pentagon: 5 + 5 + 1 faces
+    assert_eq!(assoc("path", 3).labels.len(), 11);
+    // P(K_3) = hexagon: 6 + 6 + 1 = Fubini(3)
+    assert_eq!(assoc("complete", 3).labels.len(), 13);
+    // P(path_4) = 3d associahedron: 14 + 21 + 9 + 1
+    assert_eq!(assoc("path", 4).labels.len(), 45);
+    // P(K_4) = permutahedron Pi_3: Fubini(4); ranks are the face dimensions
+    let perm3 = assoc("complete", 4);
+    assert_eq!(perm3.labels.len(), 75);
+    assert_eq!(max_rank(&perm3), 3);
+}
+
+#[test]
+fn tube_poset_of_path() {
+    let tp = generators::tube_poset(&graph("path", 3)).unwrap();
+    assert_eq!((tp.labels.len(), tp.edges.len()), (6, 6)); // 5 proper tubes + full set
+    assert_eq!(max_rank(&tp), 2); // graded by cardinality - 1
+    // {0, 2} is not a tube of 0 - 1 - 2
+    assert!(!tp.labels.contains(&"02".to_string()));
+}
+
+#[test]
+fn disconnected_graph_rejected() {
+    let g = Poset { labels: vec!["a".into(), "b".into()], ..Default::default() };
+    assert!(generators::graph_associahedron(&g).is_err());
+    assert!(generators::tube_poset(&g).is_err());
+}
+
+#[test]
+fn hexagon_face_lattice_strips() {
+    // hexagon = permutahedron of K_3; like the square, a polygon has
+    // exactly 2 cyclic rhombic strips (one per orientation)
+    let hex = generators::graph_associahedron(&graph("complete", 3)).unwrap();
+    let l = hex.to_lattice().unwrap();
+    assert_eq!(rhombic::count_strips(&l, true), 2);
+}
+
+// The lattice-family ports must reproduce the files their Python scripts
+// wrote into lattices/.
+fn checked_in(name: &str) -> String {
+    let path = format!("{}/lattices/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+fn lattice_file(gen: Result<Poset, String>) -> String {
+    gen.expect("generator").to_lattice_file().unwrap()
+}
+
+#[test]
+fn lattice_families_reproduce_checked_in_files() {
+    for n in [4, 5, 6, 7] {
+        let file = format!("int_partitions_{}", n);
+        assert_eq!(lattice_file(generators::int_partitions(n)), checked_in(&file), "{}", file);
+    }
+    for n in [4, 6, 7, 8, 9, 10] {
+        let file = format!("int_partitions_ref_{}", n);
+        assert_eq!(lattice_file(generators::int_partitions_ref(n)), checked_in(&file), "{}", file);
+    }
+    for (a, b) in [(1, 2), (1, 3), (3, 3)] {
+        let file = format!("prod_of_claws_{}{}", a, b);
+        assert_eq!(lattice_file(generators::prod_of_claws(a, b)), checked_in(&file), "{}", file);
+    }
+    for n in 3..=7 {
+        let file = format!("boolean_{}", n);
+        assert_eq!(lattice_file(generators::boolean(n)), checked_in(&file), "{}", file);
+    }
+}
+
+#[test]
+fn fence_reproduces_checked_in_files_up_to_labels() {
+    // the checked-in fence files carry placeholder labels
+    let strip_labels = |s: &str| -> Vec<String> {
+        s.lines()
+            .map(|l| {
+                let f: Vec<&str> = l.splitn(3, ": ").collect();
+                format!("{}: {}", f[0], f[2])
+            })
+            .collect()
+    };
+    for n in 5..=12 {
+        let file = format!("fence_distributed_{}", n);
+        let ours = lattice_file(generators::fence(n));
+        assert_eq!(strip_labels(&ours), strip_labels(&checked_in(&file)), "{}", file);
+    }
+    let j3 = lattice_file(generators::fence(3));
+    assert!(j3.starts_with("0: empty: "));
+    assert!(j3.contains(": a1b1b2: "));
+}
+
+#[test]
+fn rooted_trees_match_gen_trees_script() {
+    let counts: Vec<usize> =
+        (1..=9).map(|n| generators::rooted_trees(n).unwrap().len()).collect();
+    assert_eq!(counts, [1, 1, 2, 4, 9, 20, 48, 115, 286]); // OEIS A000081
+
+    // `python3 gen_trees.py 5`, line by line
+    let trees = generators::rooted_trees(5).unwrap();
+    let expected: [&[(usize, usize)]; 9] = [
+        &[(0, 1), (1, 2), (2, 3), (3, 4)],
+        &[(0, 1), (1, 2), (2, 3), (2, 4)],
+        &[(0, 1), (1, 2), (1, 4), (2, 3)],
+        &[(0, 1), (1, 2), (1, 3), (1, 4)],
+        &[(0, 1), (0, 4), (1, 2), (2, 3)],
+        &[(0, 1), (0, 4), (1, 2), (1, 3)],
+        &[(0, 1), (0, 3), (1, 2), (3, 4)],
+        &[(0, 1), (0, 3), (0, 4), (1, 2)],
+        &[(0, 1), (0, 2), (0, 3), (0, 4)],
+    ];
+    for (t, e) in trees.iter().zip(expected) {
+        assert_eq!(t.edges, e);
+    }
+}
+
+#[test]
+fn classic_lattices_have_known_sizes() {
+    // Tamari: Catalan(n) elements, each with n-1 rotation neighbours
+    assert_eq!(size_and_covers(generators::tamari(3)), (5, 5));
+    assert_eq!(size_and_covers(generators::tamari(4)), (14, 21));
+    // weak order: n! elements, n!(n-1)/2 covers
+    assert_eq!(size_and_covers(generators::weak_order(4)), (24, 36));
+    // Bell and Catalan numbers
+    assert_eq!(size_and_covers(generators::set_partitions(4)).0, 15);
+    assert_eq!(size_and_covers(generators::set_partitions(5)).0, 52);
+    assert_eq!(size_and_covers(generators::noncrossing(4)).0, 14);
+    assert_eq!(size_and_covers(generators::noncrossing(6)).0, 132);
+    // L(m, n): binomial(m + n, m); L(2, 2) is the 6-element Young interval
+    assert_eq!(size_and_covers(generators::young_interval(2, 2)), (6, 6));
+    assert_eq!(size_and_covers(generators::young_interval(3, 4)).0, 35);
+    // subspaces of F_2^n: 1 + 3 + 1 for n = 2, then 16, 67, 374
+    assert_eq!(size_and_covers(generators::subspaces(2)), (5, 6));
+    assert_eq!(size_and_covers(generators::subspaces(3)).0, 16);
+    assert_eq!(size_and_covers(generators::subspaces(4)).0, 67);
+    assert_eq!(size_and_covers(generators::subspaces(5)).0, 374);
+}
+
+#[test]
+fn classic_lattice_labels() {
+    let labels = |gen: Result<Poset, String>| gen.unwrap().labels;
+    assert_eq!(labels(generators::tamari(2)), ["(ab)c", "a(bc)"]);
+    assert_eq!(labels(generators::weak_order(3))[..2], ["123", "132"]);
+    assert_eq!(labels(generators::set_partitions(3)), ["1|2|3", "12|3", "13|2", "1|23", "123"]);
+    // 13|24 is the one crossing partition of {1..4}
+    assert!(!labels(generators::noncrossing(4)).contains(&"13|24".to_string()));
+    assert_eq!(labels(generators::young_interval(2, 1)), ["empty", "1", "11"]);
+    let f2 = labels(generators::subspaces(2));
+    assert_eq!(f2.first().unwrap(), "0");
+    assert_eq!(f2.last().unwrap(), "<10,01>");
+}
+
+#[test]
+fn nestohedra_reproduce_graph_associahedra() {
+    for n in 2..=5 {
+        let path = generators::graph_associahedron(&graph("path", n)).unwrap();
+        let complete = generators::graph_associahedron(&graph("complete", n)).unwrap();
+        // all intervals of 0..n-1 / all subsets, as building sets
+        let intervals: Vec<Vec<usize>> =
+            (0..n).flat_map(|i| (i..n).map(move |j| (i..=j).collect())).collect();
+        let subsets: Vec<Vec<usize>> = (1..1usize << n)
+            .map(|m| (0..n).filter(|&v| (m >> v) & 1 == 1).collect())
+            .collect();
+        let json = |sets: &Vec<Vec<usize>>| serde_json::to_string(sets).unwrap();
+        assert_eq!(generators::nestohedron(&json(&intervals)).unwrap(), path);
+        assert_eq!(generators::nestohedron(&json(&subsets)).unwrap(), complete);
+        // the graph's edges generate its graphical building set
+        let edges: Vec<Vec<usize>> = (0..n - 1).map(|i| vec![i, i + 1]).collect();
+        assert_eq!(generators::hypergraph_nestohedron(&json(&edges)).unwrap(), path);
+    }
+    // face counts: associahedron K_5 / permutahedron of order 4
+    assert_eq!(
+        size_and_covers(generators::nestohedron("01 12 23 012 123 0123")).0,
+        1 + 14 + 21 + 9
+    );
+    assert_eq!(
+        size_and_covers(generators::hypergraph_nestohedron("01 02 03 12 13 23")).0,
+        1 + 24 + 36 + 14
+    );
+}
+
+#[test]
+fn nestohedra_beyond_graphs() {
+    // one hyperedge on all vertices: the building set {singletons, [n]}
+    // gives the simplex, here the triangle (3 vertices, 3 edges, 1 face)
+    assert_eq!(size_and_covers(generators::hypergraph_nestohedron("123")).0, 7);
+    // Stanley–Pitman: building set of the initial intervals [1, i] — a
+    // combinatorial cube, here the square
+    assert_eq!(size_and_covers(generators::nestohedron("12 123")).0, 9);
+    assert_eq!(size_and_covers(generators::nestohedron("12 123 1234")).0, 27);
+    // closure is required up front in the strict variant
+    assert!(generators::nestohedron("12 23").is_err());
+    assert!(generators::hypergraph_nestohedron("12 23").is_ok());
+}
+
+#[test]
+fn generators_scale_past_the_old_caps() {
+    // little Schröder numbers: faces of the associahedron of a path; the
+    // path on 8 vertices has 20793 tubings (the old cap was 20,000)
+    let faces: Vec<usize> = (2..=8)
+        .map(|n| size_and_covers(generators::graph_associahedron(&graph("path", n))).0)
+        .collect();
+    assert_eq!(faces, [3, 11, 45, 197, 903, 4279, 20793]);
+    // 40 vertices: 40·41/2 tubes (old cap: 10 vertices)
+    assert_eq!(size_and_covers(generators::tube_poset(&graph("path", 40))).0, 820);
+    // J(chain of 40) without scanning 2^40 masks (old cap: 20 elements)
+    let chain = Poset {
+        labels: (0..40).map(|i| i.to_string()).collect(),
+        edges: (0..39).map(|i| (i, i + 1)).collect(),
+        ranks: None,
+    };
+    assert_eq!(size_and_covers(generators::distributive(&chain)), (41, 40));
+}
+
+#[test]
+fn tube_poset_matches_brute_force_on_small_graphs() {
+    // the connected-subset enumeration against filtering all 2^n subsets
+    for (kind, n) in [("cycle", 7), ("star", 6), ("complete", 5), ("path", 8)] {
+        let g = graph(kind, n);
+        let connected = |m: usize| {
+            let mut seen = m & m.wrapping_neg();
+            loop {
+                let grow = g.edges.iter().fold(seen, |s, &(a, b)| {
+                    let (ia, ib) = ((s >> a) & 1 == 1, (s >> b) & 1 == 1);
+                    if ia || ib { s | ((1 << a | 1 << b) & m) } else { s }
+                });
+                if grow == seen {
+                    return seen == m;
+                }
+                seen = grow;
+            }
+        };
+        let brute = (1..1usize << n).filter(|&m| connected(m)).count();
+        let ours = size_and_covers(generators::tube_poset(&g)).0;
+        assert_eq!(ours, brute, "{} {}", kind, n);
+    }
+}
@@ -0,0 +1,78 @@
+//! End-to-end tests of `strip_stream`'s limits: a job over one ends with an
+//! `error` of its kind, and the process with exit code 1.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use rhombic_strips::generators;
+
+/// Run `job` in one-shot mode with `limits`; its messages and exit code.
+fn run(limits: &[&str], job: Value) -> (Vec<Value>, Option<i32>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_strip_stream"))
+        .args(limits)
+        .env("RHOMBIC_CACHE", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("start strip_stream");
+    writeln!(child.stdin.take().unwrap(), "{}", job).unwrap();
+    let out = child.wait_with_output().unwrap();
+    let messages = String::from_utf8(out.stdout).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    (messages, out.status.code())
+}
+
+fn kind(messages: &[Value]) -> &Value {
+    let last = messages.last().unwrap();
+    assert_eq!(last["type"], "error");
+    &last["kind"]
+}
+
+#[test]
+fn jobs_over_a_limit_end_with_an_error_of_its_kind() {
+    let cube = generators::cube(3).unwrap();
+    let count = json!({ "graph": cube, "mode": "count" });
+
+    let (messages, code) = run(&["--max-faces", "20"], count.clone());
+    assert_eq!((kind(&messages), code), (&json!("faces"), Some(1)));
+    assert!(messages[0]["message"].as_str().unwrap().contains("27 faces"));
+    let (messages, _) = run(&["--max-memory", "4K"], count.clone());
+    assert_eq!(kind(&messages), "memory");
+    let (messages, code) = run(&["--max-faces", "27", "--max-memory", "1M"], count);
+    assert_eq!((messages.last().unwrap()["count"].clone(), code), (json!(672), Some(0)));
+
+    // the strips up to the limit, then the error; a lower cap is no error
+    let (messages, _) = run(&["--max-strips", "3"], json!({ "graph": cube, "mode": "enumerate" }));
+    let strips: usize = messages.iter().filter(|m| m["type"] == "strips").map(|m| m["strips"].as_array().unwrap().len()).sum();
+    assert_eq!((kind(&messages), strips), (&json!("strips"), 3));
+    let (messages, _) = run(&["--max-strips", "3"], json!({ "graph": cube, "mode": "enumerate", "cap": 2 }));
+    assert_eq!(messages.last().unwrap()["capped"], true);
+
+    let long = json!({ "graph": generators::boolean(6).unwrap(), "mode": "count" });
+    let (messages, code) = run(&["--time-limit", "0.3"], long);
+    assert_eq!((kind(&messages), code), (&json!("time"), Some(1)));
+
+    // searches through long stretches without a strip stop on time too
+    let sparse = json!({ "labels": (0..13).map(|i| i.to_string()).collect::<Vec<_>>(), "edges": (0..12).map(|i| [i, 12]).collect::<Vec<_>>() });
+    for job in [
+        json!({ "graph": sparse, "mode": "count" }),
+        json!({ "graph": sparse, "mode": "enumerate" }),
+        json!({ "graph": sparse, "mode": "bounds" }),
+        json!({ "graph": sparse, "mode": "enumerate", "aggregate": ["paths"] }),
+    ] {
+        let started = Instant::now();
+        let (messages, _) = run(&["--time-limit", "0.3"], job);
+        assert_eq!(kind(&messages), "time");
+        assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+    }
+
+    let (_, code) = run(&["--time-limit", "1e30"], json!({ "graph": cube, "mode": "count" }));
+    assert_eq!(code, Some(2));
+
+    let (messages, _) = run(&[], json!({ "graph": cube, "mode": "frobnicate" }));
+    assert_eq!(kind(&messages), "invalid");
+    let (messages, _) = run(&[], json!({ "mode": "count" }));
+    assert_eq!(kind(&messages), "invalid");
+}
@@ -0,0 +1,171 @@
+//! End-to-end tests of `strip_stream --serve`: a plain HTTP client against
+//! the relay, as `www/app.js` talks to it.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+
+use serde_json::{json, Value};
+
+use rhombic_strips::generators;
+
+/// A relay on a free port; killed when dropped.
+struct Relay {
+    child: Child,
+    port: u16,
+}
+
+impl Relay {
+    fn start(args: &[&str], path: Option<&str>) -> Relay {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_strip_stream"));
+        cmd.args(["--serve", "--port", "0"])
+            .args(args)
+            .env("RHOMBIC_TOKEN", "sesame")
+            .env("RHOMBIC_CACHE", "off")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        if let Some(path) = path {
+            cmd.env("PATH", path);
+        }
+        let mut child = cmd.spawn().expect("start strip_stream --serve");
+        let mut banner = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut banner).unwrap();
+        // relay: listening on 127.0.0.1:<port>  [...]
+        let port = banner.split(':').nth(2).and_then(|p| p.split_whitespace().next()).unwrap();
+        Relay { child, port: port.parse().unwrap() }
+    }
+
+    /// Send one request; the status and the (de-chunked) body.
+    fn request(&self, method: &str, path: &str, token: Option<&str>, body: &str) -> (u16, String, String) {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        let token = token.map_or(String::new(), |t| format!("X-Rhombic-Token: {}\r\n", t));
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            token,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = if head.contains("Transfer-Encoding: chunked") { dechunk(body) } else { body.to_string() };
+        (status, head.to_string(), body)
+    }
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn dechunk(mut body: &str) -> String {
+    let mut out = String::new();
+    loop {
+        let (size, rest) = body.split_once("\r\n").unwrap();
+        let size = usize::from_str_radix(size, 16).unwrap();
+        if size == 0 {
+            return out;
+        }
+        out += &rest[..size];
+        body = &rest[size + 2..];
+    }
+}
+
+fn messages(body: &str) -> Vec<Value> {
+    body.lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+fn count_job() -> String {
+    json!({ "graph": generators::boolean(3).unwrap(), "mode": "count", "cyclic": false }).to_string()
+}
+
+#[test]
+fn ping_pairing_and_jobs() {
+    let relay = Relay::start(&[], None);
+
+    let (status, head, body) = relay.request("GET", "/ping", None, "");
+    assert_eq!(status, 200);
+    assert!(head.contains("Access-Control-Allow-Origin: *"));
+    let ping: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!((ping["ok"].clone(), ping["mode"].clone(), ping["paired"].clone()), (json!(true), json!("direct"), json!(false)));
+    let (_, _, body) = relay.request("GET", "/ping?t=1", Some("sesame"), "");
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["paired"], true);
+
+    // the preflight of a public page reaching loopback
+    let (status, head, _) = relay.request("OPTIONS", "/job", None, "");
+    assert_eq!(status, 204);
+    assert!(head.contains("Access-Control-Allow-Private-Network: true"));
+    assert!(head.contains("Access-Control-Allow-Headers: Content-Type, X-Rhombic-Token"));
+
+    assert_eq!(relay.request("POST", "/job", None, &count_job()).0, 401);
+    assert_eq!(relay.request("POST", "/job", Some("wrong"), &count_job()).0, 401);
+    assert_eq!(relay.request("POST", "/job", Some("sesame"), "not json").0, 400);
+    assert_eq!(relay.request("GET", "/elsewhere", Some("sesame"), "").0, 404);
+    let mut blank = TcpStream::connect(("127.0.0.1", relay.port)).unwrap();
+    blank.write_all(b"\r\nGET /ping HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    blank.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400"), "{:?}", response);
+
+    let (status, head, body) = relay.request("POST", "/job", Some("sesame"), &count_job());
+    assert_eq!(status, 200);
+    assert!(head.contains("Content-Type: application/x-ndjson"));
+    let last = messages(&body).pop().unwrap();
+    assert_eq!((last["type"].clone(), last["count"].clone()), (json!("done"), json!(12)));
+}
+
+#[cfg(unix)]
+#[test]
+fn srun_runs_the_job_and_its_chatter_becomes_notes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // a stand-in srun: one line of chatter, then the command after the options
+    let dir = std::env::temp_dir().join(format!("rhombic_srun_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let srun = dir.join("srun");
+    std::fs::write(
+        &srun,
+        "#!/bin/sh\necho \"srun: job 7 queued and waiting for resources\" >&2\n\
+         while [ \"${1#-}\" != \"$1\" ]; do shift; done\nexec \"$@\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&srun, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", dir.display(), std::env::var("PATH").unwrap_or_default());
+
+    let relay = Relay::start(&["--srun=--partition=test --time=0:05:00"], Some(&path));
+    let (_, _, body) = relay.request("GET", "/ping", Some("sesame"), "");
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["mode"], "slurm");
+
+    let (_, _, body) = relay.request("POST", "/job", Some("sesame"), &count_job());
+    let messages = messages(&body);
+    assert!(messages.iter().any(|m| m["type"] == "note" && m["message"].as_str().unwrap().contains("queued")));
+    assert_eq!(messages.last().unwrap()["count"], 12);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn serve_needs_a_token() {
+    let out = Command::new(env!("CARGO_BIN_EXE_strip_stream"))
+        .args(["--serve", "--port", "0"])
+        .env_remove("RHOMBIC_TOKEN")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    let out = Command::new(env!("CARGO_BIN_EXE_strip_stream")).arg("--port").output().unwrap();
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn jobs_keep_to_the_relays_limits() {
+    let relay = Relay::start(&["--max-faces", "5"], None);
+    let (_, _, body) = relay.request("POST", "/job", Some("sesame"), &count_job());
+    let last = messages(&body).pop().unwrap();
+    assert_eq!((last["type"].clone(), last["kind"].clone()), (json!("error"), json!("faces")));
+}
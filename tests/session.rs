@@ -0,0 +1,136 @@
+//! End-to-end test of `strip_stream --session`: many jobs over one pipe,
+//! tagged by id, cancelled one by one, scripts among them, each finished
+//! one logged.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use rhombic_strips::rhombic::{self, Strip};
+use rhombic_strips::{codec, generators};
+
+#[test]
+fn jobs_by_id_over_one_pipe() {
+    let log = std::env::temp_dir().join(format!("rhombic_session_log_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_strip_stream"))
+        .arg("--session")
+        .arg("--log")
+        .arg(&log)
+        .env("RHOMBIC_CACHE", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("start strip_stream --session");
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut send = move |v: Value| writeln!(stdin, "{}", v).unwrap();
+    let mut until_last_of = |id: Value| {
+        let mut messages = Vec::new();
+        loop {
+            let m: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+            let last = m["id"] == id && ["done", "error", "cancelled"].contains(&m["type"].as_str().unwrap());
+            messages.push(m);
+            if last {
+                return messages;
+            }
+        }
+    };
+    let cube = generators::boolean(3).unwrap();
+
+    send(json!({ "id": 1, "graph": cube, "mode": "count" }));
+    let messages = until_last_of(json!(1));
+    assert!(messages.iter().all(|m| m["id"] == 1));
+    assert_eq!(messages.last().unwrap()["count"], 12);
+    let summary = &messages.last().unwrap()["summary"];
+    assert_eq!((summary["mode"].clone(), summary["count"].clone(), summary["complete"].clone()), (json!("count"), json!(12), json!(true)));
+    assert_eq!(summary["lattice"].as_str().unwrap().len(), 16);
+    assert!(summary["paths"].as_u64().unwrap() > 0 && summary["seeds"].as_u64().unwrap() > 0);
+
+    // the same poset again: its lattice is kept
+    send(json!({ "id": "e", "graph": cube, "mode": "enumerate", "cap": 5 }));
+    let messages = until_last_of(json!("e"));
+    assert!(messages[0]["message"].as_str().unwrap().contains("reusing its lattice"));
+    assert_eq!((messages.last().unwrap()["count"].clone(), messages.last().unwrap()["capped"].clone()), (json!(5), json!(true)));
+
+    // filtered, and only the tables of what the filter keeps
+    let filter = json!({ "alsoCyclic": true });
+    send(json!({ "id": "f", "graph": cube, "mode": "enumerate", "filter": filter }));
+    let messages = until_last_of(json!("f"));
+    let sent: usize = messages.iter().filter(|m| m["type"] == "strips").map(|m| m["strips"].as_array().unwrap().len()).sum();
+    let done = messages.last().unwrap();
+    assert_eq!((done["count"].clone(), done["searched"].clone()), (json!(sent), json!(12)));
+    assert_eq!(done["summary"]["filter"]["alsoCyclic"], true);
+    send(json!({ "id": "a", "graph": cube, "mode": "enumerate", "filter": filter, "aggregate": ["paths", "widths"] }));
+    let messages = until_last_of(json!("a"));
+    let tables = messages.iter().find(|m| m["type"] == "aggregate").unwrap();
+    let per_path: u64 = tables["paths"].as_array().unwrap().iter().map(|p| p["count"].as_u64().unwrap()).sum();
+    assert_eq!(per_path as usize, sent);
+    assert!(tables["widths"].is_array() && tables["boundaries"].is_null());
+    assert!(!messages.iter().any(|m| m["type"] == "strips"));
+    send(json!({ "id": "x", "graph": cube, "mode": "count", "aggregate": ["paths"] }));
+    assert_eq!(until_last_of(json!("x")).last().unwrap()["kind"], "invalid");
+
+    // compact strips: the same ones, without their edges
+    send(json!({ "id": "c", "graph": cube, "mode": "enumerate", "encoding": "compact" }));
+    let messages = until_last_of(json!("c"));
+    let mut strips: Vec<Strip> = Vec::new();
+    for m in messages.iter().filter(|m| m["type"] == "strips") {
+        assert!(m["encoding"] == "compact" && m["strips"].is_null());
+        strips.extend(codec::decode_strips(&codec::decode_base64(m["data"].as_str().unwrap()).unwrap()).unwrap());
+    }
+    let l = cube.to_lattice().unwrap();
+    assert_eq!(strips, rhombic::strips(&l, false).collect::<Vec<_>>());
+    assert_eq!(messages.last().unwrap()["count"], 12);
+    send(json!({ "id": "z", "graph": cube, "mode": "enumerate", "encoding": "zip" }));
+    assert_eq!(until_last_of(json!("z")).last().unwrap()["kind"], "invalid");
+
+    // the Scripts panel's jobs, in the scripts worker's shapes
+    send(json!({ "id": "s", "mode": "survey", "n": 4 }));
+    let messages = until_last_of(json!("s"));
+    let results: usize = messages.iter().filter(|m| m["type"] == "survey").map(|m| m["results"].as_array().unwrap().len()).sum();
+    assert_eq!((results, messages.last().unwrap()["count"].clone()), (9, json!(9))); // connected graphs on 2 to 4 vertices
+    assert!(messages.last().unwrap()["summary"]["lattice"].is_null());
+    send(json!({ "id": "b", "graph": cube, "mode": "bounds" }));
+    let messages = until_last_of(json!("b"));
+    let tally = messages.iter().rfind(|m| m["type"] == "bounds").unwrap();
+    assert_eq!((tally["count"].clone(), tally["done"].clone()), (json!(12), json!(true)));
+
+    // a long count, cancelled while a short job runs beside it
+    send(json!({ "id": 3, "graph": generators::boolean(6).unwrap(), "mode": "count" }));
+    send(json!({ "id": 4, "graph": cube, "mode": "exists" }));
+    send(json!({ "cmd": "cancel", "id": 3 }));
+    let messages = until_last_of(json!(3));
+    assert_eq!(messages.last().unwrap()["type"], "cancelled");
+    assert!(!messages.iter().any(|m| m["id"] == 3 && m["type"] == "done"));
+
+    // ... and jobs searching where no strip turns up for long
+    let sparse = json!({ "labels": (0..13).map(|i| i.to_string()).collect::<Vec<_>>(), "edges": (0..12).map(|i| [i, 12]).collect::<Vec<_>>() });
+    for (id, mode) in [(6, "count"), (7, "bounds"), (8, "enumerate")] {
+        send(json!({ "id": id, "graph": sparse, "mode": mode }));
+        std::thread::sleep(Duration::from_millis(300));
+        let cancelled = Instant::now();
+        send(json!({ "cmd": "cancel", "id": id }));
+        assert_eq!(until_last_of(json!(id)).last().unwrap()["type"], "cancelled");
+        assert!(cancelled.elapsed() < Duration::from_secs(5), "{} took {:?} to cancel", mode, cancelled.elapsed());
+    }
+
+    send(json!({ "graph": cube, "mode": "count" }));
+    send(json!({ "id": 5, "graph": cube, "mode": "frobnicate" }));
+    let messages = until_last_of(json!(5));
+    assert!(messages.iter().any(|m| m["type"] == "error" && m["id"].is_null()));
+    assert_eq!(messages.last().unwrap()["type"], "error");
+
+    drop(send);
+    assert!(child.wait().unwrap().success());
+
+    // a line per job done: the count, enumerations, survey, bounds and existence
+    let logged: Vec<Value> = std::fs::read_to_string(&log).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    std::fs::remove_file(&log).unwrap();
+    let mut modes: Vec<&str> = logged.iter().map(|s| s["mode"].as_str().unwrap()).collect();
+    modes.sort();
+    assert_eq!(modes, ["bounds", "count", "enumerate", "enumerate", "enumerate", "enumerate", "exists", "survey"]);
+    assert!(logged.iter().any(|s| s["mode"] == "enumerate" && s["complete"] == false));
+}
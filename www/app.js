@@ -12,9 +12,31 @@ import init, {
   gen_simplex,
   infer_digit_relations,
   gen_distributive,
+  gen_fence,
+  gen_boolean,
+  gen_int_partitions,
+  gen_int_partitions_ref,
+  gen_prod_of_claws,
+  gen_rooted_trees,
+  gen_tamari,
+  gen_weak_order,
+  gen_set_partitions,
+  gen_noncrossing,
+  gen_young_interval,
+  gen_subspaces,
   gen_graph,
   gen_tube_poset,
   gen_graph_associahedron,
+  gen_nestohedron,
+  gen_hypergraph_nestohedron,
+  get_limits,
+  set_limits,
+  strip_layout,
+  strip_svg,
+  hasse_layout,
+  tikz_strip,
+  tikz_hasse,
+  StripDecoder,
 } from './pkg/rhombic_strips.js';
 
 // ---------------------------------------------------------------------------
@@ -50,8 +72,8 @@ const state = {
 
   // computation
   worker: null,
-  job: null,               // {kind, started, liveCount}
-  strips: [],              // {layers:[[id]], edges:[[id,id]], cyclicEdges:[[id,id]]}
+  job: null,               // {kind, started, liveCount, progress}
+  strips: [],              // {layers:[[id]], edges:[[id,id]], cyclicEdges:[[id,id]], cyclic}
   cursor: 0,
   totalStrips: null,
   viewing: false,
@@ -193,7 +215,7 @@ function replaceGraph(wire, mode, msg, layout = 'rank') {
   state.nextId = 0;
   const ids = wire.labels.map((l) => addNode(l, 0, 0));
   for (const [a, b] of wire.edges) state.edges.push([ids[a], ids[b]]);
-  if (layout === 'rank' && wire.ranks) layoutByRank(wire.ranks);
+  if (layout === 'rank' && wire.ranks) layoutByRank();
   else if (layout === 'line') layoutLine();
   else layoutCircle();
   fitView();
@@ -205,21 +227,16 @@ function replaceGraph(wire, mode, msg, layout = 'rank') {
 // Layouts
 // ---------------------------------------------------------------------------
 
-function layoutByRank(ranks) {
-  // ranks in node order; rows centered, higher rank higher on screen
-  const rows = new Map();
+/// Hasse diagram: rows by rank, higher rank higher on screen, ordered to
+/// avoid crossings (layout.rs, like gui.rs layout_by_rank). Throws if the
+/// relation has a cycle.
+function layoutByRank() {
+  const { wire } = toWire();
+  const pos = JSON.parse(hasse_layout(JSON.stringify(wire)));
   state.nodes.forEach((n, i) => {
-    const r = ranks[i];
-    if (!rows.has(r)) rows.set(r, []);
-    rows.get(r).push(n);
+    n.x = pos[i][0] * X_STEP;
+    n.y = -pos[i][1] * Y_STEP;
   });
-  for (const [r, row] of rows) {
-    const w = (row.length - 1) * X_STEP;
-    row.forEach((n, k) => {
-      n.x = -w / 2 + k * X_STEP;
-      n.y = -r * Y_STEP;
-    });
-  }
 }
 
 function layoutCircle() {
@@ -242,10 +259,8 @@ function layoutLine() {
 }
 
 function arrangeByRank() {
-  const { wire } = toWire();
   try {
-    const ranks = JSON.parse(poset_ranks(JSON.stringify(wire)));
-    layoutByRank(ranks);
+    layoutByRank();
     fitView();
     refresh();
   } catch (e) {
@@ -253,18 +268,48 @@ function arrangeByRank() {
   }
 }
 
-/// Layered layout of the displayed strip (like gui.rs arrange_as_strip).
+/// The strip in wire indices, for the wasm layout and SVG export; null if
+/// the diagram lost one of its nodes.
+function stripWire(view) {
+  const { wire, idMap } = toWire();
+  const idx = new Map(idMap.map((id, i) => [id, i]));
+  const layers = view.layers.map((layer) => layer.map((id) => idx.get(id)));
+  if (layers.flat().some((i) => i === undefined)) return null;
+  return { wire: JSON.stringify(wire), layers: JSON.stringify(layers) };
+}
+
+/// Layout of the displayed strip: rows, or rings for cyclic strips
+/// (plotting::strip_layout, like gui.rs arrange_as_strip).
 function arrangeAsStrip(idx) {
   const view = state.strips[idx];
   if (!view) return;
-  view.layers.forEach((layer, li) => {
-    layer.forEach((id, i) => {
-      const n = nodeById(id);
-      if (!n) return;
-      n.x = (i - (layer.length - 1) / 2) * X_STEP;
-      n.y = -li * STRIP_Y_STEP;
+  const w = stripWire(view);
+  let layout = null;
+  try {
+    if (w) layout = JSON.parse(strip_layout(w.wire, w.layers, view.cyclic));
+  } catch (e) {
+    log(String(e), true);
+  }
+  if (layout) {
+    const { idMap } = toWire();
+    const yStep = view.cyclic ? X_STEP : STRIP_Y_STEP;
+    for (const [i, x, y] of layout.nodes) {
+      const n = nodeById(idMap[i]);
+      if (n) {
+        n.x = x * X_STEP;
+        n.y = -y * yStep;
+      }
+    }
+  } else {
+    view.layers.forEach((layer, li) => {
+      layer.forEach((id, i) => {
+        const n = nodeById(id);
+        if (!n) return;
+        n.x = (i - (layer.length - 1) / 2) * X_STEP;
+        n.y = -li * STRIP_Y_STEP;
+      });
     });
-  });
+  }
   fitView();
 }
 
@@ -918,7 +963,9 @@ $('btn-clear').addEventListener('click', () => {
 // ---------------------------------------------------------------------------
 
 const exampleN = () =>
-  Math.min(12, Math.max(1, parseInt($('example-n').value, 10) || 3));
+  Math.min(20, Math.max(1, parseInt($('example-n').value, 10) || 3));
+const exampleM = () =>
+  Math.min(20, Math.max(0, parseInt($('example-m').value, 10) || 0));
 
 function runGen(fn, mode, describe, layout = 'rank') {
   try {
@@ -975,6 +1022,94 @@ document.querySelectorAll('[data-graph]').forEach((btn) =>
   })
 );
 
+// The wasm generators start with small caps so one click cannot freeze the
+// tab; the limits panel raises them for this session.
+function showLimits() {
+  const lim = JSON.parse(get_limits());
+  $('limit-faces').value = lim.max_faces;
+  $('limit-vertices').value = lim.associahedron_vertices;
+  $('limit-poset').value = lim.distributive_elements;
+}
+
+$('limits-box').addEventListener('toggle', () => {
+  if ($('limits-box').open) showLimits();
+});
+
+$('btn-limits').addEventListener('click', () => {
+  const int = (id) => parseInt($(id).value, 10);
+  try {
+    set_limits(JSON.stringify({
+      max_faces: int('limit-faces'),
+      associahedron_vertices: int('limit-vertices'),
+      tube_poset_vertices: int('limit-vertices'),
+      distributive_elements: int('limit-poset'),
+    }));
+    showLimits();
+    log('Generator limits updated.');
+  } catch (e) {
+    log(String(e), true);
+  }
+});
+
+$('btn-nestohedron').addEventListener('click', () => {
+  const spec = $('building-input').value.trim();
+  runGen(
+    () => gen_nestohedron(spec),
+    'poset',
+    (w) => `Nestohedron of ${spec}: ${w.labels.length} faces.`
+  );
+});
+
+$('btn-hypergraph').addEventListener('click', () => {
+  const spec = $('building-input').value.trim();
+  runGen(
+    () => gen_hypergraph_nestohedron(spec),
+    'poset',
+    (w) => `Hypergraphic nestohedron of ${spec}: ${w.labels.length} faces.`
+  );
+});
+
+// Rust ports of the generator scripts in lattices/, and the classic lattices
+const FAMILIES = {
+  fence: [(n) => gen_fence(n), (n) => `J(F_${n})`],
+  boolean: [(n) => gen_boolean(n), (n) => `B_${n}`],
+  partitions: [(n) => gen_int_partitions(n), () => "Young's lattice"],
+  refinement: [(n) => gen_int_partitions_ref(n), (n) => `Partitions of ${n} by refinement`],
+  claws: [(n, m) => gen_prod_of_claws(n, m), (n, m) => `C_${n} × C_${m}`],
+  tamari: [(n) => gen_tamari(n), (n) => `Tamari T_${n}`],
+  weak: [(n) => gen_weak_order(n), (n) => `Weak order on S_${n}`],
+  setpart: [(n) => gen_set_partitions(n), (n) => `Π_${n}`],
+  noncrossing: [(n) => gen_noncrossing(n), (n) => `NC(${n})`],
+  young: [(n, m) => gen_young_interval(n, m), (n, m) => `L(${n}, ${m})`],
+  subspaces: [(n) => gen_subspaces(n), (n) => `Subspaces of F_2^${n}`],
+};
+
+document.querySelectorAll('[data-family]').forEach((btn) =>
+  btn.addEventListener('click', () => {
+    const [gen, name] = FAMILIES[btn.dataset.family];
+    const n = exampleN(), m = exampleM();
+    runGen(
+      () => gen(n, m),
+      'poset',
+      (w) => `${name(n, m)}: ${w.labels.length} elements.`
+    );
+  })
+);
+
+$('btn-rooted-tree').addEventListener('click', () => {
+  const n = exampleN(), m = exampleM();
+  try {
+    const trees = JSON.parse(gen_rooted_trees(n));
+    if (m >= trees.length) {
+      log(`Only ${trees.length} rooted trees on ${n} vertices (#0..#${trees.length - 1}).`, true);
+      return;
+    }
+    replaceGraph(trees[m], 'graph', `Rooted tree #${m} of ${trees.length} on ${n} vertices.`, 'circle');
+  } catch (e) {
+    log(String(e), true);
+  }
+});
+
 $('btn-grid').addEventListener('click', () => {
   const spec = $('grid-input').value.trim();
   runGen(
@@ -1056,53 +1191,33 @@ $('file-input').addEventListener('change', async (e) => {
   }
 });
 
-// ---- TikZ export (port of gui.rs export_tikz) --------------------------------
+// ---- TikZ export (tikz.rs: the same code as the CLI and gui.rs) ---------------
+
+$('tikz-options').value = localStorage.getItem('rhombic.tikz') || '';
 
 $('btn-tikz').addEventListener('click', () => {
+  const spec = $('tikz-options').value.trim();
+  localStorage.setItem('rhombic.tikz', spec);
   const strip = currentStrip();
-  const nodeIds = strip
-    ? strip.layers.flat()
-    : state.nodes.map((n) => n.id);
-  const edges = strip
-    ? [...strip.edges, ...strip.cyclicEdges]
-    : state.edges;
-
-  const safe = (s) =>
-    s.replace(/[{}]/g, '')
-      .replace(/[,|]/g, '_')
-      .replace(/\*/g, 'top')
-      .replace(/\?/g, 'empty');
-
-  const scale = 0.02;
-  let tex = '\\documentclass[tikz, border=1cm]{standalone}\n\\begin{document}\n';
-  tex += '\\begin{tikzpicture}[y=-1cm]\n\n% Coordinates\n';
-  for (const id of nodeIds) {
-    const n = nodeById(id);
-    if (!n) continue;
-    tex += `\\coordinate (${safe(n.label)}) at (${(n.x * scale).toFixed(2)}, ${(n.y * scale).toFixed(2)});\n`;
-  }
-  tex += '\n% Edges\n\\foreach \\a/\\b in {';
-  tex += edges
-    .map(([a, b]) => {
-      const na = nodeById(a), nb = nodeById(b);
-      return na && nb ? `${safe(na.label)}/${safe(nb.label)}` : null;
-    })
-    .filter(Boolean)
-    .join(', ');
-  tex += '} {\n    \\draw (\\a) -- (\\b);\n}\n\n% Nodes\n\\foreach \\v/\\l in {';
-  tex += nodeIds
-    .map((id) => {
-      const n = nodeById(id);
-      return n ? `${safe(n.label)}/{${n.label.replace(/\|/g, '$|$')}}` : null;
-    })
-    .filter(Boolean)
-    .join(', ');
-  tex +=
-    '} {\n    \\node[draw, circle, fill=white, inner sep=2pt] at (\\v) {\\footnotesize \\l};\n}\n';
-  tex += '\\end{tikzpicture}\n\\end{document}\n';
-
-  download('lattice_output.tex', tex, 'application/x-tex');
-  log('Exported lattice_output.tex.');
+  const w = strip && stripWire(strip);
+  if (strip && !w) {
+    log('Graph changed since the strip was computed.', true);
+    return;
+  }
+  try {
+    let tex;
+    if (strip) {
+      tex = tikz_strip(w.wire, w.layers, strip.cyclic, spec);
+    } else {
+      // canvas units to layout units, y up
+      const positions = state.nodes.map((n) => [n.x / X_STEP, -n.y / Y_STEP]);
+      tex = tikz_hasse(JSON.stringify(toWire().wire), JSON.stringify(positions), spec);
+    }
+    download('lattice_output.tex', tex, 'application/x-tex');
+    log('Exported lattice_output.tex.');
+  } catch (e) {
+    log(String(e), true);
+  }
 });
 
 // ---------------------------------------------------------------------------
@@ -1303,10 +1418,42 @@ remoteOverlay.addEventListener('click', (e) => {
 /// chunked NDJSON response line by line, and feeds each message into the same
 /// applyJobMessage the worker path uses. Cancel = abort the fetch; the helper
 /// kills the process group, which also releases a Slurm allocation.
+/// Strips are asked for compactly (layers only); the job's StripDecoder,
+/// built with the first of them, recomputes their edges here. A helper that
+/// answers in JSON works too.
 async function startRemoteJob(kind, wire) {
   const job = state.job;
   const ctrl = new AbortController();
+  const cyclic = $('cyclic').checked;
   remote.abort = ctrl;
+  job.wire = wire;
+  try {
+    await streamRelayJob(
+      {
+        graph: wire,
+        cyclic,
+        mode: kind,
+        cap: kind === 'enumerate' ? ENUM_CAP : 0,
+        encoding: 'compact',
+      },
+      ctrl,
+      () => state.job === job,
+      applyJobMessage
+    );
+  } finally {
+    if (remote.abort === ctrl) remote.abort = null;
+    if (job.decoder) {
+      job.decoder.free();
+      job.decoder = null;
+    }
+  }
+}
+
+/// POST `body` to the helper and hand every NDJSON line of the answer to
+/// `onMessage` while `live()` holds (a superseded job aborts its fetch).
+/// HTTP and network failures, and a stream cut short, arrive as
+/// {type: 'error'} messages.
+async function streamRelayJob(body, ctrl, live, onMessage) {
   try {
     const res = await fetch(`${RELAY}/job`, {
       method: 'POST',
@@ -1315,20 +1462,15 @@ async function startRemoteJob(kind, wire) {
         'Content-Type': 'application/json',
         'X-Rhombic-Token': remote.token,
       },
-      body: JSON.stringify({
-        graph: wire,
-        cyclic: $('cyclic').checked,
-        mode: kind,
-        cap: kind === 'enumerate' ? ENUM_CAP : 0,
-      }),
+      body: JSON.stringify(body),
     });
     if (res.status === 401) {
-      applyJobMessage({ type: 'error', message: 'Not paired with the compute helper.' });
+      onMessage({ type: 'error', message: 'Not paired with the compute helper.' });
       openRemoteSetup();
       return;
     }
     if (!res.ok) {
-      applyJobMessage({ type: 'error', message: `Compute helper error: HTTP ${res.status}.` });
+      onMessage({ type: 'error', message: `Compute helper error: HTTP ${res.status}.` });
       return;
     }
 
@@ -1338,7 +1480,7 @@ async function startRemoteJob(kind, wire) {
     let sawDone = false;
     for (;;) {
       const { value, done } = await reader.read();
-      if (state.job !== job) { ctrl.abort(); return; } // superseded / cancelled
+      if (!live()) { ctrl.abort(); return; } // superseded / cancelled
       if (done) break;
       buf += decoder.decode(value, { stream: true });
       let nl;
@@ -1348,24 +1490,22 @@ async function startRemoteJob(kind, wire) {
         if (!line) continue;
         const msg = JSON.parse(line);
         if (msg.type === 'done' || msg.type === 'error') sawDone = true;
-        applyJobMessage(msg);
+        onMessage(msg);
       }
     }
-    if (!sawDone && state.job === job) {
-      applyJobMessage({
+    if (!sawDone && live()) {
+      onMessage({
         type: 'error',
         message: 'The compute helper closed the stream unexpectedly.',
       });
     }
   } catch (err) {
-    if (ctrl.signal.aborted || state.job !== job) return; // user cancelled
-    applyJobMessage({
+    if (ctrl.signal.aborted || !live()) return; // user cancelled
+    onMessage({
       type: 'error',
       message: `Compute helper unreachable: ${err.message}`,
     });
     setBackend(remote.backend, false); // refresh the status line
-  } finally {
-    if (remote.abort === ctrl) remote.abort = null;
   }
 }
 
@@ -1395,7 +1535,8 @@ function startJob(kind) {
   }
 
   const isRemote = remote.backend !== 'wasm';
-  state.job = { kind, started: performance.now(), liveCount: 0, idMap, remote: isRemote };
+  const cyclic = $('cyclic').checked;
+  state.job = { kind, started: performance.now(), liveCount: 0, progress: null, idMap, cyclic, remote: isRemote };
   if (isRemote) {
     startRemoteJob(kind, wire);
   } else {
@@ -1420,6 +1561,25 @@ function elapsed(job) {
   return `${((performance.now() - job.started) / 1000).toFixed(1)}s`;
 }
 
+/// "45s", "3m 20s", "2h 05m", "3d 04h" — as src/progress.rs writes them.
+function durationText(secs) {
+  const s = Math.max(0, Math.round(secs));
+  const pad = (n) => String(n).padStart(2, '0');
+  if (s < 60) return `${s}s`;
+  if (s < 3600) return `${Math.floor(s / 60)}m ${pad(s % 60)}s`;
+  if (s < 86400) return `${Math.floor(s / 3600)}h ${pad(Math.floor((s % 3600) / 60))}m`;
+  return `${Math.floor(s / 86400)}d ${pad(Math.floor((s % 86400) / 3600))}h`;
+}
+
+/// "42.5% done, 1.2k strips/s, about 3m 20s left".
+function progressText(p) {
+  const rate = p.rate >= 1e6 ? `${(p.rate / 1e6).toFixed(1)}M`
+    : p.rate >= 1e3 ? `${(p.rate / 1e3).toFixed(1)}k`
+    : p.rate.toFixed(0);
+  const left = p.eta == null ? 'time left unknown yet' : `about ${durationText(p.eta)} left`;
+  return `${p.percent.toFixed(1)}% done, ${rate} strips/s, ${left}`;
+}
+
 function updateJobUi() {
   const row = $('job-row');
   if (!state.job) {
@@ -1432,10 +1592,11 @@ function updateJobUi() {
   row.style.display = ''; // Clear inline styles so your CSS can take over again
   
   const j = state.job;
-  const status =
-    j.kind === 'count' ? `counted ${j.liveCount} …`
-    : j.kind === 'enumerate' ? `found ${state.strips.length} …`
-    : 'searching …';
+  const found =
+    j.kind === 'count' ? `counted ${j.liveCount}`
+    : j.kind === 'enumerate' ? `found ${state.strips.length}`
+    : 'searching';
+  const status = j.progress ? `${found}, ${progressText(j.progress)}` : `${found} …`;
   $('job-status').textContent = `${status} (${elapsed(j)})`;
 }
 
@@ -1449,12 +1610,21 @@ function onWorkerMessage(e) {
 function applyJobMessage(msg) {
   const job = state.job;
   if (!job) return; // stale message from a cancelled job
+  if (typeof msg.percent === 'number') {
+    job.progress = { percent: msg.percent, rate: msg.rate, eta: msg.eta };
+  }
 
   if (msg.type === 'note') {
     log(msg.message);
   } else if (msg.type === 'strips') {
     const map = (f) => job.idMap[f];
-    for (const s of msg.strips) {
+    let strips = msg.strips;
+    if (msg.encoding === 'compact') {
+      // the lattice is built only for a job that sends strips
+      job.decoder ??= new StripDecoder(JSON.stringify(job.wire), job.cyclic);
+      strips = JSON.parse(job.decoder.decode(msg.data));
+    }
+    for (const s of strips) {
       state.strips.push({
         layers: s.layers.map((l) => l.map(map).filter((x) => x !== undefined)),
         edges: s.edges
@@ -1463,10 +1633,11 @@ function applyJobMessage(msg) {
         cyclicEdges: s.cyclicEdges
           .map(([a, b]) => [map(a), map(b)])
           .filter(([a, b]) => a !== undefined && b !== undefined),
+        cyclic: job.cyclic,
       });
     }
     job.liveCount = msg.count;
-    if (state.strips.length === msg.strips.length) {
+    if (state.strips.length === strips.length) {
       // first arrivals: show immediately
       state.cursor = 0;
       state.viewing = true;
@@ -1582,6 +1753,19 @@ $('btn-arrange-strip').addEventListener('click', () => {
   refresh();
 });
 
+$('btn-svg-strip').addEventListener('click', () => {
+  const view = state.strips[state.cursor];
+  const w = view && stripWire(view);
+  if (!w) return;
+  try {
+    const name = view.cyclic ? 'strip_cyclic.svg' : 'strip.svg';
+    download(name, strip_svg(w.wire, w.layers, view.cyclic), 'image/svg+xml');
+    log(`Saved ${name}.`);
+  } catch (e) {
+    log(String(e), true);
+  }
+});
+
 $('btn-toggle-strip').addEventListener('click', () => {
   state.viewing = !state.viewing;
   refresh();
@@ -1605,12 +1789,16 @@ $('btn-copy-layers').addEventListener('click', async () => {
 // Scripts panel: batch jobs in a dedicated worker (src/scripts.rs). Uses its
 // own Worker instance so a running script never fights the strip search over
 // one thread; cancellation terminates the worker (same rationale as strips).
+// With the This machine / Cluster backend the same jobs run in parallel as
+// strip_stream's `survey` and `bounds` modes, which stream the worker's
+// messages; cancellation aborts the fetch.
 // Script jobs survive diagram edits: the survey is independent of the editor,
 // and the boundary job snapshots the poset when it starts.
 // ---------------------------------------------------------------------------
 
 const scripts = {
   worker: null,
+  abort: null, // AbortController of a script running on the compute helper
   job: null, // {kind: 'survey' | 'bounds', started}
   survey: { results: [], opts: null, done: false },
   bounds: { pairs: [], count: 0, distinct: 0, done: false },
@@ -1626,7 +1814,26 @@ function scriptWorker() {
   return scripts.worker;
 }
 
+/// Start a script on the chosen backend: `body` is the strip_stream job,
+/// `cmd` the worker's message.
+function runScript(body, cmd) {
+  if (remote.backend === 'wasm') {
+    scriptWorker().postMessage(cmd);
+    return;
+  }
+  const job = scripts.job;
+  const ctrl = new AbortController();
+  scripts.abort = ctrl;
+  streamRelayJob(body, ctrl, () => scripts.job === job, onScriptMessage).finally(() => {
+    if (scripts.abort === ctrl) scripts.abort = null;
+  });
+}
+
 function cancelScript(quiet = false) {
+  if (scripts.abort) {
+    scripts.abort.abort(); // the helper kills the process on disconnect
+    scripts.abort = null;
+  }
   if (scripts.worker) {
     // terminate, don't post: one script step can take arbitrarily long
     // (a single hard strip-existence search), so a 'cancel' message might
@@ -1679,7 +1886,7 @@ $('btn-run-survey').addEventListener('click', () => {
   cancelScript(true);
   scripts.survey = { results: [], opts: { n, linear, cyclic }, done: false };
   scripts.job = { kind: 'survey', started: performance.now() };
-  scriptWorker().postMessage({ cmd: 'survey', maxN: n, linear, cyclic });
+  runScript({ mode: 'survey', n, linear, cyclic }, { cmd: 'survey', maxN: n, linear, cyclic });
   updateScriptJobUi('generating graphs …');
   renderSurvey();
   log(`Surveying all connected graphs on ≤ ${n} vertices…`);
@@ -1704,7 +1911,7 @@ $('btn-run-bounds').addEventListener('click', () => {
   cancelScript(true);
   scripts.bounds = { pairs: [], count: 0, distinct: 0, done: false };
   scripts.job = { kind: 'bounds', started: performance.now() };
-  scriptWorker().postMessage({ cmd: 'bounds', graph: wire });
+  runScript({ mode: 'bounds', graph: wire }, { cmd: 'bounds', graph: wire });
   updateScriptJobUi('enumerating strips …');
   renderBounds();
   log('Enumerating strip boundaries…');
@@ -1724,6 +1931,10 @@ function onScriptMessage(msg) {
     updateScriptJobUi('');
     return;
   }
+  if (msg.type === 'note') {
+    log(msg.message); // from the compute helper
+    return;
+  }
 
   if (msg.type === 'survey' && job.kind === 'survey') {
     const st = scripts.survey;
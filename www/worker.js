@@ -55,11 +55,13 @@ function pump() {
   }
 
   const res = JSON.parse(en.step(BUDGET_MS, BATCH));
+  // percent done, strips/s and seconds left, as strip_stream sends them
+  const progress = { count: res.count, percent: res.percent, rate: res.rate, eta: res.eta };
   if (res.strips.length > 0) {
     sent += res.strips.length;
-    postMessage({ type: 'strips', strips: res.strips, count: res.count });
+    postMessage({ type: 'strips', strips: res.strips, ...progress });
   } else {
-    postMessage({ type: 'progress', count: res.count });
+    postMessage({ type: 'progress', ...progress });
   }
 
   if (res.done) {